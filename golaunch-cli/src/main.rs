@@ -1,5 +1,24 @@
 use clap::{Parser, Subcommand};
-use golaunch_core::{Database, NewCommandHistory, NewItem, NewMemory, NewSlashCommand, UpdateItem};
+use golaunch_core::apps::{scan_applications, APPLICATIONS_SOURCE};
+use golaunch_core::control;
+use golaunch_core::files::{rebuild_file_index, search_file_index, FileFilter};
+use golaunch_core::permissions::{
+    evaluate, parse_command_line, tool_signature, RuleAction, RuleScope, ToolRequest,
+    GRANT_DAYS_SETTING,
+};
+use golaunch_core::schedules::{parse_run_time, run_schedule};
+use golaunch_core::snippets::snippet_uses;
+use golaunch_core::workflows::{
+    run_shell_captured, run_workflow, StepOutcome, StepRunner, WorkflowProgress, WorkflowStatus,
+    WORKFLOW_ACTION_TYPE,
+};
+use golaunch_core::{
+    builtin_action_name, expand_action_value, expand_snippet, CommandHistory, Database, ItemAction,
+    ManagedItem, NewCommandHistory, NewItem, NewItemAction, NewMemory, NewPermissionRule,
+    NewSchedule, NewSlashCommand, PermissionRule, RunningProcess, SnippetInputs, ToolCallRecord,
+    UpdateItem, COPY_ACTION_TYPE, SNIPPET_ACTION_TYPE,
+};
+use serde_json::{json, Value};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -15,6 +34,7 @@ struct Cli {
     command: Commands,
 }
 #[derive(Subcommand)]
+#[allow(clippy::enum_variant_names)]
 enum Commands {
     /// Add a new item to the launcher
     Add {
@@ -22,11 +42,14 @@ enum Commands {
         #[arg(long)]
         title: String,
 
-        /// Action type: 'command', 'url', or 'script'
+        /// Action type: 'command', 'url', 'script', 'snippet' or 'workflow'
         #[arg(long, default_value = "command")]
         action_type: String,
 
-        /// The command, URL, or script to execute
+        /// The command, URL, or script to execute, the snippet template
+        /// (placeholders: {date}, {time}, {date:%d.%m.%Y}, {clipboard}, {selection},
+        /// {cursor}, {args}, {1}, {2}, ...; write {{ and }} for literal braces), or the
+        /// workflow's JSON list of steps
         #[arg(long)]
         action_value: String,
 
@@ -45,6 +68,10 @@ enum Commands {
         /// Comma-separated tags
         #[arg(long)]
         tags: Option<String>,
+
+        /// Global hotkey that executes the item directly (e.g. "Ctrl+Alt+T")
+        #[arg(long)]
+        hotkey: Option<String>,
     },
 
     /// Remove an item by ID
@@ -107,6 +134,10 @@ enum Commands {
         /// Enable or disable the item
         #[arg(long)]
         enabled: Option<bool>,
+
+        /// Global hotkey (pass an empty string to clear it)
+        #[arg(long)]
+        hotkey: Option<String>,
     },
 
     /// Import items from a JSON file
@@ -141,6 +172,10 @@ enum Commands {
         #[arg(long)]
         search: Option<String>,
 
+        /// Only show executions of this item
+        #[arg(long)]
+        item: Option<String>,
+
         /// Output as JSON
         #[arg(long)]
         json: bool,
@@ -149,6 +184,63 @@ enum Commands {
     Run {
         /// The item ID to execute
         id: String,
+
+        /// Arguments for snippet placeholders ({args}, {1}, {2}, ...)
+        #[arg(long, default_value = "")]
+        args: String,
+
+        /// Run one of the item's actions (ID or label) instead of its primary action
+        #[arg(long)]
+        action: Option<String>,
+    },
+
+    /// Manage extra actions on an item (e.g. copy a URL instead of opening it)
+    Action {
+        #[command(subcommand)]
+        action: ActionCommands,
+    },
+
+    /// Run items and slash commands on a timetable or trigger
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleCommands,
+    },
+
+    /// Decide which agent tool calls run without asking
+    Permissions {
+        #[command(subcommand)]
+        action: PermissionCommands,
+    },
+
+    /// List processes started by the running GoLaunch app
+    Ps {
+        /// Show the last output lines of this process instead
+        #[arg(long, value_name = "PID")]
+        tail: Option<u32>,
+
+        /// Number of output lines with --tail
+        #[arg(long, default_value = "20")]
+        lines: usize,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Stop a process started by the running GoLaunch app
+    Kill {
+        /// Process ID (see `ps`)
+        pid: u32,
+    },
+
+    /// Call a method on the running GoLaunch app (lists the methods without one)
+    App {
+        /// Method name, e.g. `show` or `acp.prompt`
+        method: Option<String>,
+
+        /// Parameters as key=value pairs (values are read as JSON when they parse,
+        /// otherwise as text), or a single JSON object
+        params: Vec<String>,
     },
 
     /// Manage agent conversations
@@ -162,6 +254,432 @@ enum Commands {
         #[command(subcommand)]
         action: SlashCommandActions,
     },
+
+    /// Manage global hotkeys
+    Hotkeys {
+        #[command(subcommand)]
+        action: HotkeyCommands,
+    },
+
+    /// Configure launch context providers (cwd, git, editor file, scripts)
+    Context {
+        #[command(subcommand)]
+        action: ContextCommands,
+    },
+
+    /// Browse and manage clipboard history
+    Clipboard {
+        #[command(subcommand)]
+        action: ClipboardCommands,
+    },
+
+    /// Discover installed applications (.desktop files)
+    Apps {
+        #[command(subcommand)]
+        action: AppCommands,
+    },
+
+    /// Build and search the local file index
+    Files {
+        #[command(subcommand)]
+        action: FileCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum ActionCommands {
+    /// Add an action to an item
+    Add {
+        /// The item ID
+        item: String,
+
+        /// Label shown in the launcher
+        #[arg(long)]
+        label: String,
+
+        /// Action type: url, command, script, snippet or copy
+        #[arg(long = "type")]
+        action_type: String,
+
+        /// Action value; {value} is replaced by the item's own value (default: {value})
+        #[arg(long, default_value = "{value}")]
+        value: String,
+
+        /// Launcher shortcut while the item is selected (e.g. "Ctrl+Enter", "Alt+Enter")
+        #[arg(long)]
+        shortcut: Option<String>,
+
+        /// Sort position (defaults to after the existing actions)
+        #[arg(long)]
+        position: Option<i64>,
+    },
+
+    /// List an item's actions, including the built-in ones
+    List {
+        /// The item ID
+        item: String,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Remove an action from an item
+    Rm {
+        /// The item ID
+        item: String,
+
+        /// Action ID or label
+        action: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScheduleCommands {
+    /// Schedule an item or slash command
+    Add {
+        /// Item ID to run
+        #[arg(
+            long,
+            conflicts_with = "slash_command",
+            required_unless_present = "slash_command"
+        )]
+        item: Option<String>,
+
+        /// Slash command name to run (without the leading /)
+        #[arg(long)]
+        slash_command: Option<String>,
+
+        /// When to run: a cron expression ("0 9 * * 1-5"), "every weekday 09:00",
+        /// "every 15 minutes", "hourly", "on start" or "on change <table>"
+        #[arg(long)]
+        when: String,
+
+        /// What to do with runs missed while GoLaunch was not running: skip or run-once
+        #[arg(long, default_value = "skip")]
+        missed: String,
+    },
+
+    /// List schedules with their last and next runs
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Remove a schedule
+    Rm {
+        /// The schedule ID
+        id: String,
+    },
+
+    /// Run a schedule now and record the result
+    RunNow {
+        /// The schedule ID
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum PermissionCommands {
+    /// List rules in the order they are tried, and remembered "always" answers
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Add a rule; every filter given must match for it to apply
+    Add {
+        /// allow, deny or ask
+        action: String,
+
+        /// Command prefix, one glob per word; `**` matches any number of words
+        /// (e.g. "git ** status")
+        #[arg(long)]
+        command: Option<String>,
+
+        /// Tool kind: read, edit, delete, move, search, execute, think, fetch or other
+        #[arg(long)]
+        kind: Option<String>,
+
+        /// Glob on the tool call title
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Only for this agent ID
+        #[arg(long)]
+        agent: Option<String>,
+
+        /// Glob on the app or window title the launcher was opened from
+        #[arg(long)]
+        context: Option<String>,
+
+        /// Insert at this position (1 = tried first) instead of last
+        #[arg(long)]
+        position: Option<i64>,
+    },
+
+    /// Remove a rule
+    Rm {
+        /// Rule ID or position
+        rule: String,
+    },
+
+    /// Move a rule to another position
+    Move {
+        /// Rule ID or position
+        rule: String,
+
+        /// New position (1 = tried first)
+        position: i64,
+    },
+
+    /// Show what the rules decide for a tool call
+    Test {
+        /// Shell command of the tool call
+        #[arg(long)]
+        command: Option<String>,
+
+        /// Tool kind
+        #[arg(long, default_value = "execute")]
+        kind: String,
+
+        /// Tool call title
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Agent ID
+        #[arg(long)]
+        agent: Option<String>,
+
+        /// App the launcher was opened from
+        #[arg(long)]
+        app: Option<String>,
+
+        /// Window title the launcher was opened from
+        #[arg(long)]
+        window: Option<String>,
+    },
+
+    /// Forget remembered "always allow"/"always reject" answers
+    Revoke {
+        /// Grant ID (see `permissions list`)
+        #[arg(conflicts_with_all = ["agent", "all"])]
+        id: Option<String>,
+
+        /// Revoke all grants of this agent
+        #[arg(long)]
+        agent: Option<String>,
+
+        /// Revoke all grants
+        #[arg(long, conflicts_with = "agent")]
+        all: bool,
+    },
+
+    /// Show or set how many days remembered answers last (0 = forever)
+    GrantDays { days: Option<i64> },
+
+    /// Show or set the folders agents may read and write files in
+    Roots {
+        /// Folders replacing the current ones
+        paths: Vec<PathBuf>,
+
+        /// Go back to the default (the slash-commands folder)
+        #[arg(long, conflicts_with = "paths")]
+        reset: bool,
+    },
+
+    /// Replace all rules with the defaults
+    Reset,
+}
+
+#[derive(Subcommand)]
+enum FileCommands {
+    /// Rebuild the file index, optionally changing what gets indexed first. The index is
+    /// off until it is turned on with --enabled true
+    Index {
+        /// Turn the index (and the launcher's live updates of it) on or off
+        #[arg(long)]
+        enabled: Option<bool>,
+        /// Directory to index (repeatable; replaces the configured roots, ~ allowed)
+        #[arg(long = "root")]
+        roots: Vec<String>,
+        /// Gitignore-style pattern to skip (repeatable; replaces the configured list)
+        #[arg(long = "ignore")]
+        ignore_globs: Vec<String>,
+        /// Honour .gitignore files
+        #[arg(long)]
+        gitignore: Option<bool>,
+        /// Index hidden files and directories
+        #[arg(long)]
+        hidden: Option<bool>,
+        /// Stop crawling after this many entries
+        #[arg(long)]
+        max_entries: Option<usize>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Search indexed files by name or path ("~/src/app" matches by path prefix)
+    Search {
+        query: String,
+        /// Maximum number of results
+        #[arg(long, default_value = "20")]
+        limit: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AppCommands {
+    /// Sync applications from the XDG applications directories into the launcher
+    Scan {
+        /// Only list what was found, without changing the database
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum HotkeyCommands {
+    /// List all global hotkeys (launcher toggle, items and slash commands)
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Change the shortcut that shows/hides the launcher
+    SetToggle {
+        /// The new shortcut (e.g. "Ctrl+Shift+Space")
+        hotkey: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ContextCommands {
+    /// Enable a context provider (e.g. "cwd", "git", "editor", "browser", "script:<name>")
+    Enable {
+        /// Provider id
+        provider: String,
+    },
+
+    /// Disable a context provider
+    Disable {
+        /// Provider id
+        provider: String,
+    },
+
+    /// List registered shell-script providers
+    Scripts {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Register a shell-script provider. It receives GOLAUNCH_WINDOW_TITLE,
+    /// GOLAUNCH_PROCESS_NAME, GOLAUNCH_PID and GOLAUNCH_CWD and prints key=value lines.
+    AddScript {
+        /// Script provider name (letters, digits, '-' or '_')
+        name: String,
+        /// Shell command to run
+        command: String,
+    },
+
+    /// Remove a shell-script provider
+    RemoveScript {
+        /// Script provider name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ClipboardCommands {
+    /// List recent clipboard entries (pinned first)
+    List {
+        /// Maximum number of entries
+        #[arg(long, default_value = "20")]
+        limit: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Search clipboard history
+    Search {
+        /// Search query
+        query: String,
+        /// Maximum number of entries
+        #[arg(long, default_value = "20")]
+        limit: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Copy an entry back to the clipboard
+    Copy {
+        /// Entry ID
+        id: String,
+        /// Keep serving the clipboard until another app takes it over (used internally on Linux)
+        #[arg(long, hide = true)]
+        foreground: bool,
+    },
+
+    /// Pin an entry so it is never pruned
+    Pin {
+        /// Entry ID
+        id: String,
+    },
+
+    /// Unpin an entry
+    Unpin {
+        /// Entry ID
+        id: String,
+    },
+
+    /// Remove an entry
+    Remove {
+        /// Entry ID
+        id: String,
+    },
+
+    /// Delete the history (pinned entries are kept unless --all is given)
+    Clear {
+        /// Also delete pinned entries
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Show or change clipboard history settings
+    Config {
+        /// Turn history recording on or off
+        #[arg(long)]
+        enabled: Option<bool>,
+        /// Also record copied images
+        #[arg(long)]
+        images: Option<bool>,
+        /// Number of unpinned entries to keep
+        #[arg(long)]
+        max_entries: Option<usize>,
+        /// Largest copy (in bytes) that is recorded
+        #[arg(long)]
+        max_item_bytes: Option<usize>,
+        /// Comma-separated process names that are never recorded (e.g. "1password,keepassxc")
+        #[arg(long)]
+        exclude: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -255,6 +773,10 @@ enum ConversationCommands {
         /// Conversation ID
         id: String,
 
+        /// Include the agent's tool calls and their permission outcomes
+        #[arg(long)]
+        tools: bool,
+
         /// Output as JSON
         #[arg(long)]
         json: bool,
@@ -290,6 +812,21 @@ enum SlashCommandActions {
         /// Path to the script file
         #[arg(long)]
         script_path: String,
+
+        /// Global hotkey that runs the command directly (e.g. "Ctrl+Alt+K")
+        #[arg(long)]
+        hotkey: Option<String>,
+    },
+
+    /// Set or clear the global hotkey of a slash command
+    SetHotkey {
+        /// Command name
+        #[arg(long)]
+        name: String,
+
+        /// The hotkey to assign (omit to clear)
+        #[arg(long)]
+        hotkey: Option<String>,
     },
 
     /// Remove a slash command by name
@@ -325,6 +862,264 @@ fn get_db(db_path: Option<PathBuf>) -> Result<Database, String> {
     }
 }
 
+/// Runs workflow steps from a terminal: commands are waited on and their output shown,
+/// snippet and copy steps print their text.
+struct CliStepRunner;
+
+impl StepRunner for CliStepRunner {
+    fn run_step(
+        &mut self,
+        action_type: &str,
+        value: &str,
+        previous: &StepOutcome,
+    ) -> Result<StepOutcome, String> {
+        match action_type {
+            "url" => {
+                open::that(value).map_err(|e| format!("Failed to open URL: {e}"))?;
+                Ok(StepOutcome::success())
+            }
+            "command" | "script" => {
+                let outcome = run_shell_captured(value, previous)?;
+                if !outcome.output.is_empty() {
+                    println!("{}", outcome.output);
+                }
+                Ok(outcome)
+            }
+            SNIPPET_ACTION_TYPE => {
+                let clipboard = snippet_uses(value, "clipboard")
+                    .then(|| arboard::Clipboard::new().ok()?.get_text().ok())
+                    .flatten();
+                let expansion = expand_snippet(
+                    value,
+                    &SnippetInputs {
+                        clipboard,
+                        selection: None,
+                        args: String::new(),
+                    },
+                )?;
+                println!("{}", expansion.text);
+                Ok(StepOutcome {
+                    exit_code: 0,
+                    output: expansion.text,
+                })
+            }
+            COPY_ACTION_TYPE => {
+                println!("{value}");
+                Ok(StepOutcome {
+                    exit_code: 0,
+                    output: value.to_string(),
+                })
+            }
+            other => Err(format!("Unknown action type: {other}")),
+        }
+    }
+}
+
+/// Build the params object for `app` from `key=value` arguments or one JSON object.
+fn app_params(args: &[String]) -> Result<Value, String> {
+    if let [arg] = args {
+        if arg.trim_start().starts_with('{') {
+            return serde_json::from_str(arg).map_err(|e| format!("Invalid JSON params: {e}"));
+        }
+    }
+    let mut params = serde_json::Map::new();
+    for arg in args {
+        let (key, value) = arg
+            .split_once('=')
+            .ok_or_else(|| format!("Expected key=value, got '{arg}'"))?;
+        let value = serde_json::from_str(value).unwrap_or_else(|_| Value::from(value));
+        params.insert(key.to_string(), value);
+    }
+    Ok(Value::Object(params))
+}
+
+fn print_workflow_progress(progress: WorkflowProgress) {
+    // The run as a whole is reported by the caller
+    if progress.step == 0 {
+        return;
+    }
+    let prefix = format!("[{}/{}] {}", progress.step, progress.total, progress.label);
+    match progress.status {
+        WorkflowStatus::Running => eprintln!("{prefix}"),
+        WorkflowStatus::Skipped => eprintln!("{prefix}: skipped"),
+        WorkflowStatus::Succeeded => {}
+        WorkflowStatus::Failed => match (&progress.error, progress.exit_code) {
+            (Some(error), _) => eprintln!("{prefix}: failed: {error}"),
+            (None, Some(code)) => eprintln!("{prefix}: exited with {code}"),
+            (None, None) => eprintln!("{prefix}: failed"),
+        },
+    }
+}
+
+/// Find one of an item's actions (built-in ones included) by ID or label.
+fn find_item_action(db: &Database, item_id: &str, key: &str) -> Result<ItemAction, String> {
+    db.list_all_item_actions(item_id)?
+        .into_iter()
+        .find(|a| a.id == key || a.label.eq_ignore_ascii_case(key))
+        .ok_or_else(|| format!("Item {item_id} has no action '{key}'"))
+}
+
+/// A rule by ID or by its position in `permissions list`.
+fn find_permission_rule(db: &Database, key: &str) -> Result<PermissionRule, String> {
+    let rules = db.list_permission_rules()?;
+    let position = key.parse::<i64>().ok();
+    rules
+        .into_iter()
+        .find(|rule| rule.id == key || Some(rule.position) == position)
+        .ok_or_else(|| format!("Permission rule {key} not found"))
+}
+
+fn print_tool_calls(calls: &[ToolCallRecord]) {
+    if calls.is_empty() {
+        println!("No tool calls");
+        return;
+    }
+    println!("=== Tool calls ===\n");
+    for call in calls {
+        println!(
+            "[{}] {} ({})",
+            call.created_at,
+            call.title.as_deref().unwrap_or("Unknown tool"),
+            call.kind.as_deref().unwrap_or("other")
+        );
+        if let Some(command) = &call.command_preview {
+            println!("    $ {command}");
+        }
+        if call.decided_by.is_some() {
+            let options: Vec<&str> = call
+                .permission_options
+                .iter()
+                .map(|o| o.name.as_str())
+                .collect();
+            let answer = match (call.decided_by.as_deref(), &call.chosen_option) {
+                (Some("cancelled"), _) | (_, None) => "cancelled".to_string(),
+                (Some("user"), Some(option)) => format!("{option} (by you)"),
+                (Some(source), Some(option)) => format!("{option} (auto, {source})"),
+                (None, Some(option)) => option.clone(),
+            };
+            if options.is_empty() {
+                println!("    permission: {answer}");
+            } else {
+                println!("    permission: {answer} [options: {}]", options.join(", "));
+            }
+        }
+        if !call.statuses.is_empty() {
+            let statuses: Vec<&str> = call.statuses.iter().map(|s| s.status.as_str()).collect();
+            println!("    status: {}", statuses.join(" -> "));
+        }
+    }
+}
+
+fn print_history(entries: &[CommandHistory], json: bool) {
+    if json {
+        println!("{}", serde_json::to_string_pretty(entries).unwrap());
+    } else if entries.is_empty() {
+        println!("No command history found");
+    } else {
+        let header = format!(
+            "{:<38} {:<30} {:<10} {:<20} {}",
+            "ID", "COMMAND", "TYPE", "EXECUTED AT", "SOURCE"
+        );
+        println!("{header}");
+        println!("{}", "-".repeat(108));
+        for entry in entries {
+            let cmd_display = if entry.command_text.len() > 28 {
+                format!("{}...", &entry.command_text[..25])
+            } else {
+                entry.command_text.clone()
+            };
+            println!(
+                "{:<38} {:<30} {:<10} {:<20} {}",
+                entry.id, cmd_display, entry.action_type, entry.executed_at, entry.source
+            );
+        }
+        println!("\nTotal: {} entries", entries.len());
+    }
+}
+
+fn print_clipboard_entries(entries: &[golaunch_core::ClipboardEntry], json: bool) {
+    if json {
+        println!("{}", serde_json::to_string_pretty(entries).unwrap());
+        return;
+    }
+    if entries.is_empty() {
+        println!("No clipboard entries found");
+        return;
+    }
+
+    let header = format!(
+        "{:<38} {:<3} {:<24} {:<16} {}",
+        "ID", "PIN", "COPIED AT", "SOURCE", "PREVIEW"
+    );
+    println!("{header}");
+    println!("{}", "-".repeat(120));
+    for entry in entries {
+        let copied_at: String = entry.last_copied_at.chars().take(19).collect();
+        println!(
+            "{:<38} {:<3} {:<24} {:<16} {}",
+            entry.id,
+            if entry.pinned { "*" } else { "" },
+            copied_at,
+            entry.source_app.as_deref().unwrap_or("-"),
+            entry.preview
+        );
+    }
+    println!("\nTotal: {} entries", entries.len());
+}
+
+/// Put a history entry on the system clipboard.
+///
+/// On Linux the clipboard is owned by the process that set it, so the CLI re-runs itself
+/// in the background with `--foreground` to keep serving the contents after it returns.
+fn copy_clipboard_entry(
+    db: &Database,
+    db_path: Option<&PathBuf>,
+    id: &str,
+    foreground: bool,
+) -> Result<(), String> {
+    let entry = db.get_clipboard_entry(id)?;
+
+    if cfg!(target_os = "linux") && !foreground {
+        let exe = std::env::current_exe().map_err(|e| format!("Failed to locate CLI: {e}"))?;
+        let mut cmd = std::process::Command::new(exe);
+        if let Some(path) = db_path {
+            cmd.arg("--db").arg(path);
+        }
+        cmd.args(["clipboard", "copy", id, "--foreground"])
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to start clipboard owner: {e}"))?;
+    } else {
+        let mut clipboard =
+            arboard::Clipboard::new().map_err(|e| format!("Failed to init clipboard: {e}"))?;
+        let set = clipboard.set();
+        #[cfg(target_os = "linux")]
+        let set = {
+            use arboard::SetExtLinux;
+            set.wait()
+        };
+        let result = if entry.content_type == "image" {
+            let image = db.get_clipboard_image(id)?;
+            set.image(arboard::ImageData {
+                width: image.width,
+                height: image.height,
+                bytes: image.rgba.into(),
+            })
+        } else {
+            set.text(entry.content.clone())
+        };
+        result.map_err(|e| format!("Failed to set clipboard: {e}"))?;
+    }
+
+    db.touch_clipboard_entry(id)?;
+    if !foreground {
+        println!("Copied to clipboard: {}", entry.preview);
+    }
+    Ok(())
+}
+
 fn main() {
     let cli = Cli::parse();
 
@@ -344,6 +1139,7 @@ fn run(cli: Cli) -> Result<(), String> {
             icon,
             category,
             tags,
+            hotkey,
         } => {
             let db = get_db(cli.db)?;
             let item = db.add_item(NewItem {
@@ -354,6 +1150,7 @@ fn run(cli: Cli) -> Result<(), String> {
                 action_value,
                 category,
                 tags,
+                hotkey,
             })?;
             println!("{}", serde_json::to_string_pretty(&item).unwrap());
             Ok(())
@@ -444,6 +1241,7 @@ fn run(cli: Cli) -> Result<(), String> {
             category,
             tags,
             enabled,
+            hotkey,
         } => {
             let db = get_db(cli.db)?;
             let item = db.update_item(
@@ -457,6 +1255,7 @@ fn run(cli: Cli) -> Result<(), String> {
                     category,
                     tags,
                     enabled,
+                    hotkey,
                 },
             )?;
             println!("{}", serde_json::to_string_pretty(&item).unwrap());
@@ -584,72 +1383,124 @@ fn run(cli: Cli) -> Result<(), String> {
         Commands::History {
             limit,
             search,
+            item,
             json,
         } => {
             let db = get_db(cli.db)?;
-            let entries = match search {
-                Some(query) => db.search_command_history(&query)?,
-                None => db.get_recent_commands(limit)?,
+            let entries = match (search, item) {
+                (Some(query), _) => db.search_command_history(&query)?,
+                (None, Some(item_id)) => db.get_item_history(&item_id, limit)?,
+                (None, None) => db.get_recent_commands(limit)?,
             };
-
-            if json {
-                println!("{}", serde_json::to_string_pretty(&entries).unwrap());
-            } else if entries.is_empty() {
-                println!("No command history found");
-            } else {
-                let header = format!(
-                    "{:<38} {:<30} {:<10} {:<20} {}",
-                    "ID", "COMMAND", "TYPE", "EXECUTED AT", "SOURCE"
-                );
-                println!("{header}");
-                println!("{}", "-".repeat(108));
-                for entry in &entries {
-                    let cmd_display = if entry.command_text.len() > 28 {
-                        format!("{}...", &entry.command_text[..25])
-                    } else {
-                        entry.command_text.clone()
-                    };
-                    println!(
-                        "{:<38} {:<30} {:<10} {:<20} {}",
-                        entry.id, cmd_display, entry.action_type, entry.executed_at, entry.source
-                    );
-                }
-                println!("\nTotal: {} entries", entries.len());
-            }
+            print_history(&entries, json);
             Ok(())
         }
-        Commands::Run { id } => {
+        Commands::Run { id, args, action } => {
             let db = get_db(cli.db)?;
             let item = db.get_item(&id)?;
+            let (action_type, action_value) = match action {
+                Some(key) => {
+                    let action = find_item_action(&db, &id, &key)?;
+                    match builtin_action_name(&action.id) {
+                        Some("copy_value") => {
+                            println!("{}", item.action_value);
+                            return Ok(());
+                        }
+                        Some("disable") => {
+                            db.update_item(
+                                &id,
+                                UpdateItem {
+                                    enabled: Some(false),
+                                    ..Default::default()
+                                },
+                            )?;
+                            println!("Item {} ({}) disabled", item.title, id);
+                            return Ok(());
+                        }
+                        Some("history") => {
+                            print_history(&db.get_item_history(&id, 20)?, false);
+                            return Ok(());
+                        }
+                        Some(_) => {
+                            return Err(format!(
+                                "'{}' is only available in the launcher (use `golaunch-cli update {id}`)",
+                                action.label
+                            ));
+                        }
+                        None => {
+                            let value = expand_action_value(
+                                &action.action_type,
+                                &action.value,
+                                &item.action_value,
+                            );
+                            (action.action_type, value)
+                        }
+                    }
+                }
+                None => (item.action_type.clone(), item.action_value.clone()),
+            };
+
             db.increment_frequency(&id)?;
             let _ = db.record_command(NewCommandHistory {
                 item_id: Some(id.clone()),
-                command_text: item.action_value.clone(),
-                action_type: item.action_type.clone(),
+                command_text: action_value.clone(),
+                action_type: action_type.clone(),
                 source: Some("cli".to_string()),
+                exit_code: None,
             });
 
-            match item.action_type.as_str() {
+            match action_type.as_str() {
                 "url" => {
-                    open::that(&item.action_value)
-                        .map_err(|e| format!("Failed to open URL: {e}"))?;
+                    open::that(&action_value).map_err(|e| format!("Failed to open URL: {e}"))?;
                 }
                 "command" | "script" => {
                     #[cfg(target_os = "windows")]
                     {
                         std::process::Command::new("cmd")
-                            .args(["/C", &item.action_value])
+                            .args(["/C", &action_value])
                             .spawn()
-                            .map_err(|e| format!("Failed to execute {}: {e}", item.action_type))?;
+                            .map_err(|e| format!("Failed to execute {}: {e}", action_type))?;
                     }
                     #[cfg(not(target_os = "windows"))]
                     {
                         std::process::Command::new("sh")
-                            .args(["-c", &item.action_value])
+                            .args(["-c", &action_value])
                             .spawn()
-                            .map_err(|e| format!("Failed to execute {}: {e}", item.action_type))?;
+                            .map_err(|e| format!("Failed to execute {}: {e}", action_type))?;
                     }
                 }
+                SNIPPET_ACTION_TYPE => {
+                    // There is no focused app to paste into from a terminal, so print
+                    // the expansion for piping instead.
+                    let clipboard = snippet_uses(&action_value, "clipboard")
+                        .then(|| arboard::Clipboard::new().ok()?.get_text().ok())
+                        .flatten();
+                    let expansion = expand_snippet(
+                        &action_value,
+                        &SnippetInputs {
+                            clipboard,
+                            selection: None,
+                            args,
+                        },
+                    )?;
+                    println!("{}", expansion.text);
+                    return Ok(());
+                }
+                WORKFLOW_ACTION_TYPE => {
+                    let succeeded =
+                        run_workflow(&db, &item, &mut CliStepRunner, &mut print_workflow_progress)?;
+                    return if succeeded {
+                        println!("Workflow {} completed", item.title);
+                        Ok(())
+                    } else {
+                        Err(format!("Workflow '{}' failed", item.title))
+                    };
+                }
+                COPY_ACTION_TYPE => {
+                    // No clipboard worth writing to from a terminal; print for piping.
+                    println!("{action_value}");
+                    return Ok(());
+                }
                 other => return Err(format!("Unknown action type: {other}")),
             }
 
@@ -657,6 +1508,408 @@ fn run(cli: Cli) -> Result<(), String> {
             Ok(())
         }
 
+        Commands::Action { action } => {
+            let db = get_db(cli.db)?;
+            match action {
+                ActionCommands::Add {
+                    item,
+                    label,
+                    action_type,
+                    value,
+                    shortcut,
+                    position,
+                } => {
+                    let action = db.add_item_action(NewItemAction {
+                        item_id: item,
+                        label,
+                        action_type,
+                        value,
+                        shortcut,
+                        position,
+                    })?;
+                    println!("{}", serde_json::to_string_pretty(&action).unwrap());
+                }
+                ActionCommands::List { item, json } => {
+                    let actions = db.list_all_item_actions(&item)?;
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&actions).unwrap());
+                    } else {
+                        let header = format!(
+                            "{:<38} {:<20} {:<10} {:<20} {}",
+                            "ID", "LABEL", "TYPE", "SHORTCUT", "VALUE"
+                        );
+                        println!("{header}");
+                        println!("{}", "-".repeat(108));
+                        for action in &actions {
+                            println!(
+                                "{:<38} {:<20} {:<10} {:<20} {}",
+                                action.id,
+                                action.label,
+                                action.action_type,
+                                action.shortcut.as_deref().unwrap_or("-"),
+                                action.value
+                            );
+                        }
+                    }
+                }
+                ActionCommands::Rm { item, action } => {
+                    let action = find_item_action(&db, &item, &action)?;
+                    if action.builtin {
+                        return Err(format!(
+                            "'{}' is a built-in action and cannot be removed",
+                            action.label
+                        ));
+                    }
+                    db.remove_item_action(&action.id)?;
+                    println!("Action '{}' removed from item {item}", action.label);
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Schedule { action } => {
+            let db = get_db(cli.db)?;
+            match action {
+                ScheduleCommands::Add {
+                    item,
+                    slash_command,
+                    when,
+                    missed,
+                } => {
+                    let (target_type, target_id) = match (item, slash_command) {
+                        (Some(item), _) => ("item", item),
+                        (None, Some(name)) => (
+                            "slash_command",
+                            db.get_slash_command_by_name(name.trim_start_matches('/'))?
+                                .id,
+                        ),
+                        (None, None) => return Err("Pass --item or --slash-command".to_string()),
+                    };
+                    let schedule = db.add_schedule(NewSchedule {
+                        target_type: target_type.to_string(),
+                        target_id,
+                        spec: when,
+                        missed_policy: Some(missed),
+                    })?;
+                    println!("{}", serde_json::to_string_pretty(&schedule).unwrap());
+                }
+                ScheduleCommands::List { json } => {
+                    let schedules = db.list_schedules()?;
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&schedules).unwrap());
+                    } else if schedules.is_empty() {
+                        println!("No schedules found");
+                    } else {
+                        let local = |time: &Option<String>| {
+                            time.as_deref()
+                                .and_then(parse_run_time)
+                                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                                .unwrap_or_else(|| "-".to_string())
+                        };
+                        let header = format!(
+                            "{:<38} {:<24} {:<22} {:<17} {:<17} {}",
+                            "ID", "TARGET", "WHEN", "NEXT RUN", "LAST RUN", "STATUS"
+                        );
+                        println!("{header}");
+                        println!("{}", "-".repeat(130));
+                        for schedule in &schedules {
+                            println!(
+                                "{:<38} {:<24} {:<22} {:<17} {:<17} {}",
+                                schedule.id,
+                                schedule.target_name.as_deref().unwrap_or("(missing)"),
+                                schedule.spec,
+                                local(&schedule.next_run_at),
+                                local(&schedule.last_run_at),
+                                schedule.last_status.as_deref().unwrap_or("-")
+                            );
+                        }
+                        println!("\nTotal: {} schedules", schedules.len());
+                    }
+                }
+                ScheduleCommands::Rm { id } => {
+                    if db.remove_schedule(&id)? {
+                        println!("Schedule {id} removed");
+                    } else {
+                        return Err(format!("Schedule {id} not found"));
+                    }
+                }
+                ScheduleCommands::RunNow { id } => {
+                    let schedule = db.get_schedule(&id)?;
+                    let exit_code = run_schedule(
+                        &db,
+                        &schedule,
+                        &mut CliStepRunner,
+                        &mut print_workflow_progress,
+                    )?;
+                    if exit_code != 0 {
+                        return Err(format!(
+                            "Schedule {id} ({}) exited with {exit_code}",
+                            schedule.target_name.as_deref().unwrap_or("missing target")
+                        ));
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Permissions { action } => {
+            let db = get_db(cli.db)?;
+            match action {
+                PermissionCommands::List { json } => {
+                    let rules = db.list_permission_rules()?;
+                    let grants = db.list_permission_grants()?;
+                    if json {
+                        let list = json!({ "rules": rules, "grants": grants });
+                        println!("{}", serde_json::to_string_pretty(&list).unwrap());
+                        return Ok(());
+                    }
+                    if rules.is_empty() {
+                        println!("No permission rules; every tool call asks");
+                    } else {
+                        let header = format!(
+                            "{:<4} {:<6} {:<9} {:<34} {}",
+                            "#", "ACTION", "KIND", "COMMAND", "FILTERS"
+                        );
+                        println!("{header}");
+                        println!("{}", "-".repeat(90));
+                        for rule in &rules {
+                            let filters: Vec<String> = [
+                                ("title", &rule.title),
+                                ("agent", &rule.agent_id),
+                                ("context", &rule.context),
+                            ]
+                            .into_iter()
+                            .filter_map(|(name, value)| {
+                                value.as_ref().map(|value| format!("{name}={value}"))
+                            })
+                            .collect();
+                            println!(
+                                "{:<4} {:<6} {:<9} {:<34} {}",
+                                rule.position,
+                                rule.action,
+                                rule.tool_kind.as_deref().unwrap_or("*"),
+                                rule.command.as_deref().unwrap_or("*"),
+                                filters.join(" ")
+                            );
+                        }
+                        println!(
+                            "\nTotal: {} rules (tool calls no rule matches ask)",
+                            rules.len()
+                        );
+                    }
+                    if !grants.is_empty() {
+                        println!("\nRemembered decisions (\"always\" answers):");
+                        let header = format!(
+                            "{:<38} {:<16} {:<7} {:<17} {}",
+                            "ID", "AGENT", "ANSWER", "EXPIRES", "TOOL CALL"
+                        );
+                        println!("{header}");
+                        println!("{}", "-".repeat(110));
+                        for grant in &grants {
+                            let expires = grant
+                                .expires_at
+                                .as_deref()
+                                .and_then(parse_run_time)
+                                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                                .unwrap_or_else(|| "never".to_string());
+                            println!(
+                                "{:<38} {:<16} {:<7} {:<17} {}",
+                                grant.id, grant.agent_id, grant.decision, expires, grant.signature
+                            );
+                        }
+                    }
+                }
+                PermissionCommands::Revoke { id, agent, all } => match (id, agent) {
+                    (Some(id), _) => {
+                        if !db.revoke_permission_grant(&id)? {
+                            return Err(format!("Permission grant {id} not found"));
+                        }
+                        println!("Grant {id} revoked");
+                    }
+                    (None, agent) if agent.is_some() || all => {
+                        let count = db.revoke_permission_grants(agent.as_deref())?;
+                        println!("{count} grants revoked");
+                    }
+                    (None, _) => return Err("Pass a grant ID, --agent or --all".to_string()),
+                },
+                PermissionCommands::Roots { paths, reset } => {
+                    if reset || !paths.is_empty() {
+                        let paths = paths
+                            .iter()
+                            .map(|path| {
+                                std::fs::canonicalize(path)
+                                    .map_err(|e| format!("Invalid folder {}: {e}", path.display()))
+                            })
+                            .collect::<Result<Vec<_>, _>>()?;
+                        db.set_agent_file_roots(&paths)?;
+                    }
+                    for root in db.agent_file_roots()? {
+                        println!("{}", root.display());
+                    }
+                }
+                PermissionCommands::GrantDays { days } => {
+                    if let Some(days) = days {
+                        if days < 0 {
+                            return Err("Days must be 0 (never expire) or more".to_string());
+                        }
+                        db.set_setting(GRANT_DAYS_SETTING, &days.to_string())?;
+                    }
+                    match db.permission_grant_days()? {
+                        Some(days) => println!("New grants expire after {days} days"),
+                        None => println!("New grants never expire"),
+                    }
+                }
+                PermissionCommands::Add {
+                    action,
+                    command,
+                    kind,
+                    title,
+                    agent,
+                    context,
+                    position,
+                } => {
+                    let rule = db.add_permission_rule(NewPermissionRule {
+                        action,
+                        tool_kind: kind,
+                        title,
+                        command,
+                        agent_id: agent,
+                        context,
+                        position,
+                    })?;
+                    println!("{}", serde_json::to_string_pretty(&rule).unwrap());
+                }
+                PermissionCommands::Rm { rule } => {
+                    let rule = find_permission_rule(&db, &rule)?;
+                    db.remove_permission_rule(&rule.id)?;
+                    println!("Rule {} removed", rule.position);
+                }
+                PermissionCommands::Move { rule, position } => {
+                    let rule = find_permission_rule(&db, &rule)?;
+                    let moved = db.move_permission_rule(&rule.id, position)?;
+                    println!("Rule moved to position {}", moved.position);
+                }
+                PermissionCommands::Test {
+                    command,
+                    kind,
+                    title,
+                    agent,
+                    app,
+                    window,
+                } => {
+                    let rules = db.list_permission_rules()?;
+                    let request = ToolRequest {
+                        kind: Some(&kind),
+                        title: title.as_deref(),
+                        command: command.as_deref(),
+                    };
+                    let scope = RuleScope {
+                        agent_id: agent,
+                        source_app: app,
+                        source_window: window,
+                    };
+                    if let Some(command) = &command {
+                        match parse_command_line(command) {
+                            Ok(commands) => {
+                                for command in commands {
+                                    println!("  {}", command.words.join(" "));
+                                }
+                            }
+                            Err(e) => println!("  (cannot parse: {e})"),
+                        }
+                    }
+                    let decision = evaluate(&rules, &request, &scope);
+                    // Remembered answers apply where the rules would ask
+                    let grant = match (&scope.agent_id, decision) {
+                        (Some(agent), RuleAction::Ask) => {
+                            db.find_permission_grant(agent, &tool_signature(&request))?
+                        }
+                        _ => None,
+                    };
+                    match grant {
+                        Some(grant) => println!("{} (remembered)", grant.decision),
+                        None => println!("{}", decision.as_str()),
+                    }
+                }
+                PermissionCommands::Reset => {
+                    db.reset_permission_rules()?;
+                    println!("Permission rules reset to the defaults");
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Ps { tail, lines, json } => {
+            if let Some(pid) = tail {
+                let output =
+                    control::call("processes.tail", json!({ "pid": pid, "lines": lines }))?;
+                let output: Vec<String> = serde_json::from_value(output)
+                    .map_err(|e| format!("Invalid response from GoLaunch: {e}"))?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&output).unwrap());
+                } else {
+                    for line in output {
+                        println!("{line}");
+                    }
+                }
+                return Ok(());
+            }
+
+            let processes: Vec<RunningProcess> =
+                serde_json::from_value(control::call("processes.list", Value::Null)?)
+                    .map_err(|e| format!("Invalid response from GoLaunch: {e}"))?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&processes).unwrap());
+            } else if processes.is_empty() {
+                println!("No processes started by GoLaunch");
+            } else {
+                let header = format!(
+                    "{:<8} {:<10} {:<20} {:<24} {}",
+                    "PID", "STATUS", "STARTED", "TITLE", "COMMAND"
+                );
+                println!("{header}");
+                println!("{}", "-".repeat(100));
+                for process in &processes {
+                    let status = match (process.running, process.exit_code) {
+                        (true, _) => "running".to_string(),
+                        (false, Some(code)) => format!("exit {code}"),
+                        (false, None) => "killed".to_string(),
+                    };
+                    // RFC 3339 down to the second
+                    let started = process
+                        .started_at
+                        .get(..19)
+                        .unwrap_or(&process.started_at)
+                        .replace('T', " ");
+                    println!(
+                        "{:<8} {:<10} {:<20} {:<24} {}",
+                        process.pid, status, started, process.title, process.command
+                    );
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Kill { pid } => {
+            control::call("processes.kill", json!({ "pid": pid }))?;
+            println!("Process {pid} stopped");
+            Ok(())
+        }
+
+        Commands::App { method, params } => {
+            let Some(method) = method else {
+                for (name, description) in control::METHODS {
+                    println!("{name:<16} {description}");
+                }
+                return Ok(());
+            };
+            let result = control::call(&method, app_params(&params)?)?;
+            if !result.is_null() {
+                println!("{}", serde_json::to_string_pretty(&result).unwrap());
+            }
+            Ok(())
+        }
+
         Commands::Conversations { action } => {
             let db = get_db(cli.db)?;
             match action {
@@ -710,16 +1963,24 @@ fn run(cli: Cli) -> Result<(), String> {
                         }
                     }
                 }
-                ConversationCommands::Show { id, json } => {
+                ConversationCommands::Show { id, tools, json } => {
                     let messages = db.get_conversation_messages(&id)?;
+                    let tool_calls = if tools {
+                        db.list_tool_calls(&id)?
+                    } else {
+                        Vec::new()
+                    };
                     if json {
                         let conv = db.get_conversation(&id)?;
-                        let output = serde_json::json!({
+                        let mut output = serde_json::json!({
                             "conversation": conv,
                             "messages": messages,
                         });
+                        if tools {
+                            output["tool_calls"] = serde_json::json!(tool_calls);
+                        }
                         println!("{}", serde_json::to_string_pretty(&output).unwrap());
-                    } else if messages.is_empty() {
+                    } else if messages.is_empty() && tool_calls.is_empty() {
                         println!("No messages in conversation {id}");
                     } else {
                         let conv = db.get_conversation(&id)?;
@@ -728,9 +1989,18 @@ fn run(cli: Cli) -> Result<(), String> {
                             let role_label = match msg.role.as_str() {
                                 "user" => "You",
                                 "assistant" => "Agent",
+                                "thought" => "Agent thinking",
+                                "plan" => "Plan",
                                 other => other,
                             };
-                            println!("[{} - {}]\n{}\n", role_label, msg.created_at, msg.content);
+                            let incomplete = if msg.incomplete { ", incomplete" } else { "" };
+                            println!(
+                                "[{} - {}{}]\n{}\n",
+                                role_label, msg.created_at, incomplete, msg.content
+                            );
+                        }
+                        if tools {
+                            print_tool_calls(&tool_calls);
                         }
                     }
                 }
@@ -797,14 +2067,20 @@ fn run(cli: Cli) -> Result<(), String> {
                     name,
                     description,
                     script_path,
+                    hotkey,
                 } => {
                     let cmd = db.add_slash_command(NewSlashCommand {
                         name,
                         description,
                         script_path,
+                        hotkey,
                     })?;
                     println!("{}", serde_json::to_string_pretty(&cmd).unwrap());
                 }
+                SlashCommandActions::SetHotkey { name, hotkey } => {
+                    let cmd = db.set_slash_command_hotkey(&name, hotkey.as_deref())?;
+                    println!("{}", serde_json::to_string_pretty(&cmd).unwrap());
+                }
                 SlashCommandActions::Remove { name } => {
                     if db.remove_slash_command_by_name(&name)? {
                         println!("Slash command '/{name}' removed successfully");
@@ -822,6 +2098,7 @@ fn run(cli: Cli) -> Result<(), String> {
                         command_text: format!("/{} {}", name, args),
                         action_type: "slash_command".to_string(),
                         source: Some("cli".to_string()),
+                        exit_code: None,
                     });
 
                     #[cfg(target_os = "windows")]
@@ -860,5 +2137,294 @@ fn run(cli: Cli) -> Result<(), String> {
             }
             Ok(())
         }
+
+        Commands::Hotkeys { action } => {
+            let db = get_db(cli.db)?;
+            match action {
+                HotkeyCommands::List { json } => {
+                    let bindings = db.list_hotkey_bindings()?;
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&bindings).unwrap());
+                    } else {
+                        let header = format!("{:<24} {:<15} {}", "HOTKEY", "TYPE", "TARGET");
+                        println!("{header}");
+                        println!("{}", "-".repeat(70));
+                        for b in &bindings {
+                            println!("{:<24} {:<15} {}", b.hotkey, b.target_type, b.label);
+                        }
+                    }
+                }
+                HotkeyCommands::SetToggle { hotkey } => {
+                    let normalized = db.set_toggle_hotkey(&hotkey)?;
+                    println!("Launcher toggle hotkey set to {normalized}");
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Context { action } => {
+            let db = get_db(cli.db)?;
+            match action {
+                ContextCommands::Enable { provider } => {
+                    db.set_context_provider_enabled(&provider, true)?;
+                    println!("Enabled context provider: {provider}");
+                }
+                ContextCommands::Disable { provider } => {
+                    db.set_context_provider_enabled(&provider, false)?;
+                    println!("Disabled context provider: {provider}");
+                }
+                ContextCommands::Scripts { json } => {
+                    let scripts = db.list_context_scripts()?;
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&scripts).unwrap());
+                    } else if scripts.is_empty() {
+                        println!("No context scripts registered.");
+                    } else {
+                        let header = format!("{:<20} {:<8} {}", "NAME", "ENABLED", "COMMAND");
+                        println!("{header}");
+                        println!("{}", "-".repeat(70));
+                        for script in &scripts {
+                            let enabled =
+                                db.is_context_provider_enabled(&format!("script:{}", script.name))?;
+                            println!(
+                                "{:<20} {:<8} {}",
+                                script.name,
+                                if enabled { "yes" } else { "no" },
+                                script.command
+                            );
+                        }
+                    }
+                }
+                ContextCommands::AddScript { name, command } => {
+                    let script = db.set_context_script(&name, &command)?;
+                    println!("Registered context script: {}", script.name);
+                }
+                ContextCommands::RemoveScript { name } => {
+                    if db.remove_context_script(&name)? {
+                        println!("Context script '{name}' removed successfully");
+                    } else {
+                        eprintln!("Context script '{name}' not found");
+                        std::process::exit(1);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Files { action } => {
+            let db = get_db(cli.db)?;
+            match action {
+                FileCommands::Index {
+                    enabled,
+                    roots,
+                    ignore_globs,
+                    gitignore,
+                    hidden,
+                    max_entries,
+                    json,
+                } => {
+                    let mut settings = db.get_file_index_settings()?;
+                    let changed = enabled.is_some()
+                        || !roots.is_empty()
+                        || !ignore_globs.is_empty()
+                        || gitignore.is_some()
+                        || hidden.is_some()
+                        || max_entries.is_some();
+                    if let Some(v) = enabled {
+                        settings.enabled = v;
+                    }
+                    if !roots.is_empty() {
+                        settings.roots = roots;
+                    }
+                    if !ignore_globs.is_empty() {
+                        settings.ignore_globs = ignore_globs;
+                    }
+                    if let Some(v) = gitignore {
+                        settings.respect_gitignore = v;
+                    }
+                    if let Some(v) = hidden {
+                        settings.include_hidden = v;
+                    }
+                    if let Some(v) = max_entries {
+                        settings.max_entries = v;
+                    }
+                    if changed {
+                        // Validate roots and globs before saving them.
+                        FileFilter::new(&settings)?;
+                        db.set_file_index_settings(&settings)?;
+                    }
+                    if !settings.enabled {
+                        db.replace_file_index(&[])?;
+                        println!("The file index is off (turn it on with --enabled true)");
+                        return Ok(());
+                    }
+
+                    let report = rebuild_file_index(&db, &settings)?;
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+                    } else {
+                        println!(
+                            "Indexed {} files and {} directories from {} in {} ms",
+                            report.files,
+                            report.dirs,
+                            settings.roots.join(", "),
+                            report.elapsed_ms
+                        );
+                        if report.truncated {
+                            println!(
+                                "Stopped at the limit of {} entries (raise it with --max-entries)",
+                                settings.max_entries
+                            );
+                        }
+                        if let Some(problem) = db.file_watch_problem()? {
+                            println!("{problem}");
+                        }
+                    }
+                }
+                FileCommands::Search { query, limit, json } => {
+                    let entries = search_file_index(&db, &query, limit)?;
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&entries).unwrap());
+                    } else if entries.is_empty() {
+                        println!("No files found.");
+                    } else {
+                        for entry in &entries {
+                            let suffix = if entry.is_dir { "/" } else { "" };
+                            println!("{}{suffix}", entry.path);
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Apps { action } => {
+            let db = get_db(cli.db)?;
+            match action {
+                AppCommands::Scan { dry_run, json } => {
+                    let apps = scan_applications();
+                    let items: Vec<ManagedItem> =
+                        apps.iter().map(|app| app.to_managed_item()).collect();
+                    if dry_run {
+                        if json {
+                            println!("{}", serde_json::to_string_pretty(&items).unwrap());
+                        } else {
+                            for (app, item) in apps.iter().zip(&items) {
+                                println!("{:<32} {}", item.title, app.path.display());
+                            }
+                            println!("\nFound {} applications", apps.len());
+                        }
+                        return Ok(());
+                    }
+
+                    let report = db.sync_managed_items(APPLICATIONS_SOURCE, items)?;
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+                    } else {
+                        println!(
+                            "Scanned {} applications: {} added, {} updated, {} removed, {} unchanged, {} kept with your edits",
+                            apps.len(),
+                            report.added,
+                            report.updated,
+                            report.removed,
+                            report.unchanged,
+                            report.preserved
+                        );
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Clipboard { action } => {
+            let db = get_db(cli.db.clone())?;
+            match action {
+                ClipboardCommands::List { limit, json } => {
+                    print_clipboard_entries(&db.list_clipboard_entries(limit)?, json);
+                }
+                ClipboardCommands::Search { query, limit, json } => {
+                    print_clipboard_entries(&db.search_clipboard_entries(&query, limit)?, json);
+                }
+                ClipboardCommands::Copy { id, foreground } => {
+                    copy_clipboard_entry(&db, cli.db.as_ref(), &id, foreground)?;
+                }
+                ClipboardCommands::Pin { id } => {
+                    if db.set_clipboard_entry_pinned(&id, true)? {
+                        println!("Pinned clipboard entry {id}");
+                    } else {
+                        eprintln!("Clipboard entry {id} not found");
+                        std::process::exit(1);
+                    }
+                }
+                ClipboardCommands::Unpin { id } => {
+                    if db.set_clipboard_entry_pinned(&id, false)? {
+                        println!("Unpinned clipboard entry {id}");
+                    } else {
+                        eprintln!("Clipboard entry {id} not found");
+                        std::process::exit(1);
+                    }
+                }
+                ClipboardCommands::Remove { id } => {
+                    if db.remove_clipboard_entry(&id)? {
+                        println!("Clipboard entry {id} removed successfully");
+                    } else {
+                        eprintln!("Clipboard entry {id} not found");
+                        std::process::exit(1);
+                    }
+                }
+                ClipboardCommands::Clear { all } => {
+                    let removed = db.clear_clipboard_history(all)?;
+                    println!("Removed {removed} clipboard entries");
+                }
+                ClipboardCommands::Config {
+                    enabled,
+                    images,
+                    max_entries,
+                    max_item_bytes,
+                    exclude,
+                    json,
+                } => {
+                    let mut settings = db.get_clipboard_settings()?;
+                    let changed = enabled.is_some()
+                        || images.is_some()
+                        || max_entries.is_some()
+                        || max_item_bytes.is_some()
+                        || exclude.is_some();
+                    if let Some(v) = enabled {
+                        settings.enabled = v;
+                    }
+                    if let Some(v) = images {
+                        settings.capture_images = v;
+                    }
+                    if let Some(v) = max_entries {
+                        settings.max_entries = v;
+                    }
+                    if let Some(v) = max_item_bytes {
+                        settings.max_item_bytes = v;
+                    }
+                    if let Some(list) = exclude {
+                        settings.excluded_apps = list
+                            .split(',')
+                            .map(|a| a.trim().to_string())
+                            .filter(|a| !a.is_empty())
+                            .collect();
+                    }
+                    if changed {
+                        db.set_clipboard_settings(&settings)?;
+                    }
+
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&settings).unwrap());
+                    } else {
+                        println!("enabled:        {}", settings.enabled);
+                        println!("images:         {}", settings.capture_images);
+                        println!("max entries:    {}", settings.max_entries);
+                        println!("max item bytes: {}", settings.max_item_bytes);
+                        println!("excluded apps:  {}", settings.excluded_apps.join(", "));
+                    }
+                }
+            }
+            Ok(())
+        }
     }
 }
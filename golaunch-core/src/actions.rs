@@ -0,0 +1,122 @@
+use crate::hotkeys::normalize_hotkey;
+use crate::models::ItemAction;
+
+/// Item action type that copies its value to the clipboard.
+pub const COPY_ACTION_TYPE: &str = "copy";
+
+/// Action types a custom item action can have.
+pub const ITEM_ACTION_TYPES: &[&str] = &["url", "command", "script", "snippet", COPY_ACTION_TYPE];
+
+/// Action type (and id prefix) of the built-in actions every item has.
+pub const BUILTIN_ACTION_TYPE: &str = "builtin";
+
+/// Built-in actions as (name, label, shortcut). The launcher handles `edit` and
+/// `history` itself; the others run in the backend.
+const BUILTIN_ACTIONS: &[(&str, &str, Option<&str>)] = &[
+    ("copy_value", "Copy value", Some("CmdOrCtrl+Shift+C")),
+    ("edit", "Edit item", Some("CmdOrCtrl+E")),
+    ("disable", "Disable item", None),
+    ("history", "Show in history", Some("CmdOrCtrl+Y")),
+];
+
+/// The built-in actions for an item. They are listed after the item's custom actions.
+pub fn builtin_item_actions(item_id: &str) -> Vec<ItemAction> {
+    BUILTIN_ACTIONS
+        .iter()
+        .enumerate()
+        .map(|(i, (name, label, shortcut))| ItemAction {
+            id: format!("{BUILTIN_ACTION_TYPE}:{name}"),
+            item_id: item_id.to_string(),
+            label: label.to_string(),
+            action_type: BUILTIN_ACTION_TYPE.to_string(),
+            value: name.to_string(),
+            shortcut: shortcut.and_then(|s| normalize_hotkey(s).ok()),
+            position: i as i64,
+            builtin: true,
+            created_at: String::new(),
+        })
+        .collect()
+}
+
+/// The name of a built-in action id ("builtin:copy_value" -> "copy_value").
+pub fn builtin_action_name(action_id: &str) -> Option<&str> {
+    let name = action_id
+        .strip_prefix(BUILTIN_ACTION_TYPE)?
+        .strip_prefix(':')?;
+    BUILTIN_ACTIONS
+        .iter()
+        .any(|(builtin, _, _)| *builtin == name)
+        .then_some(name)
+}
+
+/// Action types whose value is run by a shell (`sh -c`, or `cmd /C` on Windows).
+const SHELL_ACTION_TYPES: &[&str] = &["command", "script"];
+
+/// An action's effective value: `{value}` is replaced by the item's action value, and
+/// an empty value means the item's value as-is. In shell actions the item's value is
+/// filled in as one quoted word, so a URL with `&` or a `$(...)` is never run as code.
+pub fn expand_action_value(action_type: &str, value: &str, item_value: &str) -> String {
+    if value.is_empty() {
+        item_value.to_string()
+    } else if SHELL_ACTION_TYPES.contains(&action_type) {
+        value.replace("{value}", &shell_quote(item_value))
+    } else {
+        value.replace("{value}", item_value)
+    }
+}
+
+/// Quote text as one word for `sh -c`, or for `cmd /C` on Windows.
+pub fn shell_quote(text: &str) -> String {
+    if cfg!(target_os = "windows") {
+        // cmd has no escape for these inside double quotes, so they are replaced.
+        let cleaned: String = text
+            .chars()
+            .map(|c| match c {
+                '"' | '%' | '!' | '\r' | '\n' => ' ',
+                c => c,
+            })
+            .collect();
+        format!("\"{cleaned}\"")
+    } else {
+        format!("'{}'", text.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_values_in_shell_actions() {
+        let url = "https://x/?a=1&b=2";
+        assert_eq!(
+            expand_action_value("url", "{value}#top", url),
+            "https://x/?a=1&b=2#top"
+        );
+        assert_eq!(expand_action_value("command", "", "ls -la"), "ls -la");
+        if cfg!(not(target_os = "windows")) {
+            assert_eq!(
+                expand_action_value("command", "firefox --private-window {value}", url),
+                "firefox --private-window 'https://x/?a=1&b=2'"
+            );
+            assert_eq!(
+                expand_action_value("script", "echo {value}", "it's $(id)"),
+                r"echo 'it'\''s $(id)'"
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shell_actions_get_the_value_as_one_word() {
+        let value = expand_action_value("command", "printf %s {value}", "a=1&b=2; echo $(id)");
+        let output = std::process::Command::new("sh")
+            .args(["-c", &value])
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "a=1&b=2; echo $(id)"
+        );
+    }
+}
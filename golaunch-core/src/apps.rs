@@ -0,0 +1,245 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::models::ManagedItem;
+
+/// Item source for applications discovered from `.desktop` files.
+pub const APPLICATIONS_SOURCE: &str = "applications";
+
+/// Category given to discovered applications.
+pub const APPLICATIONS_CATEGORY: &str = "Applications";
+
+/// A launchable application parsed from a `.desktop` entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopApp {
+    /// Desktop file ID, e.g. "org.gnome.Nautilus.desktop".
+    pub id: String,
+    pub name: String,
+    pub generic_name: Option<String>,
+    pub comment: Option<String>,
+    pub keywords: Vec<String>,
+    /// Exec line with field codes removed.
+    pub exec: String,
+    pub icon: Option<String>,
+    pub terminal: bool,
+    pub path: PathBuf,
+}
+
+impl DesktopApp {
+    /// The launcher item this application is synced to.
+    pub fn to_managed_item(&self) -> ManagedItem {
+        let command = if self.terminal {
+            format!("${{TERMINAL:-x-terminal-emulator}} -e {}", self.exec)
+        } else {
+            self.exec.clone()
+        };
+        let mut tags: Vec<&str> = self.keywords.iter().map(String::as_str).collect();
+        if let Some(generic) = &self.generic_name {
+            tags.push(generic);
+        }
+        ManagedItem {
+            source_key: self.id.clone(),
+            title: self.name.clone(),
+            subtitle: self.comment.clone().or_else(|| self.generic_name.clone()),
+            icon: self.icon.clone(),
+            action_type: "command".to_string(),
+            action_value: command,
+            category: APPLICATIONS_CATEGORY.to_string(),
+            tags: tags.join(","),
+        }
+    }
+}
+
+/// XDG `applications` directories in precedence order: `$XDG_DATA_HOME` first, then
+/// each entry of `$XDG_DATA_DIRS`.
+pub fn application_dirs() -> Vec<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| dirs::home_dir().map(|h| h.join(".local/share")));
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    let mut seen = HashSet::new();
+    data_home
+        .into_iter()
+        .chain(data_dirs.split(':').map(PathBuf::from))
+        .map(|dir| dir.join("applications"))
+        .filter(|dir| seen.insert(dir.clone()))
+        .collect()
+}
+
+/// Scan the XDG application directories. When the same desktop file ID exists in
+/// several directories, the one with the highest precedence wins, even if it hides
+/// the application (that is how users remove entries from menus).
+pub fn scan_applications() -> Vec<DesktopApp> {
+    scan_application_dirs(&application_dirs())
+}
+
+pub fn scan_application_dirs(dirs: &[PathBuf]) -> Vec<DesktopApp> {
+    let mut seen_ids = HashSet::new();
+    let mut apps = Vec::new();
+    for dir in dirs {
+        let mut files = Vec::new();
+        collect_desktop_files(dir, &mut files);
+        files.sort();
+        for path in files {
+            let Some(id) = desktop_file_id(dir, &path) else {
+                continue;
+            };
+            if !seen_ids.insert(id.clone()) {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Some(app) = parse_desktop_entry(&id, &path, &content) {
+                apps.push(app);
+            }
+        }
+    }
+    apps.sort_by_key(|app| app.name.to_lowercase());
+    apps
+}
+
+fn collect_desktop_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_desktop_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "desktop") {
+            files.push(path);
+        }
+    }
+}
+
+/// Desktop file ID per the XDG spec: the path relative to the applications directory
+/// with `/` replaced by `-`.
+fn desktop_file_id(dir: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(dir).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Some(parts.join("-"))
+}
+
+/// Parse the `[Desktop Entry]` group. Returns `None` for anything that should not be
+/// listed: non-applications, `NoDisplay`/`Hidden` entries, or entries without `Exec`.
+pub fn parse_desktop_entry(id: &str, path: &Path, content: &str) -> Option<DesktopApp> {
+    let mut in_entry = false;
+    let mut name = None;
+    let mut generic_name = None;
+    let mut comment = None;
+    let mut keywords = Vec::new();
+    let mut exec = None;
+    let mut icon = None;
+    let mut terminal = false;
+    let mut kind = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        // Localized keys (Name[de]=...) are skipped in favour of the default value.
+        let value = unescape_value(value.trim());
+        match key.trim() {
+            "Type" => kind = Some(value),
+            "Name" => name = Some(value),
+            "GenericName" => generic_name = Some(value),
+            "Comment" => comment = Some(value),
+            "Keywords" => {
+                keywords = value
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|k| !k.is_empty())
+                    .map(String::from)
+                    .collect()
+            }
+            "Exec" => exec = Some(value),
+            "Icon" => icon = Some(value),
+            "Terminal" => terminal = value == "true",
+            "NoDisplay" | "Hidden" if value == "true" => return None,
+            _ => {}
+        }
+    }
+
+    if kind.as_deref() != Some("Application") {
+        return None;
+    }
+    let exec = strip_field_codes(&exec?);
+    if exec.is_empty() {
+        return None;
+    }
+    Some(DesktopApp {
+        id: id.to_string(),
+        name: name.filter(|n| !n.is_empty())?,
+        generic_name: generic_name.filter(|g| !g.is_empty()),
+        comment: comment.filter(|c| !c.is_empty()),
+        keywords,
+        exec,
+        icon: icon.filter(|i| !i.is_empty()),
+        terminal,
+        path: path.to_path_buf(),
+    })
+}
+
+/// Resolve the escape sequences allowed in string values (`\s`, `\n`, `\t`, `\r`, `\\`).
+fn unescape_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => out.push(' '),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('\\') => out.push('\\'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Remove Exec field codes (`%f`, `%U`, `%i`, ...) since the launcher passes no files
+/// or URLs; `%%` becomes a literal `%`.
+pub fn strip_field_codes(exec: &str) -> String {
+    let mut out = String::with_capacity(exec.len());
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+        } else if chars.next() == Some('%') {
+            out.push('%');
+        }
+        // Any other code (including deprecated ones) expands to nothing.
+    }
+    out.split_whitespace()
+        .filter(|arg| !arg.is_empty() && *arg != "\"\"" && *arg != "''")
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,262 @@
+//! Local control socket shared by the running app (server) and the CLI (client): a
+//! Unix domain socket, or a named pipe on Windows. Each connection carries one
+//! line-delimited JSON-RPC 2.0 request and its response. Requests must carry the
+//! per-user token from `token_path()`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// JSON-RPC error code for an unknown method.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code for missing or invalid parameters.
+pub const INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC error code for a request that could not be parsed.
+pub const PARSE_ERROR: i64 = -32700;
+/// Error code for a method that ran and failed.
+pub const APP_ERROR: i64 = -32000;
+/// Error code for a request without the right token.
+pub const UNAUTHORIZED: i64 = -32001;
+
+/// Methods the app answers, with their parameters, for `golaunch-cli app`.
+pub const METHODS: &[(&str, &str)] = &[
+    ("show", "Show the launcher [query]"),
+    ("hide", "Hide the launcher"),
+    ("toggle", "Show or hide the launcher"),
+    ("refresh", "Reload items in the launcher"),
+    (
+        "search",
+        "Search like the launcher: query [providers] [limit]",
+    ),
+    ("execute_item", "Run an item: id [action_id]"),
+    (
+        "acp.prompt",
+        "Send a prompt to an agent, returning its conversation: text [conversation_id] [agent_id]",
+    ),
+    (
+        "acp.status",
+        "Agent status, uptime, restart count and last error",
+    ),
+    (
+        "context.get",
+        "Launch context captured when the launcher opened",
+    ),
+    ("processes.list", "Processes started by the app"),
+    ("processes.tail", "Recent output of a process: pid [lines]"),
+    ("processes.kill", "Stop a process: pid"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcRequest {
+    pub jsonrpc: String,
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+    /// Contents of the token file.
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcResponse {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+impl RpcResponse {
+    pub fn new(id: Value, result: Result<Value, RpcError>) -> Self {
+        let (result, error) = match result {
+            Ok(value) => (Some(value), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result,
+            error,
+        }
+    }
+}
+
+fn golaunch_dir() -> Result<PathBuf, String> {
+    let data_dir = dirs::data_local_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| "Cannot determine home directory".to_string())?;
+    Ok(data_dir.join("golaunch"))
+}
+
+/// Where the app listens: the per-user runtime directory when there is one
+/// (`$XDG_RUNTIME_DIR`), otherwise next to the database.
+#[cfg(unix)]
+pub fn socket_path() -> Result<PathBuf, String> {
+    match dirs::runtime_dir() {
+        Some(dir) => Ok(dir.join("golaunch").join("control.sock")),
+        None => Ok(golaunch_dir()?.join("control.sock")),
+    }
+}
+
+/// The per-user named pipe the app listens on.
+#[cfg(windows)]
+pub fn socket_path() -> Result<PathBuf, String> {
+    let user = std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string());
+    Ok(PathBuf::from(format!(r"\\.\pipe\golaunch-control-{user}")))
+}
+
+/// File holding the secret that requests must present, readable only by the user.
+pub fn token_path() -> Result<PathBuf, String> {
+    Ok(golaunch_dir()?.join("control.token"))
+}
+
+/// Read the token, creating it on first use. A token file others can read (e.g.
+/// restored from a backup) is made private again.
+pub fn load_or_create_token() -> Result<String, String> {
+    let path = token_path()?;
+    if let Ok(token) = std::fs::read_to_string(&path) {
+        if !token.trim().is_empty() {
+            make_private(&path)?;
+            return Ok(token.trim().to_string());
+        }
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    let token = format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    write_private(&path, &token)?;
+    Ok(token)
+}
+
+#[cfg(unix)]
+fn write_private(path: &std::path::Path, contents: &str) -> Result<(), String> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+#[cfg(unix)]
+fn make_private(path: &std::path::Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    let metadata =
+        std::fs::metadata(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    if metadata.permissions().mode() & 0o077 != 0 {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to restrict {}: {e}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_private(_path: &std::path::Path) -> Result<(), String> {
+    Ok(())
+}
+
+/// The user's profile directory is private on Windows already.
+#[cfg(not(unix))]
+fn write_private(path: &std::path::Path, contents: &str) -> Result<(), String> {
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Call a method on the running app and return its result.
+pub fn call(method: &str, params: Value) -> Result<Value, String> {
+    let token = std::fs::read_to_string(token_path()?)
+        .map_err(|e| format!("GoLaunch is not running (no control token): {e}"))?;
+    let request = RpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Value::from(1),
+        method: method.to_string(),
+        params,
+        token: Some(token.trim().to_string()),
+    };
+    let mut line =
+        serde_json::to_string(&request).map_err(|e| format!("Failed to encode request: {e}"))?;
+    line.push('\n');
+
+    let response = exchange(&line)?;
+    let response: RpcResponse = serde_json::from_str(&response)
+        .map_err(|e| format!("Invalid response from GoLaunch: {e}"))?;
+    match (response.result, response.error) {
+        (_, Some(error)) => Err(error.message),
+        (Some(result), None) => Ok(result),
+        (None, None) => Ok(Value::Null),
+    }
+}
+
+#[cfg(unix)]
+fn exchange(request: &str) -> Result<String, String> {
+    let path = socket_path()?;
+    let stream = std::os::unix::net::UnixStream::connect(&path).map_err(|e| {
+        format!(
+            "GoLaunch is not running (no control socket at {}): {e}",
+            path.display()
+        )
+    })?;
+    round_trip(stream, request)
+}
+
+#[cfg(windows)]
+fn exchange(request: &str) -> Result<String, String> {
+    let path = socket_path()?;
+    let pipe = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&path)
+        .map_err(|e| {
+            format!(
+                "GoLaunch is not running (no control pipe at {}): {e}",
+                path.display()
+            )
+        })?;
+    round_trip(pipe, request)
+}
+
+/// Write the request line and read the response line.
+fn round_trip<S: std::io::Read + std::io::Write>(
+    mut stream: S,
+    request: &str,
+) -> Result<String, String> {
+    use std::io::{BufRead, BufReader};
+    stream
+        .write_all(request.as_bytes())
+        .and_then(|_| stream.flush())
+        .map_err(|e| format!("Failed to send request: {e}"))?;
+    let mut response = String::new();
+    BufReader::new(stream)
+        .read_line(&mut response)
+        .map_err(|e| format!("Failed to read response: {e}"))?;
+    if response.trim().is_empty() {
+        return Err("GoLaunch closed the connection without answering".to_string());
+    }
+    Ok(response)
+}
@@ -1,12 +1,66 @@
+use crate::actions::{builtin_item_actions, ITEM_ACTION_TYPES};
+use crate::hotkeys::{
+    default_toggle_hotkey, hotkeys_equal, normalize_hotkey, TOGGLE_HOTKEY_SETTING,
+};
 use crate::models::{
-    CommandHistory, CommandSuggestion, Conversation, ConversationMessage, ConversationWithPreview,
-    Item, Memory, NewCommandHistory, NewConversation, NewConversationMessage, NewItem, NewMemory,
-    NewSlashCommand, Setting, SlashCommand, UpdateItem,
+    ClipboardEntry, ClipboardImage, ClipboardSettings, CommandHistory, CommandSuggestion,
+    ContextScript, Conversation, ConversationMessage, ConversationWithPreview, FileEntry,
+    FileIndexSettings, HotkeyBinding, Item, ItemAction, ManagedItem, Memory, NewClipboardEntry,
+    NewCommandHistory, NewConversation, NewConversationMessage, NewItem, NewItemAction, NewMemory,
+    NewPermissionRule, NewSchedule, NewSlashCommand, PermissionGrant, PermissionRule, Schedule,
+    Setting, SlashCommand, SyncReport, ToolCallPermission, ToolCallRecord, ToolCallStatus,
+    UpdateItem,
+};
+use crate::permissions::{
+    default_rules, validate_rule, DEFAULT_GRANT_DAYS, FILE_ROOTS_SETTING, GRANT_DAYS_SETTING,
 };
+use crate::schedules::{self, MissedRunPolicy, ScheduleTrigger, WATCHED_TABLES};
+use crate::snippets::{validate_snippet, SNIPPET_ACTION_TYPE};
+use crate::workflows::{parse_workflow, WORKFLOW_ACTION_TYPE};
 use rusqlite::{params, Connection, Result as SqlResult};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use uuid::Uuid;
 
+/// Schedules joined with the title of the item or name of the slash command they run.
+const SCHEDULE_SELECT: &str = "SELECT s.id, s.target_type, s.target_id,
+        COALESCE(i.title, '/' || c.name), s.spec, s.missed_policy, s.enabled,
+        s.last_run_at, s.last_status, s.last_exit_code, s.next_run_at, s.created_at
+     FROM schedules s
+     LEFT JOIN items i ON s.target_type = 'item' AND i.id = s.target_id
+     LEFT JOIN slash_commands c ON s.target_type = 'slash_command' AND c.id = s.target_id";
+
+const PERMISSION_RULE_SELECT: &str = "SELECT id, position, action, tool_kind, title, command,
+        agent_id, context, created_at FROM permission_rules";
+
+/// Set once the default permission rules were stored, so deleting them sticks.
+const PERMISSION_RULES_SEEDED_SETTING: &str = "permission_rules_seeded";
+
+/// Settings prefix for per-provider launch context toggles ("true"/"false").
+const CONTEXT_PROVIDER_PREFIX: &str = "context.provider.";
+/// Settings prefix for user shell-script context providers, keyed by name.
+const CONTEXT_SCRIPT_PREFIX: &str = "context.script.";
+
+const CLIPBOARD_ENABLED_SETTING: &str = "clipboard.enabled";
+const CLIPBOARD_IMAGES_SETTING: &str = "clipboard.images";
+const CLIPBOARD_MAX_ENTRIES_SETTING: &str = "clipboard.max_entries";
+const CLIPBOARD_MAX_ITEM_BYTES_SETTING: &str = "clipboard.max_item_bytes";
+const CLIPBOARD_EXCLUDED_APPS_SETTING: &str = "clipboard.excluded_apps";
+
+const FILES_ENABLED_SETTING: &str = "files.enabled";
+const FILES_ROOTS_SETTING: &str = "files.roots";
+const FILES_IGNORE_SETTING: &str = "files.ignore";
+const FILES_GITIGNORE_SETTING: &str = "files.respect_gitignore";
+const FILES_HIDDEN_SETTING: &str = "files.include_hidden";
+const FILES_MAX_ENTRIES_SETTING: &str = "files.max_entries";
+/// Unix time of the last full file index rebuild.
+const FILES_INDEXED_AT_SETTING: &str = "files.indexed_at";
+/// Why some indexed directories get no live updates, while that is the case.
+const FILES_WATCH_PROBLEM_SETTING: &str = "files.watch_problem";
+
+const CLIPBOARD_COLUMNS: &str = "id, content_type, content, preview, size_bytes, source_app, \
+     pinned, copy_count, created_at, last_copied_at";
+
 pub struct Database {
     conn: Connection,
 }
@@ -111,6 +165,8 @@ impl Database {
                 CREATE TABLE IF NOT EXISTS conversations (
                     id TEXT PRIMARY KEY,
                     title TEXT NOT NULL,
+                    agent_id TEXT,
+                    agent_session_id TEXT,
                     created_at TEXT NOT NULL DEFAULT (datetime('now')),
                     updated_at TEXT NOT NULL DEFAULT (datetime('now'))
                 );
@@ -121,10 +177,35 @@ impl Database {
                     conversation_id TEXT NOT NULL REFERENCES conversations(id),
                     role TEXT NOT NULL,
                     content TEXT NOT NULL,
+                    incomplete INTEGER NOT NULL DEFAULT 0,
                     created_at TEXT NOT NULL DEFAULT (datetime('now'))
                 );
                 CREATE INDEX IF NOT EXISTS idx_conv_messages_conv_id ON conversation_messages(conversation_id);
 
+                CREATE TABLE IF NOT EXISTS tool_calls (
+                    id TEXT PRIMARY KEY,
+                    conversation_id TEXT NOT NULL REFERENCES conversations(id),
+                    tool_call_id TEXT NOT NULL,
+                    title TEXT,
+                    kind TEXT,
+                    status TEXT,
+                    command_preview TEXT,
+                    permission_options TEXT,
+                    chosen_option TEXT,
+                    decided_by TEXT,
+                    auto_allowed INTEGER NOT NULL DEFAULT 0,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                    UNIQUE(conversation_id, tool_call_id)
+                );
+
+                CREATE TABLE IF NOT EXISTS tool_call_statuses (
+                    id INTEGER PRIMARY KEY,
+                    tool_call_id TEXT NOT NULL REFERENCES tool_calls(id),
+                    status TEXT NOT NULL,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+                );
+                CREATE INDEX IF NOT EXISTS idx_tool_call_statuses_call ON tool_call_statuses(tool_call_id);
+
                 CREATE TABLE IF NOT EXISTS slash_commands (
                     id TEXT PRIMARY KEY,
                     name TEXT NOT NULL UNIQUE,
@@ -136,20 +217,212 @@ impl Database {
                 );
                 CREATE INDEX IF NOT EXISTS idx_slash_commands_name ON slash_commands(name);
                 CREATE INDEX IF NOT EXISTS idx_slash_commands_usage ON slash_commands(usage_count);
+
+                CREATE TABLE IF NOT EXISTS clipboard_history (
+                    id TEXT PRIMARY KEY,
+                    content_type TEXT NOT NULL DEFAULT 'text',
+                    content TEXT NOT NULL DEFAULT '',
+                    preview TEXT NOT NULL DEFAULT '',
+                    image_data BLOB,
+                    image_width INTEGER,
+                    image_height INTEGER,
+                    size_bytes INTEGER NOT NULL DEFAULT 0,
+                    content_hash TEXT NOT NULL UNIQUE,
+                    source_app TEXT,
+                    pinned INTEGER NOT NULL DEFAULT 0,
+                    copy_count INTEGER NOT NULL DEFAULT 1,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                    last_copied_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f', 'now'))
+                );
+                CREATE INDEX IF NOT EXISTS idx_clipboard_history_last_copied ON clipboard_history(last_copied_at);
+
+                CREATE TABLE IF NOT EXISTS item_actions (
+                    id TEXT PRIMARY KEY,
+                    item_id TEXT NOT NULL,
+                    label TEXT NOT NULL,
+                    action_type TEXT NOT NULL,
+                    value TEXT NOT NULL DEFAULT '',
+                    shortcut TEXT,
+                    position INTEGER NOT NULL DEFAULT 0,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+                );
+                CREATE INDEX IF NOT EXISTS idx_item_actions_item_id ON item_actions(item_id);
+
+                CREATE TABLE IF NOT EXISTS schedules (
+                    id TEXT PRIMARY KEY,
+                    target_type TEXT NOT NULL,
+                    target_id TEXT NOT NULL,
+                    spec TEXT NOT NULL,
+                    missed_policy TEXT NOT NULL DEFAULT 'skip',
+                    enabled INTEGER NOT NULL DEFAULT 1,
+                    last_run_at TEXT,
+                    last_status TEXT,
+                    last_exit_code INTEGER,
+                    next_run_at TEXT,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+                );
+                CREATE INDEX IF NOT EXISTS idx_schedules_target ON schedules(target_type, target_id);
+
+                CREATE TABLE IF NOT EXISTS permission_rules (
+                    id TEXT PRIMARY KEY,
+                    position INTEGER NOT NULL,
+                    action TEXT NOT NULL,
+                    tool_kind TEXT,
+                    title TEXT,
+                    command TEXT,
+                    agent_id TEXT,
+                    context TEXT,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+                );
+
+                CREATE TABLE IF NOT EXISTS permission_grants (
+                    id TEXT PRIMARY KEY,
+                    agent_id TEXT NOT NULL,
+                    signature TEXT NOT NULL,
+                    decision TEXT NOT NULL,
+                    expires_at TEXT,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                    UNIQUE(agent_id, signature)
+                );
+
+                CREATE TABLE IF NOT EXISTS table_versions (
+                    table_name TEXT PRIMARY KEY,
+                    version INTEGER NOT NULL DEFAULT 0
+                );
+
+                CREATE TABLE IF NOT EXISTS file_index (
+                    id INTEGER PRIMARY KEY,
+                    path TEXT NOT NULL UNIQUE,
+                    name TEXT NOT NULL,
+                    is_dir INTEGER NOT NULL DEFAULT 0,
+                    size INTEGER NOT NULL DEFAULT 0,
+                    modified INTEGER
+                );
+                CREATE VIRTUAL TABLE IF NOT EXISTS file_index_fts USING fts5(
+                    name, path, content='file_index', content_rowid='id', tokenize='trigram'
+                );
+                CREATE TRIGGER IF NOT EXISTS file_index_ai AFTER INSERT ON file_index BEGIN
+                    INSERT INTO file_index_fts(rowid, name, path) VALUES (new.id, new.name, new.path);
+                END;
+                CREATE TRIGGER IF NOT EXISTS file_index_ad AFTER DELETE ON file_index BEGIN
+                    INSERT INTO file_index_fts(file_index_fts, rowid, name, path)
+                    VALUES ('delete', old.id, old.name, old.path);
+                END;
+                CREATE TRIGGER IF NOT EXISTS file_index_au AFTER UPDATE ON file_index BEGIN
+                    INSERT INTO file_index_fts(file_index_fts, rowid, name, path)
+                    VALUES ('delete', old.id, old.name, old.path);
+                    INSERT INTO file_index_fts(rowid, name, path) VALUES (new.id, new.name, new.path);
+                END;
                 ",
             )
-            .map_err(|e| format!("Failed to initialize database: {e}"))
+            .map_err(|e| format!("Failed to initialize database: {e}"))?;
+
+        // Columns added after the initial schema
+        self.ensure_column("items", "hotkey", "TEXT")?;
+        self.ensure_column("slash_commands", "hotkey", "TEXT")?;
+        // Items owned by an indexer (e.g. discovered applications)
+        self.ensure_column("items", "source", "TEXT")?;
+        self.ensure_column("items", "source_key", "TEXT")?;
+        self.ensure_column("items", "user_modified", "INTEGER NOT NULL DEFAULT 0")?;
+        // Exit status of runs that wait for completion (workflow steps)
+        self.ensure_column("command_history", "exit_code", "INTEGER")?;
+        // Messages of agent turns that were cancelled, failed or are still running
+        self.ensure_column(
+            "conversation_messages",
+            "incomplete",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        // The agent session a conversation was last held in, to resume it
+        self.ensure_column("conversations", "agent_id", "TEXT")?;
+        self.ensure_column("conversations", "agent_session_id", "TEXT")?;
+        self.conn
+            .execute(
+                "CREATE UNIQUE INDEX IF NOT EXISTS idx_items_source ON items(source, source_key)",
+                [],
+            )
+            .map_err(|e| format!("Failed to initialize database: {e}"))?;
+        self.create_change_triggers()?;
+        // Rescans used to remove items without their actions and schedules
+        self.conn
+            .execute_batch(
+                "DELETE FROM item_actions WHERE item_id NOT IN (SELECT id FROM items);
+                 DELETE FROM schedules
+                 WHERE target_type = 'item' AND target_id NOT IN (SELECT id FROM items);",
+            )
+            .map_err(|e| format!("Failed to initialize database: {e}"))?;
+        if self.get_setting(PERMISSION_RULES_SEEDED_SETTING)?.is_none() {
+            self.reset_permission_rules()?;
+        }
+        Ok(())
+    }
+
+    /// Bump `table_versions` whenever a watched table changes, so "on change" schedules
+    /// can poll for edits. Usage counters and access times are not changes.
+    fn create_change_triggers(&self) -> Result<(), String> {
+        for table in WATCHED_TABLES {
+            let columns = match *table {
+                "items" => " OF title, subtitle, icon, action_type, action_value, category, tags, enabled, hotkey",
+                "slash_commands" => " OF name, description, script_path, hotkey",
+                "memory" => " OF key, value, context, memory_type, confidence",
+                _ => "",
+            };
+            let bump = format!(
+                "INSERT INTO table_versions (table_name, version) VALUES ('{table}', 1)
+                 ON CONFLICT(table_name) DO UPDATE SET version = version + 1;"
+            );
+            self.conn
+                .execute_batch(&format!(
+                    "CREATE TRIGGER IF NOT EXISTS {table}_version_ai AFTER INSERT ON {table} BEGIN {bump} END;
+                     CREATE TRIGGER IF NOT EXISTS {table}_version_ad AFTER DELETE ON {table} BEGIN {bump} END;
+                     CREATE TRIGGER IF NOT EXISTS {table}_version_au AFTER UPDATE{columns} ON {table} BEGIN {bump} END;"
+                ))
+                .map_err(|e| format!("Failed to initialize database: {e}"))?;
+        }
+        Ok(())
+    }
+
+    /// Add a column to an existing table if it is missing (for databases created by older versions).
+    fn ensure_column(&self, table: &str, column: &str, definition: &str) -> Result<(), String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("PRAGMA table_info({table})"))
+            .map_err(|e| format!("Failed to read schema of {table}: {e}"))?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, String>(1))
+            .map_err(|e| format!("Failed to read schema of {table}: {e}"))?
+            .collect::<SqlResult<Vec<String>>>()
+            .map_err(|e| format!("Failed to read schema of {table}: {e}"))?;
+
+        if !columns.iter().any(|c| c == column) {
+            self.conn
+                .execute(
+                    &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+                    [],
+                )
+                .map_err(|e| format!("Failed to migrate {table}.{column}: {e}"))?;
+        }
+        Ok(())
     }
 
     pub fn add_item(&self, item: NewItem) -> Result<Item, String> {
         let id = Uuid::new_v4().to_string();
         let category = item.category.unwrap_or_else(|| "General".to_string());
         let tags = item.tags.unwrap_or_default();
+        let hotkey = match item.hotkey.as_deref() {
+            Some(h) if !h.trim().is_empty() => Some(self.validate_hotkey(h, None)?),
+            _ => None,
+        };
+        if item.action_type == SNIPPET_ACTION_TYPE {
+            validate_snippet(&item.action_value)?;
+        }
+        if item.action_type == WORKFLOW_ACTION_TYPE {
+            parse_workflow(&item.action_value)?;
+        }
 
         self.conn
             .execute(
-                "INSERT INTO items (id, title, subtitle, icon, action_type, action_value, category, tags)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                "INSERT INTO items (id, title, subtitle, icon, action_type, action_value, category, tags, hotkey)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
                 params![
                     id,
                     item.title,
@@ -159,6 +432,7 @@ impl Database {
                     item.action_value,
                     category,
                     tags,
+                    hotkey,
                 ],
             )
             .map_err(|e| format!("Failed to add item: {e}"))?;
@@ -169,24 +443,9 @@ impl Database {
     pub fn get_item(&self, id: &str) -> Result<Item, String> {
         self.conn
             .query_row(
-                "SELECT id, title, subtitle, icon, action_type, action_value, category, tags, frequency, enabled, created_at, updated_at FROM items WHERE id = ?1",
+                "SELECT id, title, subtitle, icon, action_type, action_value, category, tags, frequency, enabled, created_at, updated_at, hotkey, source FROM items WHERE id = ?1",
                 params![id],
-                |row| {
-                    Ok(Item {
-                        id: row.get(0)?,
-                        title: row.get(1)?,
-                        subtitle: row.get(2)?,
-                        icon: row.get(3)?,
-                        action_type: row.get(4)?,
-                        action_value: row.get(5)?,
-                        category: row.get(6)?,
-                        tags: row.get(7)?,
-                        frequency: row.get(8)?,
-                        enabled: row.get::<_, i64>(9)? != 0,
-                        created_at: row.get(10)?,
-                        updated_at: row.get(11)?,
-                    })
-                },
+                Self::row_to_item,
             )
             .map_err(|e| format!("Item not found: {e}"))
     }
@@ -194,19 +453,42 @@ impl Database {
     pub fn update_item(&self, id: &str, update: UpdateItem) -> Result<Item, String> {
         let current = self.get_item(id)?;
 
-        let title = update.title.unwrap_or(current.title);
-        let subtitle = update.subtitle.or(current.subtitle);
-        let icon = update.icon.or(current.icon);
-        let action_type = update.action_type.unwrap_or(current.action_type);
-        let action_value = update.action_value.unwrap_or(current.action_value);
-        let category = update.category.unwrap_or(current.category);
-        let tags = update.tags.unwrap_or(current.tags);
+        let title = update.title.unwrap_or_else(|| current.title.clone());
+        let subtitle = update.subtitle.or_else(|| current.subtitle.clone());
+        let icon = update.icon.or_else(|| current.icon.clone());
+        let action_type = update
+            .action_type
+            .unwrap_or_else(|| current.action_type.clone());
+        let action_value = update
+            .action_value
+            .unwrap_or_else(|| current.action_value.clone());
+        let category = update.category.unwrap_or_else(|| current.category.clone());
+        let tags = update.tags.unwrap_or_else(|| current.tags.clone());
         let enabled = update.enabled.unwrap_or(current.enabled);
+        let hotkey = match update.hotkey.as_deref() {
+            Some(h) if h.trim().is_empty() => None,
+            Some(h) => Some(self.validate_hotkey(h, Some(id))?),
+            None => current.hotkey,
+        };
+        if action_type == SNIPPET_ACTION_TYPE {
+            validate_snippet(&action_value)?;
+        }
+        if action_type == WORKFLOW_ACTION_TYPE {
+            parse_workflow(&action_value)?;
+        }
+        // Edits to fields an indexer maintains stop it from overwriting them on the next sync.
+        let user_modified = title != current.title
+            || subtitle != current.subtitle
+            || icon != current.icon
+            || action_type != current.action_type
+            || action_value != current.action_value
+            || category != current.category
+            || tags != current.tags;
 
         self.conn
             .execute(
-                "UPDATE items SET title = ?1, subtitle = ?2, icon = ?3, action_type = ?4, action_value = ?5, category = ?6, tags = ?7, enabled = ?8, updated_at = datetime('now') WHERE id = ?9",
-                params![title, subtitle, icon, action_type, action_value, category, tags, enabled as i64, id],
+                "UPDATE items SET title = ?1, subtitle = ?2, icon = ?3, action_type = ?4, action_value = ?5, category = ?6, tags = ?7, enabled = ?8, hotkey = ?9, user_modified = user_modified OR ?10, updated_at = datetime('now') WHERE id = ?11",
+                params![title, subtitle, icon, action_type, action_value, category, tags, enabled as i64, hotkey, user_modified, id],
             )
             .map_err(|e| format!("Failed to update item: {e}"))?;
 
@@ -214,10 +496,21 @@ impl Database {
     }
 
     pub fn remove_item(&self, id: &str) -> Result<bool, String> {
-        let rows = self
-            .conn
+        Self::delete_item(&self.conn, id)
+    }
+
+    /// Delete an item with its actions and schedules.
+    fn delete_item(conn: &Connection, id: &str) -> Result<bool, String> {
+        let rows = conn
             .execute("DELETE FROM items WHERE id = ?1", params![id])
             .map_err(|e| format!("Failed to remove item: {e}"))?;
+        conn.execute("DELETE FROM item_actions WHERE item_id = ?1", params![id])
+            .map_err(|e| format!("Failed to remove item actions: {e}"))?;
+        conn.execute(
+            "DELETE FROM schedules WHERE target_type = 'item' AND target_id = ?1",
+            params![id],
+        )
+        .map_err(|e| format!("Failed to remove item schedules: {e}"))?;
         Ok(rows > 0)
     }
 
@@ -226,7 +519,7 @@ impl Database {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT id, title, subtitle, icon, action_type, action_value, category, tags, frequency, enabled, created_at, updated_at
+                "SELECT id, title, subtitle, icon, action_type, action_value, category, tags, frequency, enabled, created_at, updated_at, hotkey, source
                  FROM items
                  WHERE enabled = 1 AND (title LIKE ?1 OR subtitle LIKE ?1 OR tags LIKE ?1 OR category LIKE ?1)
                  ORDER BY frequency DESC, title ASC",
@@ -234,22 +527,7 @@ impl Database {
             .map_err(|e| format!("Failed to prepare query: {e}"))?;
 
         let items = stmt
-            .query_map(params![pattern], |row| {
-                Ok(Item {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    subtitle: row.get(2)?,
-                    icon: row.get(3)?,
-                    action_type: row.get(4)?,
-                    action_value: row.get(5)?,
-                    category: row.get(6)?,
-                    tags: row.get(7)?,
-                    frequency: row.get(8)?,
-                    enabled: row.get::<_, i64>(9)? != 0,
-                    created_at: row.get(10)?,
-                    updated_at: row.get(11)?,
-                })
-            })
+            .query_map(params![pattern], Self::row_to_item)
             .map_err(|e| format!("Failed to execute query: {e}"))?
             .collect::<SqlResult<Vec<Item>>>()
             .map_err(|e| format!("Failed to collect results: {e}"))?;
@@ -264,19 +542,19 @@ impl Database {
     ) -> Result<Vec<Item>, String> {
         let sql = match (category, include_disabled) {
             (Some(_), false) => {
-                "SELECT id, title, subtitle, icon, action_type, action_value, category, tags, frequency, enabled, created_at, updated_at
+                "SELECT id, title, subtitle, icon, action_type, action_value, category, tags, frequency, enabled, created_at, updated_at, hotkey, source
                  FROM items WHERE category = ?1 AND enabled = 1 ORDER BY frequency DESC, title ASC"
             }
             (Some(_), true) => {
-                "SELECT id, title, subtitle, icon, action_type, action_value, category, tags, frequency, enabled, created_at, updated_at
+                "SELECT id, title, subtitle, icon, action_type, action_value, category, tags, frequency, enabled, created_at, updated_at, hotkey, source
                  FROM items WHERE category = ?1 ORDER BY frequency DESC, title ASC"
             }
             (None, false) => {
-                "SELECT id, title, subtitle, icon, action_type, action_value, category, tags, frequency, enabled, created_at, updated_at
+                "SELECT id, title, subtitle, icon, action_type, action_value, category, tags, frequency, enabled, created_at, updated_at, hotkey, source
                  FROM items WHERE enabled = 1 ORDER BY frequency DESC, title ASC"
             }
             (None, true) => {
-                "SELECT id, title, subtitle, icon, action_type, action_value, category, tags, frequency, enabled, created_at, updated_at
+                "SELECT id, title, subtitle, icon, action_type, action_value, category, tags, frequency, enabled, created_at, updated_at, hotkey, source
                  FROM items ORDER BY frequency DESC, title ASC"
             }
         };
@@ -331,8 +609,256 @@ impl Database {
         Ok(imported)
     }
 
+    /// Export user-created items. Indexer-managed items are left out since a rescan
+    /// recreates them.
     pub fn export_items(&self) -> Result<Vec<Item>, String> {
-        self.list_items(None, true)
+        Ok(self
+            .list_items(None, true)?
+            .into_iter()
+            .filter(|item| item.source.is_none())
+            .collect())
+    }
+
+    /// Make the items of `source` match `items`: new keys are added, unedited items are
+    /// refreshed and keys that are no longer present are removed. Items the user edited
+    /// keep their fields; frequency, hotkey and enabled state are never touched.
+    pub fn sync_managed_items(
+        &self,
+        source: &str,
+        items: Vec<ManagedItem>,
+    ) -> Result<SyncReport, String> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed to start transaction: {e}"))?;
+
+        let mut existing: HashMap<String, (String, bool, ManagedItem)> = HashMap::new();
+        {
+            let mut stmt = tx
+                .prepare(
+                    "SELECT id, source_key, user_modified, title, subtitle, icon, action_type, action_value, category, tags
+                     FROM items WHERE source = ?1",
+                )
+                .map_err(|e| format!("Failed to prepare query: {e}"))?;
+            let rows = stmt
+                .query_map(params![source], |row| {
+                    let key: String = row.get(1)?;
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, i64>(2)? != 0,
+                        ManagedItem {
+                            source_key: key,
+                            title: row.get(3)?,
+                            subtitle: row.get(4)?,
+                            icon: row.get(5)?,
+                            action_type: row.get(6)?,
+                            action_value: row.get(7)?,
+                            category: row.get(8)?,
+                            tags: row.get(9)?,
+                        },
+                    ))
+                })
+                .map_err(|e| format!("Failed to execute query: {e}"))?
+                .collect::<SqlResult<Vec<_>>>()
+                .map_err(|e| format!("Failed to collect results: {e}"))?;
+            for (id, modified, item) in rows {
+                existing.insert(item.source_key.clone(), (id, modified, item));
+            }
+        }
+
+        let mut report = SyncReport::default();
+        for item in items {
+            match existing.remove(&item.source_key) {
+                None => {
+                    tx.execute(
+                        "INSERT INTO items (id, title, subtitle, icon, action_type, action_value, category, tags, source, source_key)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                        params![
+                            Uuid::new_v4().to_string(),
+                            item.title,
+                            item.subtitle,
+                            item.icon,
+                            item.action_type,
+                            item.action_value,
+                            item.category,
+                            item.tags,
+                            source,
+                            item.source_key,
+                        ],
+                    )
+                    .map_err(|e| format!("Failed to add item: {e}"))?;
+                    report.added += 1;
+                }
+                Some((_, true, _)) => report.preserved += 1,
+                Some((_, false, current))
+                    if current.title == item.title
+                        && current.subtitle == item.subtitle
+                        && current.icon == item.icon
+                        && current.action_type == item.action_type
+                        && current.action_value == item.action_value
+                        && current.category == item.category
+                        && current.tags == item.tags =>
+                {
+                    report.unchanged += 1
+                }
+                Some((id, false, _)) => {
+                    tx.execute(
+                        "UPDATE items SET title = ?1, subtitle = ?2, icon = ?3, action_type = ?4, action_value = ?5, category = ?6, tags = ?7, updated_at = datetime('now') WHERE id = ?8",
+                        params![
+                            item.title,
+                            item.subtitle,
+                            item.icon,
+                            item.action_type,
+                            item.action_value,
+                            item.category,
+                            item.tags,
+                            id,
+                        ],
+                    )
+                    .map_err(|e| format!("Failed to update item: {e}"))?;
+                    report.updated += 1;
+                }
+            }
+        }
+
+        // Whatever is left is no longer provided by the source (e.g. uninstalled apps).
+        for (id, _, _) in existing.into_values() {
+            Self::delete_item(&tx, &id)?;
+            report.removed += 1;
+        }
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+        Ok(report)
+    }
+
+    // --- Item actions ---
+
+    pub fn add_item_action(&self, action: NewItemAction) -> Result<ItemAction, String> {
+        let item = self.get_item(&action.item_id)?;
+        if !ITEM_ACTION_TYPES.contains(&action.action_type.as_str()) {
+            return Err(format!(
+                "Unknown action type '{}' (use one of: {})",
+                action.action_type,
+                ITEM_ACTION_TYPES.join(", ")
+            ));
+        }
+        if action.label.trim().is_empty() {
+            return Err("Action label cannot be empty".to_string());
+        }
+        if action.action_type == SNIPPET_ACTION_TYPE {
+            validate_snippet(&action.value.replace("{value}", ""))?;
+        }
+
+        let shortcut = match action.shortcut.as_deref() {
+            Some(s) if !s.trim().is_empty() => {
+                let normalized = normalize_hotkey(s)?;
+                let taken = self.list_item_actions(&item.id)?.into_iter().find(|a| {
+                    a.shortcut
+                        .as_deref()
+                        .is_some_and(|s| hotkeys_equal(s, &normalized))
+                });
+                if let Some(existing) = taken {
+                    return Err(format!(
+                        "Shortcut {normalized} is already used by action '{}'",
+                        existing.label
+                    ));
+                }
+                Some(normalized)
+            }
+            _ => None,
+        };
+
+        let position = match action.position {
+            Some(position) => position,
+            None => self
+                .conn
+                .query_row(
+                    "SELECT COALESCE(MAX(position) + 1, 0) FROM item_actions WHERE item_id = ?1",
+                    params![item.id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to add item action: {e}"))?,
+        };
+
+        let id = Uuid::new_v4().to_string();
+        self.conn
+            .execute(
+                "INSERT INTO item_actions (id, item_id, label, action_type, value, shortcut, position)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    id,
+                    item.id,
+                    action.label.trim(),
+                    action.action_type,
+                    action.value,
+                    shortcut,
+                    position
+                ],
+            )
+            .map_err(|e| format!("Failed to add item action: {e}"))?;
+
+        self.get_item_action(&id)
+    }
+
+    pub fn get_item_action(&self, id: &str) -> Result<ItemAction, String> {
+        self.conn
+            .query_row(
+                "SELECT id, item_id, label, action_type, value, shortcut, position, created_at
+                 FROM item_actions WHERE id = ?1",
+                params![id],
+                Self::row_to_item_action,
+            )
+            .map_err(|e| format!("Item action not found: {e}"))
+    }
+
+    /// The item's custom actions, in order.
+    pub fn list_item_actions(&self, item_id: &str) -> Result<Vec<ItemAction>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, item_id, label, action_type, value, shortcut, position, created_at
+                 FROM item_actions WHERE item_id = ?1
+                 ORDER BY position ASC, created_at ASC",
+            )
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+
+        let actions = stmt
+            .query_map(params![item_id], Self::row_to_item_action)
+            .map_err(|e| format!("Failed to execute query: {e}"))?
+            .collect::<SqlResult<Vec<ItemAction>>>()
+            .map_err(|e| format!("Failed to collect results: {e}"))?;
+
+        Ok(actions)
+    }
+
+    /// Every action available on an item: its custom actions, then the built-in ones.
+    pub fn list_all_item_actions(&self, item_id: &str) -> Result<Vec<ItemAction>, String> {
+        let mut actions = self.list_item_actions(item_id)?;
+        actions.extend(builtin_item_actions(item_id));
+        Ok(actions)
+    }
+
+    pub fn remove_item_action(&self, id: &str) -> Result<bool, String> {
+        let rows = self
+            .conn
+            .execute("DELETE FROM item_actions WHERE id = ?1", params![id])
+            .map_err(|e| format!("Failed to remove item action: {e}"))?;
+        Ok(rows > 0)
+    }
+
+    fn row_to_item_action(row: &rusqlite::Row) -> rusqlite::Result<ItemAction> {
+        Ok(ItemAction {
+            id: row.get(0)?,
+            item_id: row.get(1)?,
+            label: row.get(2)?,
+            action_type: row.get(3)?,
+            value: row.get(4)?,
+            shortcut: row.get(5)?,
+            position: row.get(6)?,
+            builtin: false,
+            created_at: row.get(7)?,
+        })
     }
 
     // --- Settings CRUD ---
@@ -396,14 +922,15 @@ impl Database {
 
         self.conn
             .execute(
-                "INSERT INTO command_history (id, item_id, command_text, action_type, source)
-                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                "INSERT INTO command_history (id, item_id, command_text, action_type, source, exit_code)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
                 params![
                     id,
                     entry.item_id,
                     entry.command_text,
                     entry.action_type,
-                    source
+                    source,
+                    entry.exit_code
                 ],
             )
             .map_err(|e| format!("Failed to record command: {e}"))?;
@@ -411,22 +938,25 @@ impl Database {
         self.get_command_history_entry(&id)
     }
 
+    fn row_to_command_history(row: &rusqlite::Row) -> rusqlite::Result<CommandHistory> {
+        Ok(CommandHistory {
+            id: row.get(0)?,
+            item_id: row.get(1)?,
+            command_text: row.get(2)?,
+            action_type: row.get(3)?,
+            executed_at: row.get(4)?,
+            source: row.get(5)?,
+            exit_code: row.get(6)?,
+        })
+    }
+
     fn get_command_history_entry(&self, id: &str) -> Result<CommandHistory, String> {
         self.conn
             .query_row(
-                "SELECT id, item_id, command_text, action_type, executed_at, source
+                "SELECT id, item_id, command_text, action_type, executed_at, source, exit_code
                  FROM command_history WHERE id = ?1",
                 params![id],
-                |row| {
-                    Ok(CommandHistory {
-                        id: row.get(0)?,
-                        item_id: row.get(1)?,
-                        command_text: row.get(2)?,
-                        action_type: row.get(3)?,
-                        executed_at: row.get(4)?,
-                        source: row.get(5)?,
-                    })
-                },
+                Self::row_to_command_history,
             )
             .map_err(|e| format!("Command history entry not found: {e}"))
     }
@@ -436,7 +966,7 @@ impl Database {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT id, item_id, command_text, action_type, executed_at, source
+                "SELECT id, item_id, command_text, action_type, executed_at, source, exit_code
                  FROM command_history
                  WHERE command_text LIKE ?1
                  ORDER BY executed_at DESC
@@ -445,16 +975,33 @@ impl Database {
             .map_err(|e| format!("Failed to prepare query: {e}"))?;
 
         let entries = stmt
-            .query_map(params![pattern], |row| {
-                Ok(CommandHistory {
-                    id: row.get(0)?,
-                    item_id: row.get(1)?,
-                    command_text: row.get(2)?,
-                    action_type: row.get(3)?,
-                    executed_at: row.get(4)?,
-                    source: row.get(5)?,
-                })
-            })
+            .query_map(params![pattern], Self::row_to_command_history)
+            .map_err(|e| format!("Failed to execute query: {e}"))?
+            .collect::<SqlResult<Vec<CommandHistory>>>()
+            .map_err(|e| format!("Failed to collect results: {e}"))?;
+
+        Ok(entries)
+    }
+
+    /// Most recent executions of one item.
+    pub fn get_item_history(
+        &self,
+        item_id: &str,
+        limit: usize,
+    ) -> Result<Vec<CommandHistory>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, item_id, command_text, action_type, executed_at, source, exit_code
+                 FROM command_history
+                 WHERE item_id = ?1
+                 ORDER BY executed_at DESC
+                 LIMIT ?2",
+            )
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+
+        let entries = stmt
+            .query_map(params![item_id, limit as i64], Self::row_to_command_history)
             .map_err(|e| format!("Failed to execute query: {e}"))?
             .collect::<SqlResult<Vec<CommandHistory>>>()
             .map_err(|e| format!("Failed to collect results: {e}"))?;
@@ -466,7 +1013,7 @@ impl Database {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT id, item_id, command_text, action_type, executed_at, source
+                "SELECT id, item_id, command_text, action_type, executed_at, source, exit_code
                  FROM command_history
                  ORDER BY executed_at DESC
                  LIMIT ?1",
@@ -474,16 +1021,7 @@ impl Database {
             .map_err(|e| format!("Failed to prepare query: {e}"))?;
 
         let entries = stmt
-            .query_map(params![limit as i64], |row| {
-                Ok(CommandHistory {
-                    id: row.get(0)?,
-                    item_id: row.get(1)?,
-                    command_text: row.get(2)?,
-                    action_type: row.get(3)?,
-                    executed_at: row.get(4)?,
-                    source: row.get(5)?,
-                })
-            })
+            .query_map(params![limit as i64], Self::row_to_command_history)
             .map_err(|e| format!("Failed to execute query: {e}"))?
             .collect::<SqlResult<Vec<CommandHistory>>>()
             .map_err(|e| format!("Failed to collect results: {e}"))?;
@@ -821,7 +1359,7 @@ impl Database {
             .prepare(
                 "SELECT c.id, c.title, c.created_at, c.updated_at,
                         (SELECT COUNT(*) FROM conversation_messages WHERE conversation_id = c.id) as message_count,
-                        (SELECT content FROM conversation_messages WHERE conversation_id = c.id ORDER BY created_at DESC LIMIT 1) as last_message_preview
+                        (SELECT content FROM conversation_messages WHERE conversation_id = c.id AND role IN ('user', 'assistant') ORDER BY created_at DESC, rowid DESC LIMIT 1) as last_message_preview
                  FROM conversations c
                  ORDER BY c.updated_at DESC
                  LIMIT ?1",
@@ -856,7 +1394,7 @@ impl Database {
             .prepare(
                 "SELECT DISTINCT c.id, c.title, c.created_at, c.updated_at,
                         (SELECT COUNT(*) FROM conversation_messages WHERE conversation_id = c.id) as message_count,
-                        (SELECT content FROM conversation_messages WHERE conversation_id = c.id ORDER BY created_at DESC LIMIT 1) as last_message_preview
+                        (SELECT content FROM conversation_messages WHERE conversation_id = c.id AND role IN ('user', 'assistant') ORDER BY created_at DESC, rowid DESC LIMIT 1) as last_message_preview
                  FROM conversations c
                  LEFT JOIN conversation_messages m ON m.conversation_id = c.id
                  WHERE c.title LIKE ?1 OR m.content LIKE ?1
@@ -884,13 +1422,26 @@ impl Database {
     }
 
     pub fn delete_conversation(&self, id: &str) -> Result<bool, String> {
-        // Delete messages first (no FK cascade without pragma)
+        // Delete messages and tool calls first (no FK cascade without pragma)
         self.conn
             .execute(
                 "DELETE FROM conversation_messages WHERE conversation_id = ?1",
                 params![id],
             )
             .map_err(|e| format!("Failed to delete conversation messages: {e}"))?;
+        self.conn
+            .execute(
+                "DELETE FROM tool_call_statuses WHERE tool_call_id IN
+                 (SELECT id FROM tool_calls WHERE conversation_id = ?1)",
+                params![id],
+            )
+            .map_err(|e| format!("Failed to delete conversation tool calls: {e}"))?;
+        self.conn
+            .execute(
+                "DELETE FROM tool_calls WHERE conversation_id = ?1",
+                params![id],
+            )
+            .map_err(|e| format!("Failed to delete conversation tool calls: {e}"))?;
         let rows = self
             .conn
             .execute("DELETE FROM conversations WHERE id = ?1", params![id])
@@ -898,6 +1449,39 @@ impl Database {
         Ok(rows > 0)
     }
 
+    /// The agent and ACP session a conversation was last held in.
+    pub fn conversation_agent_session(
+        &self,
+        conversation_id: &str,
+    ) -> Result<Option<(String, String)>, String> {
+        self.conn
+            .query_row(
+                "SELECT agent_id, agent_session_id FROM conversations WHERE id = ?1",
+                params![conversation_id],
+                |row| {
+                    Ok(row
+                        .get::<_, Option<String>>(0)?
+                        .zip(row.get::<_, Option<String>>(1)?))
+                },
+            )
+            .map_err(|e| format!("Conversation not found: {e}"))
+    }
+
+    pub fn set_conversation_agent_session(
+        &self,
+        conversation_id: &str,
+        agent_id: &str,
+        session_id: &str,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE conversations SET agent_id = ?2, agent_session_id = ?3 WHERE id = ?1",
+                params![conversation_id, agent_id, session_id],
+            )
+            .map_err(|e| format!("Failed to save agent session: {e}"))?;
+        Ok(())
+    }
+
     pub fn touch_conversation(&self, id: &str) -> Result<(), String> {
         self.conn
             .execute(
@@ -915,9 +1499,15 @@ impl Database {
         let id = Uuid::new_v4().to_string();
         self.conn
             .execute(
-                "INSERT INTO conversation_messages (id, conversation_id, role, content)
-                 VALUES (?1, ?2, ?3, ?4)",
-                params![id, msg.conversation_id, msg.role, msg.content],
+                "INSERT INTO conversation_messages (id, conversation_id, role, content, incomplete)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    id,
+                    msg.conversation_id,
+                    msg.role,
+                    msg.content,
+                    msg.incomplete
+                ],
             )
             .map_err(|e| format!("Failed to add conversation message: {e}"))?;
 
@@ -926,47 +1516,128 @@ impl Database {
 
         self.conn
             .query_row(
-                "SELECT id, conversation_id, role, content, created_at
+                "SELECT id, conversation_id, role, content, incomplete, created_at
                  FROM conversation_messages WHERE id = ?1",
                 params![id],
-                |row| {
-                    Ok(ConversationMessage {
-                        id: row.get(0)?,
-                        conversation_id: row.get(1)?,
-                        role: row.get(2)?,
-                        content: row.get(3)?,
-                        created_at: row.get(4)?,
-                    })
-                },
+                Self::row_to_conversation_message,
             )
             .map_err(|e| format!("Conversation message not found: {e}"))
     }
 
-    pub fn get_conversation_messages(
+    /// Add a message just before `before_id` in its conversation, for a message that
+    /// was missed when it happened (an answer replayed by a resumed agent session).
+    pub fn insert_conversation_message_before(
         &self,
-        conversation_id: &str,
-    ) -> Result<Vec<ConversationMessage>, String> {
-        let mut stmt = self
+        msg: NewConversationMessage,
+        before_id: &str,
+    ) -> Result<ConversationMessage, String> {
+        let tx = self
             .conn
-            .prepare(
-                "SELECT id, conversation_id, role, content, created_at
-                 FROM conversation_messages
-                 WHERE conversation_id = ?1
-                 ORDER BY created_at ASC",
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed to start transaction: {e}"))?;
+        let (created_at, rowid): (String, i64) = tx
+            .query_row(
+                "SELECT created_at, rowid FROM conversation_messages WHERE id = ?1",
+                params![before_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
             )
-            .map_err(|e| format!("Failed to prepare query: {e}"))?;
-
-        let messages = stmt
-            .query_map(params![conversation_id], |row| {
-                Ok(ConversationMessage {
-                    id: row.get(0)?,
-                    conversation_id: row.get(1)?,
-                    role: row.get(2)?,
-                    content: row.get(3)?,
-                    created_at: row.get(4)?,
+            .map_err(|e| format!("Conversation message not found: {e}"))?;
+        let id = Uuid::new_v4().to_string();
+        tx.execute(
+            "INSERT INTO conversation_messages (id, conversation_id, role, content, incomplete, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                id,
+                msg.conversation_id,
+                msg.role,
+                msg.content,
+                msg.incomplete,
+                created_at
+            ],
+        )
+        .map_err(|e| format!("Failed to add conversation message: {e}"))?;
+        // Messages sort by (created_at, rowid); moving the later ones to new rowids puts
+        // the inserted message ahead of them.
+        let later: Vec<String> = {
+            let mut stmt = tx
+                .prepare(
+                    "SELECT id FROM conversation_messages
+                     WHERE conversation_id = ?1 AND id != ?2
+                       AND (created_at > ?3 OR (created_at = ?3 AND rowid >= ?4))
+                     ORDER BY created_at ASC, rowid ASC",
+                )
+                .map_err(|e| format!("Failed to prepare query: {e}"))?;
+            let ids = stmt
+                .query_map(params![msg.conversation_id, id, created_at, rowid], |row| {
+                    row.get(0)
                 })
-            })
-            .map_err(|e| format!("Failed to execute query: {e}"))?
+                .map_err(|e| format!("Failed to execute query: {e}"))?
+                .collect::<SqlResult<Vec<String>>>()
+                .map_err(|e| format!("Failed to collect results: {e}"))?;
+            ids
+        };
+        for later_id in later {
+            tx.execute(
+                "UPDATE conversation_messages
+                 SET rowid = (SELECT MAX(rowid) + 1 FROM conversation_messages) WHERE id = ?1",
+                params![later_id],
+            )
+            .map_err(|e| format!("Failed to add conversation message: {e}"))?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+
+        self.conn
+            .query_row(
+                "SELECT id, conversation_id, role, content, incomplete, created_at
+                 FROM conversation_messages WHERE id = ?1",
+                params![id],
+                Self::row_to_conversation_message,
+            )
+            .map_err(|e| format!("Conversation message not found: {e}"))
+    }
+
+    /// Replace the content of a message that is still being written (an agent answer).
+    pub fn update_conversation_message(&self, id: &str, content: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE conversation_messages SET content = ?2 WHERE id = ?1",
+                params![id, content],
+            )
+            .map_err(|e| format!("Failed to update conversation message: {e}"))?;
+        Ok(())
+    }
+
+    /// Mark the messages of a turn the agent finished as complete.
+    pub fn complete_conversation_messages(&self, ids: &[String]) -> Result<(), String> {
+        for id in ids {
+            self.conn
+                .execute(
+                    "UPDATE conversation_messages SET incomplete = 0 WHERE id = ?1",
+                    params![id],
+                )
+                .map_err(|e| format!("Failed to complete conversation message: {e}"))?;
+        }
+        Ok(())
+    }
+
+    pub fn get_conversation_messages(
+        &self,
+        conversation_id: &str,
+    ) -> Result<Vec<ConversationMessage>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, conversation_id, role, content, incomplete, created_at
+                 FROM conversation_messages
+                 WHERE conversation_id = ?1
+                 ORDER BY created_at ASC, rowid ASC",
+            )
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+
+        let messages = stmt
+            .query_map(params![conversation_id], Self::row_to_conversation_message)
+            .map_err(|e| format!("Failed to execute query: {e}"))?
             .collect::<SqlResult<Vec<ConversationMessage>>>()
             .map_err(|e| format!("Failed to collect results: {e}"))?;
 
@@ -982,7 +1653,7 @@ impl Database {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT id, conversation_id, role, content, created_at
+                "SELECT id, conversation_id, role, content, incomplete, created_at
                  FROM conversation_messages
                  WHERE content LIKE ?1
                  ORDER BY created_at DESC
@@ -991,15 +1662,10 @@ impl Database {
             .map_err(|e| format!("Failed to prepare query: {e}"))?;
 
         let messages = stmt
-            .query_map(params![pattern, limit as i64], |row| {
-                Ok(ConversationMessage {
-                    id: row.get(0)?,
-                    conversation_id: row.get(1)?,
-                    role: row.get(2)?,
-                    content: row.get(3)?,
-                    created_at: row.get(4)?,
-                })
-            })
+            .query_map(
+                params![pattern, limit as i64],
+                Self::row_to_conversation_message,
+            )
             .map_err(|e| format!("Failed to execute query: {e}"))?
             .collect::<SqlResult<Vec<ConversationMessage>>>()
             .map_err(|e| format!("Failed to collect results: {e}"))?;
@@ -1020,24 +1686,16 @@ impl Database {
             let mut stmt = self
                 .conn
                 .prepare(
-                    "SELECT id, conversation_id, role, content, created_at
+                    "SELECT id, conversation_id, role, content, incomplete, created_at
                      FROM conversation_messages
-                     WHERE conversation_id = ?1
-                     ORDER BY created_at DESC
+                     WHERE conversation_id = ?1 AND role IN ('user', 'assistant')
+                     ORDER BY created_at DESC, rowid DESC
                      LIMIT 5",
                 )
                 .map_err(|e| format!("Failed to prepare query: {e}"))?;
 
             let mut messages: Vec<ConversationMessage> = stmt
-                .query_map(params![preview.id], |row| {
-                    Ok(ConversationMessage {
-                        id: row.get(0)?,
-                        conversation_id: row.get(1)?,
-                        role: row.get(2)?,
-                        content: row.get(3)?,
-                        created_at: row.get(4)?,
-                    })
-                })
+                .query_map(params![preview.id], Self::row_to_conversation_message)
                 .map_err(|e| format!("Failed to execute query: {e}"))?
                 .collect::<SqlResult<Vec<ConversationMessage>>>()
                 .map_err(|e| format!("Failed to collect results: {e}"))?;
@@ -1050,6 +1708,17 @@ impl Database {
         Ok(result)
     }
 
+    fn row_to_conversation_message(row: &rusqlite::Row) -> rusqlite::Result<ConversationMessage> {
+        Ok(ConversationMessage {
+            id: row.get(0)?,
+            conversation_id: row.get(1)?,
+            role: row.get(2)?,
+            content: row.get(3)?,
+            incomplete: row.get(4)?,
+            created_at: row.get(5)?,
+        })
+    }
+
     fn row_to_memory(row: &rusqlite::Row) -> rusqlite::Result<Memory> {
         Ok(Memory {
             id: row.get(0)?,
@@ -1078,6 +1747,166 @@ impl Database {
             enabled: row.get::<_, i64>(9)? != 0,
             created_at: row.get(10)?,
             updated_at: row.get(11)?,
+            hotkey: row.get(12)?,
+            source: row.get(13)?,
+        })
+    }
+
+    // --- Tool calls ---
+
+    /// Record a tool call or a change to it. Missing fields keep their stored value;
+    /// a new status is added to the call's status history.
+    pub fn record_tool_call(
+        &self,
+        conversation_id: &str,
+        tool_call_id: &str,
+        title: Option<&str>,
+        kind: Option<&str>,
+        status: Option<&str>,
+    ) -> Result<(), String> {
+        let id = self.ensure_tool_call(conversation_id, tool_call_id)?;
+        self.conn
+            .execute(
+                "UPDATE tool_calls SET title = COALESCE(?1, title), kind = COALESCE(?2, kind)
+                 WHERE id = ?3",
+                params![title, kind, id],
+            )
+            .map_err(|e| format!("Failed to record tool call: {e}"))?;
+        let Some(status) = status else {
+            return Ok(());
+        };
+        let changed = self
+            .conn
+            .execute(
+                "UPDATE tool_calls SET status = ?1 WHERE id = ?2 AND status IS NOT ?1",
+                params![status, id],
+            )
+            .map_err(|e| format!("Failed to record tool call: {e}"))?;
+        if changed > 0 {
+            self.conn
+                .execute(
+                    "INSERT INTO tool_call_statuses (tool_call_id, status) VALUES (?1, ?2)",
+                    params![id, status],
+                )
+                .map_err(|e| format!("Failed to record tool call: {e}"))?;
+        }
+        Ok(())
+    }
+
+    /// Record the permission request of a tool call and how it was answered.
+    pub fn record_tool_call_permission(
+        &self,
+        conversation_id: &str,
+        tool_call_id: &str,
+        permission: &ToolCallPermission,
+    ) -> Result<(), String> {
+        let id = self.ensure_tool_call(conversation_id, tool_call_id)?;
+        let chosen = permission.chosen_option_id.as_ref().and_then(|chosen| {
+            permission
+                .options
+                .iter()
+                .find(|option| &option.option_id == chosen)
+        });
+        let auto_allowed = matches!(permission.decided_by.as_str(), "rule" | "remembered")
+            && chosen.is_some_and(|option| option.kind.to_lowercase().starts_with("allow"));
+        let options = serde_json::to_string(&permission.options)
+            .map_err(|e| format!("Failed to encode permission options: {e}"))?;
+        self.conn
+            .execute(
+                "UPDATE tool_calls SET command_preview = COALESCE(?1, command_preview),
+                 permission_options = ?2, chosen_option = ?3, decided_by = ?4, auto_allowed = ?5
+                 WHERE id = ?6",
+                params![
+                    permission.command_preview,
+                    options,
+                    chosen.map(|option| option.name.clone()),
+                    permission.decided_by,
+                    auto_allowed as i64,
+                    id
+                ],
+            )
+            .map_err(|e| format!("Failed to record tool call permission: {e}"))?;
+        Ok(())
+    }
+
+    /// Tool calls of a conversation in the order they were made.
+    pub fn list_tool_calls(&self, conversation_id: &str) -> Result<Vec<ToolCallRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, conversation_id, tool_call_id, title, kind, status, command_preview,
+                        permission_options, chosen_option, decided_by, auto_allowed, created_at
+                 FROM tool_calls WHERE conversation_id = ?1 ORDER BY created_at ASC, rowid ASC",
+            )
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+        let mut calls = stmt
+            .query_map(params![conversation_id], Self::row_to_tool_call)
+            .map_err(|e| format!("Failed to execute query: {e}"))?
+            .collect::<SqlResult<Vec<ToolCallRecord>>>()
+            .map_err(|e| format!("Failed to collect results: {e}"))?;
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT status, created_at FROM tool_call_statuses
+                 WHERE tool_call_id = ?1 ORDER BY id ASC",
+            )
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+        for call in &mut calls {
+            call.statuses = stmt
+                .query_map(params![call.id], |row| {
+                    Ok(ToolCallStatus {
+                        status: row.get(0)?,
+                        created_at: row.get(1)?,
+                    })
+                })
+                .map_err(|e| format!("Failed to execute query: {e}"))?
+                .collect::<SqlResult<Vec<ToolCallStatus>>>()
+                .map_err(|e| format!("Failed to collect results: {e}"))?;
+        }
+        Ok(calls)
+    }
+
+    /// Row ID of a tool call, creating the row on first sight.
+    fn ensure_tool_call(
+        &self,
+        conversation_id: &str,
+        tool_call_id: &str,
+    ) -> Result<String, String> {
+        self.conn
+            .execute(
+                "INSERT OR IGNORE INTO tool_calls (id, conversation_id, tool_call_id)
+                 VALUES (?1, ?2, ?3)",
+                params![Uuid::new_v4().to_string(), conversation_id, tool_call_id],
+            )
+            .map_err(|e| format!("Failed to record tool call: {e}"))?;
+        self.conn
+            .query_row(
+                "SELECT id FROM tool_calls WHERE conversation_id = ?1 AND tool_call_id = ?2",
+                params![conversation_id, tool_call_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to record tool call: {e}"))
+    }
+
+    fn row_to_tool_call(row: &rusqlite::Row) -> rusqlite::Result<ToolCallRecord> {
+        let options: Option<String> = row.get(7)?;
+        Ok(ToolCallRecord {
+            id: row.get(0)?,
+            conversation_id: row.get(1)?,
+            tool_call_id: row.get(2)?,
+            title: row.get(3)?,
+            kind: row.get(4)?,
+            status: row.get(5)?,
+            command_preview: row.get(6)?,
+            permission_options: options
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            chosen_option: row.get(8)?,
+            decided_by: row.get(9)?,
+            auto_allowed: row.get::<_, i64>(10)? != 0,
+            statuses: Vec::new(),
+            created_at: row.get(11)?,
         })
     }
 
@@ -1085,11 +1914,15 @@ impl Database {
 
     pub fn add_slash_command(&self, cmd: NewSlashCommand) -> Result<SlashCommand, String> {
         let id = Uuid::new_v4().to_string();
+        let hotkey = match cmd.hotkey.as_deref() {
+            Some(h) if !h.trim().is_empty() => Some(self.validate_hotkey(h, None)?),
+            _ => None,
+        };
         self.conn
             .execute(
-                "INSERT INTO slash_commands (id, name, description, script_path)
-                 VALUES (?1, ?2, ?3, ?4)",
-                params![id, cmd.name, cmd.description, cmd.script_path],
+                "INSERT INTO slash_commands (id, name, description, script_path, hotkey)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![id, cmd.name, cmd.description, cmd.script_path, hotkey],
             )
             .map_err(|e| format!("Failed to add slash command: {e}"))?;
         self.get_slash_command(&id)
@@ -1098,7 +1931,7 @@ impl Database {
     pub fn get_slash_command(&self, id: &str) -> Result<SlashCommand, String> {
         self.conn
             .query_row(
-                "SELECT id, name, description, script_path, usage_count, created_at, updated_at
+                "SELECT id, name, description, script_path, usage_count, created_at, updated_at, hotkey
                  FROM slash_commands WHERE id = ?1",
                 params![id],
                 Self::row_to_slash_command,
@@ -1109,7 +1942,7 @@ impl Database {
     pub fn get_slash_command_by_name(&self, name: &str) -> Result<SlashCommand, String> {
         self.conn
             .query_row(
-                "SELECT id, name, description, script_path, usage_count, created_at, updated_at
+                "SELECT id, name, description, script_path, usage_count, created_at, updated_at, hotkey
                  FROM slash_commands WHERE name = ?1",
                 params![name],
                 Self::row_to_slash_command,
@@ -1121,7 +1954,7 @@ impl Database {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT id, name, description, script_path, usage_count, created_at, updated_at
+                "SELECT id, name, description, script_path, usage_count, created_at, updated_at, hotkey
                  FROM slash_commands
                  ORDER BY usage_count DESC, name ASC",
             )
@@ -1141,7 +1974,7 @@ impl Database {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT id, name, description, script_path, usage_count, created_at, updated_at
+                "SELECT id, name, description, script_path, usage_count, created_at, updated_at, hotkey
                  FROM slash_commands
                  WHERE name LIKE ?1 OR description LIKE ?1
                  ORDER BY usage_count DESC, name ASC",
@@ -1158,6 +1991,13 @@ impl Database {
     }
 
     pub fn remove_slash_command_by_name(&self, name: &str) -> Result<bool, String> {
+        self.conn
+            .execute(
+                "DELETE FROM schedules WHERE target_type = 'slash_command'
+                 AND target_id IN (SELECT id FROM slash_commands WHERE name = ?1)",
+                params![name],
+            )
+            .map_err(|e| format!("Failed to remove slash command schedules: {e}"))?;
         let rows = self
             .conn
             .execute("DELETE FROM slash_commands WHERE name = ?1", params![name])
@@ -1184,6 +2024,1173 @@ impl Database {
             usage_count: row.get(4)?,
             created_at: row.get(5)?,
             updated_at: row.get(6)?,
+            hotkey: row.get(7)?,
+        })
+    }
+
+    /// Set or clear (with `None`) the global hotkey of a slash command.
+    pub fn set_slash_command_hotkey(
+        &self,
+        name: &str,
+        hotkey: Option<&str>,
+    ) -> Result<SlashCommand, String> {
+        let cmd = self.get_slash_command_by_name(name)?;
+        let hotkey = match hotkey {
+            Some(h) if !h.trim().is_empty() => Some(self.validate_hotkey(h, Some(&cmd.id))?),
+            _ => None,
+        };
+        self.conn
+            .execute(
+                "UPDATE slash_commands SET hotkey = ?1, updated_at = datetime('now') WHERE id = ?2",
+                params![hotkey, cmd.id],
+            )
+            .map_err(|e| format!("Failed to update slash command hotkey: {e}"))?;
+        self.get_slash_command(&cmd.id)
+    }
+
+    // --- Schedules ---
+
+    pub fn add_schedule(&self, schedule: NewSchedule) -> Result<Schedule, String> {
+        match schedule.target_type.as_str() {
+            "item" => {
+                self.get_item(&schedule.target_id)?;
+            }
+            "slash_command" => {
+                self.get_slash_command(&schedule.target_id)?;
+            }
+            other => {
+                return Err(format!(
+                    "Invalid schedule target type '{other}' (use item or slash_command)"
+                ))
+            }
+        }
+        let spec = schedule.spec.trim().to_string();
+        let trigger = ScheduleTrigger::parse(&spec)?;
+        let missed_policy = match schedule.missed_policy.as_deref() {
+            Some(policy) => MissedRunPolicy::parse(policy)?,
+            None => MissedRunPolicy::Skip,
+        };
+        let next_run_at = schedules::next_run_at(&trigger, chrono::Local::now());
+        let id = Uuid::new_v4().to_string();
+        self.conn
+            .execute(
+                "INSERT INTO schedules (id, target_type, target_id, spec, missed_policy, next_run_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    id,
+                    schedule.target_type,
+                    schedule.target_id,
+                    spec,
+                    missed_policy.as_str(),
+                    next_run_at
+                ],
+            )
+            .map_err(|e| format!("Failed to add schedule: {e}"))?;
+        self.get_schedule(&id)
+    }
+
+    pub fn get_schedule(&self, id: &str) -> Result<Schedule, String> {
+        self.conn
+            .query_row(
+                &format!("{SCHEDULE_SELECT} WHERE s.id = ?1"),
+                params![id],
+                Self::row_to_schedule,
+            )
+            .map_err(|e| format!("Schedule not found: {e}"))
+    }
+
+    /// All schedules, soonest first; trigger-based schedules come last.
+    pub fn list_schedules(&self) -> Result<Vec<Schedule>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "{SCHEDULE_SELECT} ORDER BY s.next_run_at IS NULL, s.next_run_at, s.created_at"
+            ))
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+        let schedules = stmt
+            .query_map([], Self::row_to_schedule)
+            .map_err(|e| format!("Failed to execute query: {e}"))?
+            .collect::<SqlResult<Vec<Schedule>>>()
+            .map_err(|e| format!("Failed to collect results: {e}"))?;
+        Ok(schedules)
+    }
+
+    pub fn remove_schedule(&self, id: &str) -> Result<bool, String> {
+        let rows = self
+            .conn
+            .execute("DELETE FROM schedules WHERE id = ?1", params![id])
+            .map_err(|e| format!("Failed to remove schedule: {e}"))?;
+        Ok(rows > 0)
+    }
+
+    /// Store the outcome of a run together with the schedule's next due time.
+    pub fn record_schedule_run(
+        &self,
+        id: &str,
+        status: &str,
+        exit_code: Option<i32>,
+        next_run_at: Option<&str>,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE schedules SET last_run_at = datetime('now'), last_status = ?1,
+                 last_exit_code = ?2, next_run_at = ?3 WHERE id = ?4",
+                params![status, exit_code, next_run_at, id],
+            )
+            .map_err(|e| format!("Failed to record schedule run: {e}"))?;
+        Ok(())
+    }
+
+    /// Move a schedule's next due time without running it (skipped missed runs).
+    pub fn set_schedule_next_run(&self, id: &str, next_run_at: Option<&str>) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE schedules SET next_run_at = ?1 WHERE id = ?2",
+                params![next_run_at, id],
+            )
+            .map_err(|e| format!("Failed to update schedule: {e}"))?;
+        Ok(())
+    }
+
+    /// Change counters of the watched tables; a table that never changed is missing.
+    pub fn table_versions(&self) -> Result<HashMap<String, i64>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT table_name, version FROM table_versions")
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+        let versions = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| format!("Failed to execute query: {e}"))?
+            .collect::<SqlResult<HashMap<String, i64>>>()
+            .map_err(|e| format!("Failed to collect results: {e}"))?;
+        Ok(versions)
+    }
+
+    fn row_to_schedule(row: &rusqlite::Row) -> rusqlite::Result<Schedule> {
+        Ok(Schedule {
+            id: row.get(0)?,
+            target_type: row.get(1)?,
+            target_id: row.get(2)?,
+            target_name: row.get(3)?,
+            spec: row.get(4)?,
+            missed_policy: row.get(5)?,
+            enabled: row.get::<_, i64>(6)? != 0,
+            last_run_at: row.get(7)?,
+            last_status: row.get(8)?,
+            last_exit_code: row.get(9)?,
+            next_run_at: row.get(10)?,
+            created_at: row.get(11)?,
+        })
+    }
+
+    // --- Permission rules ---
+
+    /// Rules in the order they are tried.
+    pub fn list_permission_rules(&self) -> Result<Vec<PermissionRule>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "{PERMISSION_RULE_SELECT} ORDER BY position ASC, created_at ASC"
+            ))
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+        let rules = stmt
+            .query_map([], Self::row_to_permission_rule)
+            .map_err(|e| format!("Failed to execute query: {e}"))?
+            .collect::<SqlResult<Vec<PermissionRule>>>()
+            .map_err(|e| format!("Failed to collect results: {e}"))?;
+        Ok(rules)
+    }
+
+    pub fn get_permission_rule(&self, id: &str) -> Result<PermissionRule, String> {
+        self.conn
+            .query_row(
+                &format!("{PERMISSION_RULE_SELECT} WHERE id = ?1"),
+                params![id],
+                Self::row_to_permission_rule,
+            )
+            .map_err(|e| format!("Permission rule not found: {e}"))
+    }
+
+    /// Store a rule at `rule.position` (1-based), moving later rules down, or last.
+    pub fn add_permission_rule(&self, rule: NewPermissionRule) -> Result<PermissionRule, String> {
+        validate_rule(&rule)?;
+        let count = self.count_permission_rules()?;
+        let position = rule.position.unwrap_or(count + 1).clamp(1, count + 1);
+        let blank_to_none = |value: Option<String>| {
+            value
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        self.conn
+            .execute(
+                "UPDATE permission_rules SET position = position + 1 WHERE position >= ?1",
+                params![position],
+            )
+            .map_err(|e| format!("Failed to add permission rule: {e}"))?;
+        let id = Uuid::new_v4().to_string();
+        self.conn
+            .execute(
+                "INSERT INTO permission_rules
+                 (id, position, action, tool_kind, title, command, agent_id, context)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    id,
+                    position,
+                    rule.action.trim().to_lowercase(),
+                    blank_to_none(rule.tool_kind).map(|kind| kind.to_lowercase()),
+                    blank_to_none(rule.title),
+                    blank_to_none(rule.command),
+                    blank_to_none(rule.agent_id),
+                    blank_to_none(rule.context),
+                ],
+            )
+            .map_err(|e| format!("Failed to add permission rule: {e}"))?;
+        self.get_permission_rule(&id)
+    }
+
+    pub fn remove_permission_rule(&self, id: &str) -> Result<bool, String> {
+        let rule = match self.get_permission_rule(id) {
+            Ok(rule) => rule,
+            Err(_) => return Ok(false),
+        };
+        self.conn
+            .execute("DELETE FROM permission_rules WHERE id = ?1", params![id])
+            .map_err(|e| format!("Failed to remove permission rule: {e}"))?;
+        self.conn
+            .execute(
+                "UPDATE permission_rules SET position = position - 1 WHERE position > ?1",
+                params![rule.position],
+            )
+            .map_err(|e| format!("Failed to remove permission rule: {e}"))?;
+        Ok(true)
+    }
+
+    /// Move a rule to a 1-based position, shifting the rules in between.
+    pub fn move_permission_rule(&self, id: &str, position: i64) -> Result<PermissionRule, String> {
+        let rule = self.get_permission_rule(id)?;
+        let position = position.clamp(1, self.count_permission_rules()?);
+        let (sql, from, to) = if position < rule.position {
+            (
+                "UPDATE permission_rules SET position = position + 1
+                 WHERE position >= ?1 AND position < ?2",
+                position,
+                rule.position,
+            )
+        } else {
+            (
+                "UPDATE permission_rules SET position = position - 1
+                 WHERE position > ?1 AND position <= ?2",
+                rule.position,
+                position,
+            )
+        };
+        self.conn
+            .execute(sql, params![from, to])
+            .map_err(|e| format!("Failed to move permission rule: {e}"))?;
+        self.conn
+            .execute(
+                "UPDATE permission_rules SET position = ?1 WHERE id = ?2",
+                params![position, id],
+            )
+            .map_err(|e| format!("Failed to move permission rule: {e}"))?;
+        self.get_permission_rule(id)
+    }
+
+    /// Replace all rules with the defaults.
+    pub fn reset_permission_rules(&self) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM permission_rules", [])
+            .map_err(|e| format!("Failed to reset permission rules: {e}"))?;
+        for rule in default_rules() {
+            self.add_permission_rule(rule)?;
+        }
+        self.set_setting(PERMISSION_RULES_SEEDED_SETTING, "true")
+    }
+
+    fn count_permission_rules(&self) -> Result<i64, String> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM permission_rules", [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| format!("Failed to count permission rules: {e}"))
+    }
+
+    fn row_to_permission_rule(row: &rusqlite::Row) -> rusqlite::Result<PermissionRule> {
+        Ok(PermissionRule {
+            id: row.get(0)?,
+            position: row.get(1)?,
+            action: row.get(2)?,
+            tool_kind: row.get(3)?,
+            title: row.get(4)?,
+            command: row.get(5)?,
+            agent_id: row.get(6)?,
+            context: row.get(7)?,
+            created_at: row.get(8)?,
         })
     }
+
+    // --- Permission grants ---
+
+    /// Remember an "always" answer for an agent's tool call, replacing an earlier one.
+    pub fn add_permission_grant(
+        &self,
+        agent_id: &str,
+        signature: &str,
+        decision: &str,
+    ) -> Result<PermissionGrant, String> {
+        if decision != "allow" && decision != "reject" {
+            return Err(format!(
+                "Invalid grant decision '{decision}' (use allow or reject)"
+            ));
+        }
+        let expires = self
+            .permission_grant_days()?
+            .map(|days| format!("+{days} days"));
+        self.conn
+            .execute(
+                "INSERT INTO permission_grants (id, agent_id, signature, decision, expires_at)
+                 VALUES (?1, ?2, ?3, ?4, CASE WHEN ?5 IS NULL THEN NULL ELSE datetime('now', ?5) END)
+                 ON CONFLICT(agent_id, signature) DO UPDATE SET decision = ?4,
+                 expires_at = excluded.expires_at, created_at = datetime('now')",
+                params![
+                    Uuid::new_v4().to_string(),
+                    agent_id,
+                    signature,
+                    decision,
+                    expires
+                ],
+            )
+            .map_err(|e| format!("Failed to save permission grant: {e}"))?;
+        self.find_permission_grant(agent_id, signature)?
+            .ok_or_else(|| "Permission grant not found".to_string())
+    }
+
+    /// The unexpired grant for an agent's tool call, if any.
+    pub fn find_permission_grant(
+        &self,
+        agent_id: &str,
+        signature: &str,
+    ) -> Result<Option<PermissionGrant>, String> {
+        match self.conn.query_row(
+            "SELECT id, agent_id, signature, decision, expires_at, created_at
+             FROM permission_grants
+             WHERE agent_id = ?1 AND signature = ?2
+               AND (expires_at IS NULL OR expires_at > datetime('now'))",
+            params![agent_id, signature],
+            Self::row_to_permission_grant,
+        ) {
+            Ok(grant) => Ok(Some(grant)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(format!("Failed to get permission grant: {e}")),
+        }
+    }
+
+    /// Unexpired grants, newest first. Expired ones are deleted.
+    pub fn list_permission_grants(&self) -> Result<Vec<PermissionGrant>, String> {
+        self.conn
+            .execute(
+                "DELETE FROM permission_grants WHERE expires_at <= datetime('now')",
+                [],
+            )
+            .map_err(|e| format!("Failed to prune permission grants: {e}"))?;
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, agent_id, signature, decision, expires_at, created_at
+                 FROM permission_grants ORDER BY created_at DESC, agent_id, signature",
+            )
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+        let grants = stmt
+            .query_map([], Self::row_to_permission_grant)
+            .map_err(|e| format!("Failed to execute query: {e}"))?
+            .collect::<SqlResult<Vec<PermissionGrant>>>()
+            .map_err(|e| format!("Failed to collect results: {e}"))?;
+        Ok(grants)
+    }
+
+    pub fn revoke_permission_grant(&self, id: &str) -> Result<bool, String> {
+        let rows = self
+            .conn
+            .execute("DELETE FROM permission_grants WHERE id = ?1", params![id])
+            .map_err(|e| format!("Failed to revoke permission grant: {e}"))?;
+        Ok(rows > 0)
+    }
+
+    /// Revoke every grant, or only those of one agent. Returns how many were removed.
+    pub fn revoke_permission_grants(&self, agent_id: Option<&str>) -> Result<usize, String> {
+        self.conn
+            .execute(
+                "DELETE FROM permission_grants WHERE ?1 IS NULL OR agent_id = ?1",
+                params![agent_id],
+            )
+            .map_err(|e| format!("Failed to revoke permission grants: {e}"))
+    }
+
+    /// Days a new grant lasts; `None` when grants don't expire.
+    pub fn permission_grant_days(&self) -> Result<Option<i64>, String> {
+        let days = match self.get_setting(GRANT_DAYS_SETTING)? {
+            Some(value) => value.trim().parse::<i64>().unwrap_or(DEFAULT_GRANT_DAYS),
+            None => DEFAULT_GRANT_DAYS,
+        };
+        Ok((days > 0).then_some(days))
+    }
+
+    /// Folders agents may read and write files in.
+    pub fn agent_file_roots(&self) -> Result<Vec<PathBuf>, String> {
+        match self.get_setting(FILE_ROOTS_SETTING)? {
+            Some(roots) if !roots.is_empty() => Ok(std::env::split_paths(&roots).collect()),
+            _ => Ok(vec![Self::slash_commands_dir()?]),
+        }
+    }
+
+    /// Replace the agent file roots; an empty list restores the default.
+    pub fn set_agent_file_roots(&self, roots: &[PathBuf]) -> Result<(), String> {
+        if roots.is_empty() {
+            self.delete_setting(FILE_ROOTS_SETTING)?;
+            return Ok(());
+        }
+        let joined = std::env::join_paths(roots).map_err(|e| format!("Invalid file roots: {e}"))?;
+        self.set_setting(FILE_ROOTS_SETTING, &joined.to_string_lossy())
+    }
+
+    fn row_to_permission_grant(row: &rusqlite::Row) -> rusqlite::Result<PermissionGrant> {
+        Ok(PermissionGrant {
+            id: row.get(0)?,
+            agent_id: row.get(1)?,
+            signature: row.get(2)?,
+            decision: row.get(3)?,
+            expires_at: row.get(4)?,
+            created_at: row.get(5)?,
+        })
+    }
+
+    // --- Hotkeys ---
+
+    /// The launcher toggle shortcut, falling back to the platform default.
+    pub fn get_toggle_hotkey(&self) -> Result<String, String> {
+        Ok(self
+            .get_setting(TOGGLE_HOTKEY_SETTING)?
+            .filter(|h| !h.trim().is_empty())
+            .unwrap_or_else(|| default_toggle_hotkey().to_string()))
+    }
+
+    /// Validate and store the launcher toggle shortcut.
+    pub fn set_toggle_hotkey(&self, hotkey: &str) -> Result<String, String> {
+        let normalized = normalize_hotkey(hotkey)?;
+        if let Some(conflict) = self
+            .list_hotkey_bindings()?
+            .into_iter()
+            .find(|b| b.target_type != "toggle" && hotkeys_equal(&b.hotkey, &normalized))
+        {
+            return Err(format!(
+                "Hotkey {normalized} is already assigned to {} '{}'",
+                conflict.target_type.replace('_', " "),
+                conflict.label
+            ));
+        }
+        self.set_setting(TOGGLE_HOTKEY_SETTING, &normalized)?;
+        Ok(normalized)
+    }
+
+    /// All global hotkeys: the launcher toggle plus enabled items and slash commands.
+    pub fn list_hotkey_bindings(&self) -> Result<Vec<HotkeyBinding>, String> {
+        let mut bindings = vec![HotkeyBinding {
+            hotkey: self.get_toggle_hotkey()?,
+            target_type: "toggle".to_string(),
+            target_id: None,
+            label: "Toggle launcher".to_string(),
+        }];
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT hotkey, 'item', id, title FROM items
+                 WHERE hotkey IS NOT NULL AND hotkey != '' AND enabled = 1
+                 UNION ALL
+                 SELECT hotkey, 'slash_command', id, '/' || name FROM slash_commands
+                 WHERE hotkey IS NOT NULL AND hotkey != ''",
+            )
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(HotkeyBinding {
+                    hotkey: row.get(0)?,
+                    target_type: row.get(1)?,
+                    target_id: row.get(2)?,
+                    label: row.get(3)?,
+                })
+            })
+            .map_err(|e| format!("Failed to execute query: {e}"))?
+            .collect::<SqlResult<Vec<HotkeyBinding>>>()
+            .map_err(|e| format!("Failed to collect results: {e}"))?;
+
+        bindings.extend(rows);
+        Ok(bindings)
+    }
+
+    /// Normalize a hotkey and make sure no other binding already uses it.
+    /// `owner_id` is the item or slash command being edited, which may keep its own hotkey.
+    fn validate_hotkey(&self, hotkey: &str, owner_id: Option<&str>) -> Result<String, String> {
+        let normalized = normalize_hotkey(hotkey)?;
+        let conflict = self.list_hotkey_bindings()?.into_iter().find(|b| {
+            hotkeys_equal(&b.hotkey, &normalized)
+                && (owner_id.is_none() || b.target_id.as_deref() != owner_id)
+        });
+        match conflict {
+            Some(b) => Err(format!(
+                "Hotkey {normalized} is already assigned to {} '{}'",
+                b.target_type.replace('_', " "),
+                b.label
+            )),
+            None => Ok(normalized),
+        }
+    }
+
+    // --- Context providers ---
+
+    /// Whether a launch context provider is enabled. Providers are on unless disabled.
+    pub fn is_context_provider_enabled(&self, provider_id: &str) -> Result<bool, String> {
+        Ok(self
+            .get_setting(&format!("{CONTEXT_PROVIDER_PREFIX}{provider_id}"))?
+            .map(|v| v != "false")
+            .unwrap_or(true))
+    }
+
+    pub fn set_context_provider_enabled(
+        &self,
+        provider_id: &str,
+        enabled: bool,
+    ) -> Result<(), String> {
+        self.set_setting(
+            &format!("{CONTEXT_PROVIDER_PREFIX}{provider_id}"),
+            if enabled { "true" } else { "false" },
+        )
+    }
+
+    /// User-registered shell-script context providers, ordered by name.
+    pub fn list_context_scripts(&self) -> Result<Vec<ContextScript>, String> {
+        Ok(self
+            .get_all_settings()?
+            .into_iter()
+            .filter_map(|s| {
+                let name = s.key.strip_prefix(CONTEXT_SCRIPT_PREFIX)?.to_string();
+                Some(ContextScript {
+                    name,
+                    command: s.value,
+                })
+            })
+            .collect())
+    }
+
+    pub fn set_context_script(&self, name: &str, command: &str) -> Result<ContextScript, String> {
+        let name = name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "Invalid context script name '{name}': use letters, digits, '-' or '_'"
+            ));
+        }
+        if command.trim().is_empty() {
+            return Err("Context script command cannot be empty".to_string());
+        }
+        self.set_setting(&format!("{CONTEXT_SCRIPT_PREFIX}{name}"), command)?;
+        Ok(ContextScript {
+            name: name.to_string(),
+            command: command.to_string(),
+        })
+    }
+
+    pub fn remove_context_script(&self, name: &str) -> Result<bool, String> {
+        let removed = self.delete_setting(&format!("{CONTEXT_SCRIPT_PREFIX}{name}"))?;
+        if removed {
+            self.delete_setting(&format!("{CONTEXT_PROVIDER_PREFIX}script:{name}"))?;
+        }
+        Ok(removed)
+    }
+
+    // --- Clipboard history ---
+
+    pub fn get_clipboard_settings(&self) -> Result<ClipboardSettings, String> {
+        let defaults = ClipboardSettings::default();
+        let flag = |key: &str, default: bool| -> Result<bool, String> {
+            Ok(self
+                .get_setting(key)?
+                .map(|v| v == "true")
+                .unwrap_or(default))
+        };
+        let number = |key: &str, default: usize| -> Result<usize, String> {
+            Ok(self
+                .get_setting(key)?
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(default))
+        };
+
+        Ok(ClipboardSettings {
+            enabled: flag(CLIPBOARD_ENABLED_SETTING, defaults.enabled)?,
+            capture_images: flag(CLIPBOARD_IMAGES_SETTING, defaults.capture_images)?,
+            max_entries: number(CLIPBOARD_MAX_ENTRIES_SETTING, defaults.max_entries)?,
+            max_item_bytes: number(CLIPBOARD_MAX_ITEM_BYTES_SETTING, defaults.max_item_bytes)?,
+            excluded_apps: match self.get_setting(CLIPBOARD_EXCLUDED_APPS_SETTING)? {
+                Some(list) => list
+                    .split(',')
+                    .map(|a| a.trim().to_string())
+                    .filter(|a| !a.is_empty())
+                    .collect(),
+                None => defaults.excluded_apps,
+            },
+        })
+    }
+
+    pub fn set_clipboard_settings(&self, settings: &ClipboardSettings) -> Result<(), String> {
+        let flag = |v: bool| if v { "true" } else { "false" };
+        self.set_setting(CLIPBOARD_ENABLED_SETTING, flag(settings.enabled))?;
+        self.set_setting(CLIPBOARD_IMAGES_SETTING, flag(settings.capture_images))?;
+        self.set_setting(
+            CLIPBOARD_MAX_ENTRIES_SETTING,
+            &settings.max_entries.to_string(),
+        )?;
+        self.set_setting(
+            CLIPBOARD_MAX_ITEM_BYTES_SETTING,
+            &settings.max_item_bytes.to_string(),
+        )?;
+        self.set_setting(
+            CLIPBOARD_EXCLUDED_APPS_SETTING,
+            &settings.excluded_apps.join(","),
+        )?;
+        self.prune_clipboard_history(settings.max_entries)?;
+        Ok(())
+    }
+
+    /// Record a copy. Returns `None` when the entry is empty or over the size cap.
+    /// Copying the same content again moves the existing entry to the top instead of
+    /// adding a duplicate.
+    pub fn add_clipboard_entry(
+        &self,
+        entry: NewClipboardEntry,
+    ) -> Result<Option<ClipboardEntry>, String> {
+        let settings = self.get_clipboard_settings()?;
+
+        let (preview, size, hash, image) = match (entry.content_type.as_str(), entry.image) {
+            ("text", _) => {
+                if entry.content.trim().is_empty() {
+                    return Ok(None);
+                }
+                let preview = clipboard_preview(&entry.content);
+                let hash = content_hash(b"text", entry.content.as_bytes());
+                (preview, entry.content.len(), hash, None)
+            }
+            ("image", Some(image)) => {
+                if image.rgba.is_empty() {
+                    return Ok(None);
+                }
+                let preview = format!("Image {}×{}", image.width, image.height);
+                let dims = format!("{}x{}", image.width, image.height);
+                let hash = content_hash(dims.as_bytes(), &image.rgba);
+                (preview, image.rgba.len(), hash, Some(image))
+            }
+            (other, _) => return Err(format!("Unsupported clipboard content type: {other}")),
+        };
+        if size > settings.max_item_bytes {
+            return Ok(None);
+        }
+
+        let existing: Option<String> = match self.conn.query_row(
+            "SELECT id FROM clipboard_history WHERE content_hash = ?1",
+            params![hash],
+            |row| row.get(0),
+        ) {
+            Ok(id) => Some(id),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(format!("Failed to look up clipboard entry: {e}")),
+        };
+
+        let id = match existing {
+            Some(id) => {
+                self.conn
+                    .execute(
+                        "UPDATE clipboard_history
+                         SET copy_count = copy_count + 1,
+                             source_app = COALESCE(?2, source_app),
+                             last_copied_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+                         WHERE id = ?1",
+                        params![id, entry.source_app],
+                    )
+                    .map_err(|e| format!("Failed to update clipboard entry: {e}"))?;
+                id
+            }
+            None => {
+                let id = Uuid::new_v4().to_string();
+                self.conn
+                    .execute(
+                        "INSERT INTO clipboard_history
+                         (id, content_type, content, preview, image_data, image_width, image_height,
+                          size_bytes, content_hash, source_app)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                        params![
+                            id,
+                            entry.content_type,
+                            entry.content,
+                            preview,
+                            image.as_ref().map(|i| i.rgba.as_slice()),
+                            image.as_ref().map(|i| i.width as i64),
+                            image.as_ref().map(|i| i.height as i64),
+                            size as i64,
+                            hash,
+                            entry.source_app,
+                        ],
+                    )
+                    .map_err(|e| format!("Failed to add clipboard entry: {e}"))?;
+                id
+            }
+        };
+
+        self.prune_clipboard_history(settings.max_entries)?;
+        self.get_clipboard_entry(&id).map(Some)
+    }
+
+    pub fn get_clipboard_entry(&self, id: &str) -> Result<ClipboardEntry, String> {
+        self.conn
+            .query_row(
+                &format!("SELECT {CLIPBOARD_COLUMNS} FROM clipboard_history WHERE id = ?1"),
+                params![id],
+                Self::row_to_clipboard_entry,
+            )
+            .map_err(|e| format!("Clipboard entry not found: {e}"))
+    }
+
+    pub fn get_clipboard_image(&self, id: &str) -> Result<ClipboardImage, String> {
+        let (rgba, width, height): (Option<Vec<u8>>, Option<i64>, Option<i64>) = self
+            .conn
+            .query_row(
+                "SELECT image_data, image_width, image_height FROM clipboard_history WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|e| format!("Clipboard entry not found: {e}"))?;
+
+        match (rgba, width, height) {
+            (Some(rgba), Some(width), Some(height)) => Ok(ClipboardImage {
+                width: width as usize,
+                height: height as usize,
+                rgba,
+            }),
+            _ => Err(format!("Clipboard entry {id} is not an image")),
+        }
+    }
+
+    /// Most recent entries, pinned entries first.
+    pub fn list_clipboard_entries(&self, limit: usize) -> Result<Vec<ClipboardEntry>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {CLIPBOARD_COLUMNS} FROM clipboard_history
+                 ORDER BY pinned DESC, last_copied_at DESC
+                 LIMIT ?1"
+            ))
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+
+        let entries = stmt
+            .query_map(params![limit as i64], Self::row_to_clipboard_entry)
+            .map_err(|e| format!("Failed to execute query: {e}"))?
+            .collect::<SqlResult<Vec<ClipboardEntry>>>()
+            .map_err(|e| format!("Failed to collect results: {e}"))?;
+
+        Ok(entries)
+    }
+
+    pub fn search_clipboard_entries(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<ClipboardEntry>, String> {
+        let pattern = format!("%{query}%");
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {CLIPBOARD_COLUMNS} FROM clipboard_history
+                 WHERE content LIKE ?1 OR preview LIKE ?1 OR source_app LIKE ?1
+                 ORDER BY pinned DESC, last_copied_at DESC
+                 LIMIT ?2"
+            ))
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+
+        let entries = stmt
+            .query_map(params![pattern, limit as i64], Self::row_to_clipboard_entry)
+            .map_err(|e| format!("Failed to execute query: {e}"))?
+            .collect::<SqlResult<Vec<ClipboardEntry>>>()
+            .map_err(|e| format!("Failed to collect results: {e}"))?;
+
+        Ok(entries)
+    }
+
+    /// Move an entry to the top of the history (e.g. after copying it back).
+    pub fn touch_clipboard_entry(&self, id: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE clipboard_history
+                 SET copy_count = copy_count + 1,
+                     last_copied_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+                 WHERE id = ?1",
+                params![id],
+            )
+            .map_err(|e| format!("Failed to update clipboard entry: {e}"))?;
+        Ok(())
+    }
+
+    pub fn set_clipboard_entry_pinned(&self, id: &str, pinned: bool) -> Result<bool, String> {
+        let rows = self
+            .conn
+            .execute(
+                "UPDATE clipboard_history SET pinned = ?2 WHERE id = ?1",
+                params![id, pinned as i64],
+            )
+            .map_err(|e| format!("Failed to update clipboard entry: {e}"))?;
+        Ok(rows > 0)
+    }
+
+    pub fn remove_clipboard_entry(&self, id: &str) -> Result<bool, String> {
+        let rows = self
+            .conn
+            .execute("DELETE FROM clipboard_history WHERE id = ?1", params![id])
+            .map_err(|e| format!("Failed to remove clipboard entry: {e}"))?;
+        Ok(rows > 0)
+    }
+
+    /// Delete the history, keeping pinned entries unless `include_pinned` is set.
+    pub fn clear_clipboard_history(&self, include_pinned: bool) -> Result<usize, String> {
+        let sql = if include_pinned {
+            "DELETE FROM clipboard_history"
+        } else {
+            "DELETE FROM clipboard_history WHERE pinned = 0"
+        };
+        self.conn
+            .execute(sql, [])
+            .map_err(|e| format!("Failed to clear clipboard history: {e}"))
+    }
+
+    fn prune_clipboard_history(&self, max_entries: usize) -> Result<(), String> {
+        self.conn
+            .execute(
+                "DELETE FROM clipboard_history
+                 WHERE pinned = 0 AND id NOT IN (
+                     SELECT id FROM clipboard_history WHERE pinned = 0
+                     ORDER BY last_copied_at DESC LIMIT ?1
+                 )",
+                params![max_entries as i64],
+            )
+            .map_err(|e| format!("Failed to prune clipboard history: {e}"))?;
+        Ok(())
+    }
+
+    fn row_to_clipboard_entry(row: &rusqlite::Row) -> rusqlite::Result<ClipboardEntry> {
+        Ok(ClipboardEntry {
+            id: row.get(0)?,
+            content_type: row.get(1)?,
+            content: row.get(2)?,
+            preview: row.get(3)?,
+            size_bytes: row.get(4)?,
+            source_app: row.get(5)?,
+            pinned: row.get::<_, i64>(6)? != 0,
+            copy_count: row.get(7)?,
+            created_at: row.get(8)?,
+            last_copied_at: row.get(9)?,
+        })
+    }
+
+    // --- File index ---
+
+    pub fn get_file_index_settings(&self) -> Result<FileIndexSettings, String> {
+        let defaults = FileIndexSettings::default();
+        let flag = |key: &str, default: bool| -> Result<bool, String> {
+            Ok(self
+                .get_setting(key)?
+                .map(|v| v == "true")
+                .unwrap_or(default))
+        };
+        // Paths and globs may contain commas, so lists are stored one per line.
+        let list = |key: &str, default: Vec<String>| -> Result<Vec<String>, String> {
+            Ok(match self.get_setting(key)? {
+                Some(value) => value
+                    .lines()
+                    .map(|l| l.trim().to_string())
+                    .filter(|l| !l.is_empty())
+                    .collect(),
+                None => default,
+            })
+        };
+
+        Ok(FileIndexSettings {
+            enabled: flag(FILES_ENABLED_SETTING, defaults.enabled)?,
+            roots: list(FILES_ROOTS_SETTING, defaults.roots)?,
+            ignore_globs: list(FILES_IGNORE_SETTING, defaults.ignore_globs)?,
+            respect_gitignore: flag(FILES_GITIGNORE_SETTING, defaults.respect_gitignore)?,
+            include_hidden: flag(FILES_HIDDEN_SETTING, defaults.include_hidden)?,
+            max_entries: self
+                .get_setting(FILES_MAX_ENTRIES_SETTING)?
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(defaults.max_entries),
+        })
+    }
+
+    pub fn set_file_index_settings(&self, settings: &FileIndexSettings) -> Result<(), String> {
+        let flag = |v: bool| if v { "true" } else { "false" };
+        self.set_setting(FILES_ENABLED_SETTING, flag(settings.enabled))?;
+        self.set_setting(FILES_ROOTS_SETTING, &settings.roots.join("\n"))?;
+        self.set_setting(FILES_IGNORE_SETTING, &settings.ignore_globs.join("\n"))?;
+        self.set_setting(FILES_GITIGNORE_SETTING, flag(settings.respect_gitignore))?;
+        self.set_setting(FILES_HIDDEN_SETTING, flag(settings.include_hidden))?;
+        self.set_setting(FILES_MAX_ENTRIES_SETTING, &settings.max_entries.to_string())
+    }
+
+    /// Why indexed directories aren't being watched, if some aren't (usually the
+    /// inotify watch limit).
+    pub fn file_watch_problem(&self) -> Result<Option<String>, String> {
+        self.get_setting(FILES_WATCH_PROBLEM_SETTING)
+    }
+
+    pub fn set_file_watch_problem(&self, problem: Option<&str>) -> Result<(), String> {
+        match problem {
+            Some(problem) => self.set_setting(FILES_WATCH_PROBLEM_SETTING, problem),
+            None => self.delete_setting(FILES_WATCH_PROBLEM_SETTING).map(|_| ()),
+        }
+    }
+
+    /// Unix time of the last full rebuild, if the index was ever built.
+    pub fn file_index_built_at(&self) -> Result<Option<i64>, String> {
+        Ok(self
+            .get_setting(FILES_INDEXED_AT_SETTING)?
+            .and_then(|v| v.parse().ok()))
+    }
+
+    /// Replace the whole index in one transaction.
+    pub fn replace_file_index(&self, entries: &[FileEntry]) -> Result<(), String> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed to start transaction: {e}"))?;
+        tx.execute("DELETE FROM file_index", [])
+            .map_err(|e| format!("Failed to clear file index: {e}"))?;
+        {
+            let mut stmt = tx
+                .prepare(
+                    "INSERT OR REPLACE INTO file_index (path, name, is_dir, size, modified)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                )
+                .map_err(|e| format!("Failed to prepare query: {e}"))?;
+            for entry in entries {
+                stmt.execute(params![
+                    entry.path,
+                    entry.name,
+                    entry.is_dir,
+                    entry.size as i64,
+                    entry.modified
+                ])
+                .map_err(|e| format!("Failed to index {}: {e}", entry.path))?;
+            }
+        }
+        self.set_setting(
+            FILES_INDEXED_AT_SETTING,
+            &chrono::Utc::now().timestamp().to_string(),
+        )?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit transaction: {e}"))
+    }
+
+    /// Add or refresh a single entry.
+    pub fn upsert_file_entry(&self, entry: &FileEntry) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO file_index (path, name, is_dir, size, modified)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT(path) DO UPDATE SET
+                     name = excluded.name, is_dir = excluded.is_dir,
+                     size = excluded.size, modified = excluded.modified",
+                params![
+                    entry.path,
+                    entry.name,
+                    entry.is_dir,
+                    entry.size as i64,
+                    entry.modified
+                ],
+            )
+            .map_err(|e| format!("Failed to index {}: {e}", entry.path))?;
+        Ok(())
+    }
+
+    pub fn has_file_entry(&self, path: &str) -> Result<bool, String> {
+        self.conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM file_index WHERE path = ?1)",
+                params![path],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to query file index: {e}"))
+    }
+
+    /// Remove a path and, for directories, everything below it. Returns the number of
+    /// removed entries.
+    pub fn remove_file_entries(&self, path: &str) -> Result<usize, String> {
+        let separator = std::path::MAIN_SEPARATOR;
+        let prefix = format!(
+            "{}{separator}%",
+            like_escape(path.trim_end_matches(separator))
+        );
+        self.conn
+            .execute(
+                "DELETE FROM file_index WHERE path = ?1 OR path LIKE ?2 ESCAPE '^'",
+                params![path, prefix],
+            )
+            .map_err(|e| format!("Failed to remove {path} from file index: {e}"))
+    }
+
+    pub fn count_file_entries(&self) -> Result<usize, String> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM file_index", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map(|n| n as usize)
+            .map_err(|e| format!("Failed to count file index: {e}"))
+    }
+
+    /// All indexed directories, e.g. to set up change watches.
+    pub fn list_indexed_dirs(&self) -> Result<Vec<String>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path FROM file_index WHERE is_dir = 1")
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+        let dirs = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| format!("Failed to execute query: {e}"))?
+            .collect::<SqlResult<Vec<String>>>()
+            .map_err(|e| format!("Failed to collect results: {e}"))?;
+        Ok(dirs)
+    }
+
+    /// Search file names and paths. Every whitespace-separated term must match;
+    /// entries whose name matches the whole query rank first, then shorter paths.
+    pub fn search_files(&self, query: &str, limit: usize) -> Result<Vec<FileEntry>, String> {
+        let terms: Vec<&str> = query.split_whitespace().collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let name_pattern = format!("%{}%", like_escape(query.trim()));
+
+        // The trigram tokenizer needs at least three characters per term; shorter
+        // queries fall back to a plain scan of the names.
+        let (sql, pattern) = if terms.iter().all(|t| t.chars().count() >= 3) {
+            let fts_query = terms
+                .iter()
+                .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+                .collect::<Vec<_>>()
+                .join(" ");
+            (
+                "SELECT f.path, f.name, f.is_dir, f.size, f.modified
+                 FROM file_index_fts JOIN file_index f ON f.id = file_index_fts.rowid
+                 WHERE file_index_fts MATCH ?1
+                 ORDER BY f.name LIKE ?2 ESCAPE '^' DESC, length(f.path), f.path
+                 LIMIT ?3",
+                fts_query,
+            )
+        } else {
+            (
+                "SELECT path, name, is_dir, size, modified
+                 FROM file_index
+                 WHERE name LIKE ?1 ESCAPE '^'
+                 ORDER BY name LIKE ?2 ESCAPE '^' DESC, length(path), path
+                 LIMIT ?3",
+                name_pattern.clone(),
+            )
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+        let entries = stmt
+            .query_map(
+                params![pattern, name_pattern, limit as i64],
+                Self::row_to_file_entry,
+            )
+            .map_err(|e| format!("Failed to execute query: {e}"))?
+            .collect::<SqlResult<Vec<FileEntry>>>()
+            .map_err(|e| format!("Failed to collect results: {e}"))?;
+        Ok(entries)
+    }
+
+    /// Entries whose path starts with `prefix`, shallowest first.
+    pub fn search_files_by_prefix(
+        &self,
+        prefix: &str,
+        limit: usize,
+    ) -> Result<Vec<FileEntry>, String> {
+        let pattern = format!("{}%", like_escape(prefix));
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT path, name, is_dir, size, modified
+                 FROM file_index
+                 WHERE path LIKE ?1 ESCAPE '^'
+                 ORDER BY length(path) - length(replace(path, '/', '')), is_dir DESC, path
+                 LIMIT ?2",
+            )
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+        let entries = stmt
+            .query_map(params![pattern, limit as i64], Self::row_to_file_entry)
+            .map_err(|e| format!("Failed to execute query: {e}"))?
+            .collect::<SqlResult<Vec<FileEntry>>>()
+            .map_err(|e| format!("Failed to collect results: {e}"))?;
+        Ok(entries)
+    }
+
+    fn row_to_file_entry(row: &rusqlite::Row) -> rusqlite::Result<FileEntry> {
+        Ok(FileEntry {
+            path: row.get(0)?,
+            name: row.get(1)?,
+            is_dir: row.get::<_, i64>(2)? != 0,
+            size: row.get::<_, i64>(3)? as u64,
+            modified: row.get(4)?,
+        })
+    }
+}
+
+/// First non-empty line of copied text, shortened for list views.
+fn clipboard_preview(text: &str) -> String {
+    const MAX_CHARS: usize = 120;
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or_default();
+    if line.chars().count() > MAX_CHARS {
+        let truncated: String = line.chars().take(MAX_CHARS - 1).collect();
+        format!("{truncated}…")
+    } else {
+        line.to_string()
+    }
+}
+
+/// Stable 64-bit FNV-1a hash used to de-duplicate clipboard entries.
+fn content_hash(kind: &[u8], data: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in kind.iter().chain([0u8].iter()).chain(data) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// Escape `%`, `_` and the escape character itself for `LIKE ... ESCAPE '^'`.
+fn like_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '^') {
+            out.push('^');
+        }
+        out.push(c);
+    }
+    out
 }
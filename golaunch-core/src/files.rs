@@ -0,0 +1,374 @@
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use ignore::gitignore::Gitignore;
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::WalkBuilder;
+use notify::{RecursiveMode, Watcher};
+
+use crate::db::Database;
+use crate::models::{FileEntry, FileIndexReport, FileIndexSettings};
+
+/// Batch filesystem events for this long before touching the index.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Expand a configured root (`~`, `~/dir` or an absolute path).
+pub fn expand_root(root: &str) -> Option<PathBuf> {
+    let root = root.trim();
+    let path = if root == "~" {
+        dirs::home_dir()?
+    } else if let Some(rest) = root.strip_prefix("~/") {
+        dirs::home_dir()?.join(rest)
+    } else {
+        PathBuf::from(root)
+    };
+    path.is_absolute().then_some(path)
+}
+
+struct Root {
+    path: PathBuf,
+    overrides: Override,
+}
+
+/// Decides which paths belong in the index, for both full crawls and single changes.
+pub struct FileFilter {
+    roots: Vec<Root>,
+    respect_gitignore: bool,
+    include_hidden: bool,
+    max_entries: usize,
+}
+
+impl FileFilter {
+    pub fn new(settings: &FileIndexSettings) -> Result<Self, String> {
+        let mut roots = Vec::new();
+        for root in &settings.roots {
+            let Some(path) = expand_root(root) else {
+                return Err(format!(
+                    "Invalid index root '{root}': use an absolute path or ~"
+                ));
+            };
+            let mut builder = OverrideBuilder::new(&path);
+            for glob in &settings.ignore_globs {
+                // Overrides are allow-lists; a leading '!' turns a glob into an ignore rule.
+                builder
+                    .add(&format!("!{glob}"))
+                    .map_err(|e| format!("Invalid ignore glob '{glob}': {e}"))?;
+            }
+            let overrides = builder
+                .build()
+                .map_err(|e| format!("Invalid ignore globs: {e}"))?;
+            roots.push(Root { path, overrides });
+        }
+        Ok(Self {
+            roots,
+            respect_gitignore: settings.respect_gitignore,
+            include_hidden: settings.include_hidden,
+            max_entries: settings.max_entries,
+        })
+    }
+
+    pub fn roots(&self) -> impl Iterator<Item = &Path> {
+        self.roots.iter().map(|r| r.path.as_path())
+    }
+
+    fn root_for(&self, path: &Path) -> Option<&Root> {
+        self.roots
+            .iter()
+            .filter(|r| path.starts_with(&r.path) && path != r.path)
+            .max_by_key(|r| r.path.components().count())
+    }
+
+    /// Whether `path` (not a root itself) should be in the index.
+    pub fn should_index(&self, path: &Path, is_dir: bool) -> bool {
+        let Some(root) = self.root_for(path) else {
+            return false;
+        };
+
+        // Every directory between the root and the path must be allowed too.
+        let mut current = root.path.clone();
+        let relative = path.strip_prefix(&root.path).unwrap_or(path);
+        let depth = relative.components().count();
+        for (i, component) in relative.components().enumerate() {
+            current.push(component);
+            let component_is_dir = i + 1 < depth || is_dir;
+            let hidden = component.as_os_str().to_string_lossy().starts_with('.');
+            if hidden && !self.include_hidden {
+                return false;
+            }
+            if root
+                .overrides
+                .matched(&current, component_is_dir)
+                .is_ignore()
+            {
+                return false;
+            }
+        }
+
+        if self.respect_gitignore {
+            // The nearest .gitignore with a matching rule decides.
+            let mut dir = path.parent();
+            while let Some(d) = dir {
+                let gitignore = d.join(".gitignore");
+                if gitignore.is_file() {
+                    let (matcher, _) = Gitignore::new(&gitignore);
+                    let matched = matcher.matched_path_or_any_parents(path, is_dir);
+                    if matched.is_ignore() {
+                        return false;
+                    }
+                    if matched.is_whitelist() {
+                        break;
+                    }
+                }
+                if d == root.path {
+                    break;
+                }
+                dir = d.parent();
+            }
+        }
+        true
+    }
+
+    fn walker(&self, start: &Path, root: &Root) -> WalkBuilder {
+        let mut builder = WalkBuilder::new(start);
+        builder
+            .hidden(!self.include_hidden)
+            .git_ignore(self.respect_gitignore)
+            .require_git(false)
+            // Only .gitignore files under the root count, matching `should_index`.
+            .parents(false)
+            .ignore(false)
+            .git_global(false)
+            .git_exclude(false)
+            .follow_links(false)
+            .overrides(root.overrides.clone());
+        builder
+    }
+}
+
+fn file_entry(path: &Path, metadata: &std::fs::Metadata) -> FileEntry {
+    FileEntry {
+        path: path.to_string_lossy().into_owned(),
+        name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        is_dir: metadata.is_dir(),
+        size: if metadata.is_dir() { 0 } else { metadata.len() },
+        modified: metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64),
+    }
+}
+
+/// Crawl `start` (which must be under `root`), adding entries until `limit` is reached.
+/// Returns whether the crawl was cut short.
+fn crawl(
+    filter: &FileFilter,
+    root: &Root,
+    start: &Path,
+    seen: &mut HashSet<PathBuf>,
+    entries: &mut Vec<FileEntry>,
+    limit: usize,
+) -> bool {
+    for result in filter.walker(start, root).build() {
+        let Ok(dir_entry) = result else {
+            continue;
+        };
+        if dir_entry.depth() == 0 && start == root.path {
+            continue;
+        }
+        let Ok(metadata) = dir_entry.metadata() else {
+            continue;
+        };
+        // The walker only sees .gitignore files from `start` down; below the root,
+        // check the rules from the directories in between as well.
+        if start != root.path && !filter.should_index(dir_entry.path(), metadata.is_dir()) {
+            continue;
+        }
+        if !seen.insert(dir_entry.path().to_path_buf()) {
+            continue;
+        }
+        if entries.len() >= limit {
+            return true;
+        }
+        entries.push(file_entry(dir_entry.path(), &metadata));
+    }
+    false
+}
+
+/// Rebuild the whole index from the configured roots.
+pub fn rebuild_file_index(
+    db: &Database,
+    settings: &FileIndexSettings,
+) -> Result<FileIndexReport, String> {
+    let started = Instant::now();
+    let filter = FileFilter::new(settings)?;
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+    let mut truncated = false;
+    for root in &filter.roots {
+        if truncated {
+            break;
+        }
+        truncated = crawl(
+            &filter,
+            root,
+            &root.path,
+            &mut seen,
+            &mut entries,
+            filter.max_entries,
+        );
+    }
+    db.replace_file_index(&entries)?;
+
+    let dirs = entries.iter().filter(|e| e.is_dir).count();
+    Ok(FileIndexReport {
+        files: entries.len() - dirs,
+        dirs,
+        truncated,
+        elapsed_ms: started.elapsed().as_millis(),
+    })
+}
+
+/// Search the index the way the launcher does: queries starting with `~` or `/` match
+/// by path prefix, anything else by name and path terms.
+pub fn search_file_index(
+    db: &Database,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<FileEntry>, String> {
+    let query = query.trim();
+    if let Some(rest) = query.strip_prefix('~') {
+        return match expand_root("~") {
+            Some(home) => {
+                db.search_files_by_prefix(&format!("{}{rest}", home.to_string_lossy()), limit)
+            }
+            None => Ok(Vec::new()),
+        };
+    }
+    if Path::new(query).is_absolute() {
+        return db.search_files_by_prefix(query, limit);
+    }
+    db.search_files(query, limit)
+}
+
+/// Bring the index in line with the current state of one path. Returns directories
+/// that were newly indexed (so a watcher can start watching them).
+pub fn apply_path_change(
+    db: &Database,
+    filter: &FileFilter,
+    path: &Path,
+) -> Result<Vec<PathBuf>, String> {
+    let path_str = path.to_string_lossy();
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) if filter.should_index(path, metadata.is_dir()) => metadata,
+        // Deleted, moved away or now ignored
+        _ => {
+            db.remove_file_entries(&path_str)?;
+            return Ok(Vec::new());
+        }
+    };
+
+    let known = db.has_file_entry(&path_str)?;
+    if known {
+        db.upsert_file_entry(&file_entry(path, &metadata))?;
+        return Ok(Vec::new());
+    }
+
+    let room = filter.max_entries.saturating_sub(db.count_file_entries()?);
+    if room == 0 {
+        return Ok(Vec::new());
+    }
+    if !metadata.is_dir() {
+        db.upsert_file_entry(&file_entry(path, &metadata))?;
+        return Ok(Vec::new());
+    }
+
+    // A new directory (created or moved in) brings its whole subtree.
+    let Some(root) = filter.root_for(path) else {
+        return Ok(Vec::new());
+    };
+    let mut entries = Vec::new();
+    crawl(filter, root, path, &mut HashSet::new(), &mut entries, room);
+    let mut new_dirs = Vec::new();
+    for entry in &entries {
+        db.upsert_file_entry(entry)?;
+        if entry.is_dir {
+            new_dirs.push(PathBuf::from(&entry.path));
+        }
+    }
+    Ok(new_dirs)
+}
+
+/// Keep the index up to date with filesystem changes until the process exits. Each
+/// indexed directory gets its own non-recursive watch so ignored trees (node_modules,
+/// build output) cost nothing. Directories that can't be watched are reported and
+/// recorded (see [`Database::file_watch_problem`]).
+pub fn watch_file_index(db: &Database, settings: &FileIndexSettings) -> Result<(), String> {
+    let filter = FileFilter::new(settings)?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(tx).map_err(|e| format!("Failed to start watcher: {e}"))?;
+    // Running out of watches (fs.inotify.max_user_watches) only loses live updates for
+    // those directories; the next rebuild still picks up their changes.
+    let mut unwatched = 0;
+    let mut watch_all = |dirs: &mut dyn Iterator<Item = &Path>| {
+        let mut first_error = None;
+        for dir in dirs {
+            if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+                unwatched += 1;
+                first_error.get_or_insert_with(|| format!("{}: {e}", dir.display()));
+            }
+        }
+        if let Some(error) = first_error {
+            let problem = format!(
+                "{unwatched} indexed directories get no live updates ({error}). Raise \
+                 fs.inotify.max_user_watches or index fewer folders."
+            );
+            eprintln!("{problem}");
+            if let Err(e) = db.set_file_watch_problem(Some(&problem)) {
+                eprintln!("Failed to record file watch problem: {e}");
+            }
+        }
+    };
+    db.set_file_watch_problem(None)?;
+    let indexed_dirs: Vec<PathBuf> = db
+        .list_indexed_dirs()?
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+    watch_all(
+        &mut filter
+            .roots()
+            .chain(indexed_dirs.iter().map(PathBuf::as_path)),
+    );
+
+    while let Ok(first) = rx.recv() {
+        let mut paths = BTreeSet::new();
+        let mut pending = Some(first);
+        while let Some(result) = pending {
+            if let Ok(event) = result {
+                if !event.kind.is_access() {
+                    paths.extend(event.paths);
+                }
+            }
+            pending = rx.recv_timeout(WATCH_DEBOUNCE).ok();
+        }
+        if paths.is_empty() {
+            continue;
+        }
+
+        for path in &paths {
+            match apply_path_change(db, &filter, path) {
+                Ok(new_dirs) => watch_all(&mut new_dirs.iter().map(PathBuf::as_path)),
+                Err(e) => eprintln!("Failed to update file index for {}: {e}", path.display()),
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,160 @@
+/// Settings key holding the launcher toggle shortcut.
+pub const TOGGLE_HOTKEY_SETTING: &str = "hotkey.toggle";
+
+/// Default toggle shortcut: Option+Space on macOS, Ctrl+Space on Windows/Linux.
+pub fn default_toggle_hotkey() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "Alt+Space"
+    } else {
+        "Ctrl+Space"
+    }
+}
+
+/// Normalize a hotkey string such as "alt+ctrl+t" into the canonical "Ctrl+Alt+T" form.
+///
+/// Modifiers are de-duplicated and ordered Ctrl, Alt, Shift, Super. The final token is the
+/// key itself; the app performs the full key-name validation when it registers the shortcut.
+pub fn normalize_hotkey(input: &str) -> Result<String, String> {
+    let tokens: Vec<&str> = input.split('+').map(str::trim).collect();
+    if tokens.iter().any(|t| t.is_empty()) {
+        return Err(format!(
+            "Invalid hotkey '{input}': empty key in combination"
+        ));
+    }
+
+    let (key, modifiers) = tokens
+        .split_last()
+        .ok_or_else(|| format!("Invalid hotkey '{input}'"))?;
+
+    let mut ctrl = false;
+    let mut alt = false;
+    let mut shift = false;
+    let mut sup = false;
+    for token in modifiers {
+        match token.to_lowercase().as_str() {
+            "ctrl" | "control" => ctrl = true,
+            "alt" | "option" => alt = true,
+            "shift" => shift = true,
+            "super" | "cmd" | "command" | "meta" | "win" => sup = true,
+            "cmdorctrl" | "cmdorcontrol" | "commandorctrl" | "commandorcontrol" => {
+                if cfg!(target_os = "macos") {
+                    sup = true;
+                } else {
+                    ctrl = true;
+                }
+            }
+            other => {
+                return Err(format!(
+                    "Invalid hotkey '{input}': '{other}' is not a modifier (use Ctrl, Alt, Shift or Super)"
+                ))
+            }
+        }
+    }
+
+    if is_modifier(key) {
+        return Err(format!(
+            "Invalid hotkey '{input}': missing a key after the modifiers"
+        ));
+    }
+
+    let mut key_chars = key.chars();
+    let is_function_key = matches!(key_chars.next(), Some('f' | 'F'))
+        && key.len() > 1
+        && key_chars.all(|c| c.is_ascii_digit());
+    let has_modifier = ctrl || alt || shift || sup;
+    if !has_modifier && !is_function_key {
+        return Err(format!(
+            "Invalid hotkey '{input}': global hotkeys need at least one modifier"
+        ));
+    }
+
+    let mut parts: Vec<String> = Vec::new();
+    if ctrl {
+        parts.push("Ctrl".to_string());
+    }
+    if alt {
+        parts.push("Alt".to_string());
+    }
+    if shift {
+        parts.push("Shift".to_string());
+    }
+    if sup {
+        parts.push("Super".to_string());
+    }
+    parts.push(normalize_key(key));
+
+    Ok(parts.join("+"))
+}
+
+/// Whether two hotkey strings describe the same key combination.
+pub fn hotkeys_equal(a: &str, b: &str) -> bool {
+    match (normalize_hotkey(a), normalize_hotkey(b)) {
+        (Ok(a), Ok(b)) => a.eq_ignore_ascii_case(&b),
+        _ => a.trim().eq_ignore_ascii_case(b.trim()),
+    }
+}
+
+fn is_modifier(token: &str) -> bool {
+    matches!(
+        token.to_lowercase().as_str(),
+        "ctrl"
+            | "control"
+            | "alt"
+            | "option"
+            | "shift"
+            | "super"
+            | "cmd"
+            | "command"
+            | "meta"
+            | "win"
+            | "cmdorctrl"
+            | "cmdorcontrol"
+            | "commandorctrl"
+            | "commandorcontrol"
+    )
+}
+
+fn normalize_key(key: &str) -> String {
+    let mut chars = key.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_modifier_order_and_case() {
+        assert_eq!(normalize_hotkey("alt+ctrl+t").unwrap(), "Ctrl+Alt+T");
+        assert_eq!(
+            normalize_hotkey(" Shift + control + Alt + space ").unwrap(),
+            "Ctrl+Alt+Shift+Space"
+        );
+        assert_eq!(normalize_hotkey("win+ctrl+ctrl+k").unwrap(), "Ctrl+Super+K");
+        assert_eq!(normalize_hotkey("F5").unwrap(), "F5");
+        assert_eq!(normalize_hotkey("option+f12").unwrap(), "Alt+F12");
+    }
+
+    #[test]
+    fn rejects_malformed_hotkeys() {
+        assert!(normalize_hotkey("").is_err());
+        assert!(normalize_hotkey("ctrl+").is_err());
+        assert!(normalize_hotkey("ctrl++t").is_err());
+        assert!(normalize_hotkey("ctrl+alt").is_err());
+        assert!(normalize_hotkey("hyper+t").is_err());
+        assert!(normalize_hotkey("t").is_err());
+        assert!(normalize_hotkey("f").is_err());
+    }
+
+    #[test]
+    fn compares_hotkeys_by_key_combination() {
+        assert!(hotkeys_equal("alt+ctrl+t", "Ctrl+Alt+T"));
+        assert!(hotkeys_equal("control+space", "ctrl+Space"));
+        assert!(!hotkeys_equal("ctrl+t", "ctrl+shift+t"));
+        assert!(hotkeys_equal(" not a hotkey ", "NOT A HOTKEY"));
+        assert!(!hotkeys_equal("ctrl+t", "t"));
+    }
+}
@@ -1,9 +1,29 @@
+pub mod actions;
+pub mod apps;
+pub mod control;
 pub mod db;
+pub mod files;
+pub mod hotkeys;
 pub mod models;
+pub mod permissions;
+pub mod schedules;
+pub mod snippets;
+pub mod workflows;
 
+pub use actions::{
+    builtin_action_name, builtin_item_actions, expand_action_value, BUILTIN_ACTION_TYPE,
+    COPY_ACTION_TYPE, ITEM_ACTION_TYPES,
+};
 pub use db::Database;
 pub use models::{
-    CommandHistory, CommandSuggestion, Conversation, ConversationMessage, ConversationWithPreview,
-    Item, Memory, NewCommandHistory, NewConversation, NewConversationMessage, NewItem, NewMemory,
-    NewSlashCommand, Setting, SlashCommand, UpdateItem,
+    ClipboardEntry, ClipboardImage, ClipboardSettings, CommandHistory, CommandSuggestion,
+    ContextScript, Conversation, ConversationMessage, ConversationWithPreview, FileEntry,
+    FileIndexReport, FileIndexSettings, HotkeyBinding, Item, ItemAction, ManagedItem, Memory,
+    NewClipboardEntry, NewCommandHistory, NewConversation, NewConversationMessage, NewItem,
+    NewItemAction, NewMemory, NewPermissionRule, NewSchedule, NewSlashCommand, PermissionGrant,
+    PermissionRule, RunningProcess, Schedule, Setting, SlashCommand, SyncReport, ToolCallOption,
+    ToolCallPermission, ToolCallRecord, ToolCallStatus, UpdateItem,
+};
+pub use snippets::{
+    expand_snippet, validate_snippet, SnippetExpansion, SnippetInputs, SNIPPET_ACTION_TYPE,
 };
@@ -12,6 +12,10 @@ pub struct Item {
     pub tags: String,
     pub frequency: i64,
     pub enabled: bool,
+    pub hotkey: Option<String>,
+    /// Indexer that manages this item (e.g. "applications"); `None` for user-created items.
+    #[serde(default)]
+    pub source: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -25,6 +29,9 @@ pub struct NewItem {
     pub action_value: String,
     pub category: Option<String>,
     pub tags: Option<String>,
+    /// Global hotkey that executes the item directly (e.g. "Ctrl+Alt+T").
+    #[serde(default)]
+    pub hotkey: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -37,6 +44,165 @@ pub struct UpdateItem {
     pub category: Option<String>,
     pub tags: Option<String>,
     pub enabled: Option<bool>,
+    /// New hotkey; an empty string clears the current binding.
+    pub hotkey: Option<String>,
+}
+
+/// An extra action on an item besides its primary `action_type`/`action_value`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemAction {
+    pub id: String,
+    pub item_id: String,
+    pub label: String,
+    pub action_type: String,
+    /// Action value; `{value}` is replaced by the item's own action value (shell-quoted
+    /// in command and script actions).
+    pub value: String,
+    /// Launcher shortcut while the item is selected (e.g. "Ctrl+Enter").
+    pub shortcut: Option<String>,
+    pub position: i64,
+    /// Built-in actions exist for every item and are not stored.
+    #[serde(default)]
+    pub builtin: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewItemAction {
+    pub item_id: String,
+    pub label: String,
+    pub action_type: String,
+    pub value: String,
+    #[serde(default)]
+    pub shortcut: Option<String>,
+    /// Sort position; defaults to after the item's existing actions.
+    #[serde(default)]
+    pub position: Option<i64>,
+}
+
+/// A launcher item or slash command that runs on a timetable or trigger.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    pub id: String,
+    /// "item" or "slash_command".
+    pub target_type: String,
+    pub target_id: String,
+    /// Item title or "/name" of the target, if it still exists.
+    pub target_name: Option<String>,
+    /// Cron expression, phrase ("every weekday 09:00") or trigger ("on start").
+    pub spec: String,
+    /// "skip" or "run-once".
+    pub missed_policy: String,
+    pub enabled: bool,
+    pub last_run_at: Option<String>,
+    /// "ok", "exit <code>" or "error: <message>".
+    pub last_status: Option<String>,
+    pub last_exit_code: Option<i32>,
+    /// Next due time (UTC) for timetable schedules; `None` for triggers.
+    pub next_run_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewSchedule {
+    pub target_type: String,
+    pub target_id: String,
+    pub spec: String,
+    #[serde(default)]
+    pub missed_policy: Option<String>,
+}
+
+/// A rule deciding whether an agent's tool call runs without asking (see
+/// `permissions::evaluate`). Empty filters match anything.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionRule {
+    pub id: String,
+    /// 1-based; the first matching rule decides.
+    pub position: i64,
+    /// "allow", "deny" or "ask".
+    pub action: String,
+    /// ACP tool kind ("read", "edit", "execute", ...).
+    pub tool_kind: Option<String>,
+    /// Glob on the tool call title.
+    pub title: Option<String>,
+    /// Command prefix, one glob per word; `**` matches any number of words.
+    pub command: Option<String>,
+    pub agent_id: Option<String>,
+    /// Glob on the app or window title the launcher was opened from.
+    pub context: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NewPermissionRule {
+    pub action: String,
+    #[serde(default)]
+    pub tool_kind: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+    #[serde(default)]
+    pub context: Option<String>,
+    /// Insert before the rule at this position; appended when `None`.
+    #[serde(default)]
+    pub position: Option<i64>,
+}
+
+/// A remembered "always allow" or "always reject" answer to an agent's permission
+/// request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionGrant {
+    pub id: String,
+    pub agent_id: String,
+    /// Tool kind and command or title (see `permissions::tool_signature`).
+    pub signature: String,
+    /// "allow" or "reject".
+    pub decision: String,
+    /// UTC; `None` never expires.
+    pub expires_at: Option<String>,
+    pub created_at: String,
+}
+
+/// A child process started by the app (an item or slash command), as listed by `ps`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunningProcess {
+    pub pid: u32,
+    pub item_id: Option<String>,
+    /// Item title or "/name" of the slash command.
+    pub title: String,
+    pub command: String,
+    /// Local time, RFC 3339.
+    pub started_at: String,
+    pub running: bool,
+    /// Set once the process has exited (`None` when killed by a signal).
+    pub exit_code: Option<i32>,
+}
+
+/// An item produced by an indexer, keyed by `source_key` within its source.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagedItem {
+    pub source_key: String,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub icon: Option<String>,
+    pub action_type: String,
+    pub action_value: String,
+    pub category: String,
+    pub tags: String,
+}
+
+/// Outcome of syncing an indexer's items into the database.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+    /// Items left as they are because the user edited them.
+    pub preserved: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +219,9 @@ pub struct CommandHistory {
     pub action_type: String,
     pub executed_at: String,
     pub source: String,
+    /// Exit code, for runs that were waited on; `None` for fire-and-forget launches.
+    #[serde(default)]
+    pub exit_code: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,6 +230,8 @@ pub struct NewCommandHistory {
     pub command_text: String,
     pub action_type: String,
     pub source: Option<String>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -112,16 +283,69 @@ pub struct NewConversation {
 pub struct ConversationMessage {
     pub id: String,
     pub conversation_id: String,
+    /// "user", "assistant", "thought" or "plan".
     pub role: String,
     pub content: String,
+    /// Part of an agent turn that was cancelled, failed or hasn't finished.
+    pub incomplete: bool,
+    pub created_at: String,
+}
+
+/// A tool call an agent made during a conversation, with its permission outcome.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    pub id: String,
+    pub conversation_id: String,
+    /// The agent's ID for the call, unique within its session.
+    pub tool_call_id: String,
+    pub title: Option<String>,
+    /// ACP tool kind ("read", "edit", "execute", ...).
+    pub kind: Option<String>,
+    /// Latest status: "pending", "in_progress", "completed" or "failed".
+    pub status: Option<String>,
+    pub command_preview: Option<String>,
+    /// Options of the permission request, if the agent asked for one.
+    pub permission_options: Vec<ToolCallOption>,
+    /// Name of the option chosen.
+    pub chosen_option: Option<String>,
+    /// "user", "rule", "remembered" or "cancelled"; `None` without a permission request.
+    pub decided_by: Option<String>,
+    /// Allowed by a rule or remembered answer without asking.
+    pub auto_allowed: bool,
+    pub statuses: Vec<ToolCallStatus>,
     pub created_at: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallOption {
+    pub option_id: String,
+    pub name: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallStatus {
+    pub status: String,
+    pub created_at: String,
+}
+
+/// How a tool call's permission request was answered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallPermission {
+    pub command_preview: Option<String>,
+    pub options: Vec<ToolCallOption>,
+    /// Option ID chosen; `None` when the request was cancelled.
+    pub chosen_option_id: Option<String>,
+    pub decided_by: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewConversationMessage {
     pub conversation_id: String,
     pub role: String,
     pub content: String,
+    #[serde(default)]
+    pub incomplete: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -143,6 +367,7 @@ pub struct SlashCommand {
     pub description: String,
     pub script_path: String,
     pub usage_count: i64,
+    pub hotkey: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -152,4 +377,166 @@ pub struct NewSlashCommand {
     pub name: String,
     pub description: String,
     pub script_path: String,
+    #[serde(default)]
+    pub hotkey: Option<String>,
+}
+
+// --- Hotkeys ---
+
+/// A global hotkey assignment, resolved from settings, items and slash commands.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeyBinding {
+    pub hotkey: String,
+    /// One of 'toggle', 'item' or 'slash_command'.
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub label: String,
+}
+
+// --- Launch context ---
+
+/// A user shell-script launch context provider. The command's `key=value` output
+/// lines are added to the agent prompt under the script's name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextScript {
+    pub name: String,
+    pub command: String,
+}
+
+// --- Clipboard history ---
+
+/// A clipboard history entry. Image pixels are not loaded with the entry;
+/// use `Database::get_clipboard_image` for those.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardEntry {
+    pub id: String,
+    /// One of 'text' or 'image'.
+    pub content_type: String,
+    /// The copied text (empty for images).
+    pub content: String,
+    /// First line of the text, or the image dimensions.
+    pub preview: String,
+    pub size_bytes: i64,
+    pub source_app: Option<String>,
+    pub pinned: bool,
+    pub copy_count: i64,
+    pub created_at: String,
+    pub last_copied_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewClipboardEntry {
+    pub content_type: String,
+    pub content: String,
+    pub image: Option<ClipboardImage>,
+    pub source_app: Option<String>,
+}
+
+/// Raw RGBA pixels of a copied image.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardImage {
+    pub width: usize,
+    pub height: usize,
+    pub rgba: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardSettings {
+    /// History is opt-in; nothing is recorded until this is set.
+    pub enabled: bool,
+    pub capture_images: bool,
+    /// Unpinned entries beyond this count are pruned, oldest first.
+    pub max_entries: usize,
+    /// Larger copies are not recorded.
+    pub max_item_bytes: usize,
+    /// Process names whose copies are never recorded (case-insensitive substring match).
+    pub excluded_apps: Vec<String>,
+}
+
+impl Default for ClipboardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            capture_images: false,
+            max_entries: 500,
+            max_item_bytes: 5 * 1024 * 1024,
+            excluded_apps: ["1password", "bitwarden", "keepass", "lastpass", "enpass"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl ClipboardSettings {
+    pub fn is_excluded(&self, source_app: Option<&str>) -> bool {
+        let Some(app) = source_app else {
+            return false;
+        };
+        let app = app.to_lowercase();
+        self.excluded_apps
+            .iter()
+            .any(|excluded| !excluded.is_empty() && app.contains(&excluded.to_lowercase()))
+    }
+}
+
+/// A file or directory in the local file index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub path: String,
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    /// Last modification time as Unix seconds, if known.
+    pub modified: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileIndexSettings {
+    /// The index is opt-in; nothing is crawled or watched until this is set.
+    pub enabled: bool,
+    /// Directories to crawl; `~` expands to the home directory.
+    pub roots: Vec<String>,
+    /// Gitignore-style patterns that are never indexed (e.g. "node_modules", "*.log").
+    pub ignore_globs: Vec<String>,
+    pub respect_gitignore: bool,
+    pub include_hidden: bool,
+    /// Crawling stops once this many entries are indexed.
+    pub max_entries: usize,
+}
+
+impl Default for FileIndexSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            roots: vec!["~".to_string()],
+            ignore_globs: [
+                "node_modules",
+                "target",
+                "__pycache__",
+                "venv",
+                ".venv",
+                "*.pyc",
+                "*.o",
+                "*.tmp",
+                "*.swp",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+            respect_gitignore: true,
+            include_hidden: false,
+            max_entries: 100_000,
+        }
+    }
+}
+
+/// Outcome of a full file index rebuild.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileIndexReport {
+    pub files: usize,
+    pub dirs: usize,
+    /// The crawl hit `max_entries` before visiting everything.
+    pub truncated: bool,
+    pub elapsed_ms: u128,
 }
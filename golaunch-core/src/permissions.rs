@@ -0,0 +1,1091 @@
+//! Rules deciding whether an agent's tool call runs without asking. Rules are tried
+//! in order; the first whose filters all match decides. Shell commands are tokenized
+//! first, and every command in a line (`a; b`, `a | b`, `sh -c "b"`) has to be allowed
+//! on its own for the line to be allowed. Wrappers such as `sudo` or `env` are looked
+//! through, so deny rules also catch the command they run.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{NewPermissionRule, PermissionRule};
+
+/// What happens to a tool call a rule matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Run it without asking.
+    Allow,
+    /// Reject it without asking.
+    Deny,
+    /// Show the permission prompt.
+    Ask,
+}
+
+impl RuleAction {
+    pub fn parse(action: &str) -> Result<Self, String> {
+        match action.trim().to_lowercase().as_str() {
+            "allow" => Ok(Self::Allow),
+            "deny" => Ok(Self::Deny),
+            "ask" => Ok(Self::Ask),
+            other => Err(format!(
+                "Invalid rule action '{other}' (use allow, deny or ask)"
+            )),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+            Self::Ask => "ask",
+        }
+    }
+}
+
+/// The tool call being decided on.
+#[derive(Debug, Clone, Default)]
+pub struct ToolRequest<'a> {
+    /// ACP tool kind: "read", "edit", "delete", "move", "search", "execute", "fetch", ...
+    pub kind: Option<&'a str>,
+    pub title: Option<&'a str>,
+    /// Shell command line shown for the call, if any.
+    pub command: Option<&'a str>,
+}
+
+/// Who is asking and where the launcher was opened from.
+#[derive(Debug, Clone, Default)]
+pub struct RuleScope {
+    pub agent_id: Option<String>,
+    /// Process name of the app the launcher was opened from.
+    pub source_app: Option<String>,
+    pub source_window: Option<String>,
+}
+
+/// One command of a shell line, after quote removal.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SimpleCommand {
+    /// Wrapper commands and their options in front of the program, e.g. `sudo -u x`.
+    pub wrappers: Vec<String>,
+    /// Program and arguments.
+    pub words: Vec<String>,
+    /// Files written by `>`, `>>` or `&>` redirections (other than /dev/null).
+    pub writes: Vec<String>,
+}
+
+impl SimpleCommand {
+    /// The command as written: wrappers followed by the program and arguments.
+    pub fn full_words(&self) -> Vec<String> {
+        [self.wrappers.as_slice(), self.words.as_slice()].concat()
+    }
+}
+
+/// Rules stored in a new database, and restored by `golaunch-cli permissions reset`:
+/// read-only golaunch-cli commands run without asking, everything else asks.
+pub fn default_rules() -> Vec<NewPermissionRule> {
+    [
+        "golaunch-cli list",
+        "golaunch-cli search",
+        "golaunch-cli history",
+        "golaunch-cli ps",
+        "golaunch-cli db-path",
+        "golaunch-cli * list",
+        "golaunch-cli * search",
+        "golaunch-cli * get",
+        "golaunch-cli conversations show",
+        "golaunch-cli conversations context",
+    ]
+    .into_iter()
+    .map(|command| NewPermissionRule {
+        action: "allow".to_string(),
+        command: Some(command.to_string()),
+        ..Default::default()
+    })
+    .collect()
+}
+
+/// Check a rule's patterns before it is stored.
+pub fn validate_rule(rule: &NewPermissionRule) -> Result<(), String> {
+    RuleAction::parse(&rule.action)?;
+    if let Some(pattern) = &rule.command {
+        command_pattern(pattern)?;
+    }
+    Ok(())
+}
+
+/// Setting holding the folders agents may read and write through ACP, joined like
+/// `PATH`. Unset means the slash-commands folder.
+pub const FILE_ROOTS_SETTING: &str = "permissions.file_roots";
+
+/// Setting holding how many days a remembered "always" decision lasts ("0" = forever).
+pub const GRANT_DAYS_SETTING: &str = "permissions.grant_days";
+pub const DEFAULT_GRANT_DAYS: i64 = 30;
+
+/// What a remembered "always allow"/"always reject" applies to: the tool kind with the
+/// command as parsed (so quoting and spacing don't matter), or with the title for tool
+/// calls that don't run a command.
+pub fn tool_signature(request: &ToolRequest) -> String {
+    let kind = request.kind.unwrap_or("other");
+    let subject = match request.command {
+        Some(line) => match parse_command_line(line) {
+            Ok(commands) => commands
+                .iter()
+                .map(|command| {
+                    let mut parts: Vec<String> =
+                        command.full_words().iter().map(|w| quote_word(w)).collect();
+                    parts.extend(
+                        command
+                            .writes
+                            .iter()
+                            .map(|w| format!("> {}", quote_word(w))),
+                    );
+                    parts.join(" ")
+                })
+                .collect::<Vec<_>>()
+                .join("; "),
+            Err(_) => line.split_whitespace().collect::<Vec<_>>().join(" "),
+        },
+        None => request.title.unwrap_or_default().trim().to_string(),
+    };
+    format!("{kind}: {subject}")
+}
+
+/// Quote a word for a shell line when it isn't plain.
+pub fn quote_word(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_alphanumeric() || "-_./:=,@%+~".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+/// Decide on a tool call. Without a matching rule the user is asked.
+pub fn evaluate(rules: &[PermissionRule], request: &ToolRequest, scope: &RuleScope) -> RuleAction {
+    let rules: Vec<&PermissionRule> = rules
+        .iter()
+        .filter(|rule| rule_applies(rule, request, scope))
+        .collect();
+    let first_action = |words: Option<&[String]>| {
+        rules
+            .iter()
+            .find(|rule| match (&rule.command, words) {
+                (None, _) => true,
+                (Some(pattern), Some(words)) => command_matches(pattern, words),
+                (Some(_), None) => false,
+            })
+            .and_then(|rule| RuleAction::parse(&rule.action).ok())
+            .unwrap_or(RuleAction::Ask)
+    };
+
+    let Some(line) = request.command else {
+        return first_action(None);
+    };
+    let commands = match parse_command_line(line) {
+        Ok(commands) if !commands.is_empty() => commands,
+        // What would run can't be told (e.g. `$(...)`), so never allow it unasked
+        _ => {
+            return match first_action(None) {
+                RuleAction::Deny => RuleAction::Deny,
+                _ => RuleAction::Ask,
+            }
+        }
+    };
+
+    let mut decision = RuleAction::Allow;
+    for command in &commands {
+        // Allowing needs a rule for the command as written, but a deny rule for the
+        // program inside a wrapper (`sudo rm`) still applies
+        let mut action = first_action(Some(&command.full_words()));
+        if !command.wrappers.is_empty() && first_action(Some(&command.words)) == RuleAction::Deny {
+            action = RuleAction::Deny;
+        }
+        // Writing files goes beyond what a rule names
+        if action == RuleAction::Allow && !command.writes.is_empty() {
+            action = RuleAction::Ask;
+        }
+        match action {
+            RuleAction::Deny => return RuleAction::Deny,
+            RuleAction::Ask => decision = RuleAction::Ask,
+            RuleAction::Allow => {}
+        }
+    }
+    decision
+}
+
+fn rule_applies(rule: &PermissionRule, request: &ToolRequest, scope: &RuleScope) -> bool {
+    let equals = |expected: &Option<String>, actual: Option<&str>| match expected {
+        None => true,
+        Some(expected) => actual.is_some_and(|actual| actual.eq_ignore_ascii_case(expected)),
+    };
+    let glob = |pattern: &Option<String>, values: &[Option<&str>]| match pattern {
+        None => true,
+        Some(pattern) => values
+            .iter()
+            .flatten()
+            .any(|value| glob_matches(pattern, value)),
+    };
+    equals(&rule.tool_kind, request.kind)
+        && equals(&rule.agent_id, scope.agent_id.as_deref())
+        && glob(&rule.title, &[request.title])
+        && glob(
+            &rule.context,
+            &[scope.source_app.as_deref(), scope.source_window.as_deref()],
+        )
+}
+
+/// Case-insensitive wildcard match: `*` matches any run of characters, `?` one.
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*`, and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    p = star_p;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Words of a command pattern: globs per word, `**` for any number of words.
+fn command_pattern(pattern: &str) -> Result<Vec<String>, String> {
+    match parse_nested(pattern, 0, true)?.as_slice() {
+        [command] if command.writes.is_empty() => Ok(command.full_words()),
+        _ => Err(format!(
+            "Invalid command pattern '{pattern}' (use a single command, e.g. \"git status\")"
+        )),
+    }
+}
+
+/// Whether a command starts with the pattern's words. The program word also matches
+/// by file name, so `golaunch-cli` matches `/usr/bin/golaunch-cli` and
+/// `golaunch-cli.exe`.
+fn command_matches(pattern: &str, words: &[String]) -> bool {
+    let Ok(pattern) = command_pattern(pattern) else {
+        return false;
+    };
+    words_match(&pattern, words, true)
+}
+
+fn words_match(pattern: &[String], words: &[String], program: bool) -> bool {
+    let Some((first, rest)) = pattern.split_first() else {
+        return true;
+    };
+    if first == "**" {
+        return (0..=words.len()).any(|skip| words_match(rest, &words[skip..], false));
+    }
+    let Some((word, remaining)) = words.split_first() else {
+        return false;
+    };
+    let matched = glob_matches(first, word) || (program && glob_matches(first, program_name(word)));
+    matched && words_match(rest, remaining, false)
+}
+
+fn program_name(word: &str) -> &str {
+    let name = word.rsplit(['/', '\\']).next().unwrap_or(word);
+    [".exe", ".cmd", ".bat"]
+        .iter()
+        .find_map(|ext| {
+            name.len()
+                .checked_sub(ext.len())
+                .filter(|&cut| name.is_char_boundary(cut) && name[cut..].eq_ignore_ascii_case(ext))
+                .map(|cut| &name[..cut])
+        })
+        .unwrap_or(name)
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    /// A word after quote removal. `expands` is set when the shell would change it
+    /// (`$`, or unquoted glob characters), so what it runs can't be read from the text.
+    Word { text: String, expands: bool },
+    /// `;`, `&&`, `||`, `|`, `|&`, `&` or a newline.
+    Separator,
+    /// A redirection operator such as `>`, `2>>` or `<`.
+    Redirect(String),
+}
+
+/// Split a POSIX shell line into its simple commands. Commands whose effect can't be
+/// read from the text (command or process substitution, subshells, here-documents,
+/// compound commands, variable assignments and program names the shell expands) are an
+/// error. `sh -c` and `eval` scripts are split too.
+pub fn parse_command_line(line: &str) -> Result<Vec<SimpleCommand>, String> {
+    parse_nested(line, 0, false)
+}
+
+/// `sh -c`-style wrappers are unpacked up to this depth.
+const MAX_NESTING: usize = 3;
+
+/// In a rule `pattern`, globs in the program word are part of the pattern.
+fn parse_nested(line: &str, depth: usize, pattern: bool) -> Result<Vec<SimpleCommand>, String> {
+    // Each command with whether each of its words is expanded by the shell
+    let mut commands = Vec::new();
+    let mut current = SimpleCommand::default();
+    let mut expands = Vec::new();
+    let mut tokens = tokenize(line)?.into_iter();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Separator => {
+                commands.push((std::mem::take(&mut current), std::mem::take(&mut expands)));
+            }
+            Token::Redirect(op) => {
+                let Some(Token::Word { text: target, .. }) = tokens.next() else {
+                    return Err(format!("Missing target after '{op}'"));
+                };
+                let duplicates_fd = op.ends_with('&') && (target == "-" || is_digits(&target));
+                let writes = op.contains('>') && !duplicates_fd;
+                if writes && target != "/dev/null" {
+                    current.writes.push(target);
+                }
+            }
+            Token::Word {
+                text: word,
+                expands: word_expands,
+            } => {
+                if current.words.is_empty() {
+                    if is_assignment(&word) {
+                        return Err(format!("Variable assignments are not supported ('{word}')"));
+                    }
+                    if RESERVED_WORDS.contains(&word.as_str()) {
+                        return Err(format!("Shell keywords are not supported ('{word}')"));
+                    }
+                }
+                current.words.push(word);
+                expands.push(word_expands);
+            }
+        }
+    }
+    commands.push((current, expands));
+
+    let mut expanded = Vec::new();
+    for (mut command, expands) in commands {
+        if command.words.is_empty() {
+            if !command.writes.is_empty() {
+                expanded.push(command);
+            }
+            continue;
+        }
+        unwrap_command(&mut command)?;
+        // `$X rm` or `/bin/r? x` runs a program the rules can't see
+        let program = command.wrappers.len();
+        if !pattern && (expands[0] || expands[program]) {
+            let word = if expands[0] {
+                command.full_words()[0].clone()
+            } else {
+                command.words[0].clone()
+            };
+            return Err(format!(
+                "'{word}' is expanded by the shell, so what it runs is unknown"
+            ));
+        }
+        match shell_script(&command) {
+            Some(script) if depth < MAX_NESTING => {
+                let mut inner = parse_nested(&script, depth + 1, pattern)?;
+                for inner_command in &mut inner {
+                    inner_command
+                        .wrappers
+                        .splice(0..0, command.wrappers.iter().cloned());
+                }
+                if let Some(first) = inner.first_mut() {
+                    first.writes.extend(command.writes.iter().cloned());
+                }
+                expanded.extend(inner);
+            }
+            Some(_) => return Err("Shell commands are nested too deeply".to_string()),
+            None => expanded.push(command),
+        }
+    }
+    Ok(expanded)
+}
+
+/// Words that start a compound command or change how the next command runs. Their
+/// effect can't be judged one simple command at a time.
+const RESERVED_WORDS: &[&str] = &[
+    "if", "then", "else", "elif", "fi", "case", "esac", "for", "select", "while", "until", "do",
+    "done", "in", "function", "time", "coproc", "{", "}", "!", "[[", "]]",
+];
+
+/// A command that runs its arguments as another command.
+struct Wrapper {
+    name: &'static str,
+    /// Short options that take a value, e.g. `u` for `sudo -u root`.
+    short_with_value: &'static str,
+    /// Long options (without `--`) that take a value when it isn't attached with `=`.
+    long_with_value: &'static [&'static str],
+    /// Short options whose effect on the command can't be followed.
+    unsupported_short: &'static str,
+    unsupported_long: &'static [&'static str],
+    /// Operands between the options and the command, e.g. the duration of `timeout`.
+    operands: usize,
+}
+
+impl Wrapper {
+    const fn plain(name: &'static str) -> Self {
+        Self {
+            name,
+            short_with_value: "",
+            long_with_value: &[],
+            unsupported_short: "",
+            unsupported_long: &[],
+            operands: 0,
+        }
+    }
+}
+
+const WRAPPERS: &[Wrapper] = &[
+    Wrapper::plain("command"),
+    Wrapper::plain("builtin"),
+    Wrapper::plain("nohup"),
+    Wrapper::plain("setsid"),
+    Wrapper {
+        short_with_value: "a",
+        ..Wrapper::plain("exec")
+    },
+    Wrapper {
+        short_with_value: "uC",
+        long_with_value: &["unset", "chdir"],
+        unsupported_short: "S",
+        unsupported_long: &["split-string"],
+        ..Wrapper::plain("env")
+    },
+    Wrapper {
+        short_with_value: "n",
+        long_with_value: &["adjustment"],
+        ..Wrapper::plain("nice")
+    },
+    Wrapper {
+        short_with_value: "ks",
+        long_with_value: &["kill-after", "signal"],
+        operands: 1,
+        ..Wrapper::plain("timeout")
+    },
+    Wrapper {
+        short_with_value: "ioe",
+        long_with_value: &["input", "output", "error"],
+        ..Wrapper::plain("stdbuf")
+    },
+    Wrapper {
+        short_with_value: "adEILnPs",
+        long_with_value: &[
+            "arg-file",
+            "delimiter",
+            "max-args",
+            "max-procs",
+            "max-chars",
+            "process-slot-var",
+        ],
+        ..Wrapper::plain("xargs")
+    },
+    Wrapper {
+        short_with_value: "CDghpRrTtUu",
+        long_with_value: &[
+            "close-from",
+            "chdir",
+            "group",
+            "host",
+            "prompt",
+            "chroot",
+            "role",
+            "type",
+            "command-timeout",
+            "other-user",
+            "user",
+        ],
+        // `sudo -e` edits the files it is given
+        unsupported_short: "e",
+        unsupported_long: &["edit"],
+        ..Wrapper::plain("sudo")
+    },
+    Wrapper {
+        short_with_value: "Cu",
+        ..Wrapper::plain("doas")
+    },
+];
+
+/// Move wrapper commands (`sudo -u x`, `env -i`, `xargs -0`, ...) from the front of
+/// `words` into `wrappers`, so rules see the program they run.
+fn unwrap_command(command: &mut SimpleCommand) -> Result<(), String> {
+    let mut start = 0;
+    while let Some(wrapper) = command
+        .words
+        .get(start)
+        .and_then(|word| WRAPPERS.iter().find(|w| w.name == program_name(word)))
+    {
+        let words = &command.words;
+        let unsupported = |word: &str| format!("'{} {word}' is not supported", wrapper.name);
+        let mut index = start + 1;
+        while let Some(word) = words.get(index) {
+            if word == "--" {
+                index += 1;
+                break;
+            }
+            if word == "-" || !word.starts_with('-') {
+                break;
+            }
+            index += 1;
+            if let Some(long) = word.strip_prefix("--") {
+                let (name, value) = match long.split_once('=') {
+                    Some((name, value)) => (name, Some(value)),
+                    None => (long, None),
+                };
+                if wrapper.unsupported_long.contains(&name) {
+                    return Err(unsupported(word));
+                }
+                if value.is_none() && wrapper.long_with_value.contains(&name) {
+                    index += 1;
+                }
+                continue;
+            }
+            for (position, flag) in word.char_indices().skip(1) {
+                if wrapper.unsupported_short.contains(flag) {
+                    return Err(unsupported(word));
+                }
+                if wrapper.short_with_value.contains(flag) {
+                    // The value is the rest of the word (`-uroot`) or the next word
+                    if position + flag.len_utf8() == word.len() {
+                        index += 1;
+                    }
+                    break;
+                }
+            }
+        }
+        index += wrapper.operands;
+        if let Some(word) = words.get(index).filter(|w| is_assignment(w)) {
+            return Err(format!("Variable assignments are not supported ('{word}')"));
+        }
+        if index >= words.len() {
+            // Nothing to run (`env`, `exec > log`): the wrapper is the command
+            break;
+        }
+        start = index;
+    }
+    command.wrappers = command.words.drain(..start).collect();
+    Ok(())
+}
+
+/// The script of `sh -c "<script>"` (also bash, zsh, dash, with flags like `-lc`), or
+/// of `eval`, which runs its arguments joined by spaces.
+fn shell_script(command: &SimpleCommand) -> Option<String> {
+    let (program, args) = command.words.split_first()?;
+    if program_name(program) == "eval" {
+        return Some(args.join(" "));
+    }
+    if !["sh", "bash", "zsh", "dash"].contains(&program_name(program)) {
+        return None;
+    }
+    let mut args = args.iter();
+    for arg in args.by_ref() {
+        match arg.strip_prefix('-') {
+            Some(flags) if !arg.starts_with("--") && flags.contains('c') => {
+                return args.next().cloned();
+            }
+            Some(_) => continue,
+            None => return None,
+        }
+    }
+    None
+}
+
+fn is_digits(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_assignment(word: &str) -> bool {
+    let Some((name, _)) = word.split_once('=') else {
+        return false;
+    };
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    let mut word = String::new();
+    // Quotes make a word even when empty (`''`)
+    let mut in_word = false;
+    // Unquoted `$`, `*`, `?`, `[` or `{`, or a `$` in double quotes
+    let mut expands = false;
+
+    fn flush(tokens: &mut Vec<Token>, word: &mut String, in_word: &mut bool, expands: &mut bool) {
+        if *in_word {
+            let text = std::mem::take(word);
+            // A lone `[` is the test command, not a pattern
+            let expands = std::mem::take(expands) && text != "[" && text != "{";
+            tokens.push(Token::Word { text, expands });
+            *in_word = false;
+        }
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\r' => flush(&mut tokens, &mut word, &mut in_word, &mut expands),
+            '\n' | ';' => {
+                flush(&mut tokens, &mut word, &mut in_word, &mut expands);
+                tokens.push(Token::Separator);
+            }
+            '&' => {
+                flush(&mut tokens, &mut word, &mut in_word, &mut expands);
+                match chars.peek() {
+                    Some('&') => {
+                        chars.next();
+                        tokens.push(Token::Separator);
+                    }
+                    Some('>') => {
+                        chars.next();
+                        let mut op = "&>".to_string();
+                        if chars.peek() == Some(&'>') {
+                            chars.next();
+                            op.push('>');
+                        }
+                        tokens.push(Token::Redirect(op));
+                    }
+                    _ => tokens.push(Token::Separator),
+                }
+            }
+            '|' => {
+                flush(&mut tokens, &mut word, &mut in_word, &mut expands);
+                if matches!(chars.peek(), Some('|') | Some('&')) {
+                    chars.next();
+                }
+                tokens.push(Token::Separator);
+            }
+            '>' | '<' => {
+                if chars.peek() == Some(&'(') {
+                    return Err("Process substitution is not supported".to_string());
+                }
+                if c == '<' && chars.peek() == Some(&'<') {
+                    return Err("Here-documents are not supported".to_string());
+                }
+                // `2>` redirects a file descriptor
+                let mut op = if in_word && is_digits(&word) {
+                    in_word = false;
+                    expands = false;
+                    std::mem::take(&mut word)
+                } else {
+                    flush(&mut tokens, &mut word, &mut in_word, &mut expands);
+                    String::new()
+                };
+                op.push(c);
+                // `>>`, `>|`, `>&`, `<&` and `<>`
+                if let Some(&next) = chars.peek() {
+                    let second = match c {
+                        '>' => matches!(next, '>' | '|' | '&'),
+                        _ => matches!(next, '&' | '>'),
+                    };
+                    if second {
+                        op.push(next);
+                        chars.next();
+                    }
+                }
+                tokens.push(Token::Redirect(op));
+            }
+            '(' | ')' => return Err("Subshells are not supported".to_string()),
+            '`' => return Err("Command substitution is not supported".to_string()),
+            '$' if chars.peek() == Some(&'(') => {
+                return Err("Command substitution is not supported".to_string())
+            }
+            '#' if !in_word => {
+                // Comment to the end of the line
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('$' | '`' | '"' | '\\')) => word.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err("Unterminated quote".to_string()),
+                        },
+                        Some('`') => {
+                            return Err("Command substitution is not supported".to_string())
+                        }
+                        Some('$') if chars.peek() == Some(&'(') => {
+                            return Err("Command substitution is not supported".to_string())
+                        }
+                        Some(c) => {
+                            expands |= c == '$';
+                            word.push(c);
+                        }
+                        None => return Err("Unterminated quote".to_string()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                // Line continuation
+                Some('\n') => {}
+                Some(c) => {
+                    in_word = true;
+                    word.push(c);
+                }
+                None => {}
+            },
+            c => {
+                in_word = true;
+                expands |= matches!(c, '$' | '*' | '?' | '[' | '{');
+                word.push(c);
+            }
+        }
+    }
+    flush(&mut tokens, &mut word, &mut in_word, &mut expands);
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<Vec<String>> {
+        parse_command_line(line)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.words)
+            .collect()
+    }
+
+    fn rule(action: &str, command: &str) -> PermissionRule {
+        PermissionRule {
+            id: command.to_string(),
+            position: 0,
+            action: action.to_string(),
+            tool_kind: None,
+            title: None,
+            command: Some(command.to_string()),
+            agent_id: None,
+            context: None,
+            created_at: String::new(),
+        }
+    }
+
+    fn decide(rules: &[PermissionRule], command: &str) -> RuleAction {
+        let request = ToolRequest {
+            kind: Some("execute"),
+            title: None,
+            command: Some(command),
+        };
+        evaluate(rules, &request, &RuleScope::default())
+    }
+
+    fn defaults() -> Vec<PermissionRule> {
+        default_rules()
+            .into_iter()
+            .map(|r| rule(&r.action, r.command.as_deref().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_quotes_and_separators() {
+        assert_eq!(
+            words(r#"git commit -m "a; b" && echo 'x | y'"#),
+            vec![vec!["git", "commit", "-m", "a; b"], vec!["echo", "x | y"]]
+        );
+        assert_eq!(
+            words("a|b||c;d&e\nf"),
+            vec![
+                vec!["a"],
+                vec!["b"],
+                vec!["c"],
+                vec!["d"],
+                vec!["e"],
+                vec!["f"]
+            ]
+        );
+        assert_eq!(
+            words(r#"echo "a\"b" c\ d ''"#),
+            vec![vec!["echo", "a\"b", "c d", ""]]
+        );
+        assert_eq!(words("ls # rm -rf /"), vec![vec!["ls"]]);
+        assert_eq!(
+            words(r#"bash -lc "golaunch-cli list; rm x""#),
+            vec![vec!["golaunch-cli", "list"], vec!["rm", "x"]]
+        );
+    }
+
+    #[test]
+    fn tracks_writes() {
+        let commands = parse_command_line("ls > out.txt 2>&1 2>/dev/null").unwrap();
+        assert_eq!(commands[0].words, vec!["ls"]);
+        assert_eq!(commands[0].writes, vec!["out.txt"]);
+    }
+
+    #[test]
+    fn unwraps_wrapper_commands() {
+        // Each command as "wrappers => program and arguments"
+        let parsed = |line: &str| -> Vec<String> {
+            parse_command_line(line)
+                .unwrap()
+                .into_iter()
+                .map(|c| format!("{} => {}", c.wrappers.join(" "), c.words.join(" ")))
+                .collect()
+        };
+        assert_eq!(
+            parsed("sudo -u root -E nohup env -i rm -rf ~"),
+            vec!["sudo -u root -E nohup env -i => rm -rf ~"]
+        );
+        assert_eq!(
+            parsed("find . | xargs -0 -n 1 command /bin/rm"),
+            vec![" => find .", "xargs -0 -n 1 command => /bin/rm"]
+        );
+        assert_eq!(
+            parsed("timeout -s KILL 5 sudo -uroot sh -c 'ls; rm x'"),
+            vec![
+                "timeout -s KILL 5 sudo -uroot => ls",
+                "timeout -s KILL 5 sudo -uroot => rm x"
+            ]
+        );
+        assert_eq!(parsed("exec > log"), vec![" => exec"]);
+        assert_eq!(words("echo if then"), vec![vec!["echo", "if", "then"]]);
+    }
+
+    #[test]
+    fn rejects_keywords_and_assignments() {
+        for line in [
+            "if true; then rm x; fi",
+            "true && { rm x; }",
+            "for f in *; do rm $f; done",
+            "while true; do rm x; done",
+            "! rm x",
+            "FOO=1 ls",
+            "ls; PATH=/tmp/evil golaunch-cli list",
+            "env LD_PRELOAD=/tmp/x.so golaunch-cli list",
+            "sudo FOO=1 rm x",
+            "env -S 'rm -rf ~'",
+            "env -iS 'rm -rf ~'",
+        ] {
+            assert!(parse_command_line(line).is_err(), "{line}");
+        }
+    }
+
+    #[test]
+    fn wrappers_and_keywords_do_not_bypass_rules() {
+        let mut rules = defaults();
+        rules.insert(0, rule("deny", "rm *"));
+        for line in [
+            "command rm -rf ~",
+            "exec rm -rf ~",
+            "env rm -rf ~",
+            "env -u HOME rm -rf ~",
+            "nohup rm -rf ~",
+            "sudo -u root rm -rf ~",
+            "find . | xargs -0 rm -f",
+            "xargs --max-args 1 rm -f",
+            "/usr/bin/env rm x",
+        ] {
+            assert_eq!(decide(&rules, line), RuleAction::Deny, "{line}");
+        }
+        for line in [
+            "true && { rm x; }",
+            "if true; then rm x; fi",
+            "FOO=1 rm x",
+            "env BAR=1 rm x",
+            "sudo golaunch-cli list",
+            "nohup golaunch-cli list",
+        ] {
+            assert_eq!(decide(&rules, line), RuleAction::Ask, "{line}");
+        }
+        // A rule naming the wrapper allows it
+        rules.insert(0, rule("allow", "sudo apt update"));
+        assert_eq!(decide(&rules, "sudo apt update"), RuleAction::Allow);
+
+        let rules = vec![rule("deny", "rm *"), rule("allow", "*")];
+        for line in [
+            r#"eval "rm -rf ~""#,
+            "eval rm -rf ~",
+            r#"sh -c 'eval "rm x"'"#,
+        ] {
+            assert_eq!(decide(&rules, line), RuleAction::Deny, "{line}");
+        }
+        for line in [
+            "/bin/r? x",
+            "/bin/r* x",
+            "/bin/[r]m x",
+            "{rm,x}",
+            r"$'\x72m' x",
+            "${X:-rm} x",
+            r#""$X" x"#,
+            "sudo $CMD x",
+            "eval $CMD",
+        ] {
+            assert_eq!(decide(&rules, line), RuleAction::Ask, "{line}");
+        }
+        // Expansions in arguments and quoted patterns are fine
+        for line in [
+            "ls *.txt",
+            "echo $HOME",
+            "'/bin/r?' x",
+            "[ -f x ] && echo yes",
+        ] {
+            assert_eq!(decide(&rules, line), RuleAction::Allow, "{line}");
+        }
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_read() {
+        for line in [
+            "echo $(rm x)",
+            "echo `rm x`",
+            "(rm x)",
+            "cat <<EOF",
+            "echo 'x",
+            "diff <(a) b",
+        ] {
+            assert!(parse_command_line(line).is_err(), "{line}");
+        }
+    }
+
+    #[test]
+    fn signatures_ignore_quoting_and_spacing() {
+        let signature = |kind, command| {
+            tool_signature(&ToolRequest {
+                kind: Some(kind),
+                title: Some("Run"),
+                command: Some(command),
+            })
+        };
+        assert_eq!(
+            signature("execute", "git   commit -m 'a b'"),
+            signature("execute", r#"git commit -m "a b""#)
+        );
+        assert_eq!(
+            signature("execute", "ls && cat x >out"),
+            "execute: ls; cat x > out"
+        );
+        assert_eq!(
+            tool_signature(&ToolRequest {
+                kind: Some("read"),
+                title: Some(" Read README.md "),
+                command: None,
+            }),
+            "read: Read README.md"
+        );
+    }
+
+    #[test]
+    fn globs() {
+        assert!(glob_matches("*code*", "Visual Studio Code"));
+        assert!(glob_matches("git ?tatus", "GIT STATUS"));
+        assert!(!glob_matches("git*", "legit"));
+    }
+
+    #[test]
+    fn default_rules_allow_only_read_only_cli_calls() {
+        let rules = defaults();
+        assert_eq!(
+            decide(&rules, "golaunch-cli list --json"),
+            RuleAction::Allow
+        );
+        assert_eq!(
+            decide(&rules, "\"/opt/GoLaunch/golaunch-cli\" memory search git"),
+            RuleAction::Allow
+        );
+        assert_eq!(
+            decide(&rules, "golaunch-cli list | grep x"),
+            RuleAction::Ask
+        );
+        assert_eq!(
+            decide(&rules, "golaunch-cli list; rm -rf ~"),
+            RuleAction::Ask
+        );
+        assert_eq!(
+            decide(&rules, "golaunch-cli list > ~/.bashrc"),
+            RuleAction::Ask
+        );
+        assert_eq!(
+            decide(&rules, "golaunch-cli list $(rm -rf ~)"),
+            RuleAction::Ask
+        );
+        assert_eq!(
+            decide(&rules, "golaunch-cli memory add k v"),
+            RuleAction::Ask
+        );
+        assert_eq!(decide(&rules, "golaunch-cli run abc"), RuleAction::Ask);
+    }
+
+    #[test]
+    fn first_matching_rule_wins_per_command() {
+        let rules = vec![
+            rule("deny", "rm"),
+            rule("allow", "git ** status"),
+            rule("allow", "*"),
+        ];
+        assert_eq!(decide(&rules, "git -C repo status"), RuleAction::Allow);
+        assert_eq!(decide(&rules, "ls && rm -rf build"), RuleAction::Deny);
+        assert_eq!(decide(&rules, "sh -c 'ls; /bin/rm x'"), RuleAction::Deny);
+    }
+
+    #[test]
+    fn scope_and_kind_filters() {
+        let mut scoped = rule("allow", "npm test");
+        scoped.agent_id = Some("claude-code".to_string());
+        scoped.context = Some("*code*".to_string());
+        let mut reads = rule("allow", "x");
+        reads.command = None;
+        reads.tool_kind = Some("read".to_string());
+        let rules = vec![scoped, reads];
+
+        let scope = RuleScope {
+            agent_id: Some("claude-code".to_string()),
+            source_app: Some("Code".to_string()),
+            source_window: None,
+        };
+        let npm = ToolRequest {
+            kind: Some("execute"),
+            title: None,
+            command: Some("npm test"),
+        };
+        assert_eq!(evaluate(&rules, &npm, &scope), RuleAction::Allow);
+        assert_eq!(
+            evaluate(&rules, &npm, &RuleScope::default()),
+            RuleAction::Ask
+        );
+
+        let read = ToolRequest {
+            kind: Some("read"),
+            title: Some("Read README.md"),
+            command: None,
+        };
+        assert_eq!(
+            evaluate(&rules, &read, &RuleScope::default()),
+            RuleAction::Allow
+        );
+    }
+}
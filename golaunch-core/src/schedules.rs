@@ -0,0 +1,613 @@
+use crate::db::Database;
+use crate::models::{NewCommandHistory, Schedule};
+use crate::workflows::{
+    run_workflow, StepOutcome, StepRunner, WorkflowProgress, WORKFLOW_ACTION_TYPE,
+};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeZone, Timelike, Utc};
+
+/// History source of scheduled runs.
+pub const SCHEDULE_HISTORY_SOURCE: &str = "schedule";
+
+/// Tables whose changes can trigger a schedule ("on change items").
+pub const WATCHED_TABLES: &[&str] = &[
+    "items",
+    "slash_commands",
+    "memory",
+    "conversations",
+    "clipboard_history",
+];
+
+/// Format of stored run times (UTC, like SQLite's `datetime('now')`).
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// How far ahead `next_after` looks; covers Feb 29 schedules.
+const MAX_SEARCH_DAYS: i64 = 366 * 8;
+
+/// When a schedule fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleTrigger {
+    Cron(CronSchedule),
+    /// Once each time the app starts.
+    AppStart,
+    /// Whenever rows of a watched table are added, removed or edited.
+    TableChange(String),
+}
+
+impl ScheduleTrigger {
+    /// Parse a cron expression ("0 9 * * 1-5", "@daily"), a phrase ("every weekday
+    /// 09:00", "every 15 minutes", "hourly"), "on start" or "on change <table>".
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let lower = spec.trim().to_ascii_lowercase();
+        let words: Vec<&str> = lower.split_whitespace().collect();
+        match words.as_slice() {
+            ["@startup" | "@start"] | ["on", "start" | "startup"] | ["app", "start"] => {
+                return Ok(Self::AppStart)
+            }
+            ["on", "change", table] | ["on", "change", "of", table] => {
+                return WATCHED_TABLES
+                    .contains(table)
+                    .then(|| Self::TableChange(table.to_string()))
+                    .ok_or_else(|| {
+                        format!(
+                            "Unknown table '{table}' (watchable: {})",
+                            WATCHED_TABLES.join(", ")
+                        )
+                    })
+            }
+            _ => {}
+        }
+        let expression = match words.first() {
+            Some(first) if first.starts_with('@') => macro_to_cron(first)?,
+            Some(&("every" | "hourly" | "daily")) => phrase_to_cron(&words)?,
+            _ => lower.clone(),
+        };
+        CronSchedule::parse(&expression).map(Self::Cron)
+    }
+}
+
+fn macro_to_cron(name: &str) -> Result<String, String> {
+    let expression = match name {
+        "@hourly" => "0 * * * *",
+        "@daily" | "@midnight" => "0 0 * * *",
+        "@weekly" => "0 0 * * 0",
+        "@monthly" => "0 0 1 * *",
+        "@yearly" | "@annually" => "0 0 1 1 *",
+        other => return Err(format!("Unknown schedule '{other}'")),
+    };
+    Ok(expression.to_string())
+}
+
+/// Turn "every weekday 09:00" and friends into a cron expression.
+fn phrase_to_cron(words: &[&str]) -> Result<String, String> {
+    let phrase = words.join(" ");
+    let invalid = || {
+        format!(
+            "Unrecognized schedule '{phrase}' (try \"every weekday 09:00\", \"every monday 10:30\", \"every 15 minutes\", \"hourly\" or a cron expression)"
+        )
+    };
+    let words = match words {
+        ["every", rest @ ..] => rest,
+        ["hourly"] => return Ok("0 * * * *".to_string()),
+        ["daily", rest @ ..] => return phrase_to_cron(&[&["every", "day"], rest].concat()),
+        _ => return Err(invalid()),
+    };
+    match words {
+        ["minute"] => return Ok("* * * * *".to_string()),
+        ["hour"] => return Ok("0 * * * *".to_string()),
+        [n, "minutes" | "mins" | "min"] => {
+            let n: u32 = n.parse().map_err(|_| invalid())?;
+            return match n {
+                1..=59 => Ok(format!("*/{n} * * * *")),
+                _ => Err(invalid()),
+            };
+        }
+        [n, "hours" | "hour"] => {
+            let n: u32 = n.parse().map_err(|_| invalid())?;
+            return match n {
+                1..=23 => Ok(format!("0 */{n} * * *")),
+                _ => Err(invalid()),
+            };
+        }
+        _ => {}
+    }
+
+    // "<days> [at] HH:MM"
+    let (time, days) = words.split_last().ok_or_else(invalid)?;
+    let days = match days {
+        [rest @ .., "at"] => rest,
+        _ => days,
+    };
+    let (hour, minute) = parse_clock(time).ok_or_else(invalid)?;
+    let weekdays = match days {
+        ["day"] => "*".to_string(),
+        ["weekday" | "weekdays"] => "1-5".to_string(),
+        ["weekend" | "weekends"] | ["weekend", "day"] => "0,6".to_string(),
+        [] => return Err(invalid()),
+        names => {
+            let mut numbers = Vec::new();
+            for name in names.join("").split(',').filter(|n| !n.is_empty()) {
+                let name = name.trim_end_matches('s');
+                numbers.push(weekday_number(name).ok_or_else(invalid)?.to_string());
+            }
+            numbers.join(",")
+        }
+    };
+    Ok(format!("{minute} {hour} * * {weekdays}"))
+}
+
+/// "09:00", "9:30", "9am", "5:15pm".
+fn parse_clock(text: &str) -> Option<(u32, u32)> {
+    let (text, pm) = match text
+        .strip_suffix("am")
+        .map(|t| (t, false))
+        .or_else(|| text.strip_suffix("pm").map(|t| (t, true)))
+    {
+        Some((t, pm)) => (t, Some(pm)),
+        None => (text, None),
+    };
+    let (hour, minute) = match text.split_once(':') {
+        Some((h, m)) if m.len() == 2 => (h.parse().ok()?, m.parse().ok()?),
+        None if pm.is_some() => (text.parse().ok()?, 0),
+        _ => return None,
+    };
+    let hour = match pm {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(false) => hour % 12,
+        Some(true) => hour % 12 + 12,
+        None => hour,
+    };
+    (hour < 24 && minute < 60).then_some((hour, minute))
+}
+
+fn weekday_number(name: &str) -> Option<u32> {
+    const NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+    NAMES
+        .iter()
+        .position(|short| name.starts_with(short) && (name.len() == 3 || full_day(short, name)))
+        .map(|i| i as u32)
+}
+
+fn full_day(short: &str, name: &str) -> bool {
+    const FULL: [&str; 7] = [
+        "sunday",
+        "monday",
+        "tuesday",
+        "wednesday",
+        "thursday",
+        "friday",
+        "saturday",
+    ];
+    FULL.iter()
+        .any(|full| full.starts_with(short) && *full == name)
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    const NAMES: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    NAMES.iter().position(|n| *n == name).map(|i| i as u32 + 1)
+}
+
+/// A standard five-field cron expression: minute, hour, day of month, month, day of week.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days: Vec<bool>,
+    months: Vec<bool>,
+    weekdays: Vec<bool>,
+    /// Cron runs on either day field matching when both are restricted.
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields.as_slice() else {
+            return Err(format!(
+                "Invalid cron expression '{expression}': expected 5 fields (minute hour day month weekday)"
+            ));
+        };
+        let field = |text: &str, min: u32, max: u32, names: fn(&str) -> Option<u32>| {
+            parse_field(text, min, max, names)
+                .map_err(|e| format!("Invalid cron expression '{expression}': {e}"))
+        };
+        let mut weekdays = field(weekday, 0, 7, weekday_number)?;
+        // 7 is Sunday too
+        if weekdays[7] {
+            weekdays[0] = true;
+        }
+        weekdays.truncate(7);
+        Ok(Self {
+            minutes: field(minute, 0, 59, |_| None)?,
+            hours: field(hour, 0, 23, |_| None)?,
+            days: field(day, 1, 31, |_| None)?,
+            months: field(month, 1, 12, month_number)?,
+            weekdays,
+            days_restricted: *day != "*",
+            weekdays_restricted: *weekday != "*",
+        })
+    }
+
+    fn matches_day(&self, date: chrono::NaiveDate) -> bool {
+        if !self.months[date.month() as usize] {
+            return false;
+        }
+        let day = self.days[date.day() as usize];
+        let weekday = self.weekdays[date.weekday().num_days_from_sunday() as usize];
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+
+    /// The first matching minute strictly after `after`. Local times skipped by a DST
+    /// change are passed over.
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        for day_offset in 0..MAX_SEARCH_DAYS {
+            let date = start.date() + Duration::days(day_offset);
+            if !self.matches_day(date) {
+                continue;
+            }
+            let first_day = day_offset == 0;
+            for hour in (0..24).filter(|h| self.hours[*h as usize]) {
+                if first_day && hour < start.hour() {
+                    continue;
+                }
+                for minute in (0..60).filter(|m| self.minutes[*m as usize]) {
+                    if first_day && hour == start.hour() && minute < start.minute() {
+                        continue;
+                    }
+                    let naive = date.and_hms_opt(hour, minute, 0)?;
+                    if let Some(time) = Local.from_local_datetime(&naive).earliest() {
+                        return Some(time);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Parse one cron field into a lookup table indexed by value.
+fn parse_field(
+    text: &str,
+    min: u32,
+    max: u32,
+    names: fn(&str) -> Option<u32>,
+) -> Result<Vec<bool>, String> {
+    let value = |v: &str| -> Result<u32, String> {
+        let n = v
+            .parse()
+            .ok()
+            .or_else(|| names(v))
+            .ok_or_else(|| format!("'{v}' is not a number"))?;
+        if (min..=max).contains(&n) {
+            Ok(n)
+        } else {
+            Err(format!("{n} is outside {min}-{max}"))
+        }
+    };
+    let mut set = vec![false; max as usize + 1];
+    for part in text.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("invalid step in '{part}'"))?,
+            ),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (value(a)?, value(b)?),
+                // "5/15" means from 5 to the end in steps of 15
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let v = value(range)?;
+                    (v, v)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("range '{range}' runs backwards"));
+        }
+        for v in (start..=end).step_by(step as usize) {
+            set[v as usize] = true;
+        }
+    }
+    Ok(set)
+}
+
+/// What to do about runs that were due while the app was not running (or asleep).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissedRunPolicy {
+    /// Wait for the next scheduled time.
+    Skip,
+    /// Run once as soon as possible, however many runs were missed.
+    RunOnce,
+}
+
+impl MissedRunPolicy {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "skip" => Ok(Self::Skip),
+            "run-once" | "once" => Ok(Self::RunOnce),
+            other => Err(format!(
+                "Unknown missed-run policy '{other}' (use skip or run-once)"
+            )),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::RunOnce => "run-once",
+        }
+    }
+}
+
+/// The next time a schedule fires after `after`, as stored in `next_run_at`. `None` for
+/// trigger-based schedules.
+pub fn next_run_at(trigger: &ScheduleTrigger, after: DateTime<Local>) -> Option<String> {
+    match trigger {
+        ScheduleTrigger::Cron(cron) => cron
+            .next_after(after)
+            .map(|t| t.with_timezone(&Utc).format(TIME_FORMAT).to_string()),
+        _ => None,
+    }
+}
+
+/// Parse a stored run time.
+pub fn parse_run_time(text: &str) -> Option<DateTime<Local>> {
+    NaiveDateTime::parse_from_str(text, TIME_FORMAT)
+        .ok()
+        .map(|t| Utc.from_utc_datetime(&t).with_timezone(&Local))
+}
+
+/// The shell command line that runs a slash command script.
+pub fn slash_command_line(script_path: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!("powershell -ExecutionPolicy Bypass -File \"{script_path}\"")
+    } else {
+        format!("sh '{}'", script_path.replace('\'', r"'\''"))
+    }
+}
+
+/// Run a schedule's target now, waiting for it to finish. The run is recorded in the
+/// command history and on the schedule, which also gets its next run time. Returns
+/// the exit code.
+pub fn run_schedule(
+    db: &Database,
+    schedule: &Schedule,
+    runner: &mut dyn StepRunner,
+    on_progress: &mut dyn FnMut(WorkflowProgress),
+) -> Result<i32, String> {
+    let result = run_target(db, schedule, runner, on_progress);
+    let (exit_code, status) = match &result {
+        Ok(run) if run.exit_code == 0 => (Some(0), "ok".to_string()),
+        Ok(run) => (Some(run.exit_code), format!("exit {}", run.exit_code)),
+        Err(e) => (None, format!("error: {e}")),
+    };
+    if let Ok(run) = &result {
+        let _ = db.record_command(NewCommandHistory {
+            item_id: (schedule.target_type == "item").then(|| schedule.target_id.clone()),
+            command_text: run.command_text.clone(),
+            action_type: run.action_type.clone(),
+            source: Some(SCHEDULE_HISTORY_SOURCE.to_string()),
+            exit_code,
+        });
+    }
+    let next = ScheduleTrigger::parse(&schedule.spec)
+        .ok()
+        .and_then(|trigger| next_run_at(&trigger, Local::now()));
+    db.record_schedule_run(&schedule.id, &status, exit_code, next.as_deref())?;
+    result.map(|run| run.exit_code)
+}
+
+/// What a scheduled run did, for the history entry.
+struct TargetRun {
+    exit_code: i32,
+    command_text: String,
+    action_type: String,
+}
+
+fn run_target(
+    db: &Database,
+    schedule: &Schedule,
+    runner: &mut dyn StepRunner,
+    on_progress: &mut dyn FnMut(WorkflowProgress),
+) -> Result<TargetRun, String> {
+    match schedule.target_type.as_str() {
+        "item" => {
+            let item = db.get_item(&schedule.target_id)?;
+            if item.action_type == WORKFLOW_ACTION_TYPE {
+                let succeeded = run_workflow(db, &item, runner, on_progress)?;
+                return Ok(TargetRun {
+                    exit_code: if succeeded { 0 } else { 1 },
+                    command_text: item.title,
+                    action_type: item.action_type,
+                });
+            }
+            let outcome = runner.run_step(
+                &item.action_type,
+                &item.action_value,
+                &StepOutcome::success(),
+            )?;
+            Ok(TargetRun {
+                exit_code: outcome.exit_code,
+                command_text: item.action_value,
+                action_type: item.action_type,
+            })
+        }
+        "slash_command" => {
+            let command = db.get_slash_command(&schedule.target_id)?;
+            let outcome = runner.run_step(
+                "command",
+                &slash_command_line(&command.script_path),
+                &StepOutcome::success(),
+            )?;
+            Ok(TargetRun {
+                exit_code: outcome.exit_code,
+                command_text: format!("/{}", command.name),
+                action_type: "slash_command".to_string(),
+            })
+        }
+        other => Err(format!("Unknown schedule target type: {other}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cron(expression: &str) -> CronSchedule {
+        CronSchedule::parse(expression).unwrap()
+    }
+
+    fn local(text: &str) -> DateTime<Local> {
+        let naive = NaiveDateTime::parse_from_str(text, TIME_FORMAT).unwrap();
+        Local.from_local_datetime(&naive).earliest().unwrap()
+    }
+
+    fn next(expression: &str, after: &str) -> String {
+        cron(expression)
+            .next_after(local(after))
+            .unwrap()
+            .format(TIME_FORMAT)
+            .to_string()
+    }
+
+    fn values(set: &[bool]) -> Vec<usize> {
+        (0..set.len()).filter(|i| set[*i]).collect()
+    }
+
+    #[test]
+    fn parses_steps_lists_and_ranges() {
+        let schedule = cron("*/15 9-17/4 1,15 jan-mar mon-fri");
+        assert_eq!(values(&schedule.minutes), vec![0, 15, 30, 45]);
+        assert_eq!(values(&schedule.hours), vec![9, 13, 17]);
+        assert_eq!(values(&schedule.days), vec![1, 15]);
+        assert_eq!(values(&schedule.months), vec![1, 2, 3]);
+        assert_eq!(values(&schedule.weekdays), vec![1, 2, 3, 4, 5]);
+
+        assert_eq!(values(&cron("5/20 * * * *").minutes), vec![5, 25, 45]);
+        // 7 is another name for Sunday
+        assert_eq!(values(&cron("0 0 * * 7").weekdays), vec![0]);
+        assert_eq!(values(&cron("0 0 * * 5-7").weekdays), vec![0, 5, 6]);
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expression in [
+            "",
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "*/x * * * *",
+            "10-5 * * * *",
+            "a * * * *",
+            "* * * foo *",
+        ] {
+            assert!(
+                CronSchedule::parse(expression).is_err(),
+                "'{expression}' should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn turns_phrases_into_cron() {
+        let parse = |spec: &str| ScheduleTrigger::parse(spec).unwrap();
+        assert_eq!(parse("every weekday 09:00"), parse("0 9 * * 1-5"));
+        assert_eq!(parse("every monday at 10:30"), parse("30 10 * * 1"));
+        assert_eq!(parse("every mon,wed 5:15pm"), parse("15 17 * * 1,3"));
+        assert_eq!(parse("every weekend 9am"), parse("0 9 * * 0,6"));
+        assert_eq!(parse("daily 12am"), parse("0 0 * * *"));
+        assert_eq!(parse("every 15 minutes"), parse("*/15 * * * *"));
+        assert_eq!(parse("every 2 hours"), parse("0 */2 * * *"));
+        assert_eq!(parse("Hourly"), parse("0 * * * *"));
+        assert_eq!(parse("@weekly"), parse("0 0 * * 0"));
+        assert_eq!(parse("on start"), ScheduleTrigger::AppStart);
+        assert_eq!(
+            parse("on change items"),
+            ScheduleTrigger::TableChange("items".to_string())
+        );
+
+        for spec in [
+            "every 0 minutes",
+            "every 60 minutes",
+            "every 24 hours",
+            "every weekday",
+            "every day 25:00",
+            "every day 9:5",
+            "every day 13pm",
+            "every funday 09:00",
+            "@sometimes",
+            "on change passwords",
+            "tomorrow",
+        ] {
+            assert!(
+                ScheduleTrigger::parse(spec).is_err(),
+                "'{spec}' should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn calculates_next_runs() {
+        // 2026-03-06 is a Friday
+        assert_eq!(
+            next("0 9 * * 1-5", "2026-03-06 08:59:30"),
+            "2026-03-06 09:00:00"
+        );
+        assert_eq!(
+            next("0 9 * * 1-5", "2026-03-06 09:00:00"),
+            "2026-03-09 09:00:00"
+        );
+        assert_eq!(
+            next("*/15 * * * *", "2026-03-06 23:50:00"),
+            "2026-03-07 00:00:00"
+        );
+        assert_eq!(
+            next("30 8 31 * *", "2026-04-01 00:00:00"),
+            "2026-05-31 08:30:00"
+        );
+        assert_eq!(
+            next("0 0 29 2 *", "2026-03-01 00:00:00"),
+            "2028-02-29 00:00:00"
+        );
+        // Both day fields restricted: either one matching is enough
+        assert_eq!(
+            next("0 12 13 * 5", "2026-03-07 00:00:00"),
+            "2026-03-13 12:00:00"
+        );
+        assert_eq!(
+            next("0 12 1 * 1", "2026-03-07 00:00:00"),
+            "2026-03-09 12:00:00"
+        );
+    }
+
+    #[test]
+    fn stored_run_times_round_trip() {
+        let trigger = ScheduleTrigger::parse("0 9 * * *").unwrap();
+        let stored = next_run_at(&trigger, local("2026-03-06 10:00:00")).unwrap();
+        assert_eq!(
+            parse_run_time(&stored).unwrap(),
+            local("2026-03-07 09:00:00")
+        );
+        assert_eq!(next_run_at(&ScheduleTrigger::AppStart, Local::now()), None);
+    }
+}
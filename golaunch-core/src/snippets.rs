@@ -0,0 +1,173 @@
+/// Item action type whose value is a text template inserted into the focused app.
+pub const SNIPPET_ACTION_TYPE: &str = "snippet";
+
+/// Values available to a snippet while it is expanded.
+#[derive(Debug, Clone, Default)]
+pub struct SnippetInputs {
+    pub clipboard: Option<String>,
+    pub selection: Option<String>,
+    /// Raw argument text; `{args}` inserts it as-is and `{1}`, `{2}`, ... insert its
+    /// whitespace-separated words (double quotes group words).
+    pub args: String,
+}
+
+/// The result of expanding a snippet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetExpansion {
+    pub text: String,
+    /// Where `{cursor}` was, counted in characters from the end of `text`.
+    pub cursor_from_end: Option<usize>,
+}
+
+enum Segment<'a> {
+    Literal(&'a str),
+    Placeholder(&'a str),
+}
+
+/// Split a template into literal text and `{placeholder}` names. `{{` and `}}` are
+/// literal braces.
+fn parse(template: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+    while let Some(pos) = rest.find(['{', '}']) {
+        if pos > 0 {
+            segments.push(Segment::Literal(&rest[..pos]));
+        }
+        let brace = &rest[pos..pos + 1];
+        let after = &rest[pos + 1..];
+        if after.starts_with(brace) {
+            segments.push(Segment::Literal(brace));
+            rest = &after[1..];
+            continue;
+        }
+        if brace == "}" {
+            return Err(format!(
+                "Unmatched '}}' in snippet at byte {pos} (write '}}}}' for a literal brace)"
+            ));
+        }
+        let end = after
+            .find('}')
+            .ok_or("Unclosed '{' in snippet (write '{{' for a literal brace)")?;
+        segments.push(Segment::Placeholder(&after[..end]));
+        rest = &after[end + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Literal(rest));
+    }
+    Ok(segments)
+}
+
+/// Check that a snippet template parses, has at most one `{cursor}` and only valid
+/// `{date:...}` formats.
+pub fn validate_snippet(template: &str) -> Result<(), String> {
+    let mut cursors = 0;
+    for segment in parse(template)? {
+        let Segment::Placeholder(name) = segment else {
+            continue;
+        };
+        let name = name.trim();
+        if name == "cursor" {
+            cursors += 1;
+        } else if let Some(format) = name.strip_prefix("date:") {
+            let invalid = chrono::format::StrftimeItems::new(format)
+                .any(|item| matches!(item, chrono::format::Item::Error));
+            if invalid {
+                return Err(format!("Invalid date format in snippet: '{format}'"));
+            }
+        }
+    }
+    if cursors > 1 {
+        return Err("A snippet can contain at most one {cursor}".to_string());
+    }
+    Ok(())
+}
+
+/// Whether the template uses the given placeholder (e.g. "selection"), so callers can
+/// skip capturing values that are never inserted.
+pub fn snippet_uses(template: &str, placeholder: &str) -> bool {
+    parse(template).is_ok_and(|segments| {
+        segments
+            .iter()
+            .any(|s| matches!(s, Segment::Placeholder(name) if name.trim() == placeholder))
+    })
+}
+
+/// Split argument text on whitespace, keeping "double quoted" groups together.
+pub fn split_snippet_args(args: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_word = false;
+    for c in args.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_word = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_word {
+                    words.push(std::mem::take(&mut current));
+                    has_word = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_word = true;
+            }
+        }
+    }
+    if has_word {
+        words.push(current);
+    }
+    words
+}
+
+/// Expand a snippet template.
+///
+/// Supported placeholders: `{date}`, `{time}`, `{datetime}`, `{date:<strftime format>}`,
+/// `{clipboard}`, `{selection}`, `{cursor}`, `{args}` and positional arguments `{1}`,
+/// `{2}`, ... Missing values expand to an empty string; unknown placeholders are kept
+/// as written.
+pub fn expand_snippet(template: &str, inputs: &SnippetInputs) -> Result<SnippetExpansion, String> {
+    validate_snippet(template)?;
+    let now = chrono::Local::now();
+    let words = split_snippet_args(&inputs.args);
+
+    let mut text = String::new();
+    let mut cursor_at: Option<usize> = None;
+    for segment in parse(template)? {
+        let name = match segment {
+            Segment::Literal(literal) => {
+                text.push_str(literal);
+                continue;
+            }
+            Segment::Placeholder(name) => name.trim(),
+        };
+        match name {
+            "date" => text.push_str(&now.format("%Y-%m-%d").to_string()),
+            "time" => text.push_str(&now.format("%H:%M").to_string()),
+            "datetime" => text.push_str(&now.format("%Y-%m-%d %H:%M").to_string()),
+            "clipboard" => text.push_str(inputs.clipboard.as_deref().unwrap_or_default()),
+            "selection" => text.push_str(inputs.selection.as_deref().unwrap_or_default()),
+            "args" => text.push_str(inputs.args.trim()),
+            "cursor" => cursor_at = Some(text.chars().count()),
+            _ => {
+                if let Some(format) = name.strip_prefix("date:") {
+                    text.push_str(&now.format(format).to_string());
+                } else if let Some(index) = name.parse::<usize>().ok().filter(|i| *i > 0) {
+                    text.push_str(words.get(index - 1).map(String::as_str).unwrap_or(""));
+                } else {
+                    text.push('{');
+                    text.push_str(name);
+                    text.push('}');
+                }
+            }
+        }
+    }
+
+    let cursor_from_end = cursor_at.map(|at| text.chars().count() - at);
+    Ok(SnippetExpansion {
+        text,
+        cursor_from_end,
+    })
+}
@@ -0,0 +1,577 @@
+use crate::actions::shell_quote;
+use crate::db::Database;
+use crate::models::{Item, NewCommandHistory};
+use crate::snippets::SNIPPET_ACTION_TYPE;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Item action type whose value is a JSON list of `WorkflowStep`s run in order.
+pub const WORKFLOW_ACTION_TYPE: &str = "workflow";
+
+/// History source of workflow steps.
+pub const WORKFLOW_HISTORY_SOURCE: &str = "workflow";
+
+/// Captured step output is cut off after this many bytes.
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Longest delay a step may ask for (one hour).
+const MAX_DELAY_MS: u64 = 60 * 60 * 1000;
+
+/// A command step still running after this long is killed and counts as failed.
+const STEP_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Environment variables holding the previous step's output and exit code in command
+/// and script steps.
+pub const OUTPUT_ENV: &str = "GOLAUNCH_OUTPUT";
+pub const EXIT_CODE_ENV: &str = "GOLAUNCH_EXIT_CODE";
+
+/// One step of a workflow: another item, or an inline action.
+///
+/// ```json
+/// [
+///   { "item": "Dashboard" },
+///   { "type": "command", "value": "./build.sh", "delay_ms": 500 },
+///   { "type": "snippet", "value": "Deployed {output}", "if": "success" }
+/// ]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowStep {
+    /// Item to run, by ID or exact title.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item: Option<String>,
+    /// Inline action type (url, command, script, snippet, copy).
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub action_type: Option<String>,
+    /// Inline action value. `{output}` and `{exit_code}` refer to the previous step that
+    /// ran, `{output:N}` to the output of step N. In command and script steps the output
+    /// is inserted as a word that is already quoted, so it must not be put inside quotes;
+    /// within a quoted string use `"$GOLAUNCH_OUTPUT"` instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Shown in progress events instead of the item title or value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// When the step runs, based on the previous step that ran: "success" (default),
+    /// "failure", "always", "exit_code == N" or "exit_code != N".
+    #[serde(default, rename = "if", skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    /// Wait this long before running the step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepCondition {
+    Always,
+    Success,
+    Failure,
+    ExitCode(i32),
+    NotExitCode(i32),
+}
+
+impl StepCondition {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let compact: String = text.split_whitespace().collect();
+        match compact.to_ascii_lowercase().as_str() {
+            "" | "success" => return Ok(Self::Success),
+            "failure" => return Ok(Self::Failure),
+            "always" => return Ok(Self::Always),
+            _ => {}
+        }
+        let invalid = || {
+            format!(
+                "Invalid step condition '{text}' (use success, failure, always, \"exit_code == N\" or \"exit_code != N\")"
+            )
+        };
+        let rest = compact.strip_prefix("exit_code").ok_or_else(invalid)?;
+        if let Some(code) = rest.strip_prefix("==") {
+            code.parse().map(Self::ExitCode).map_err(|_| invalid())
+        } else if let Some(code) = rest.strip_prefix("!=") {
+            code.parse().map(Self::NotExitCode).map_err(|_| invalid())
+        } else {
+            Err(invalid())
+        }
+    }
+
+    /// Whether a step runs after a previous step that exited with `previous` (0 when
+    /// it is the first step to run).
+    pub fn matches(self, previous: i32) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Success => previous == 0,
+            Self::Failure => previous != 0,
+            Self::ExitCode(code) => previous == code,
+            Self::NotExitCode(code) => previous != code,
+        }
+    }
+}
+
+/// The result of running one step.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StepOutcome {
+    pub exit_code: i32,
+    /// Standard output, trimmed of trailing whitespace.
+    pub output: String,
+}
+
+impl StepOutcome {
+    pub fn success() -> Self {
+        Self::default()
+    }
+}
+
+/// Runs resolved step actions; the app and the CLI each provide their own.
+pub trait StepRunner {
+    /// `previous` is the outcome of the previous step that ran, for
+    /// [`run_shell_captured`].
+    fn run_step(
+        &mut self,
+        action_type: &str,
+        value: &str,
+        previous: &StepOutcome,
+    ) -> Result<StepOutcome, String>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+/// Progress of a workflow run, reported when the run and each step start and end.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkflowProgress {
+    pub run_id: String,
+    pub item_id: String,
+    pub workflow: String,
+    /// 1-based step number; 0 for the workflow as a whole.
+    pub step: usize,
+    pub total: usize,
+    pub label: String,
+    pub status: WorkflowStatus,
+    pub exit_code: Option<i32>,
+    pub error: Option<String>,
+}
+
+/// Parse and check a workflow definition.
+pub fn parse_workflow(definition: &str) -> Result<Vec<WorkflowStep>, String> {
+    let steps: Vec<WorkflowStep> = serde_json::from_str(definition)
+        .map_err(|e| format!("Invalid workflow (expected a JSON list of steps): {e}"))?;
+    if steps.is_empty() {
+        return Err("A workflow needs at least one step".to_string());
+    }
+    for (index, step) in steps.iter().enumerate() {
+        let n = index + 1;
+        match (&step.item, &step.action_type) {
+            (Some(_), Some(_)) => {
+                return Err(format!(
+                    "Step {n}: use either \"item\" or \"type\", not both"
+                ))
+            }
+            (None, None) => return Err(format!("Step {n}: needs an \"item\" or a \"type\"")),
+            (None, Some(action_type)) => {
+                if action_type == WORKFLOW_ACTION_TYPE {
+                    return Err(format!("Step {n}: workflows cannot be nested"));
+                }
+                if step.value.is_none() {
+                    return Err(format!("Step {n}: inline steps need a \"value\""));
+                }
+            }
+            (Some(_), None) => {}
+        }
+        if let Some(condition) = &step.condition {
+            StepCondition::parse(condition).map_err(|e| format!("Step {n}: {e}"))?;
+        }
+        if step.delay_ms.is_some_and(|ms| ms > MAX_DELAY_MS) {
+            return Err(format!("Step {n}: delay_ms is limited to {MAX_DELAY_MS}"));
+        }
+    }
+    Ok(steps)
+}
+
+/// Fill `{output}`, `{exit_code}` and `{output:N}` from earlier steps. Other
+/// placeholders are left alone. Filled-in output has its braces escaped in snippets and
+/// is shell-quoted in command and script steps, so it is never run as code.
+fn fill_placeholders(
+    template: &str,
+    previous: &StepOutcome,
+    outputs: &[Option<String>],
+    action_type: &str,
+) -> String {
+    let escape = |text: &str| match action_type {
+        SNIPPET_ACTION_TYPE => text.replace('{', "{{").replace('}', "}}"),
+        "command" | "script" => shell_quote(text),
+        _ => text.to_string(),
+    };
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = &after[..end];
+        let value = match name {
+            "output" => Some(escape(&previous.output)),
+            "exit_code" => Some(previous.exit_code.to_string()),
+            _ => name
+                .strip_prefix("output:")
+                .and_then(|n| n.trim().parse::<usize>().ok())
+                .and_then(|n| outputs.get(n.checked_sub(1)?).cloned().flatten())
+                .map(|output| escape(&output)),
+        };
+        match value {
+            Some(value) => result.push_str(&value),
+            None => result.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &after[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Find the item a step refers to, by ID or else by title.
+fn find_step_item(db: &Database, reference: &str) -> Result<Item, String> {
+    if let Ok(item) = db.get_item(reference) {
+        return Ok(item);
+    }
+    db.list_items(None, true)?
+        .into_iter()
+        .find(|item| item.title.eq_ignore_ascii_case(reference.trim()))
+        .ok_or_else(|| format!("No item with ID or title '{reference}'"))
+}
+
+fn step_label(step: &WorkflowStep, action_value: &str, item: Option<&Item>) -> String {
+    if let Some(label) = &step.label {
+        return label.clone();
+    }
+    if let Some(item) = item {
+        return item.title.clone();
+    }
+    let line = action_value.lines().next().unwrap_or_default();
+    if line.chars().count() > 60 {
+        format!("{}…", line.chars().take(60).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+/// Run a workflow item's steps in order, recording each step in the command history
+/// and reporting progress. Returns whether every step that ran succeeded.
+pub fn run_workflow(
+    db: &Database,
+    workflow: &Item,
+    runner: &mut dyn StepRunner,
+    on_progress: &mut dyn FnMut(WorkflowProgress),
+) -> Result<bool, String> {
+    let steps = parse_workflow(&workflow.action_value)?;
+    let run_id = Uuid::new_v4().to_string();
+    let total = steps.len();
+    let progress = |step: usize, label: &str, status: WorkflowStatus| WorkflowProgress {
+        run_id: run_id.clone(),
+        item_id: workflow.id.clone(),
+        workflow: workflow.title.clone(),
+        step,
+        total,
+        label: label.to_string(),
+        status,
+        exit_code: None,
+        error: None,
+    };
+
+    on_progress(progress(0, &workflow.title, WorkflowStatus::Running));
+    let mut previous = StepOutcome::success();
+    let mut outputs: Vec<Option<String>> = Vec::with_capacity(total);
+    let mut all_succeeded = true;
+
+    for (index, step) in steps.iter().enumerate() {
+        let n = index + 1;
+        let resolved = match &step.item {
+            Some(reference) => find_step_item(db, reference).and_then(|item| {
+                if item.action_type == WORKFLOW_ACTION_TYPE {
+                    Err(format!("Step {n}: workflows cannot be nested"))
+                } else {
+                    Ok((
+                        item.action_type.clone(),
+                        item.action_value.clone(),
+                        Some(item),
+                    ))
+                }
+            }),
+            None => Ok((
+                step.action_type.clone().unwrap_or_default(),
+                step.value.clone().unwrap_or_default(),
+                None,
+            )),
+        };
+        let (action_type, template, item) = match resolved {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                let mut failed = progress(n, &format!("Step {n}"), WorkflowStatus::Failed);
+                failed.error = Some(e);
+                on_progress(failed);
+                outputs.push(None);
+                previous = StepOutcome {
+                    exit_code: -1,
+                    output: String::new(),
+                };
+                all_succeeded = false;
+                continue;
+            }
+        };
+        let label = step_label(step, &template, item.as_ref());
+
+        let condition = StepCondition::parse(step.condition.as_deref().unwrap_or(""))?;
+        if !condition.matches(previous.exit_code) {
+            on_progress(progress(n, &label, WorkflowStatus::Skipped));
+            outputs.push(None);
+            continue;
+        }
+
+        on_progress(progress(n, &label, WorkflowStatus::Running));
+        if let Some(ms) = step.delay_ms.filter(|ms| *ms > 0) {
+            std::thread::sleep(Duration::from_millis(ms));
+        }
+
+        // Items run as they are; only inline values see earlier steps' output
+        let value = if item.is_some() {
+            template
+        } else {
+            fill_placeholders(&template, &previous, &outputs, &action_type)
+        };
+        let result = runner.run_step(&action_type, &value, &previous);
+        let exit_code = match &result {
+            Ok(outcome) => outcome.exit_code,
+            Err(_) => -1,
+        };
+        let _ = db.record_command(NewCommandHistory {
+            item_id: Some(workflow.id.clone()),
+            command_text: value,
+            action_type: action_type.clone(),
+            source: Some(WORKFLOW_HISTORY_SOURCE.to_string()),
+            exit_code: Some(exit_code),
+        });
+
+        let status = if exit_code == 0 {
+            WorkflowStatus::Succeeded
+        } else {
+            all_succeeded = false;
+            WorkflowStatus::Failed
+        };
+        let mut finished = progress(n, &label, status);
+        finished.exit_code = Some(exit_code);
+        previous = match result {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                finished.error = Some(e);
+                StepOutcome {
+                    exit_code,
+                    output: String::new(),
+                }
+            }
+        };
+        on_progress(finished);
+        outputs.push(Some(previous.output.clone()));
+    }
+
+    let status = if all_succeeded {
+        WorkflowStatus::Succeeded
+    } else {
+        WorkflowStatus::Failed
+    };
+    on_progress(progress(0, &workflow.title, status));
+    Ok(all_succeeded)
+}
+
+/// Run a shell command to completion, capturing its standard output, with the previous
+/// step's output and exit code in [`OUTPUT_ENV`] and [`EXIT_CODE_ENV`]. Commands still
+/// running after [`STEP_TIMEOUT`] are killed.
+pub fn run_shell_captured(command: &str, previous: &StepOutcome) -> Result<StepOutcome, String> {
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    cmd.env(OUTPUT_ENV, &previous.output)
+        .env(EXIT_CODE_ENV, previous.exit_code.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    run_captured(cmd, STEP_TIMEOUT)
+}
+
+fn run_captured(mut cmd: Command, timeout: Duration) -> Result<StepOutcome, String> {
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to execute command: {e}"))?;
+
+    // Keep draining past the limit so the command never blocks on a full pipe.
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or("Failed to capture command output")?;
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut output = Vec::new();
+        let mut chunk = [0u8; 8192];
+        while let Ok(n) = stdout.read(&mut chunk) {
+            if n == 0 {
+                break;
+            }
+            let room = MAX_OUTPUT_BYTES.saturating_sub(output.len());
+            output.extend_from_slice(&chunk[..n.min(room)]);
+        }
+        let _ = tx.send(output);
+    });
+
+    let timed_out = || format!("Command timed out after {}s", timeout.as_secs());
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(timed_out());
+            }
+            Err(e) => return Err(format!("Failed to wait for command: {e}")),
+        }
+    };
+
+    // A background process may inherit stdout and keep it open after the shell exits.
+    let output = rx
+        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .map_err(|_| timed_out())?;
+    Ok(StepOutcome {
+        // Killed by a signal has no code
+        exit_code: status.code().unwrap_or(-1),
+        output: String::from_utf8_lossy(&output).trim_end().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(exit_code: i32, output: &str) -> StepOutcome {
+        StepOutcome {
+            exit_code,
+            output: output.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_and_matches_conditions() {
+        assert_eq!(StepCondition::parse("").unwrap(), StepCondition::Success);
+        assert_eq!(
+            StepCondition::parse(" Failure ").unwrap(),
+            StepCondition::Failure
+        );
+        assert_eq!(
+            StepCondition::parse("always").unwrap(),
+            StepCondition::Always
+        );
+        assert_eq!(
+            StepCondition::parse("exit_code == 2").unwrap(),
+            StepCondition::ExitCode(2)
+        );
+        assert_eq!(
+            StepCondition::parse("exit_code!=-1").unwrap(),
+            StepCondition::NotExitCode(-1)
+        );
+        assert!(StepCondition::parse("exit_code > 1").is_err());
+        assert!(StepCondition::parse("exit_code == x").is_err());
+        assert!(StepCondition::parse("sometimes").is_err());
+
+        assert!(StepCondition::Success.matches(0));
+        assert!(!StepCondition::Success.matches(1));
+        assert!(StepCondition::Failure.matches(-1));
+        assert!(!StepCondition::Failure.matches(0));
+        assert!(StepCondition::Always.matches(7));
+        assert!(StepCondition::ExitCode(2).matches(2));
+        assert!(!StepCondition::NotExitCode(2).matches(2));
+    }
+
+    #[test]
+    fn fills_output_placeholders() {
+        let previous = outcome(3, "b");
+        let outputs = vec![Some("a".to_string()), None, Some("b".to_string())];
+        assert_eq!(
+            fill_placeholders(
+                "{output} {exit_code} {output:1} {output:2} {output:9} {date}",
+                &previous,
+                &outputs,
+                "url"
+            ),
+            "b 3 a {output:2} {output:9} {date}"
+        );
+        assert_eq!(
+            fill_placeholders("x {output", &previous, &outputs, "url"),
+            "x {output"
+        );
+        assert_eq!(
+            fill_placeholders("Got {output}", &outcome(0, "{clipboard}"), &[], "snippet"),
+            "Got {{clipboard}}"
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn quotes_output_in_shell_steps() {
+        let previous = outcome(0, "it's $(touch pwned); rm -rf ~");
+        let command = fill_placeholders("printf %s {output}", &previous, &[], "command");
+        assert_eq!(command, r#"printf %s 'it'\''s $(touch pwned); rm -rf ~'"#);
+        assert_eq!(
+            run_shell_captured(&command, &previous).unwrap().output,
+            "it's $(touch pwned); rm -rf ~"
+        );
+
+        // Inside a quoted string the output comes from the environment instead
+        let command = r#"printf '%s' "got $GOLAUNCH_OUTPUT ($GOLAUNCH_EXIT_CODE)""#;
+        assert_eq!(
+            run_shell_captured(command, &outcome(2, "it's \"$HOME\""))
+                .unwrap()
+                .output,
+            r#"got it's "$HOME" (2)"#
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn limits_and_times_out_commands() {
+        let outcome = run_shell_captured(
+            "head -c 100000 /dev/zero | tr '\\0' x; exit 4",
+            &StepOutcome::success(),
+        )
+        .unwrap();
+        assert_eq!(outcome.exit_code, 4);
+        assert_eq!(outcome.output.len(), MAX_OUTPUT_BYTES);
+
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "sleep 5"]).stdout(Stdio::piped());
+        let started = Instant::now();
+        let err = run_captured(cmd, Duration::from_millis(200)).unwrap_err();
+        assert!(err.contains("timed out"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+}
@@ -1,46 +1,47 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 use agent_client_protocol::{
-    Client, ContentBlock, PermissionOptionId, RequestPermissionOutcome, RequestPermissionRequest,
-    RequestPermissionResponse, SelectedPermissionOutcome, SessionNotification, SessionUpdate,
+    Client, ContentBlock, CreateTerminalRequest, CreateTerminalResponse,
+    KillTerminalCommandRequest, KillTerminalCommandResponse, PermissionOptionId,
+    ReadTextFileRequest, ReadTextFileResponse, ReleaseTerminalRequest, ReleaseTerminalResponse,
+    RequestPermissionOutcome, RequestPermissionRequest, RequestPermissionResponse,
+    SelectedPermissionOutcome, SessionNotification, SessionUpdate, TerminalOutputRequest,
+    TerminalOutputResponse, WaitForTerminalExitRequest, WaitForTerminalExitResponse,
+    WriteTextFileRequest, WriteTextFileResponse,
 };
+use golaunch_core::permissions::{self, RuleAction, RuleScope, ToolRequest};
+use golaunch_core::{Database, ToolCallOption, ToolCallPermission};
+use serde::Serialize;
 use tokio::sync::mpsc;
 
-use super::types::{AgentUpdate, PermissionOptionInfo, PermissionRequest, PlanEntry};
+use super::files;
+use super::terminal::{TerminalCommand, TerminalManager};
+use super::transcript::{Replay, TurnTranscript};
+use super::types::{
+    AgentUpdate, AgentUpdateEvent, PermissionOptionInfo, PermissionRequest, PlanEntry, UpdateSender,
+};
 
 type PermissionResponder = tokio::sync::oneshot::Sender<RequestPermissionOutcome>;
 
-fn normalize_command_preview(text: &str) -> String {
-    text.to_lowercase()
-        .split_whitespace()
-        .collect::<Vec<_>>()
-        .join(" ")
-}
-
-fn should_auto_allow_read_lookup(command_preview: Option<&str>) -> bool {
-    let Some(preview) = command_preview else {
-        return false;
-    };
-
-    let normalized = normalize_command_preview(preview);
-    let padded = format!(" {normalized} ");
+/// Permission requests waiting for an answer, by request ID, with the session that asked.
+pub type PendingPermissions = Rc<RefCell<HashMap<String, (String, PermissionResponder)>>>;
 
-    // Only auto-allow golaunch-cli commands
-    if !padded.contains("golaunch-cli") {
-        return false;
-    }
-
-    // Block known write/mutating operations — everything else is auto-allowed
-    let is_write_operation = padded.contains(" add ")
-        || padded.contains(" remove ")
-        || padded.contains(" delete ")
-        || padded.contains(" update ")
-        || padded.contains(" import ")
-        || padded.contains(" run ");
+/// What the client knows about the prompt a session runs, by session ID.
+pub type SessionTurns = Rc<RefCell<HashMap<String, SessionTurn>>>;
 
-    !is_write_operation
+/// The prompt a session runs; set when the turn starts.
+#[derive(Default)]
+pub struct SessionTurn {
+    /// Conversation the session holds; tool calls are recorded in it.
+    pub conversation_id: Option<String>,
+    /// Where the launcher was opened from, for permission rules.
+    pub source_app: Option<String>,
+    pub source_window: Option<String>,
+    /// Messages of the turn, saved to its conversation as they stream in.
+    pub transcript: Option<TurnTranscript>,
 }
 
 fn pick_auto_allow_option_id(options: &[PermissionOptionInfo]) -> Option<String> {
@@ -61,31 +62,359 @@ fn pick_auto_allow_option_id(options: &[PermissionOptionInfo]) -> Option<String>
         .map(|o| o.option_id.clone())
 }
 
+fn pick_reject_option_id(options: &[PermissionOptionInfo]) -> Option<String> {
+    options
+        .iter()
+        .find(|o| o.kind == "RejectOnce")
+        .or_else(|| {
+            options
+                .iter()
+                .find(|o| o.kind.to_lowercase().contains("reject"))
+        })
+        .map(|o| o.option_id.clone())
+}
+
+/// The protocol's name for an enum value, e.g. "execute" or "in_progress".
+fn protocol_name<T: Serialize>(value: T) -> Option<String> {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+}
+
+/// Options of the permission request for a file write or terminal command.
+fn client_permission_options() -> Vec<PermissionOptionInfo> {
+    [
+        ("allow", "Allow", "AllowOnce"),
+        ("allow_always", "Always Allow", "AllowAlways"),
+        ("reject", "Reject", "RejectOnce"),
+    ]
+    .into_iter()
+    .map(|(option_id, name, kind)| PermissionOptionInfo {
+        option_id: option_id.to_string(),
+        name: name.to_string(),
+        kind: kind.to_string(),
+    })
+    .collect()
+}
+
+fn fs_error(message: String) -> agent_client_protocol::Error {
+    agent_client_protocol::Error::invalid_params().data(message)
+}
+
+fn terminal_error(message: String) -> agent_client_protocol::Error {
+    agent_client_protocol::Error::internal_error().data(message)
+}
+
+fn selected(option_id: String) -> RequestPermissionResponse {
+    RequestPermissionResponse::new(RequestPermissionOutcome::Selected(
+        SelectedPermissionOutcome::new(PermissionOptionId::new(option_id)),
+    ))
+}
+
 /// GoLaunch's ACP client handler.
 ///
 /// Receives session notifications and permission requests from the agent subprocess
-/// and forwards them as serializable types over channels to the Tauri event system.
+/// and forwards them as serializable types over channels to the Tauri event system,
+/// tagged with the session and conversation they belong to.
 pub struct GoLaunchClient {
-    update_tx: mpsc::UnboundedSender<AgentUpdate>,
+    update_tx: mpsc::UnboundedSender<AgentUpdateEvent>,
     permission_tx: mpsc::UnboundedSender<PermissionRequest>,
-    pending_permissions: Rc<RefCell<HashMap<String, PermissionResponder>>>,
+    pending_permissions: PendingPermissions,
+    /// Registry ID of the agent, for permission rules scoped to it.
+    agent_id: Option<String>,
+    turns: SessionTurns,
+    /// Sessions being loaded: the history the agent replays is collected here
+    /// instead of being shown and saved again.
+    replays: Rc<RefCell<HashMap<String, Replay>>>,
+    /// Numbers the permission requests for file writes and terminal commands.
+    client_requests: Cell<u64>,
+    terminals: TerminalManager,
+    /// Connection for the permission rules, remembered answers and the audit trail,
+    /// opened once for the agent's lifetime.
+    db: Result<Database, String>,
 }
 
 impl GoLaunchClient {
     pub fn new(
-        update_tx: mpsc::UnboundedSender<AgentUpdate>,
+        update_tx: mpsc::UnboundedSender<AgentUpdateEvent>,
         permission_tx: mpsc::UnboundedSender<PermissionRequest>,
+        agent_id: Option<String>,
     ) -> Self {
         Self {
             update_tx,
             permission_tx,
             pending_permissions: Rc::new(RefCell::new(HashMap::new())),
+            agent_id,
+            turns: Rc::new(RefCell::new(HashMap::new())),
+            replays: Rc::new(RefCell::new(HashMap::new())),
+            client_requests: Cell::new(0),
+            terminals: TerminalManager::default(),
+            db: Database::new(),
         }
     }
 
-    pub fn pending_permissions(&self) -> Rc<RefCell<HashMap<String, PermissionResponder>>> {
+    pub fn pending_permissions(&self) -> PendingPermissions {
         self.pending_permissions.clone()
     }
+
+    pub fn turns(&self) -> SessionTurns {
+        self.turns.clone()
+    }
+
+    pub fn replays(&self) -> Rc<RefCell<HashMap<String, Replay>>> {
+        self.replays.clone()
+    }
+
+    /// Sends updates of `session_id`, tagged with its conversation.
+    fn updates(&self, session_id: &str) -> UpdateSender {
+        UpdateSender::new(
+            self.update_tx.clone(),
+            self.agent_id.clone().unwrap_or_default(),
+            Some(session_id.to_string()),
+            self.conversation_id(session_id),
+        )
+    }
+
+    fn conversation_id(&self, session_id: &str) -> Option<String> {
+        self.turns
+            .borrow()
+            .get(session_id)
+            .and_then(|turn| turn.conversation_id.clone())
+    }
+
+    /// Agent and launch context of the session's turn, for the permission rules.
+    fn rule_scope(&self, session_id: &str) -> RuleScope {
+        let turns = self.turns.borrow();
+        let turn = turns.get(session_id);
+        RuleScope {
+            agent_id: self.agent_id.clone(),
+            source_app: turn.and_then(|t| t.source_app.clone()),
+            source_window: turn.and_then(|t| t.source_window.clone()),
+        }
+    }
+
+    fn db(&self) -> Result<&Database, String> {
+        self.db.as_ref().map_err(Clone::clone)
+    }
+
+    /// Run a tool call through the permission rules, then through the agent's remembered
+    /// "always" answers where the rules would ask. When neither can be read the user is
+    /// asked. Also returns what decided: "rule" or "remembered".
+    fn decide_permission(
+        &self,
+        request: &ToolRequest,
+        scope: &RuleScope,
+    ) -> (RuleAction, &'static str) {
+        let db = match self.db() {
+            Ok(db) => db,
+            Err(e) => {
+                eprintln!("Failed to load permission rules: {e}");
+                return (RuleAction::Ask, "rule");
+            }
+        };
+        let action = match db.list_permission_rules() {
+            Ok(rules) => permissions::evaluate(&rules, request, scope),
+            Err(e) => {
+                eprintln!("Failed to load permission rules: {e}");
+                RuleAction::Ask
+            }
+        };
+        let Some(agent_id) = scope
+            .agent_id
+            .as_deref()
+            .filter(|_| action == RuleAction::Ask)
+        else {
+            return (action, "rule");
+        };
+        match db.find_permission_grant(agent_id, &permissions::tool_signature(request)) {
+            Ok(Some(grant)) if grant.decision == "allow" => (RuleAction::Allow, "remembered"),
+            Ok(Some(_)) => (RuleAction::Deny, "remembered"),
+            Ok(None) => (RuleAction::Ask, "rule"),
+            Err(e) => {
+                eprintln!("Failed to load permission grants: {e}");
+                (RuleAction::Ask, "rule")
+            }
+        }
+    }
+
+    /// Store the user's answer if it was an "always allow" or "always reject" option.
+    fn remember_answer(
+        &self,
+        outcome: &RequestPermissionOutcome,
+        options: &[PermissionOptionInfo],
+        signature: &str,
+    ) {
+        let Some(agent_id) = &self.agent_id else {
+            return;
+        };
+        let RequestPermissionOutcome::Selected(selected) = outcome else {
+            return;
+        };
+        let chosen = selected.option_id.to_string();
+        let decision = match options.iter().find(|o| o.option_id == chosen) {
+            Some(option) if option.kind == "AllowAlways" => "allow",
+            Some(option) if option.kind == "RejectAlways" => "reject",
+            _ => return,
+        };
+        if let Err(e) = self
+            .db()
+            .and_then(|db| db.add_permission_grant(agent_id, signature, decision))
+        {
+            eprintln!("Failed to remember permission: {e}");
+        }
+    }
+
+    /// A path the agent asked for, checked against the configured file roots.
+    fn agent_path(&self, path: &Path) -> agent_client_protocol::Result<PathBuf> {
+        let roots = self
+            .db()
+            .and_then(|db| db.agent_file_roots())
+            .map_err(fs_error)?;
+        files::resolve_in_roots(path, &roots).map_err(fs_error)
+    }
+
+    fn default_terminal_cwd(&self) -> agent_client_protocol::Result<PathBuf> {
+        let roots = self
+            .db()
+            .and_then(|db| db.agent_file_roots())
+            .map_err(fs_error)?;
+        let root = roots
+            .first()
+            .ok_or_else(|| fs_error("No folders are shared with agents".to_string()))?;
+        files::resolve_in_roots(root, &roots).map_err(fs_error)
+    }
+
+    fn with_transcript(&self, session_id: &str, f: impl FnOnce(&mut TurnTranscript)) {
+        if let Some(transcript) = self
+            .turns
+            .borrow_mut()
+            .get_mut(session_id)
+            .and_then(|turn| turn.transcript.as_mut())
+        {
+            f(transcript);
+        }
+    }
+
+    /// Show a permission request in the launcher and wait for the answer.
+    async fn ask_user(&self, request: PermissionRequest) -> RequestPermissionOutcome {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.pending_permissions
+            .borrow_mut()
+            .insert(request.request_id.clone(), (request.session_id.clone(), tx));
+        let _ = self.permission_tx.send(request);
+        rx.await.unwrap_or(RequestPermissionOutcome::Cancelled)
+    }
+
+    /// Run something the agent asks GoLaunch itself to do (write a file, run a command)
+    /// through the permission rules and, where they ask, the user. The outcome goes into
+    /// the audit trail like that of the agent's own permission requests.
+    async fn approve_client_request(
+        &self,
+        session_id: &str,
+        request: &ToolRequest<'_>,
+        preview: String,
+    ) -> bool {
+        self.client_requests.set(self.client_requests.get() + 1);
+        let request_id = format!("client-{}", self.client_requests.get());
+        let options = client_permission_options();
+
+        let (decision, decided_by) = self.decide_permission(request, &self.rule_scope(session_id));
+        let automatic = match decision {
+            RuleAction::Allow => Some("allow"),
+            RuleAction::Deny => Some("reject"),
+            RuleAction::Ask => None,
+        };
+        if let Some(option_id) = automatic {
+            self.audit_permission(
+                session_id,
+                &request_id,
+                request,
+                &options,
+                Some(option_id.to_string()),
+                decided_by,
+            );
+            return decision == RuleAction::Allow;
+        }
+
+        let outcome = self
+            .ask_user(PermissionRequest {
+                request_id: request_id.clone(),
+                agent_id: self.agent_id.clone().unwrap_or_default(),
+                session_id: session_id.to_string(),
+                conversation_id: self.conversation_id(session_id),
+                tool_name: request.title.unwrap_or_default().to_string(),
+                tool_description: None,
+                command_preview: Some(preview),
+                options: options.clone(),
+            })
+            .await;
+        let chosen_option_id = match &outcome {
+            RequestPermissionOutcome::Selected(selected) => Some(selected.option_id.to_string()),
+            _ => None,
+        };
+        self.audit_permission(
+            session_id,
+            &request_id,
+            request,
+            &options,
+            chosen_option_id.clone(),
+            if chosen_option_id.is_some() {
+                "user"
+            } else {
+                "cancelled"
+            },
+        );
+        self.remember_answer(&outcome, &options, &permissions::tool_signature(request));
+        chosen_option_id.is_some_and(|chosen| {
+            options
+                .iter()
+                .any(|o| o.option_id == chosen && o.kind.starts_with("Allow"))
+        })
+    }
+
+    /// Write to the tool call audit trail of the session's conversation, if any.
+    fn audit(&self, session_id: &str, record: impl FnOnce(&Database, &str) -> Result<(), String>) {
+        let Some(conversation_id) = self.conversation_id(session_id) else {
+            return;
+        };
+        if let Err(e) = self.db().and_then(|db| record(db, &conversation_id)) {
+            eprintln!("Failed to record tool call: {e}");
+        }
+    }
+
+    fn audit_permission(
+        &self,
+        session_id: &str,
+        request_id: &str,
+        request: &ToolRequest,
+        options: &[PermissionOptionInfo],
+        chosen_option_id: Option<String>,
+        decided_by: &str,
+    ) {
+        let permission = ToolCallPermission {
+            command_preview: request.command.map(str::to_string),
+            options: options
+                .iter()
+                .map(|o| ToolCallOption {
+                    option_id: o.option_id.clone(),
+                    name: o.name.clone(),
+                    kind: o.kind.clone(),
+                })
+                .collect(),
+            chosen_option_id,
+            decided_by: decided_by.to_string(),
+        };
+        self.audit(session_id, |db, conversation_id| {
+            db.record_tool_call(
+                conversation_id,
+                request_id,
+                request.title,
+                request.kind,
+                None,
+            )?;
+            db.record_tool_call_permission(conversation_id, request_id, &permission)
+        });
+    }
 }
 
 #[async_trait::async_trait(?Send)]
@@ -94,6 +423,7 @@ impl Client for GoLaunchClient {
         &self,
         args: RequestPermissionRequest,
     ) -> agent_client_protocol::Result<RequestPermissionResponse> {
+        let session_id = args.session_id.to_string();
         let request_id = args.tool_call.tool_call_id.to_string();
 
         let tool_name = args
@@ -124,67 +454,285 @@ impl Client for GoLaunchClient {
             })
             .collect();
 
-        if should_auto_allow_read_lookup(command_preview.as_deref()) {
-            if let Some(option_id) = pick_auto_allow_option_id(&options) {
-                return Ok(RequestPermissionResponse::new(
-                    RequestPermissionOutcome::Selected(SelectedPermissionOutcome::new(
-                        PermissionOptionId::new(option_id),
-                    )),
-                ));
+        let kind = args.tool_call.fields.kind.and_then(protocol_name);
+        let request = ToolRequest {
+            kind: kind.as_deref(),
+            title: args.tool_call.fields.title.as_deref(),
+            command: command_preview.as_deref(),
+        };
+        let signature = permissions::tool_signature(&request);
+        let (decision, decided_by) =
+            self.decide_permission(&request, &self.rule_scope(&session_id));
+        match decision {
+            RuleAction::Allow => {
+                if let Some(option_id) = pick_auto_allow_option_id(&options) {
+                    self.audit_permission(
+                        &session_id,
+                        &request_id,
+                        &request,
+                        &options,
+                        Some(option_id.clone()),
+                        decided_by,
+                    );
+                    return Ok(selected(option_id));
+                }
+            }
+            RuleAction::Deny => {
+                let option_id = pick_reject_option_id(&options);
+                self.audit_permission(
+                    &session_id,
+                    &request_id,
+                    &request,
+                    &options,
+                    option_id.clone(),
+                    decided_by,
+                );
+                return Ok(match option_id {
+                    Some(option_id) => selected(option_id),
+                    None => RequestPermissionResponse::new(RequestPermissionOutcome::Cancelled),
+                });
             }
+            RuleAction::Ask => {}
         }
 
-        let (tx, rx) = tokio::sync::oneshot::channel();
+        let outcome = self
+            .ask_user(PermissionRequest {
+                request_id: request_id.clone(),
+                agent_id: self.agent_id.clone().unwrap_or_default(),
+                session_id: session_id.clone(),
+                conversation_id: self.conversation_id(&session_id),
+                tool_name,
+                tool_description: None,
+                command_preview: command_preview.clone(),
+                options: options.clone(),
+            })
+            .await;
+        let chosen_option_id = match &outcome {
+            RequestPermissionOutcome::Selected(selected) => Some(selected.option_id.to_string()),
+            _ => None,
+        };
+        let decided_by = if chosen_option_id.is_some() {
+            "user"
+        } else {
+            "cancelled"
+        };
+        self.audit_permission(
+            &session_id,
+            &request_id,
+            &request,
+            &options,
+            chosen_option_id,
+            decided_by,
+        );
+        self.remember_answer(&outcome, &options, &signature);
+        Ok(RequestPermissionResponse::new(outcome))
+    }
 
-        self.pending_permissions
-            .borrow_mut()
-            .insert(request_id.clone(), tx);
-
-        let _ = self.permission_tx.send(PermissionRequest {
-            request_id: request_id.clone(),
-            session_id: args.session_id.to_string(),
-            tool_name,
-            tool_description: None,
-            command_preview,
-            options,
-        });
+    async fn read_text_file(
+        &self,
+        args: ReadTextFileRequest,
+    ) -> agent_client_protocol::Result<ReadTextFileResponse> {
+        let path = self.agent_path(&args.path)?;
+        let content = std::fs::read_to_string(&path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                agent_client_protocol::Error::resource_not_found(Some(path.display().to_string()))
+            }
+            _ => fs_error(format!("Failed to read {}: {e}", path.display())),
+        })?;
+        Ok(ReadTextFileResponse::new(files::slice_lines(
+            &content, args.line, args.limit,
+        )))
+    }
+
+    async fn write_text_file(
+        &self,
+        args: WriteTextFileRequest,
+    ) -> agent_client_protocol::Result<WriteTextFileResponse> {
+        let path = self.agent_path(&args.path)?;
+        let old = std::fs::read_to_string(&path).ok();
+        let preview = files::diff_preview(&path, old.as_deref(), &args.content);
+        let title = format!("Write {}", path.display());
+        let request = ToolRequest {
+            kind: Some("edit"),
+            title: Some(&title),
+            command: None,
+        };
+        if !self
+            .approve_client_request(&args.session_id.to_string(), &request, preview)
+            .await
+        {
+            return Err(fs_error(format!(
+                "Writing {} was not allowed",
+                path.display()
+            )));
+        }
+        files::write_file(&path, &args.content)
+            .map_err(|e| fs_error(format!("Failed to write {}: {e}", path.display())))?;
+        Ok(WriteTextFileResponse::new())
+    }
 
-        match rx.await {
-            Ok(outcome) => Ok(RequestPermissionResponse::new(outcome)),
-            Err(_) => Ok(RequestPermissionResponse::new(
-                RequestPermissionOutcome::Cancelled,
-            )),
+    async fn create_terminal(
+        &self,
+        args: CreateTerminalRequest,
+    ) -> agent_client_protocol::Result<CreateTerminalResponse> {
+        let command = TerminalCommand {
+            command: args.command,
+            args: args.args,
+            env: args
+                .env
+                .into_iter()
+                .map(|var| (var.name, var.value))
+                .collect(),
+            // The command may only run inside the folders the agent may access, and
+            // starts in the first of them unless the agent picks one
+            cwd: Some(match args.cwd {
+                Some(cwd) => self.agent_path(&cwd)?,
+                None => self.default_terminal_cwd()?,
+            }),
+            output_byte_limit: args.output_byte_limit,
+        };
+        command.check_env().map_err(terminal_error)?;
+        let session_id = args.session_id.to_string();
+        let line = command.line();
+        let request = ToolRequest {
+            kind: Some("execute"),
+            title: Some(&line),
+            command: Some(&line),
+        };
+        if !self
+            .approve_client_request(&session_id, &request, line.clone())
+            .await
+        {
+            return Err(terminal_error(format!("Running {line} was not allowed")));
         }
+        let terminal_id = self
+            .terminals
+            .create(&command, self.updates(&session_id))
+            .map_err(terminal_error)?;
+        Ok(CreateTerminalResponse::new(terminal_id))
+    }
+
+    async fn terminal_output(
+        &self,
+        args: TerminalOutputRequest,
+    ) -> agent_client_protocol::Result<TerminalOutputResponse> {
+        let (output, truncated, exit_status) = self
+            .terminals
+            .output(&args.terminal_id.to_string())
+            .map_err(terminal_error)?;
+        Ok(TerminalOutputResponse::new(output, truncated).exit_status(exit_status))
+    }
+
+    async fn wait_for_terminal_exit(
+        &self,
+        args: WaitForTerminalExitRequest,
+    ) -> agent_client_protocol::Result<WaitForTerminalExitResponse> {
+        let exit_status = self
+            .terminals
+            .wait_for_exit(&args.terminal_id.to_string())
+            .await
+            .map_err(terminal_error)?;
+        Ok(WaitForTerminalExitResponse::new(exit_status))
+    }
+
+    async fn kill_terminal_command(
+        &self,
+        args: KillTerminalCommandRequest,
+    ) -> agent_client_protocol::Result<KillTerminalCommandResponse> {
+        self.terminals
+            .kill(&args.terminal_id.to_string())
+            .map_err(terminal_error)?;
+        Ok(KillTerminalCommandResponse::new())
+    }
+
+    async fn release_terminal(
+        &self,
+        args: ReleaseTerminalRequest,
+    ) -> agent_client_protocol::Result<ReleaseTerminalResponse> {
+        self.terminals
+            .release(&args.terminal_id.to_string())
+            .map_err(terminal_error)?;
+        Ok(ReleaseTerminalResponse::new())
     }
 
     async fn session_notification(
         &self,
         args: SessionNotification,
     ) -> agent_client_protocol::Result<()> {
+        let session_id = args.session_id.to_string();
+        if let Some(replay) = self.replays.borrow_mut().get_mut(&session_id) {
+            match &args.update {
+                SessionUpdate::UserMessageChunk(chunk) => {
+                    if let ContentBlock::Text(t) = &chunk.content {
+                        replay.push("user", &t.text);
+                    }
+                }
+                SessionUpdate::AgentMessageChunk(chunk) => {
+                    if let ContentBlock::Text(t) = &chunk.content {
+                        replay.push("assistant", &t.text);
+                    }
+                }
+                _ => replay.break_message(),
+            }
+            return Ok(());
+        }
+
+        let updates = self.updates(&session_id);
         match &args.update {
             SessionUpdate::AgentMessageChunk(chunk) => {
                 if let ContentBlock::Text(t) = &chunk.content {
-                    let _ = self.update_tx.send(AgentUpdate::MessageChunk {
+                    self.with_transcript(&session_id, |transcript| {
+                        transcript.message_chunk(&t.text)
+                    });
+                    updates.send(AgentUpdate::MessageChunk {
                         text: t.text.clone(),
                     });
                 }
             }
             SessionUpdate::AgentThoughtChunk(chunk) => {
                 if let ContentBlock::Text(t) = &chunk.content {
-                    let _ = self.update_tx.send(AgentUpdate::ThoughtChunk {
+                    self.with_transcript(&session_id, |transcript| {
+                        transcript.thought_chunk(&t.text)
+                    });
+                    updates.send(AgentUpdate::ThoughtChunk {
                         text: t.text.clone(),
                     });
                 }
             }
             SessionUpdate::ToolCall(tc) => {
-                let _ = self.update_tx.send(AgentUpdate::ToolCall {
+                let kind = protocol_name(tc.kind);
+                let status = protocol_name(tc.status);
+                self.audit(&session_id, |db, conversation_id| {
+                    db.record_tool_call(
+                        conversation_id,
+                        &tc.tool_call_id.to_string(),
+                        Some(&tc.title),
+                        kind.as_deref(),
+                        status.as_deref(),
+                    )
+                });
+                updates.send(AgentUpdate::ToolCall {
                     id: tc.tool_call_id.to_string(),
                     title: tc.title.clone(),
                     kind: format!("{:?}", tc.kind),
                 });
             }
             SessionUpdate::ToolCallUpdate(tcu) => {
-                let _ = self.update_tx.send(AgentUpdate::ToolCallUpdate {
+                let fields = &tcu.fields;
+                if fields.title.is_some() || fields.kind.is_some() || fields.status.is_some() {
+                    let kind = fields.kind.and_then(protocol_name);
+                    let status = fields.status.and_then(protocol_name);
+                    self.audit(&session_id, |db, conversation_id| {
+                        db.record_tool_call(
+                            conversation_id,
+                            &tcu.tool_call_id.to_string(),
+                            fields.title.as_deref(),
+                            kind.as_deref(),
+                            status.as_deref(),
+                        )
+                    });
+                }
+                updates.send(AgentUpdate::ToolCallUpdate {
                     id: tcu.tool_call_id.to_string(),
                     title: tcu.fields.title.clone(),
                     status: tcu.fields.status.map(|s| format!("{:?}", s)),
@@ -199,8 +747,9 @@ impl Client for GoLaunchClient {
                         priority: format!("{:?}", e.priority),
                         status: format!("{:?}", e.status),
                     })
-                    .collect();
-                let _ = self.update_tx.send(AgentUpdate::Plan { entries });
+                    .collect::<Vec<_>>();
+                self.with_transcript(&session_id, |transcript| transcript.plan(&entries));
+                updates.send(AgentUpdate::Plan { entries });
             }
             _ => {}
         }
@@ -0,0 +1,271 @@
+//! Helpers for the ACP `fs/*` client methods: keeping agent file access inside the
+//! configured roots, and previewing writes as a diff.
+
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+/// Whole-file diffs are skipped above this many line pairs to compare.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+/// Unchanged lines shown around each change.
+const DIFF_CONTEXT: usize = 2;
+
+/// Resolve an absolute path the agent asked for, following symlinks, and check it lies
+/// inside one of `roots`. A file that doesn't exist yet is resolved through its
+/// parent directory, which must exist. A dangling symlink is refused, since writing
+/// to it would create its target wherever that is.
+pub fn resolve_in_roots(path: &Path, roots: &[PathBuf]) -> Result<PathBuf, String> {
+    if !path.is_absolute() {
+        return Err(format!("Path must be absolute: {}", path.display()));
+    }
+    let resolved = match path.canonicalize() {
+        Ok(resolved) => resolved,
+        Err(_) => {
+            if path.symlink_metadata().is_ok_and(|meta| meta.is_symlink()) {
+                return Err(format!("{} is a symlink to a missing file", path.display()));
+            }
+            let name = match path.components().next_back() {
+                Some(Component::Normal(name)) => name,
+                _ => return Err(format!("Invalid file path: {}", path.display())),
+            };
+            let parent = path
+                .parent()
+                .ok_or_else(|| format!("Invalid file path: {}", path.display()))?;
+            parent
+                .canonicalize()
+                .map_err(|e| format!("Failed to resolve {}: {e}", parent.display()))?
+                .join(name)
+        }
+    };
+    let allowed = roots
+        .iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .any(|root| resolved.starts_with(root));
+    if allowed {
+        Ok(resolved)
+    } else {
+        Err(format!(
+            "{} is outside the folders the agent may access",
+            path.display()
+        ))
+    }
+}
+
+/// Write a file resolved by [`resolve_in_roots`]. Resolved paths contain no symlinks,
+/// so one found now was put there after the check and is not followed. A new file is
+/// created exclusively, which also fails on a symlink.
+pub fn write_file(path: &Path, content: &str) -> std::io::Result<()> {
+    match path.symlink_metadata() {
+        Ok(meta) if meta.is_symlink() => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "refusing to write through a symlink",
+            ))
+        }
+        Ok(_) => return std::fs::write(path, content),
+        Err(_) => {}
+    }
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?
+        .write_all(content.as_bytes())
+}
+
+/// `limit` lines of `content` starting at 1-based `line`.
+pub fn slice_lines(content: &str, line: Option<u32>, limit: Option<u32>) -> String {
+    if line.is_none() && limit.is_none() {
+        return content.to_string();
+    }
+    let skip = line.map_or(0, |line| line.saturating_sub(1) as usize);
+    let take = limit.map_or(usize::MAX, |limit| limit as usize);
+    content
+        .split_inclusive('\n')
+        .skip(skip)
+        .take(take)
+        .collect()
+}
+
+/// A unified-style diff of a file write for the permission prompt: changed lines with
+/// a little context, `-` for removed and `+` for added lines.
+pub fn diff_preview(path: &Path, old: Option<&str>, new: &str) -> String {
+    let mut preview = match old {
+        Some(_) => format!("--- {0}\n+++ {0}\n", path.display()),
+        None => format!("--- /dev/null\n+++ {}\n", path.display()),
+    };
+    let old_lines: Vec<&str> = old.map(|old| old.lines().collect()).unwrap_or_default();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    let Some(ops) = diff_lines(&old_lines, &new_lines) else {
+        // Too large to compare line by line: show it as replaced
+        for line in &old_lines {
+            preview.push_str(&format!("-{line}\n"));
+        }
+        for line in &new_lines {
+            preview.push_str(&format!("+{line}\n"));
+        }
+        return preview;
+    };
+    if ops.iter().all(|op| matches!(op, DiffOp::Same(_))) {
+        preview.push_str("(no changes)\n");
+        return preview;
+    }
+
+    // Show each change with DIFF_CONTEXT unchanged lines around it
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, DiffOp::Same(_)))
+        .map(|(i, _)| i)
+        .collect();
+    let visible = |i: usize| changed.iter().any(|&c| c.abs_diff(i) <= DIFF_CONTEXT);
+    let mut skipped = false;
+    for (i, op) in ops.iter().enumerate() {
+        if !visible(i) {
+            skipped = true;
+            continue;
+        }
+        if skipped {
+            preview.push_str("...\n");
+            skipped = false;
+        }
+        match op {
+            DiffOp::Same(line) => preview.push_str(&format!(" {line}\n")),
+            DiffOp::Removed(line) => preview.push_str(&format!("-{line}\n")),
+            DiffOp::Added(line) => preview.push_str(&format!("+{line}\n")),
+        }
+    }
+    if skipped {
+        preview.push_str("...\n");
+    }
+    preview
+}
+
+#[derive(Debug, PartialEq)]
+enum DiffOp<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Line diff from the longest common subsequence; `None` when the inputs are too large.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Option<Vec<DiffOp<'a>>> {
+    // Common head and tail don't need the table
+    let head = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let tail = old[head..]
+        .iter()
+        .rev()
+        .zip(new[head..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_mid, new_mid) = (&old[head..old.len() - tail], &new[head..new.len() - tail]);
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_DIFF_CELLS {
+        return None;
+    }
+
+    // lcs[i][j]: common lines of old_mid[i..] and new_mid[j..]
+    let (n, m) = (old_mid.len(), new_mid.len());
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_mid[i] == new_mid[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<DiffOp> = old[..head].iter().map(|l| DiffOp::Same(l)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_mid[i] == new_mid[j] {
+            ops.push(DiffOp::Same(old_mid[i]));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(DiffOp::Removed(old_mid[i]));
+            i += 1;
+        } else {
+            ops.push(DiffOp::Added(new_mid[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[old.len() - tail..].iter().map(|l| DiffOp::Same(l)));
+    Some(ops)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slices_lines() {
+        let text = "a\nb\nc\nd\n";
+        assert_eq!(slice_lines(text, None, None), text);
+        assert_eq!(slice_lines(text, Some(2), Some(2)), "b\nc\n");
+        assert_eq!(slice_lines(text, Some(4), None), "d\n");
+        assert_eq!(slice_lines(text, Some(9), None), "");
+    }
+
+    #[test]
+    fn diffs_changed_lines_with_context() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8\n";
+        let preview = diff_preview(Path::new("/x/f.sh"), Some(old), new);
+        assert_eq!(
+            preview,
+            "--- /x/f.sh\n+++ /x/f.sh\n...\n 3\n 4\n-5\n+five\n 6\n 7\n...\n"
+        );
+    }
+
+    #[test]
+    fn diffs_new_files_and_no_ops() {
+        let preview = diff_preview(Path::new("/x/new.sh"), None, "echo hi\n");
+        assert_eq!(preview, "--- /dev/null\n+++ /x/new.sh\n+echo hi\n");
+        let preview = diff_preview(Path::new("/x/f"), Some("a\n"), "a\n");
+        assert!(preview.ends_with("(no changes)\n"));
+    }
+
+    #[test]
+    fn keeps_paths_inside_roots() {
+        let root = std::env::temp_dir().join(format!("golaunch-roots-{}", std::process::id()));
+        let inside = root.join("inside");
+        std::fs::create_dir_all(&inside).unwrap();
+        std::fs::write(inside.join("a.sh"), "").unwrap();
+        let roots = vec![inside.clone()];
+
+        assert!(resolve_in_roots(&inside.join("a.sh"), &roots).is_ok());
+        assert!(resolve_in_roots(&inside.join("new.sh"), &roots).is_ok());
+        assert!(resolve_in_roots(&inside.join("../outside.sh"), &roots).is_err());
+        assert!(resolve_in_roots(&root.join("b.sh"), &roots).is_err());
+        assert!(resolve_in_roots(Path::new("a.sh"), &roots).is_err());
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&root, inside.join("up")).unwrap();
+            assert!(resolve_in_roots(&inside.join("up/b.sh"), &roots).is_err());
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_dangling_symlinks() {
+        let root = std::env::temp_dir().join(format!("golaunch-dangling-{}", std::process::id()));
+        let inside = root.join("inside");
+        std::fs::create_dir_all(&inside).unwrap();
+        let roots = vec![inside.clone()];
+        let target = root.join("bashrc");
+        let link = inside.join("link");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        assert!(resolve_in_roots(&link, &roots).is_err());
+        // Even when the check is passed first, a write doesn't create the target
+        assert!(write_file(&link, "pwned").is_err());
+        assert!(!target.exists());
+
+        write_file(&inside.join("new.sh"), "a").unwrap();
+        write_file(&inside.join("new.sh"), "b").unwrap();
+        assert_eq!(std::fs::read_to_string(inside.join("new.sh")).unwrap(), "b");
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
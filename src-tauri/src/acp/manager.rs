@@ -1,38 +1,181 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use agent_client_protocol::{
-    Agent, ClientCapabilities, ClientSideConnection, ContentBlock, Implementation,
-    InitializeRequest, NewSessionRequest, PermissionOptionId, ProtocolVersion,
-    RequestPermissionOutcome, SelectedPermissionOutcome, SessionConfigId, SessionConfigKind,
-    SessionConfigOption, SessionConfigSelectOptions, SessionConfigValueId, SessionId,
-    SetSessionConfigOptionRequest, TextContent,
+    Agent, CancelNotification, ClientCapabilities, ClientSideConnection, ContentBlock,
+    FileSystemCapability, Implementation, InitializeRequest, LoadSessionRequest, NewSessionRequest,
+    PermissionOptionId, ProtocolVersion, RequestPermissionOutcome, SelectedPermissionOutcome,
+    SessionConfigId, SessionConfigKind, SessionConfigOption, SessionConfigSelectOptions,
+    SessionConfigValueId, SessionId, SetSessionConfigOptionRequest, StopReason, TextContent,
 };
 use golaunch_core::{
-    CommandHistory, CommandSuggestion, Conversation, ConversationMessage, Item, Memory,
-    SlashCommand,
+    CommandHistory, CommandSuggestion, Conversation, ConversationMessage, Database, Item, Memory,
+    NewConversation, NewConversationMessage, SlashCommand,
 };
 use tauri::{AppHandle, Emitter};
-use tokio::sync::{mpsc, oneshot};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{mpsc, oneshot, watch, Mutex};
 
-use super::client::GoLaunchClient;
+use super::client::{GoLaunchClient, PendingPermissions, SessionTurn, SessionTurns};
+use super::transcript::{self, Replay, ReplayChange, ReplayedMessage, TurnTranscript};
 use super::types::{
-    AgentConfig, AgentStatus, AgentUpdate, PermissionRequest, SessionConfigOptionInfo,
-    SessionConfigSelectGroupInfo, SessionConfigSelectOptionInfo, SessionConfigSelectOptionsInfo,
+    AgentConfig, AgentStatus, AgentStatusInfo, AgentUpdate, AgentUpdateEvent, ConfigOptionsEvent,
+    PermissionRequest, SessionConfigOptionInfo, SessionConfigSelectGroupInfo,
+    SessionConfigSelectOptionInfo, SessionConfigSelectOptionsInfo, UpdateSender,
 };
 
+/// How long an agent gets to end a cancelled turn before its process is restarted.
+const CANCEL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Wait before restarting an agent whose process died, doubled for each restart
+/// since it last ran for `STABLE_UPTIME`.
+const RESTART_BACKOFF: Duration = Duration::from_secs(1);
+/// Restarts tried before an agent that keeps dying is left in the `Error` status.
+const MAX_RESTART_ATTEMPTS: u32 = 6;
+/// An agent that ran this long before dying is restarted after the shortest wait.
+const STABLE_UPTIME: Duration = Duration::from_secs(120);
+
+/// Messages of a conversation replayed to an agent that can't resume its session.
+const RESEED_MESSAGES: usize = 20;
+/// Longest message in a replayed transcript, in characters.
+const RESEED_MESSAGE_CHARS: usize = 500;
+
+/// Stop reason reported for turns ended by `AcpManager::cancel`.
+pub const CANCELLED_STOP_REASON: &str = "cancelled";
+
+/// Pipes to a running agent, and its process when there is one.
+pub struct AgentIo {
+    pub stdin: Box<dyn AsyncWrite + Send + Unpin>,
+    pub stdout: Box<dyn AsyncRead + Send + Unpin>,
+    pub child: Option<tokio::process::Child>,
+}
+
+/// Starts the agent; called again when it has to be restarted.
+pub type AgentSpawner = Arc<dyn Fn() -> Result<AgentIo, String> + Send + Sync>;
+
+/// Receives what the agent sends: Tauri events in the app.
+pub trait AgentEvents: Send + Sync + 'static {
+    fn update(&self, update: &AgentUpdateEvent);
+    fn permission_request(&self, request: &PermissionRequest);
+    fn config_options(&self, options: &ConfigOptionsEvent);
+}
+
+impl AgentEvents for AppHandle {
+    fn update(&self, update: &AgentUpdateEvent) {
+        let _ = self.emit("acp-update", update);
+    }
+
+    fn permission_request(&self, request: &PermissionRequest) {
+        let _ = self.emit("acp-permission-request", request);
+    }
+
+    fn config_options(&self, options: &ConfigOptionsEvent) {
+        let _ = self.emit("acp-config-options", options);
+    }
+}
+
+/// An agent session, with the worker on the connection thread that runs its prompts.
+struct Session {
+    id: SessionId,
+    prompt_tx: mpsc::UnboundedSender<PromptCommand>,
+    /// Carries the number of the last turn to cancel.
+    cancel_tx: mpsc::UnboundedSender<u64>,
+    /// Prompts sent to the session; turns are numbered from 1.
+    turns_started: u64,
+    /// Prompts the session has finished with.
+    turns_finished: watch::Receiver<u64>,
+    config_options: Vec<SessionConfigOptionInfo>,
+    /// The session was started for a conversation with earlier messages the agent
+    /// hasn't seen; the next prompt carries a condensed transcript.
+    reseed_pending: bool,
+}
+
+impl Session {
+    fn new(id: SessionId, worker: SessionWorker, options: Vec<SessionConfigOptionInfo>) -> Self {
+        Self {
+            id,
+            prompt_tx: worker.prompt_tx,
+            cancel_tx: worker.cancel_tx,
+            turns_started: 0,
+            turns_finished: worker.turns_finished,
+            config_options: options,
+            reseed_pending: false,
+        }
+    }
+
+    fn turn_active(&self) -> bool {
+        *self.turns_finished.borrow() < self.turns_started
+    }
+}
+
+/// Channels to the worker of a session, sent back from the connection thread.
+struct SessionWorker {
+    prompt_tx: mpsc::UnboundedSender<PromptCommand>,
+    cancel_tx: mpsc::UnboundedSender<u64>,
+    turns_finished: watch::Receiver<u64>,
+}
+
 pub struct AcpManager {
     status: AgentStatus,
-    session_id: Option<SessionId>,
-    prompt_tx: Option<mpsc::UnboundedSender<PromptCommand>>,
-    cancel_tx: Option<mpsc::UnboundedSender<()>>,
     permission_resolve_tx: Option<mpsc::UnboundedSender<(String, String)>>,
     config_option_tx: Option<mpsc::UnboundedSender<ConfigOptionCommand>>,
     shutdown_tx: Option<mpsc::UnboundedSender<()>>,
-    config_options: Vec<SessionConfigOptionInfo>,
+    session_command_tx: Option<mpsc::UnboundedSender<SessionCommand>>,
+    /// Sessions by the conversation they hold.
+    sessions: HashMap<String, Session>,
+    /// A session opened before a conversation needed it; the next new conversation
+    /// takes it.
+    spare_session: Option<Session>,
+    /// Whether the agent can resume earlier sessions (`session/load`).
+    load_session_supported: bool,
+    /// Kept to restart an agent that ignores a cancel or was shut down while idle.
+    events: Option<Arc<dyn AgentEvents>>,
+    spawner: Option<AgentSpawner>,
+    /// Registry ID of the connected agent, for permission rules scoped to it.
+    agent_id: Option<String>,
+    cancel_timeout: Duration,
+    restart_backoff: Duration,
+    /// When the agent was last given something to do.
+    last_used: Instant,
+    /// Counts connections, so that the exit of a replaced one is ignored.
+    connection: u64,
+    /// When the running agent process was started.
+    started_at: Option<Instant>,
+    restart_count: u32,
+    /// Restarts since the agent last ran for `STABLE_UPTIME`; sets the backoff.
+    restart_attempts: u32,
+    last_error: Option<String>,
+    /// Tells the supervisor started by `shared` that a connection died, and why.
+    exit_tx: Option<mpsc::UnboundedSender<(u64, String)>>,
 }
 
 enum PromptCommand {
     Prompt {
-        session_id: SessionId,
         content: Vec<ContentBlock>,
+        /// Where the launcher was opened from, for permission rules.
+        source_app: Option<String>,
+        source_window: Option<String>,
+        /// Conversation the turn is saved to, with its tool calls.
+        conversation_id: Option<String>,
+        /// The stored user message that starts the turn.
+        user_message_id: Option<String>,
+    },
+}
+
+type SessionReply<T> =
+    oneshot::Sender<Result<(T, SessionWorker, Vec<SessionConfigOptionInfo>), String>>;
+
+enum SessionCommand {
+    New {
+        reply: SessionReply<SessionId>,
+    },
+    /// Resume an earlier session; replies with the history the agent replayed.
+    Load {
+        session_id: SessionId,
+        reply: SessionReply<Vec<ReplayedMessage>>,
     },
 }
 
@@ -43,102 +186,117 @@ struct ConfigOptionCommand {
     reply: oneshot::Sender<Result<Vec<SessionConfigOptionInfo>, String>>,
 }
 
+/// What the session workers of a connection share on its thread.
+#[derive(Clone)]
+struct ConnectionShared {
+    connection: Rc<ClientSideConnection>,
+    turns: SessionTurns,
+    pending_permissions: PendingPermissions,
+    update_tx: mpsc::UnboundedSender<AgentUpdateEvent>,
+    agent_id: String,
+}
+
 impl AcpManager {
     pub fn new() -> Self {
         Self {
             status: AgentStatus::Disconnected,
-            session_id: None,
-            prompt_tx: None,
-            cancel_tx: None,
             permission_resolve_tx: None,
             config_option_tx: None,
             shutdown_tx: None,
-            config_options: Vec::new(),
+            session_command_tx: None,
+            sessions: HashMap::new(),
+            spare_session: None,
+            load_session_supported: false,
+            events: None,
+            spawner: None,
+            agent_id: None,
+            cancel_timeout: CANCEL_TIMEOUT,
+            restart_backoff: RESTART_BACKOFF,
+            last_used: Instant::now(),
+            connection: 0,
+            started_at: None,
+            restart_count: 0,
+            restart_attempts: 0,
+            last_error: None,
+            exit_tx: None,
         }
     }
 
+    /// A manager that restarts its agent when the agent process exits or its pipes
+    /// fail. Must be called on a Tokio runtime.
+    pub fn shared() -> Arc<Mutex<Self>> {
+        let (exit_tx, exit_rx) = mpsc::unbounded_channel();
+        let manager = Arc::new(Mutex::new(Self {
+            exit_tx: Some(exit_tx),
+            ..Self::new()
+        }));
+        tokio::spawn(Self::supervise(Arc::downgrade(&manager), exit_rx));
+        manager
+    }
+
     pub fn status(&self) -> AgentStatus {
         self.status
     }
 
+    pub fn status_info(&self) -> AgentStatusInfo {
+        AgentStatusInfo {
+            agent_id: self.agent_id.clone(),
+            status: self.status,
+            uptime_secs: self.started_at.map(|started| started.elapsed().as_secs()),
+            restart_count: self.restart_count,
+            last_error: self.last_error.clone(),
+        }
+    }
+
     pub async fn connect(&mut self, app: AppHandle, config: AgentConfig) -> Result<(), String> {
         if self.status == AgentStatus::Connected {
             return Ok(());
         }
+        let spawner = process_spawner(&config)?;
+        self.agent_id = Some(config.agent_id.clone());
+        self.connect_with(Arc::new(app), spawner).await
+    }
 
+    /// Start the agent with `spawner` and open a session on it.
+    pub async fn connect_with(
+        &mut self,
+        events: Arc<dyn AgentEvents>,
+        spawner: AgentSpawner,
+    ) -> Result<(), String> {
         self.status = AgentStatus::Connecting;
-        let _ = app.emit(
-            "acp-update",
+        self.emit(
+            &events,
+            None,
             AgentUpdate::StatusChange {
                 status: AgentStatus::Connecting,
+                reason: None,
             },
         );
 
-        let binary = if config.binary_path.is_empty() {
-            return Err("No binary path configured".to_string());
-        } else {
-            config.binary_path.clone()
+        self.connection += 1;
+        let connection_id = self.connection;
+        let exit_tx = self.exit_tx.clone();
+
+        let AgentIo {
+            stdin: child_stdin,
+            stdout: child_stdout,
+            mut child,
+        } = match spawner() {
+            Ok(io) => io,
+            Err(e) => return Err(self.connect_failed(&events, e)),
         };
 
-        let args: Vec<String> = if config.args.is_empty() {
-            vec![]
-        } else {
-            config.args.split_whitespace().map(String::from).collect()
-        };
-
-        // Resolve the binary path: check if it's on PATH, otherwise look in
-        // our install directory (AppData/Local/GoLaunch/agents/<agent_id>/)
-        let resolved_binary = resolve_binary_path(&binary, &config.agent_id);
-
-        // On Windows, commands like "npx" are actually .cmd batch scripts
-        // that cannot be spawned directly. We need to run them through cmd.exe.
-        #[cfg(target_os = "windows")]
-        let mut cmd = {
-            let mut c = tokio::process::Command::new("cmd");
-            let mut cmd_args = vec!["/C".to_string(), resolved_binary.clone()];
-            cmd_args.extend(args.clone());
-            c.args(&cmd_args);
-            c
-        };
-        #[cfg(not(target_os = "windows"))]
-        let mut cmd = {
-            let mut c = tokio::process::Command::new(&resolved_binary);
-            c.args(&args);
-            c
-        };
-
-        cmd.stdin(std::process::Stdio::piped())
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::null());
-
-        // Parse env vars from "KEY=VALUE,KEY2=VALUE2"
-        if !config.env.is_empty() {
-            for pair in config.env.split(',') {
-                if let Some((k, v)) = pair.split_once('=') {
-                    cmd.env(k.trim(), v.trim());
-                }
-            }
-        }
-
-        let mut child = cmd
-            .spawn()
-            .map_err(|e| format!("Failed to spawn agent process: {e}"))?;
-
-        let child_stdin = child.stdin.take().ok_or("Failed to get agent stdin")?;
-        let child_stdout = child.stdout.take().ok_or("Failed to get agent stdout")?;
-
         // Channels for bridging async ACP events to Tauri
-        let (update_tx, mut update_rx) = mpsc::unbounded_channel::<AgentUpdate>();
+        let (update_tx, mut update_rx) = mpsc::unbounded_channel::<AgentUpdateEvent>();
         let (permission_tx, mut permission_rx) = mpsc::unbounded_channel::<PermissionRequest>();
-        let (prompt_tx, prompt_rx) = mpsc::unbounded_channel::<PromptCommand>();
-        let (cancel_tx, cancel_rx) = mpsc::unbounded_channel::<()>();
         let (perm_resolve_tx, perm_resolve_rx) = mpsc::unbounded_channel::<(String, String)>();
         let (config_option_tx, config_option_rx) = mpsc::unbounded_channel::<ConfigOptionCommand>();
         let (shutdown_tx, shutdown_rx) = mpsc::unbounded_channel::<()>();
+        let (session_command_tx, session_command_rx) = mpsc::unbounded_channel::<SessionCommand>();
+        let agent_id = self.agent_id.clone();
 
-        // Session initialization oneshot (now returns config options too)
-        let (session_tx, session_rx) =
-            oneshot::channel::<Result<(SessionId, Vec<SessionConfigOptionInfo>), String>>();
+        // Initialization oneshot: whether the agent can load sessions
+        let (init_tx, init_rx) = oneshot::channel::<Result<bool, String>>();
 
         // Spawn the ACP connection on a dedicated thread with LocalSet
         // (required because Client trait is !Send)
@@ -151,8 +309,11 @@ impl AcpManager {
             let local = tokio::task::LocalSet::new();
 
             local.block_on(&rt, async move {
-                let acp_client = GoLaunchClient::new(update_tx.clone(), permission_tx);
+                let acp_client =
+                    GoLaunchClient::new(update_tx.clone(), permission_tx, agent_id.clone());
                 let pending_perms = acp_client.pending_permissions();
+                let turns = acp_client.turns();
+                let replays = acp_client.replays();
 
                 let stdin_async =
                     tokio_util::compat::TokioAsyncWriteCompatExt::compat_write(child_stdin);
@@ -164,11 +325,14 @@ impl AcpManager {
                         tokio::task::spawn_local(fut);
                     });
 
-                // Spawn I/O handler
+                // Spawn I/O handler, reporting when the pipes close or fail
+                let (io_closed_tx, io_closed_rx) = oneshot::channel::<String>();
                 tokio::task::spawn_local(async move {
-                    if let Err(e) = io_future.await {
-                        eprintln!("ACP I/O error: {e:?}");
-                    }
+                    let reason = match io_future.await {
+                        Ok(()) => "Agent closed its connection".to_string(),
+                        Err(e) => format!("Agent connection failed: {e:?}"),
+                    };
+                    let _ = io_closed_tx.send(reason);
                 });
 
                 // Initialize the connection
@@ -176,42 +340,35 @@ impl AcpManager {
                     .initialize(
                         InitializeRequest::new(ProtocolVersion::LATEST)
                             .client_info(Implementation::new("GoLaunch", "0.1.0"))
-                            .client_capabilities(ClientCapabilities::new()),
+                            .client_capabilities(
+                                ClientCapabilities::new()
+                                    .fs(FileSystemCapability::new()
+                                        .read_text_file(true)
+                                        .write_text_file(true))
+                                    .terminal(true),
+                            ),
                     )
                     .await;
 
-                if let Err(e) = init_result {
-                    let _ = session_tx.send(Err(format!("Initialize failed: {e:?}")));
-                    return;
-                }
-
-                // Create a new session
-                let cwd = std::env::current_dir().unwrap_or_else(|_| "/".into());
-                let session_result = connection.new_session(NewSessionRequest::new(cwd)).await;
-
-                let (session_id, initial_config_options) = match session_result {
+                match init_result {
                     Ok(resp) => {
-                        let config_infos = resp
-                            .config_options
-                            .as_ref()
-                            .map(|opts| opts.iter().map(convert_config_option).collect())
-                            .unwrap_or_default();
-                        (resp.session_id, config_infos)
+                        let _ = init_tx.send(Ok(resp.agent_capabilities.load_session));
                     }
                     Err(e) => {
-                        let _ = session_tx.send(Err(format!("New session failed: {e:?}")));
+                        let _ = init_tx.send(Err(format!("Initialize failed: {e:?}")));
+                        if let Some(child) = child.as_mut() {
+                            let _ = child.kill().await;
+                        }
                         return;
                     }
-                };
-
-                let _ = session_tx.send(Ok((session_id.clone(), initial_config_options)));
+                }
 
                 // Handle permission resolves from the Tauri thread
                 let pending_perms_clone = pending_perms.clone();
                 let mut perm_resolve_rx = perm_resolve_rx;
                 tokio::task::spawn_local(async move {
                     while let Some((request_id, option_id)) = perm_resolve_rx.recv().await {
-                        if let Some(responder) =
+                        if let Some((_, responder)) =
                             pending_perms_clone.borrow_mut().remove(&request_id)
                         {
                             let outcome = RequestPermissionOutcome::Selected(
@@ -222,44 +379,17 @@ impl AcpManager {
                     }
                 });
 
-                // Wrap connection in Rc for sharing between prompt and config handlers
-                let connection = std::rc::Rc::new(connection);
-
-                // Handle prompts from the Tauri thread
-                let conn_for_prompts = connection.clone();
-                let mut prompt_rx = prompt_rx;
-                tokio::task::spawn_local(async move {
-                    while let Some(cmd) = prompt_rx.recv().await {
-                        match cmd {
-                            PromptCommand::Prompt {
-                                session_id,
-                                content,
-                            } => {
-                                let result = conn_for_prompts
-                                    .prompt(agent_client_protocol::PromptRequest::new(
-                                        session_id, content,
-                                    ))
-                                    .await;
-
-                                match result {
-                                    Ok(resp) => {
-                                        let _ = update_tx.send(AgentUpdate::TurnComplete {
-                                            stop_reason: format!("{:?}", resp.stop_reason),
-                                        });
-                                    }
-                                    Err(e) => {
-                                        let _ = update_tx.send(AgentUpdate::TurnComplete {
-                                            stop_reason: format!("Error: {e:?}"),
-                                        });
-                                    }
-                                }
-                            }
-                        }
-                    }
-                });
+                // Wrap connection in Rc for sharing between the session and config handlers
+                let shared = ConnectionShared {
+                    connection: Rc::new(connection),
+                    turns,
+                    pending_permissions: pending_perms,
+                    update_tx,
+                    agent_id: agent_id.unwrap_or_default(),
+                };
 
                 // Handle config option changes from the Tauri thread
-                let conn_for_config = connection.clone();
+                let conn_for_config = shared.connection.clone();
                 let mut config_option_rx = config_option_rx;
                 tokio::task::spawn_local(async move {
                     while let Some(cmd) = config_option_rx.recv().await {
@@ -286,89 +416,243 @@ impl AcpManager {
                     }
                 });
 
-                // Handle cancels
-                let pending_perms_cancel = pending_perms;
-                let mut cancel_rx = cancel_rx;
+                // Open and resume sessions for the Tauri thread
+                let mut session_command_rx = session_command_rx;
                 tokio::task::spawn_local(async move {
-                    while let Some(()) = cancel_rx.recv().await {
-                        // Cancel all pending permissions
-                        let mut perms = pending_perms_cancel.borrow_mut();
-                        for (_id, responder) in perms.drain() {
-                            let _ = responder.send(RequestPermissionOutcome::Cancelled);
+                    while let Some(cmd) = session_command_rx.recv().await {
+                        match cmd {
+                            SessionCommand::New { reply } => {
+                                let result = match shared
+                                    .connection
+                                    .new_session(NewSessionRequest::new(agent_cwd()))
+                                    .await
+                                {
+                                    Ok(resp) => Ok((
+                                        resp.session_id.clone(),
+                                        spawn_session_worker(&shared, resp.session_id),
+                                        config_option_infos(resp.config_options.as_deref()),
+                                    )),
+                                    Err(e) => Err(format!("New session failed: {e:?}")),
+                                };
+                                let _ = reply.send(result);
+                            }
+                            SessionCommand::Load { session_id, reply } => {
+                                let key = session_id.to_string();
+                                replays.borrow_mut().insert(key.clone(), Replay::default());
+                                let result = shared
+                                    .connection
+                                    .load_session(LoadSessionRequest::new(
+                                        session_id.clone(),
+                                        agent_cwd(),
+                                    ))
+                                    .await;
+                                let replayed = replays
+                                    .borrow_mut()
+                                    .remove(&key)
+                                    .map(Replay::into_messages)
+                                    .unwrap_or_default();
+                                let result = match result {
+                                    Ok(resp) => Ok((
+                                        replayed,
+                                        spawn_session_worker(&shared, session_id),
+                                        config_option_infos(resp.config_options.as_deref()),
+                                    )),
+                                    Err(e) => Err(format!("Load session failed: {e:?}")),
+                                };
+                                let _ = reply.send(result);
+                            }
                         }
                     }
                 });
 
-                // Wait for shutdown signal
+                // Run until shut down, or until the agent exits or its pipes fail
                 let mut shutdown_rx = shutdown_rx;
-                shutdown_rx.recv().await;
+                let stopped = tokio::select! {
+                    _ = shutdown_rx.recv() => None,
+                    Ok(reason) = io_closed_rx => Some(reason),
+                    reason = process_exit(child.as_mut()) => Some(reason),
+                };
 
                 // Kill the child process
-                let _ = child.kill().await;
+                if let Some(mut child) = child {
+                    let _ = child.kill().await;
+                }
+                if let (Some(reason), Some(exit_tx)) = (stopped, exit_tx) {
+                    let _ = exit_tx.send((connection_id, reason));
+                }
             });
         });
 
-        // Wait for session initialization
-        let (session_id, initial_config_options) = session_rx
-            .await
-            .map_err(|_| "Connection thread died".to_string())??;
+        // Spawn background tasks to forward updates and permissions to Tauri events
+        let events_for_updates = events.clone();
+        tokio::spawn(async move {
+            while let Some(update) = update_rx.recv().await {
+                events_for_updates.update(&update);
+            }
+        });
+
+        let events_for_permissions = events.clone();
+        tokio::spawn(async move {
+            while let Some(perm) = permission_rx.recv().await {
+                events_for_permissions.permission_request(&perm);
+            }
+        });
 
-        self.session_id = Some(session_id);
-        self.prompt_tx = Some(prompt_tx);
-        self.cancel_tx = Some(cancel_tx);
+        // Wait for initialization
+        let init = init_rx
+            .await
+            .map_err(|_| "Connection thread died".to_string())
+            .and_then(|result| result);
+        self.shutdown_tx = Some(shutdown_tx);
+        self.load_session_supported = match init {
+            Ok(load_session_supported) => load_session_supported,
+            Err(e) => return Err(self.connect_failed(&events, e)),
+        };
         self.permission_resolve_tx = Some(perm_resolve_tx);
         self.config_option_tx = Some(config_option_tx);
-        self.shutdown_tx = Some(shutdown_tx);
-        self.config_options = initial_config_options;
+        self.session_command_tx = Some(session_command_tx);
+        self.events = Some(events.clone());
+        self.spawner = Some(spawner);
+
+        // Open a session for the first conversation
+        let session = match self.new_session().await {
+            Ok(session) => session,
+            Err(e) => return Err(self.connect_failed(&events, e)),
+        };
         self.status = AgentStatus::Connected;
-
-        let _ = app.emit(
-            "acp-update",
+        self.started_at = Some(Instant::now());
+        self.last_used = Instant::now();
+        self.emit(
+            &events,
+            None,
             AgentUpdate::StatusChange {
                 status: AgentStatus::Connected,
+                reason: None,
             },
         );
 
         // Emit initial config options if any
-        if !self.config_options.is_empty() {
-            let _ = app.emit("acp-config-options", &self.config_options);
+        if !session.config_options.is_empty() {
+            self.emit_config_options(None, &session.config_options);
         }
+        self.spare_session = Some(session);
 
-        // Spawn background tasks to forward updates and permissions to Tauri events
-        let app_for_updates = app.clone();
-        tokio::spawn(async move {
-            while let Some(update) = update_rx.recv().await {
-                let _ = app_for_updates.emit("acp-update", &update);
-            }
-        });
+        Ok(())
+    }
 
-        let app_for_perms = app;
-        tokio::spawn(async move {
-            while let Some(perm) = permission_rx.recv().await {
-                let _ = app_for_perms.emit("acp-permission-request", &perm);
-            }
+    fn connect_failed(&mut self, events: &Arc<dyn AgentEvents>, error: String) -> String {
+        self.close();
+        self.status = AgentStatus::Error;
+        self.last_error = Some(error.clone());
+        self.emit(
+            events,
+            None,
+            AgentUpdate::StatusChange {
+                status: AgentStatus::Error,
+                reason: Some(error.clone()),
+            },
+        );
+        error
+    }
+
+    /// Emit an update about the whole agent, or about the turn of a conversation.
+    fn emit(
+        &self,
+        events: &Arc<dyn AgentEvents>,
+        session: Option<(&SessionId, Option<&str>)>,
+        update: AgentUpdate,
+    ) {
+        events.update(&AgentUpdateEvent {
+            agent_id: self.agent_id.clone().unwrap_or_default(),
+            session_id: session.map(|(id, _)| id.to_string()),
+            conversation_id: session.and_then(|(_, conversation)| conversation.map(String::from)),
+            update,
         });
+    }
 
-        Ok(())
+    fn emit_config_options(
+        &self,
+        conversation_id: Option<&str>,
+        options: &[SessionConfigOptionInfo],
+    ) {
+        if let Some(events) = &self.events {
+            events.config_options(&ConfigOptionsEvent {
+                agent_id: self.agent_id.clone().unwrap_or_default(),
+                conversation_id: conversation_id.map(String::from),
+                options: options.to_vec(),
+            });
+        }
     }
 
-    pub async fn disconnect(&mut self) -> Result<(), String> {
+    /// Stop the connection thread and forget its sessions.
+    fn close(&mut self) {
         // Signal the connection thread to shut down
         if let Some(tx) = self.shutdown_tx.take() {
             let _ = tx.send(());
         }
 
-        self.prompt_tx.take();
-        self.cancel_tx.take();
         self.permission_resolve_tx.take();
         self.config_option_tx.take();
-        self.session_id.take();
-        self.config_options.clear();
+        self.session_command_tx.take();
+        self.sessions.clear();
+        self.spare_session.take();
+        self.started_at = None;
+    }
+
+    pub async fn disconnect(&mut self) -> Result<(), String> {
+        self.close();
+        self.events.take();
+        self.spawner.take();
+        self.agent_id.take();
+        self.restart_count = 0;
+        self.restart_attempts = 0;
+        self.last_error = None;
 
         self.status = AgentStatus::Disconnected;
         Ok(())
     }
 
+    /// How long the agent has had nothing to do; `None` while it runs a turn or
+    /// isn't connected.
+    pub fn idle_for(&self) -> Option<Duration> {
+        (self.status == AgentStatus::Connected && !self.turn_active())
+            .then(|| self.last_used.elapsed())
+    }
+
+    /// Shut down an agent that went unused. It is started again, with the same
+    /// configuration, when it is next needed.
+    pub fn stop_idle(&mut self) {
+        if self.status != AgentStatus::Connected {
+            return;
+        }
+        self.close();
+        self.status = AgentStatus::Idle;
+        if let Some(events) = &self.events {
+            self.emit(
+                events,
+                None,
+                AgentUpdate::StatusChange {
+                    status: AgentStatus::Idle,
+                    reason: None,
+                },
+            );
+        }
+    }
+
+    /// Start an agent that was shut down while idle.
+    async fn wake(&mut self) -> Result<(), String> {
+        self.last_used = Instant::now();
+        if self.status != AgentStatus::Idle {
+            return Ok(());
+        }
+        let (Some(events), Some(spawner)) = (self.events.clone(), self.spawner.clone()) else {
+            return Err("Not connected to agent".to_string());
+        };
+        self.connect_with(events, spawner).await
+    }
+
+    /// Send a prompt to the agent and save the turn to a conversation: the given one,
+    /// or a new one titled after the query. Returns the conversation's ID.
     #[allow(clippy::too_many_arguments)]
     pub async fn prompt(
         &mut self,
@@ -380,9 +664,39 @@ impl AcpManager {
         recent_conversations: &[(Conversation, Vec<ConversationMessage>)],
         launch_context: &crate::context::LaunchContext,
         slash_commands: &[SlashCommand],
-    ) -> Result<(), String> {
-        let session_id = self.session_id.clone().ok_or("Not connected to agent")?;
-        let prompt_tx = self.prompt_tx.as_ref().ok_or("Not connected to agent")?;
+        conversation_id: Option<String>,
+    ) -> Result<String, String> {
+        self.wake().await?;
+        if self.session_command_tx.is_none() {
+            return Err("Not connected to agent".to_string());
+        }
+
+        let db = Database::new()?;
+        let conversation_id = match conversation_id {
+            Some(id) => db.get_conversation(&id)?.id,
+            None => {
+                db.create_conversation(NewConversation {
+                    title: conversation_title(query),
+                })?
+                .id
+            }
+        };
+        self.attach_conversation(&conversation_id).await?;
+        let reseed = self
+            .sessions
+            .get_mut(&conversation_id)
+            .is_some_and(|session| std::mem::take(&mut session.reseed_pending));
+        let earlier = if reseed {
+            condensed_transcript(&db.get_conversation_messages(&conversation_id)?)
+        } else {
+            String::new()
+        };
+        let user_message = db.add_conversation_message(NewConversationMessage {
+            conversation_id: conversation_id.clone(),
+            role: "user".to_string(),
+            content: query.to_string(),
+            incomplete: true,
+        })?;
 
         let prompt_text = build_agent_prompt(
             query,
@@ -394,22 +708,344 @@ impl AcpManager {
             launch_context,
             slash_commands,
         );
-        let content = vec![ContentBlock::Text(TextContent::new(prompt_text))];
+        self.send_prompt(
+            vec![ContentBlock::Text(TextContent::new(earlier + &prompt_text))],
+            launch_context,
+            Some(conversation_id.clone()),
+            Some(user_message.id),
+        )?;
+        Ok(conversation_id)
+    }
 
-        prompt_tx
+    /// Queue a prompt in the session of `conversation_id`, or in the spare session
+    /// without one. The turn is saved to the conversation after the already stored
+    /// user message.
+    fn send_prompt(
+        &mut self,
+        content: Vec<ContentBlock>,
+        launch_context: &crate::context::LaunchContext,
+        conversation_id: Option<String>,
+        user_message_id: Option<String>,
+    ) -> Result<(), String> {
+        let session = self
+            .session_mut(conversation_id.as_deref())
+            .ok_or("Not connected to agent")?;
+        session
+            .prompt_tx
             .send(PromptCommand::Prompt {
-                session_id,
                 content,
+                source_app: launch_context.source_process_name.clone(),
+                source_window: launch_context.source_window_title.clone(),
+                conversation_id,
+                user_message_id,
             })
-            .map_err(|_| "Failed to send prompt to agent".to_string())
+            .map_err(|_| "Failed to send prompt to agent".to_string())?;
+        session.turns_started += 1;
+        self.last_used = Instant::now();
+        Ok(())
+    }
+
+    fn session(&self, conversation_id: Option<&str>) -> Option<&Session> {
+        match conversation_id {
+            Some(id) => self.sessions.get(id),
+            None => self.spare_session.as_ref(),
+        }
+    }
+
+    fn session_mut(&mut self, conversation_id: Option<&str>) -> Option<&mut Session> {
+        match conversation_id {
+            Some(id) => self.sessions.get_mut(id),
+            None => self.spare_session.as_mut(),
+        }
+    }
+
+    /// Give `conversation_id` a session, so the agent continues where it left off.
+    /// Used when a conversation is opened and before each prompt.
+    pub async fn open_conversation(&mut self, conversation_id: &str) -> Result<(), String> {
+        self.wake().await?;
+        Database::new()?.get_conversation(conversation_id)?;
+        self.attach_conversation(conversation_id).await
+    }
+
+    /// Open a session for `conversation_id` unless it has one: resume the session it
+    /// was held in when the agent supports `session/load`, or start a new one and have
+    /// the next prompt replay the conversation so far.
+    async fn attach_conversation(&mut self, conversation_id: &str) -> Result<(), String> {
+        if self.sessions.contains_key(conversation_id) {
+            return Ok(());
+        }
+        // Owned, not borrowed from the caller: a `&Database` held across the awaits
+        // below would keep the future from being `Send`
+        let db = Database::new()?;
+        let agent_id = self.agent_id.clone().unwrap_or_default();
+
+        let stored = db
+            .conversation_agent_session(conversation_id)?
+            .filter(|(stored_agent, _)| *stored_agent == agent_id);
+        if let Some((_, session_id)) = stored.filter(|_| self.load_session_supported) {
+            match self.load_session(SessionId::new(session_id)).await {
+                Ok((session, replayed)) => {
+                    let stored = db.get_conversation_messages(conversation_id)?;
+                    apply_replay(&db, conversation_id, &stored, &replayed);
+                    self.emit_config_options(Some(conversation_id), &session.config_options);
+                    self.sessions.insert(conversation_id.to_string(), session);
+                    return Ok(());
+                }
+                Err(e) => eprintln!("{e}; starting a new session"),
+            }
+        }
+
+        let mut session = match self.spare_session.take() {
+            Some(session) => session,
+            None => self.new_session().await?,
+        };
+        db.set_conversation_agent_session(conversation_id, &agent_id, &session.id.to_string())?;
+        session.reseed_pending = !db.get_conversation_messages(conversation_id)?.is_empty();
+        self.emit_config_options(Some(conversation_id), &session.config_options);
+        self.sessions.insert(conversation_id.to_string(), session);
+        Ok(())
+    }
+
+    async fn new_session(&mut self) -> Result<Session, String> {
+        let tx = self
+            .session_command_tx
+            .as_ref()
+            .ok_or("Not connected to agent")?;
+        let (reply, reply_rx) = oneshot::channel();
+        tx.send(SessionCommand::New { reply })
+            .map_err(|_| "Failed to start a new session".to_string())?;
+        let (session_id, worker, options) = reply_rx
+            .await
+            .map_err(|_| "Connection thread died".to_string())??;
+        Ok(Session::new(session_id, worker, options))
+    }
+
+    async fn load_session(
+        &mut self,
+        session_id: SessionId,
+    ) -> Result<(Session, Vec<ReplayedMessage>), String> {
+        let tx = self
+            .session_command_tx
+            .as_ref()
+            .ok_or("Not connected to agent")?;
+        let (reply, reply_rx) = oneshot::channel();
+        tx.send(SessionCommand::Load {
+            session_id: session_id.clone(),
+            reply,
+        })
+        .map_err(|_| "Failed to load the session".to_string())?;
+        let (replayed, worker, options) = reply_rx
+            .await
+            .map_err(|_| "Connection thread died".to_string())??;
+        Ok((Session::new(session_id, worker, options), replayed))
+    }
+
+    /// Whether a prompt has been sent, in any session, that the agent has not
+    /// finished yet.
+    pub fn turn_active(&self) -> bool {
+        self.sessions
+            .values()
+            .chain(&self.spare_session)
+            .any(Session::turn_active)
+    }
+
+    /// Cancel the running (and any queued) turn of a conversation: its pending
+    /// permission requests are answered with `Cancelled` and the agent is sent
+    /// `session/cancel`. The turn ends with a `TurnComplete` whose stop reason is
+    /// "cancelled". If the agent has not ended it within the cancel timeout, its
+    /// process is killed and restarted; the turns of other conversations then end
+    /// with an error.
+    pub async fn cancel(
+        manager: &Arc<Mutex<Self>>,
+        conversation_id: Option<&str>,
+    ) -> Result<(), String> {
+        let (through, mut finished, timeout, connection) = {
+            let mut this = manager.lock().await;
+            if this.status != AgentStatus::Connected {
+                return Err("Not connected to agent".to_string());
+            }
+            let timeout = this.cancel_timeout;
+            let connection = this.connection;
+            // A conversation without a session has nothing running
+            let Some(session) = this.session_mut(conversation_id) else {
+                return Ok(());
+            };
+            session
+                .cancel_tx
+                .send(session.turns_started)
+                .map_err(|_| "Failed to send cancel to agent".to_string())?;
+            (
+                session.turns_started,
+                session.turns_finished.clone(),
+                timeout,
+                connection,
+            )
+        };
+        if *finished.borrow() >= through {
+            return Ok(());
+        }
+
+        let manager = Arc::clone(manager);
+        let conversation_id = conversation_id.map(str::to_string);
+        tokio::spawn(async move {
+            let ended = tokio::time::timeout(timeout, finished.wait_for(|n| *n >= through))
+                .await
+                .is_ok();
+            // A closed channel means the session was replaced or closed meanwhile
+            if ended {
+                return;
+            }
+            let mut this = manager.lock().await;
+            // The turn may have ended while the lock was held, or the agent was
+            // restarted for another reason
+            if *finished.borrow() >= through || this.connection != connection {
+                return;
+            }
+            eprintln!("Agent did not stop after cancel; restarting it");
+            if let Err(e) = this.restart(conversation_id.as_deref()).await {
+                eprintln!("Failed to restart agent: {e}");
+            }
+        });
+        Ok(())
+    }
+
+    /// Kill the agent process and start it again because the turn of `cancelled` (a
+    /// conversation, or the spare session) did not stop. That turn ends as cancelled;
+    /// other running turns end with the restart as their error, since nobody cancelled
+    /// them. Conversations get a session again when they are next used.
+    async fn restart(&mut self, cancelled: Option<&str>) -> Result<(), String> {
+        let (Some(events), Some(spawner)) = (self.events.clone(), self.spawner.clone()) else {
+            return Err("Not connected to agent".to_string());
+        };
+        let reason = "Agent restarted because it did not stop after a cancel";
+        self.end_running_turns(&events, |conversation_id| {
+            if conversation_id == cancelled {
+                CANCELLED_STOP_REASON.to_string()
+            } else {
+                format!("Error: {reason}")
+            }
+        });
+        self.last_error = Some(reason.to_string());
+        self.close();
+        self.restart_count += 1;
+        self.connect_with(events, spawner).await
     }
 
-    pub async fn cancel(&mut self) -> Result<(), String> {
-        let cancel_tx = self.cancel_tx.as_ref().ok_or("Not connected to agent")?;
+    /// End the turns still running, before their sessions close, with the stop reason
+    /// `stop_reason` gives for their conversation (`None` for the spare session).
+    fn end_running_turns(
+        &self,
+        events: &Arc<dyn AgentEvents>,
+        stop_reason: impl Fn(Option<&str>) -> String,
+    ) {
+        let sessions = self
+            .sessions
+            .iter()
+            .map(|(conversation_id, session)| (session, Some(conversation_id.as_str())))
+            .chain(self.spare_session.iter().map(|session| (session, None)));
+        for (session, conversation_id) in sessions {
+            if session.turn_active() {
+                self.emit(
+                    events,
+                    Some((&session.id, conversation_id)),
+                    AgentUpdate::TurnComplete {
+                        stop_reason: stop_reason(conversation_id),
+                    },
+                );
+            }
+        }
+    }
 
-        cancel_tx
-            .send(())
-            .map_err(|_| "Failed to send cancel to agent".to_string())
+    /// Restart the agent whenever one of its connections dies, for as long as the
+    /// manager exists.
+    async fn supervise(
+        manager: std::sync::Weak<Mutex<Self>>,
+        mut exits: mpsc::UnboundedReceiver<(u64, String)>,
+    ) {
+        while let Some((connection, reason)) = exits.recv().await {
+            let Some(manager) = manager.upgrade() else {
+                return;
+            };
+            Self::recover(&manager, connection, reason).await;
+        }
+    }
+
+    /// Report that the agent died and start it again, waiting twice as long after
+    /// each restart that fails or dies soon after. Conversations that had a session
+    /// get one again.
+    async fn recover(manager: &Arc<Mutex<Self>>, mut connection: u64, reason: String) {
+        let conversations = {
+            let mut this = manager.lock().await;
+            // Closed on purpose, or replaced meanwhile
+            if this.connection != connection || this.status != AgentStatus::Connected {
+                return;
+            }
+            eprintln!("Agent stopped: {reason}");
+            this.crashed(reason)
+        };
+        loop {
+            let delay = {
+                let this = manager.lock().await;
+                if this.restart_attempts >= MAX_RESTART_ATTEMPTS {
+                    eprintln!("Agent keeps stopping; not restarting it again");
+                    return;
+                }
+                this.restart_backoff * 2u32.pow(this.restart_attempts)
+            };
+            tokio::time::sleep(delay).await;
+
+            let mut this = manager.lock().await;
+            // Disconnected or connected again meanwhile
+            if this.connection != connection || this.status != AgentStatus::Error {
+                return;
+            }
+            let (Some(events), Some(spawner)) = (this.events.clone(), this.spawner.clone()) else {
+                return;
+            };
+            this.restart_attempts += 1;
+            this.restart_count += 1;
+            if let Err(e) = this.connect_with(events, spawner).await {
+                eprintln!("Failed to restart agent: {e}");
+                connection = this.connection;
+                continue;
+            }
+            for conversation_id in &conversations {
+                if let Err(e) = this.open_conversation(conversation_id).await {
+                    eprintln!("Failed to resume conversation {conversation_id}: {e}");
+                }
+            }
+            return;
+        }
+    }
+
+    /// Mark the agent failed after its connection died, ending its running turns.
+    /// Returns the conversations that had a session.
+    fn crashed(&mut self, reason: String) -> Vec<String> {
+        if self
+            .started_at
+            .is_some_and(|started| started.elapsed() >= STABLE_UPTIME)
+        {
+            self.restart_attempts = 0;
+        }
+        let events = self.events.clone();
+        if let Some(events) = &events {
+            self.end_running_turns(events, |_| format!("Error: {reason}"));
+        }
+        let conversations = self.sessions.keys().cloned().collect();
+        self.close();
+        self.status = AgentStatus::Error;
+        self.last_error = Some(reason.clone());
+        if let Some(events) = &events {
+            self.emit(
+                events,
+                None,
+                AgentUpdate::StatusChange {
+                    status: AgentStatus::Error,
+                    reason: Some(reason),
+                },
+            );
+        }
+        conversations
     }
 
     pub async fn resolve_permission(
@@ -426,16 +1062,36 @@ impl AcpManager {
             .map_err(|_| "Failed to resolve permission".to_string())
     }
 
-    pub fn get_config_options(&self) -> Vec<SessionConfigOptionInfo> {
-        self.config_options.clone()
+    /// Config options of the conversation's session; a conversation without one
+    /// will get the spare session's.
+    pub fn get_config_options(
+        &self,
+        conversation_id: Option<&str>,
+    ) -> Vec<SessionConfigOptionInfo> {
+        self.session(conversation_id)
+            .or(self.spare_session.as_ref())
+            .map(|session| session.config_options.clone())
+            .unwrap_or_default()
     }
 
     pub async fn set_config_option(
         &mut self,
+        conversation_id: Option<&str>,
         config_id: &str,
         value: &str,
     ) -> Result<Vec<SessionConfigOptionInfo>, String> {
-        let session_id = self.session_id.clone().ok_or("Not connected to agent")?;
+        self.wake().await?;
+        match conversation_id {
+            Some(id) => self.attach_conversation(id).await?,
+            None if self.spare_session.is_none() => {
+                self.spare_session = Some(self.new_session().await?);
+            }
+            None => {}
+        }
+        let session_id = self
+            .session(conversation_id)
+            .map(|session| session.id.clone())
+            .ok_or("Not connected to agent")?;
         let tx = self
             .config_option_tx
             .as_ref()
@@ -455,11 +1111,154 @@ impl AcpManager {
             .await
             .map_err(|_| "Config option response channel closed".to_string())??;
 
-        self.config_options = updated.clone();
+        if let Some(session) = self.session_mut(conversation_id) {
+            session.config_options = updated.clone();
+        }
         Ok(updated)
     }
 }
 
+/// Start running the prompts of a session on the connection thread. The worker ends
+/// when the manager drops the session.
+fn spawn_session_worker(shared: &ConnectionShared, session_id: SessionId) -> SessionWorker {
+    let (prompt_tx, mut prompt_rx) = mpsc::unbounded_channel::<PromptCommand>();
+    let (cancel_tx, mut cancel_rx) = mpsc::unbounded_channel::<u64>();
+    let (turns_finished_tx, turns_finished) = watch::channel(0u64);
+    let key = session_id.to_string();
+
+    // Turn bookkeeping shared by the prompt and cancel handlers: the turn being run
+    // (0 when idle) and the last one to cancel
+    let running_turn = Rc::new(Cell::new(0u64));
+    let cancelled_through = Rc::new(Cell::new(0u64));
+
+    // Handle prompts from the Tauri thread
+    let prompts = shared.clone();
+    let session_for_prompts = session_id.clone();
+    let running_for_prompts = running_turn.clone();
+    let cancelled_for_prompts = cancelled_through.clone();
+    tokio::task::spawn_local(async move {
+        let key = session_for_prompts.to_string();
+        let mut turn = 0u64;
+        while let Some(cmd) = prompt_rx.recv().await {
+            match cmd {
+                PromptCommand::Prompt {
+                    content,
+                    source_app,
+                    source_window,
+                    conversation_id,
+                    user_message_id,
+                } => {
+                    turn += 1;
+                    prompts.turns.borrow_mut().insert(
+                        key.clone(),
+                        SessionTurn {
+                            conversation_id: conversation_id.clone(),
+                            source_app,
+                            source_window,
+                            transcript: conversation_id
+                                .clone()
+                                .map(|id| TurnTranscript::new(id, user_message_id)),
+                        },
+                    );
+                    // Cancelled while still queued: don't start it
+                    let stop_reason = if turn <= cancelled_for_prompts.get() {
+                        CANCELLED_STOP_REASON.to_string()
+                    } else {
+                        running_for_prompts.set(turn);
+                        let result = prompts
+                            .connection
+                            .prompt(agent_client_protocol::PromptRequest::new(
+                                session_for_prompts.clone(),
+                                content,
+                            ))
+                            .await;
+                        running_for_prompts.set(0);
+
+                        match result {
+                            // Agents may still finish or fail the turn after a cancel
+                            _ if turn <= cancelled_for_prompts.get() => {
+                                CANCELLED_STOP_REASON.to_string()
+                            }
+                            Ok(resp) => stop_reason_name(resp.stop_reason),
+                            Err(e) => format!("Error: {e:?}"),
+                        }
+                    };
+                    let finished = prompts
+                        .turns
+                        .borrow_mut()
+                        .get_mut(&key)
+                        .and_then(|turn| turn.transcript.take());
+                    if let Some(finished) = finished {
+                        let complete = stop_reason != CANCELLED_STOP_REASON
+                            && !stop_reason.starts_with("Error: ");
+                        finished.finish(complete);
+                    }
+                    UpdateSender::new(
+                        prompts.update_tx.clone(),
+                        prompts.agent_id.clone(),
+                        Some(key.clone()),
+                        conversation_id,
+                    )
+                    .send(AgentUpdate::TurnComplete { stop_reason });
+                    turns_finished_tx.send_replace(turn);
+                }
+            }
+        }
+        prompts.turns.borrow_mut().remove(&key);
+    });
+
+    // Handle cancels
+    let pending_perms = shared.pending_permissions.clone();
+    let conn_for_cancel = shared.connection.clone();
+    tokio::task::spawn_local(async move {
+        while let Some(through) = cancel_rx.recv().await {
+            cancelled_through.set(cancelled_through.get().max(through));
+
+            // Cancel the session's pending permissions
+            let cancelled: Vec<String> = pending_perms
+                .borrow()
+                .iter()
+                .filter(|(_, (session, _))| *session == key)
+                .map(|(request_id, _)| request_id.clone())
+                .collect();
+            for request_id in cancelled {
+                let pending = pending_perms.borrow_mut().remove(&request_id);
+                if let Some((_, responder)) = pending {
+                    let _ = responder.send(RequestPermissionOutcome::Cancelled);
+                }
+            }
+
+            // Ask the agent to stop the running turn
+            let running = running_turn.get();
+            if running != 0 && running <= through {
+                if let Err(e) = conn_for_cancel
+                    .cancel(CancelNotification::new(session_id.clone()))
+                    .await
+                {
+                    eprintln!("Failed to send cancel to agent: {e:?}");
+                }
+            }
+        }
+    });
+
+    SessionWorker {
+        prompt_tx,
+        cancel_tx,
+        turns_finished,
+    }
+}
+
+/// Wait for the agent process to exit; never finishes for agents without one.
+async fn process_exit(child: Option<&mut tokio::process::Child>) -> String {
+    let Some(child) = child else {
+        return std::future::pending().await;
+    };
+    match child.wait().await {
+        Ok(status) => format!("Agent process exited ({status})"),
+        Err(e) => format!("Failed to wait for the agent process: {e}"),
+    }
+}
+
 /// Convert an ACP SessionConfigOption to our serializable info type.
 fn convert_config_option(opt: &SessionConfigOption) -> SessionConfigOptionInfo {
     let category = opt.category.as_ref().map(|c| format!("{:?}", c));
@@ -546,6 +1345,154 @@ fn resolve_cli_path() -> String {
     "golaunch-cli".to_string()
 }
 
+/// Save what a resumed session replayed that the conversation is missing.
+fn apply_replay(
+    db: &Database,
+    conversation_id: &str,
+    stored: &[ConversationMessage],
+    replayed: &[ReplayedMessage],
+) {
+    for change in transcript::replay_changes(stored, replayed) {
+        let result = match change {
+            ReplayChange::Update { id, content } => db.update_conversation_message(&id, &content),
+            ReplayChange::Add {
+                role,
+                content,
+                before,
+            } => {
+                let message = NewConversationMessage {
+                    conversation_id: conversation_id.to_string(),
+                    role,
+                    content,
+                    incomplete: false,
+                };
+                match before {
+                    Some(id) => db.insert_conversation_message_before(message, &id),
+                    None => db.add_conversation_message(message),
+                }
+                .map(|_| ())
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to save replayed message: {e}");
+        }
+    }
+}
+
+/// The last messages of a conversation, shortened, for an agent that starts it over
+/// in a new session. Empty when there is nothing to replay.
+fn condensed_transcript(messages: &[ConversationMessage]) -> String {
+    let messages: Vec<&ConversationMessage> = messages
+        .iter()
+        .filter(|m| (m.role == "user" || m.role == "assistant") && !m.content.trim().is_empty())
+        .collect();
+    if messages.is_empty() {
+        return String::new();
+    }
+    let mut p = String::from("## Earlier in This Conversation\n");
+    p.push_str("You lost the session this conversation was held in. So far:\n\n");
+    for msg in &messages[messages.len().saturating_sub(RESEED_MESSAGES)..] {
+        let role = if msg.role == "user" {
+            "User"
+        } else {
+            "Assistant"
+        };
+        let content = msg.content.trim();
+        let content = match content.char_indices().nth(RESEED_MESSAGE_CHARS) {
+            Some((end, _)) => format!("{}...", &content[..end]),
+            None => content.to_string(),
+        };
+        p.push_str(&format!("{role}: {content}\n"));
+    }
+    p.push('\n');
+    p
+}
+
+/// Working directory of agent sessions.
+fn agent_cwd() -> std::path::PathBuf {
+    std::env::current_dir().unwrap_or_else(|_| "/".into())
+}
+
+fn config_option_infos(options: Option<&[SessionConfigOption]>) -> Vec<SessionConfigOptionInfo> {
+    options
+        .map(|options| options.iter().map(convert_config_option).collect())
+        .unwrap_or_default()
+}
+
+/// Title of a conversation started by `query`.
+pub(crate) fn conversation_title(query: &str) -> String {
+    let query = query.trim();
+    match query.char_indices().nth(50) {
+        Some((end, _)) => format!("{}...", &query[..end]),
+        None => query.to_string(),
+    }
+}
+
+/// The protocol name of a stop reason ("end_turn", "cancelled", ...).
+fn stop_reason_name(reason: StopReason) -> String {
+    serde_json::to_value(reason)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_else(|| format!("{reason:?}"))
+}
+
+/// A spawner that runs the configured agent binary with piped stdio.
+fn process_spawner(config: &AgentConfig) -> Result<AgentSpawner, String> {
+    if config.binary_path.is_empty() {
+        return Err("No binary path configured".to_string());
+    }
+
+    let args: Vec<String> = config.args.split_whitespace().map(String::from).collect();
+
+    // Resolve the binary path: check if it's on PATH, otherwise look in
+    // our install directory (AppData/Local/GoLaunch/agents/<agent_id>/)
+    let resolved_binary = resolve_binary_path(&config.binary_path, &config.agent_id);
+
+    // Parse env vars from "KEY=VALUE,KEY2=VALUE2"
+    let env: Vec<(String, String)> = config
+        .env
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+
+    Ok(Arc::new(move || {
+        // On Windows, commands like "npx" are actually .cmd batch scripts
+        // that cannot be spawned directly. We need to run them through cmd.exe.
+        #[cfg(target_os = "windows")]
+        let mut cmd = {
+            let mut c = tokio::process::Command::new("cmd");
+            let mut cmd_args = vec!["/C".to_string(), resolved_binary.clone()];
+            cmd_args.extend(args.clone());
+            c.args(&cmd_args);
+            c
+        };
+        #[cfg(not(target_os = "windows"))]
+        let mut cmd = {
+            let mut c = tokio::process::Command::new(&resolved_binary);
+            c.args(&args);
+            c
+        };
+
+        cmd.stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .envs(env.iter().map(|(k, v)| (k, v)));
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("Failed to spawn agent process: {e}"))?;
+
+        let stdin = child.stdin.take().ok_or("Failed to get agent stdin")?;
+        let stdout = child.stdout.take().ok_or("Failed to get agent stdout")?;
+        Ok(AgentIo {
+            stdin: Box::new(stdin),
+            stdout: Box::new(stdout),
+            child: Some(child),
+        })
+    }))
+}
+
 /// Resolve a binary path for agent spawning.
 /// If the binary is on PATH (or is "npx"), use it directly.
 /// Otherwise check the GoLaunch agents install directory.
@@ -584,6 +1531,7 @@ fn resolve_binary_path(binary: &str, agent_id: &str) -> String {
 
 /// Build a structured prompt for the ACP agent that includes system instructions,
 /// CLI reference, user context, and the query.
+#[allow(clippy::too_many_arguments)]
 fn build_agent_prompt(
     query: &str,
     context_items: &[Item],
@@ -668,9 +1616,11 @@ fn build_agent_prompt(
          ```bash\n\
          # Add a new launcher command\n\
          \"{cli}\" add --title \"Title\" --action-value \"the_command\" --action-type command --category \"Category\"\n\
-         # Optional flags: --subtitle \"desc\" --icon \"emoji\" --tags \"t1,t2\"\n\n\
+         # Optional flags: --subtitle \"desc\" --icon \"emoji\" --tags \"t1,t2\" --hotkey \"Ctrl+Alt+T\"\n\n\
          # Add a URL shortcut\n\
          \"{cli}\" add --title \"Google\" --action-value \"https://google.com\" --action-type url --category \"Web\"\n\n\
+         # Add a text snippet (pasted into the focused app; {{{{ and }}}} are literal braces)\n\
+         \"{cli}\" add --title \"sig\" --action-value \"Hi {{1}}, {{cursor}} (sent {{date}})\" --action-type snippet --category \"Snippets\"\n\n\
          # List all commands (use --json for structured output)\n\
          \"{cli}\" list\n\
          \"{cli}\" list --category \"Development\" --json\n\n\
@@ -681,7 +1631,8 @@ fn build_agent_prompt(
          # Remove a command by ID\n\
          \"{cli}\" remove <id>\n\
          ```\n\
-         Action types: `command` (shell), `url` (browser), `script` (script file)\n\n\
+         Action types: `command` (shell), `url` (browser), `script` (script file), `snippet` (text template with \
+         `{{date}}`, `{{time}}`, `{{clipboard}}`, `{{selection}}`, `{{cursor}}`, `{{args}}`, `{{1}}`, `{{2}}`, ...)\n\n\
          ### Memory\n\
          ```bash\n\
          # Store a preference or fact\n\
@@ -719,6 +1670,52 @@ fn build_agent_prompt(
          \"{cli}\" conversations context --limit 5\n\
          ```\n\
          Use conversation commands to recall earlier discussions with the user.\n\n\
+         ### Clipboard History\n\
+         Only available when the user has enabled clipboard history. It can hold passwords\n\
+         and tokens, so only look at it when the user refers to something they copied.\n\
+         ```bash\n\
+         \"{cli}\" clipboard list --limit 20 --json\n\
+         \"{cli}\" clipboard search \"query\" --json\n\
+         \"{cli}\" clipboard copy <id>          # put an entry back on the clipboard\n\
+         ```\n\n\
+         ### Files\n\
+         Search the user's indexed files by name, or list a directory by path prefix.\n\
+         ```bash\n\
+         \"{cli}\" files search \"query\" --limit 20 --json\n\
+         \"{cli}\" files search ~/projects --json\n\
+         ```\n\n\
+         ### Workflows\n\
+         A `workflow` item runs a JSON list of steps in order. A step is `{{\"item\": \"<id or title>\"}}` or an inline\n\
+         `{{\"type\": \"command\", \"value\": \"...\"}}` (url, command, script, snippet, copy), with optional `\"delay_ms\"`\n\
+         and `\"if\"` (success (default), failure, always, \"exit_code == N\", \"exit_code != N\") checked against the previous step.\n\
+         Inline values can use `{{output}}` and `{{exit_code}}` of the previous step and `{{output:N}}` of step N.\n\
+         ```bash\n\
+         \"{cli}\" add --title \"Deploy\" --action-type workflow --action-value '[{{\"item\": \"Build\"}}, {{\"type\": \"url\", \"value\": \"https://dash.example.com\"}}]'\n\
+         ```\n\n\
+         ### Running Processes\n\
+         Commands started from the launcher keep running in the background. List, tail or stop them:\n\
+         ```bash\n\
+         \"{cli}\" ps --json\n\
+         \"{cli}\" ps --tail <pid> --lines 50\n\
+         \"{cli}\" kill <pid>\n\
+         ```\n\n\
+         ### Schedules\n\
+         Items and slash commands can run on a schedule: cron (\"0 9 * * 1-5\"), \"every weekday 09:00\", \"every 15 minutes\",\n\
+         \"hourly\", \"on start\" or \"on change <table>\" (items, slash_commands, memory, conversations, clipboard_history).\n\
+         ```bash\n\
+         \"{cli}\" schedule add --item <item-id> --when \"every weekday 09:00\" --missed run-once\n\
+         \"{cli}\" schedule add --slash-command <name> --when \"hourly\"\n\
+         \"{cli}\" schedule list --json\n\
+         \"{cli}\" schedule rm <schedule-id>\n\
+         ```\n\n\
+         ### Item Actions\n\
+         Items can have extra actions besides their primary one, each with an optional launcher shortcut.\n\
+         Types: url, command, script, snippet, copy. `{{value}}` in the value is replaced by the item's own value.\n\
+         ```bash\n\
+         \"{cli}\" action add <item-id> --label \"Copy URL\" --type copy --shortcut \"Ctrl+Enter\"\n\
+         \"{cli}\" action list <item-id> --json\n\
+         \"{cli}\" action rm <item-id> \"Copy URL\"\n\
+         ```\n\n\
          ### Slash Commands\n\
          Slash commands are user-defined scripts invoked with `/name args...` from the launcher.\n\
          When the user query starts with `/`, it is ALWAYS a slash command request — never treat it as a regular query.\n\n\
@@ -865,7 +1862,8 @@ fn build_agent_prompt(
     // ── Launch context ──
     let has_context = launch_context.selected_text.is_some()
         || launch_context.clipboard_text.is_some()
-        || launch_context.source_window_title.is_some();
+        || launch_context.source_window_title.is_some()
+        || !launch_context.sections.is_empty();
 
     if has_context {
         p.push_str("## Current Context\n");
@@ -892,6 +1890,12 @@ fn build_agent_prompt(
             };
             p.push_str(&format!("Clipboard contents:\n```\n{}\n```\n", truncated));
         }
+        for section in &launch_context.sections {
+            p.push_str(&format!("{}:\n", section.label));
+            for field in &section.fields {
+                p.push_str(&format!("  {}: {}\n", field.key, field.value));
+            }
+        }
         p.push('\n');
     }
 
@@ -900,3 +1904,467 @@ fn build_agent_prompt(
 
     p
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex as StdMutex;
+
+    use agent_client_protocol::{
+        AgentSideConnection, AuthenticateRequest, AuthenticateResponse, InitializeResponse,
+        NewSessionResponse, PromptRequest, PromptResponse,
+    };
+    use tokio::sync::Notify;
+
+    /// Counters shared between a test and the mock agents it starts.
+    #[derive(Default)]
+    struct MockStats {
+        spawned: AtomicUsize,
+        sessions: AtomicUsize,
+        prompts: AtomicUsize,
+        cancels: AtomicUsize,
+        /// Stops the running agent, as if its process died.
+        crash: Notify,
+    }
+
+    /// An agent whose prompts never finish on their own. With `honours_cancel` a
+    /// `session/cancel` ends the turn; without it the turn hangs forever.
+    struct MockAgent {
+        stats: Arc<MockStats>,
+        honours_cancel: bool,
+        /// Signalled by a cancel of the session.
+        cancelled: std::cell::RefCell<HashMap<String, Rc<Notify>>>,
+    }
+
+    impl MockAgent {
+        fn cancelled(&self, session_id: &SessionId) -> Rc<Notify> {
+            self.cancelled
+                .borrow_mut()
+                .entry(session_id.to_string())
+                .or_default()
+                .clone()
+        }
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl Agent for MockAgent {
+        async fn initialize(
+            &self,
+            args: InitializeRequest,
+        ) -> agent_client_protocol::Result<InitializeResponse> {
+            Ok(InitializeResponse::new(args.protocol_version))
+        }
+
+        async fn authenticate(
+            &self,
+            _args: AuthenticateRequest,
+        ) -> agent_client_protocol::Result<AuthenticateResponse> {
+            Ok(AuthenticateResponse::new())
+        }
+
+        async fn new_session(
+            &self,
+            _args: NewSessionRequest,
+        ) -> agent_client_protocol::Result<NewSessionResponse> {
+            let n = self.stats.sessions.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(NewSessionResponse::new(format!("mock-session-{n}")))
+        }
+
+        async fn prompt(
+            &self,
+            args: PromptRequest,
+        ) -> agent_client_protocol::Result<PromptResponse> {
+            self.stats.prompts.fetch_add(1, Ordering::SeqCst);
+            if self.honours_cancel {
+                self.cancelled(&args.session_id).notified().await;
+            } else {
+                std::future::pending::<()>().await;
+            }
+            Ok(PromptResponse::new(StopReason::Cancelled))
+        }
+
+        async fn cancel(&self, args: CancelNotification) -> agent_client_protocol::Result<()> {
+            self.stats.cancels.fetch_add(1, Ordering::SeqCst);
+            self.cancelled(&args.session_id).notify_one();
+            Ok(())
+        }
+    }
+
+    /// Runs a `MockAgent` on its own thread, connected through in-memory pipes.
+    fn mock_spawner(stats: Arc<MockStats>, honours_cancel: bool) -> AgentSpawner {
+        Arc::new(move || {
+            stats.spawned.fetch_add(1, Ordering::SeqCst);
+            let (client_stdin, agent_stdin) = tokio::io::duplex(64 * 1024);
+            let (agent_stdout, client_stdout) = tokio::io::duplex(64 * 1024);
+            let stats = Arc::clone(&stats);
+            let stats_for_crash = Arc::clone(&stats);
+            std::thread::spawn(move || {
+                let rt = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap();
+                let local = tokio::task::LocalSet::new();
+                local.block_on(&rt, async move {
+                    let agent = MockAgent {
+                        stats,
+                        honours_cancel,
+                        cancelled: Default::default(),
+                    };
+                    let (_connection, io_future) = AgentSideConnection::new(
+                        agent,
+                        tokio_util::compat::TokioAsyncWriteCompatExt::compat_write(agent_stdout),
+                        tokio_util::compat::TokioAsyncReadCompatExt::compat(agent_stdin),
+                        |fut| {
+                            tokio::task::spawn_local(fut);
+                        },
+                    );
+                    // Ends when the client closes the pipes, or on a crash
+                    tokio::select! {
+                        _ = io_future => {}
+                        _ = stats_for_crash.crash.notified() => {}
+                    }
+                });
+            });
+            Ok(AgentIo {
+                stdin: Box::new(client_stdin),
+                stdout: Box::new(client_stdout),
+                child: None,
+            })
+        })
+    }
+
+    #[derive(Default)]
+    struct RecordedEvents(StdMutex<Vec<AgentUpdateEvent>>);
+
+    impl AgentEvents for RecordedEvents {
+        fn update(&self, update: &AgentUpdateEvent) {
+            self.0.lock().unwrap().push(update.clone());
+        }
+
+        fn permission_request(&self, _request: &PermissionRequest) {}
+
+        fn config_options(&self, _options: &ConfigOptionsEvent) {}
+    }
+
+    impl RecordedEvents {
+        fn stop_reasons(&self) -> Vec<String> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .filter_map(|event| match &event.update {
+                    AgentUpdate::TurnComplete { stop_reason } => Some(stop_reason.clone()),
+                    _ => None,
+                })
+                .collect()
+        }
+    }
+
+    async fn wait_until(what: &str, condition: impl Fn() -> bool) {
+        for _ in 0..200 {
+            if condition() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("timed out waiting until {what}");
+    }
+
+    async fn connected_manager(
+        stats: &Arc<MockStats>,
+        honours_cancel: bool,
+    ) -> (Arc<Mutex<AcpManager>>, Arc<RecordedEvents>) {
+        let events = Arc::new(RecordedEvents::default());
+        let mut manager = AcpManager::new();
+        manager.cancel_timeout = Duration::from_millis(300);
+        manager
+            .connect_with(events.clone(), mock_spawner(stats.clone(), honours_cancel))
+            .await
+            .unwrap();
+        manager
+            .send_prompt(
+                vec![ContentBlock::Text(TextContent::new("hello"))],
+                &crate::context::LaunchContext::default(),
+                None,
+                None,
+            )
+            .unwrap();
+        (Arc::new(Mutex::new(manager)), events)
+    }
+
+    #[tokio::test]
+    async fn cancel_sends_session_cancel_and_ends_the_turn() {
+        let stats = Arc::new(MockStats::default());
+        let (manager, events) = connected_manager(&stats, true).await;
+        wait_until("the prompt reaches the agent", || {
+            stats.prompts.load(Ordering::SeqCst) == 1
+        })
+        .await;
+        assert!(manager.lock().await.turn_active());
+
+        AcpManager::cancel(&manager, None).await.unwrap();
+
+        wait_until("the turn completes", || !events.stop_reasons().is_empty()).await;
+        assert_eq!(events.stop_reasons(), vec![CANCELLED_STOP_REASON]);
+        assert_eq!(stats.cancels.load(Ordering::SeqCst), 1);
+        assert!(!manager.lock().await.turn_active());
+
+        // Well past the cancel timeout: the agent answered, so it is not restarted
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(stats.spawned.load(Ordering::SeqCst), 1);
+        assert_eq!(manager.lock().await.status(), AgentStatus::Connected);
+    }
+
+    #[tokio::test]
+    async fn agent_ignoring_cancel_is_restarted() {
+        let stats = Arc::new(MockStats::default());
+        let (manager, events) = connected_manager(&stats, false).await;
+        wait_until("the prompt reaches the agent", || {
+            stats.prompts.load(Ordering::SeqCst) == 1
+        })
+        .await;
+
+        AcpManager::cancel(&manager, None).await.unwrap();
+
+        wait_until("the agent is restarted", || {
+            stats.spawned.load(Ordering::SeqCst) == 2
+        })
+        .await;
+        wait_until("the turn completes", || !events.stop_reasons().is_empty()).await;
+        assert_eq!(events.stop_reasons(), vec![CANCELLED_STOP_REASON]);
+        assert_eq!(stats.cancels.load(Ordering::SeqCst), 1);
+
+        let manager = manager.lock().await;
+        assert_eq!(manager.status(), AgentStatus::Connected);
+        assert!(!manager.turn_active());
+    }
+
+    #[tokio::test]
+    async fn conversations_run_in_their_own_sessions() {
+        let stats = Arc::new(MockStats::default());
+        let events = Arc::new(RecordedEvents::default());
+        let mut manager = AcpManager::new();
+        manager
+            .connect_with(events.clone(), mock_spawner(stats.clone(), true))
+            .await
+            .unwrap();
+        for conversation_id in ["a", "b"] {
+            let session = manager.new_session().await.unwrap();
+            manager
+                .sessions
+                .insert(conversation_id.to_string(), session);
+            manager
+                .send_prompt(
+                    vec![ContentBlock::Text(TextContent::new("hello"))],
+                    &crate::context::LaunchContext::default(),
+                    Some(conversation_id.to_string()),
+                    None,
+                )
+                .unwrap();
+        }
+        let manager = Arc::new(Mutex::new(manager));
+        wait_until("both prompts reach the agent", || {
+            stats.prompts.load(Ordering::SeqCst) == 2
+        })
+        .await;
+
+        AcpManager::cancel(&manager, Some("a")).await.unwrap();
+
+        wait_until("the turn completes", || !events.stop_reasons().is_empty()).await;
+        let completed: Vec<Option<String>> = events
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| matches!(event.update, AgentUpdate::TurnComplete { .. }))
+            .map(|event| event.conversation_id.clone())
+            .collect();
+        assert_eq!(completed, vec![Some("a".to_string())]);
+        let manager = manager.lock().await;
+        assert!(!manager.sessions["a"].turn_active());
+        assert!(manager.sessions["b"].turn_active());
+        assert_ne!(manager.sessions["a"].id, manager.sessions["b"].id);
+    }
+
+    #[tokio::test]
+    async fn restart_after_cancel_fails_other_turns() {
+        let stats = Arc::new(MockStats::default());
+        let events = Arc::new(RecordedEvents::default());
+        let mut manager = AcpManager::new();
+        manager.cancel_timeout = Duration::from_millis(300);
+        manager
+            .connect_with(events.clone(), mock_spawner(stats.clone(), false))
+            .await
+            .unwrap();
+        for conversation_id in ["a", "b"] {
+            let session = manager.new_session().await.unwrap();
+            manager
+                .sessions
+                .insert(conversation_id.to_string(), session);
+            manager
+                .send_prompt(
+                    vec![ContentBlock::Text(TextContent::new("hello"))],
+                    &crate::context::LaunchContext::default(),
+                    Some(conversation_id.to_string()),
+                    None,
+                )
+                .unwrap();
+        }
+        let manager = Arc::new(Mutex::new(manager));
+        wait_until("both prompts reach the agent", || {
+            stats.prompts.load(Ordering::SeqCst) == 2
+        })
+        .await;
+
+        AcpManager::cancel(&manager, Some("a")).await.unwrap();
+
+        wait_until("the agent is restarted", || {
+            stats.spawned.load(Ordering::SeqCst) == 2
+        })
+        .await;
+        wait_until("both turns complete", || events.stop_reasons().len() == 2).await;
+        let mut completed: Vec<(Option<String>, String)> = events
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| match &event.update {
+                AgentUpdate::TurnComplete { stop_reason } => {
+                    Some((event.conversation_id.clone(), stop_reason.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        completed.sort();
+        assert_eq!(
+            completed[0],
+            (Some("a".to_string()), CANCELLED_STOP_REASON.to_string())
+        );
+        assert_eq!(completed[1].0.as_deref(), Some("b"));
+        assert!(completed[1].1.starts_with("Error: "), "{}", completed[1].1);
+        assert!(manager.lock().await.status_info().last_error.is_some());
+    }
+
+    #[tokio::test]
+    async fn idle_agent_is_started_again_when_needed() {
+        let stats = Arc::new(MockStats::default());
+        let events = Arc::new(RecordedEvents::default());
+        let mut manager = AcpManager::new();
+        manager
+            .connect_with(events.clone(), mock_spawner(stats.clone(), true))
+            .await
+            .unwrap();
+        assert!(manager.idle_for().is_some());
+
+        manager.stop_idle();
+        assert_eq!(manager.status(), AgentStatus::Idle);
+        assert_eq!(manager.idle_for(), None);
+
+        manager.wake().await.unwrap();
+        assert_eq!(manager.status(), AgentStatus::Connected);
+        assert_eq!(stats.spawned.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn dead_agent_is_restarted() {
+        let stats = Arc::new(MockStats::default());
+        let events = Arc::new(RecordedEvents::default());
+        let manager = AcpManager::shared();
+        {
+            let mut manager = manager.lock().await;
+            manager.restart_backoff = Duration::from_millis(50);
+            manager
+                .connect_with(events.clone(), mock_spawner(stats.clone(), true))
+                .await
+                .unwrap();
+            manager
+                .send_prompt(
+                    vec![ContentBlock::Text(TextContent::new("hello"))],
+                    &crate::context::LaunchContext::default(),
+                    None,
+                    None,
+                )
+                .unwrap();
+        }
+        wait_until("the prompt reaches the agent", || {
+            stats.prompts.load(Ordering::SeqCst) == 1
+        })
+        .await;
+
+        stats.crash.notify_waiters();
+
+        wait_until("the agent is restarted", || {
+            stats.spawned.load(Ordering::SeqCst) == 2
+        })
+        .await;
+        wait_until("the agent is connected again", || {
+            manager
+                .try_lock()
+                .is_ok_and(|manager| manager.status() == AgentStatus::Connected)
+        })
+        .await;
+        let stop_reasons = events.stop_reasons();
+        assert_eq!(stop_reasons.len(), 1);
+        assert!(stop_reasons[0].starts_with("Error: "));
+        assert!(events.0.lock().unwrap().iter().any(|event| matches!(
+            &event.update,
+            AgentUpdate::StatusChange {
+                status: AgentStatus::Error,
+                reason: Some(_),
+            }
+        )));
+
+        let info = manager.lock().await.status_info();
+        assert_eq!(info.restart_count, 1);
+        assert_eq!(
+            info.last_error.as_deref(),
+            Some("Agent closed its connection")
+        );
+        assert!(info.uptime_secs.is_some());
+    }
+
+    #[test]
+    fn condenses_earlier_messages() {
+        let message = |role: &str, content: &str| ConversationMessage {
+            id: String::new(),
+            conversation_id: String::new(),
+            role: role.to_string(),
+            content: content.to_string(),
+            incomplete: false,
+            created_at: String::new(),
+        };
+        assert_eq!(condensed_transcript(&[]), "");
+        let long = "x".repeat(RESEED_MESSAGE_CHARS + 10);
+        let transcript = condensed_transcript(&[
+            message("user", "list my items"),
+            message("thought", "looking"),
+            message("assistant", &long),
+        ]);
+        assert!(transcript.contains("User: list my items\n"));
+        assert!(!transcript.contains("looking"));
+        assert!(transcript.contains(&format!(
+            "Assistant: {}...\n",
+            &long[..RESEED_MESSAGE_CHARS]
+        )));
+    }
+
+    #[tokio::test]
+    async fn cancel_without_a_turn_does_nothing() {
+        let stats = Arc::new(MockStats::default());
+        let events = Arc::new(RecordedEvents::default());
+        let manager = Arc::new(Mutex::new(AcpManager::new()));
+        manager
+            .lock()
+            .await
+            .connect_with(events.clone(), mock_spawner(stats.clone(), true))
+            .await
+            .unwrap();
+
+        AcpManager::cancel(&manager, None).await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(events.stop_reasons().is_empty());
+        assert_eq!(stats.cancels.load(Ordering::SeqCst), 0);
+    }
+}
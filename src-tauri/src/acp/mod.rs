@@ -1,4 +1,8 @@
 pub mod client;
+pub mod files;
 pub mod manager;
+pub mod pool;
 pub mod registry;
+pub mod terminal;
+pub mod transcript;
 pub mod types;
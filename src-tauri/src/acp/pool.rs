@@ -0,0 +1,111 @@
+//! The agents GoLaunch keeps connected, one [`AcpManager`] per agent, and shutting
+//! down the ones nobody has used for a while.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use golaunch_core::Database;
+use tokio::sync::Mutex;
+
+use super::manager::AcpManager;
+
+/// Minutes an agent may go unused before it is shut down; 0 keeps agents running.
+pub const IDLE_TIMEOUT_SETTING: &str = "acp.idle_timeout_minutes";
+pub const DEFAULT_IDLE_TIMEOUT_MINUTES: u64 = 30;
+
+/// How often agents are checked for idleness.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+pub type SharedManager = Arc<Mutex<AcpManager>>;
+
+/// Connected agents by agent ID. Each has its own process and sessions.
+#[derive(Default)]
+pub struct AcpPool {
+    agents: HashMap<String, SharedManager>,
+    /// Agent for prompts that don't name one: the one connected last.
+    default_agent: Option<String>,
+}
+
+impl AcpPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The manager of `agent_id`, added when the agent has none yet.
+    pub fn manager(&mut self, agent_id: &str) -> SharedManager {
+        self.agents
+            .entry(agent_id.to_string())
+            .or_insert_with(AcpManager::shared)
+            .clone()
+    }
+
+    pub fn get(&self, agent_id: &str) -> Option<SharedManager> {
+        self.agents.get(agent_id).cloned()
+    }
+
+    pub fn set_default_agent(&mut self, agent_id: &str) {
+        self.default_agent = Some(agent_id.to_string());
+    }
+
+    /// The manager of `agent_id`, or of the default agent without one.
+    pub fn resolve(&self, agent_id: Option<&str>) -> Option<SharedManager> {
+        self.get(agent_id.or(self.default_agent.as_deref())?)
+    }
+
+    pub fn remove(&mut self, agent_id: &str) -> Option<SharedManager> {
+        if self.default_agent.as_deref() == Some(agent_id) {
+            self.default_agent = None;
+        }
+        self.agents.remove(agent_id)
+    }
+
+    pub fn drain(&mut self) -> Vec<SharedManager> {
+        self.default_agent = None;
+        self.agents.drain().map(|(_, manager)| manager).collect()
+    }
+
+    pub fn managers(&self) -> Vec<SharedManager> {
+        self.agents.values().cloned().collect()
+    }
+}
+
+/// The configured idle timeout; `None` when idle agents are kept running.
+pub fn idle_timeout(db: &Database) -> Result<Option<Duration>, String> {
+    let minutes = match db.get_setting(IDLE_TIMEOUT_SETTING)? {
+        Some(value) => value
+            .trim()
+            .parse::<u64>()
+            .unwrap_or(DEFAULT_IDLE_TIMEOUT_MINUTES),
+        None => DEFAULT_IDLE_TIMEOUT_MINUTES,
+    };
+    Ok((minutes > 0).then(|| Duration::from_secs(minutes * 60)))
+}
+
+/// Shut down every agent that has had nothing to do for `timeout`.
+pub async fn stop_idle_agents(pool: &Mutex<AcpPool>, timeout: Duration) {
+    let managers = pool.lock().await.managers();
+    for manager in managers {
+        // A manager that is locked is in use
+        let Ok(mut manager) = manager.try_lock() else {
+            continue;
+        };
+        if manager.idle_for().is_some_and(|idle| idle >= timeout) {
+            manager.stop_idle();
+        }
+    }
+}
+
+/// Check for idle agents until the app exits. The timeout is read on every check,
+/// so changing it needs no restart.
+pub async fn watch_idle_agents(pool: Arc<Mutex<AcpPool>>) {
+    let mut interval = tokio::time::interval(IDLE_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        match Database::new().and_then(|db| idle_timeout(&db)) {
+            Ok(Some(timeout)) => stop_idle_agents(&pool, timeout).await,
+            Ok(None) => {}
+            Err(e) => eprintln!("Failed to read the agent idle timeout: {e}"),
+        }
+    }
+}
@@ -0,0 +1,507 @@
+//! Terminals for the ACP `terminal/*` client methods: commands an agent runs in a
+//! child process without a PTY, with their output kept up to a byte limit and
+//! streamed to the UI as it arrives.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::rc::Rc;
+
+use agent_client_protocol::TerminalExitStatus;
+use golaunch_core::permissions::quote_word;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::{watch, Notify};
+
+use super::types::{AgentUpdate, UpdateSender};
+
+/// Output kept per terminal when the agent doesn't ask for a limit.
+pub const DEFAULT_OUTPUT_LIMIT: usize = 1024 * 1024;
+
+/// What a terminal should run, as the agent asked for it.
+pub struct TerminalCommand {
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub cwd: Option<PathBuf>,
+    pub output_byte_limit: Option<u64>,
+}
+
+impl TerminalCommand {
+    /// The command as one shell line, for the permission rules and the UI. Environment
+    /// variables go in front as `KEY=value`, which the rules never allow unasked.
+    pub fn line(&self) -> String {
+        let env = self
+            .env
+            .iter()
+            .map(|(name, value)| format!("{name}={}", quote_word(value)));
+        env.chain(
+            std::iter::once(&self.command)
+                .chain(&self.args)
+                .map(|word| quote_word(word)),
+        )
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+
+    /// Refuse environment variable names a shell couldn't assign, so each one shows up
+    /// in [`line`](Self::line) as an assignment.
+    pub fn check_env(&self) -> Result<(), String> {
+        for (name, _) in &self.env {
+            let mut chars = name.chars();
+            let valid = chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid {
+                return Err(format!("Invalid environment variable name: {name}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Output of a terminal, truncated from the start once it passes the limit.
+struct Output {
+    bytes: Vec<u8>,
+    limit: usize,
+    truncated: bool,
+}
+
+impl Output {
+    fn push(&mut self, chunk: &[u8]) {
+        self.bytes.extend_from_slice(chunk);
+        if self.bytes.len() > self.limit {
+            let mut cut = self.bytes.len() - self.limit;
+            // Don't leave half a character at the start
+            while cut < self.bytes.len() && (self.bytes[cut] & 0b1100_0000) == 0b1000_0000 {
+                cut += 1;
+            }
+            self.bytes.drain(..cut);
+            self.truncated = true;
+        }
+    }
+}
+
+struct Terminal {
+    output: Rc<RefCell<Output>>,
+    exit: watch::Receiver<Option<TerminalExitStatus>>,
+    kill: Rc<Notify>,
+}
+
+/// The terminals of one agent connection. Must be used inside a `LocalSet`.
+#[derive(Default)]
+pub struct TerminalManager {
+    terminals: RefCell<HashMap<String, Rc<Terminal>>>,
+    next_id: Cell<u64>,
+}
+
+impl TerminalManager {
+    /// Start a command and return the ID of its terminal. Its output is streamed
+    /// through `updates`, to the session that asked for it.
+    pub fn create(
+        &self,
+        command: &TerminalCommand,
+        updates: UpdateSender,
+    ) -> Result<String, String> {
+        if let Some(cwd) = &command.cwd {
+            if !cwd.is_absolute() {
+                return Err(format!(
+                    "Working directory must be absolute: {}",
+                    cwd.display()
+                ));
+            }
+        }
+
+        let mut cmd = tokio::process::Command::new(&command.command);
+        cmd.args(&command.args)
+            .envs(command.env.iter().map(|(k, v)| (k, v)))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(cwd) = &command.cwd {
+            cmd.current_dir(cwd);
+        }
+        // Own process group, so killing the terminal also stops what the command started
+        #[cfg(unix)]
+        cmd.process_group(0);
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("Failed to run {}: {e}", command.command))?;
+
+        self.next_id.set(self.next_id.get() + 1);
+        let terminal_id = format!("term-{}", self.next_id.get());
+        let limit = command
+            .output_byte_limit
+            .map_or(DEFAULT_OUTPUT_LIMIT, |limit| {
+                usize::try_from(limit).unwrap_or(usize::MAX)
+            });
+        let output = Rc::new(RefCell::new(Output {
+            bytes: Vec::new(),
+            limit,
+            truncated: false,
+        }));
+        let (exit_tx, exit_rx) = watch::channel(None);
+        let kill = Rc::new(Notify::new());
+
+        updates.send(AgentUpdate::TerminalCreated {
+            terminal_id: terminal_id.clone(),
+            command: command.line(),
+        });
+
+        let readers = [
+            child
+                .stdout
+                .take()
+                .map(|out| Box::new(out) as Box<dyn AsyncRead + Unpin>),
+            child
+                .stderr
+                .take()
+                .map(|err| Box::new(err) as Box<dyn AsyncRead + Unpin>),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|reader| {
+            tokio::task::spawn_local(read_output(
+                reader,
+                output.clone(),
+                terminal_id.clone(),
+                updates.clone(),
+            ))
+        })
+        .collect::<Vec<_>>();
+
+        let id = terminal_id.clone();
+        let killed = kill.clone();
+        tokio::task::spawn_local(async move {
+            let status = tokio::select! {
+                status = child.wait() => status,
+                _ = killed.notified() => {
+                    if let Some(pid) = child.id() {
+                        kill_tree(pid).await;
+                    }
+                    let _ = child.start_kill();
+                    child.wait().await
+                }
+            };
+            // Let the output catch up before reporting the exit
+            for reader in readers {
+                let _ = reader.await;
+            }
+            let status = match status {
+                Ok(status) => exit_status(status),
+                Err(e) => {
+                    eprintln!("Failed to wait for terminal {id}: {e}");
+                    TerminalExitStatus::new()
+                }
+            };
+            updates.send(AgentUpdate::TerminalExited {
+                terminal_id: id,
+                exit_code: status.exit_code,
+                signal: status.signal.clone(),
+            });
+            let _ = exit_tx.send(Some(status));
+        });
+
+        self.terminals.borrow_mut().insert(
+            terminal_id.clone(),
+            Rc::new(Terminal {
+                output,
+                exit: exit_rx,
+                kill,
+            }),
+        );
+        Ok(terminal_id)
+    }
+
+    /// The output so far, whether it was truncated, and the exit status once exited.
+    pub fn output(
+        &self,
+        terminal_id: &str,
+    ) -> Result<(String, bool, Option<TerminalExitStatus>), String> {
+        let terminal = self.get(terminal_id)?;
+        let output = terminal.output.borrow();
+        let exit = terminal.exit.borrow().clone();
+        Ok((
+            String::from_utf8_lossy(&output.bytes).into_owned(),
+            output.truncated,
+            exit,
+        ))
+    }
+
+    pub async fn wait_for_exit(&self, terminal_id: &str) -> Result<TerminalExitStatus, String> {
+        let mut exit = self.get(terminal_id)?.exit.clone();
+        let status = exit
+            .wait_for(Option::is_some)
+            .await
+            .map_err(|_| format!("Terminal {terminal_id} was released"))?;
+        Ok(status.clone().unwrap_or_default())
+    }
+
+    /// Kill the command but keep the terminal and its output.
+    pub fn kill(&self, terminal_id: &str) -> Result<(), String> {
+        self.get(terminal_id)?.kill.notify_one();
+        Ok(())
+    }
+
+    /// Kill the command if it still runs and forget the terminal.
+    pub fn release(&self, terminal_id: &str) -> Result<(), String> {
+        let terminal = self
+            .terminals
+            .borrow_mut()
+            .remove(terminal_id)
+            .ok_or_else(|| format!("Unknown terminal: {terminal_id}"))?;
+        terminal.kill.notify_one();
+        Ok(())
+    }
+
+    fn get(&self, terminal_id: &str) -> Result<Rc<Terminal>, String> {
+        self.terminals
+            .borrow()
+            .get(terminal_id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown terminal: {terminal_id}"))
+    }
+}
+
+async fn read_output(
+    mut reader: Box<dyn AsyncRead + Unpin>,
+    output: Rc<RefCell<Output>>,
+    terminal_id: String,
+    updates: UpdateSender,
+) {
+    let mut buf = [0u8; 8192];
+    // Bytes of a character split across reads
+    let mut pending = Vec::new();
+    loop {
+        let n = match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        output.borrow_mut().push(&buf[..n]);
+        pending.extend_from_slice(&buf[..n]);
+        let complete = complete_utf8_len(&pending);
+        if complete > 0 {
+            updates.send(AgentUpdate::TerminalOutput {
+                terminal_id: terminal_id.clone(),
+                text: String::from_utf8_lossy(&pending[..complete]).into_owned(),
+            });
+            pending.drain(..complete);
+        }
+    }
+    if !pending.is_empty() {
+        updates.send(AgentUpdate::TerminalOutput {
+            terminal_id,
+            text: String::from_utf8_lossy(&pending).into_owned(),
+        });
+    }
+}
+
+/// Kill a terminal's command and every process it started.
+async fn kill_tree(pid: u32) {
+    #[cfg(target_os = "windows")]
+    let status = tokio::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .status()
+        .await;
+    #[cfg(not(target_os = "windows"))]
+    let status = tokio::process::Command::new("kill")
+        .args(["-KILL", "--", &format!("-{pid}")])
+        .status()
+        .await;
+    if let Err(e) = status {
+        eprintln!("Failed to kill terminal process {pid}: {e}");
+    }
+}
+
+/// Length of `bytes` without a character cut off at the end.
+fn complete_utf8_len(bytes: &[u8]) -> usize {
+    match std::str::from_utf8(bytes) {
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        _ => bytes.len(),
+    }
+}
+
+fn exit_status(status: std::process::ExitStatus) -> TerminalExitStatus {
+    #[cfg(unix)]
+    let signal = {
+        use std::os::unix::process::ExitStatusExt;
+        status.signal().map(|signal| match signal {
+            1 => "SIGHUP".to_string(),
+            2 => "SIGINT".to_string(),
+            9 => "SIGKILL".to_string(),
+            15 => "SIGTERM".to_string(),
+            other => format!("signal {other}"),
+        })
+    };
+    #[cfg(not(unix))]
+    let signal: Option<String> = None;
+    TerminalExitStatus::new()
+        .exit_code(status.code().map(|code| code as u32))
+        .signal(signal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_output_from_the_start() {
+        let mut output = Output {
+            bytes: Vec::new(),
+            limit: 4,
+            truncated: false,
+        };
+        output.push(b"ab");
+        assert!(!output.truncated);
+        output.push("cdé".as_bytes());
+        assert_eq!(output.bytes, "cdé".as_bytes());
+        assert!(output.truncated);
+        // Cutting two bytes would split "é", so it goes too
+        output.push("üx".as_bytes());
+        assert_eq!(output.bytes, "üx".as_bytes());
+    }
+
+    #[test]
+    fn holds_back_split_characters() {
+        let bytes = "aé".as_bytes();
+        assert_eq!(complete_utf8_len(bytes), 3);
+        assert_eq!(complete_utf8_len(&bytes[..2]), 1);
+        assert_eq!(complete_utf8_len(b"a\xff"), 2);
+    }
+
+    #[test]
+    fn env_is_part_of_the_checked_line() {
+        use golaunch_core::permissions::{evaluate, RuleAction, RuleScope, ToolRequest};
+        use golaunch_core::PermissionRule;
+
+        let mut command = TerminalCommand {
+            command: "ls".to_string(),
+            args: vec!["-la".to_string()],
+            env: Vec::new(),
+            cwd: None,
+            output_byte_limit: None,
+        };
+        let allow_ls = PermissionRule {
+            id: "ls".to_string(),
+            position: 1,
+            action: "allow".to_string(),
+            tool_kind: None,
+            title: None,
+            command: Some("ls **".to_string()),
+            agent_id: None,
+            context: None,
+            created_at: String::new(),
+        };
+        let decide = |line: &str| {
+            let request = ToolRequest {
+                kind: Some("execute"),
+                title: Some(line),
+                command: Some(line),
+            };
+            evaluate(
+                std::slice::from_ref(&allow_ls),
+                &request,
+                &RuleScope::default(),
+            )
+        };
+        assert_eq!(decide(&command.line()), RuleAction::Allow);
+
+        command.env = vec![
+            ("LD_PRELOAD".to_string(), "/tmp/evil.so".to_string()),
+            ("MSG".to_string(), "a b".to_string()),
+        ];
+        assert!(command.check_env().is_ok());
+        assert_eq!(command.line(), "LD_PRELOAD=/tmp/evil.so MSG='a b' ls -la");
+        assert_eq!(decide(&command.line()), RuleAction::Ask);
+
+        command.env = vec![("NOT A NAME".to_string(), "x".to_string())];
+        assert!(command.check_env().is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn runs_commands_with_an_output_limit() {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let (update_tx, mut update_rx) = tokio::sync::mpsc::unbounded_channel();
+                let updates = UpdateSender::new(
+                    update_tx,
+                    "agent".to_string(),
+                    Some("session".to_string()),
+                    None,
+                );
+                let terminals = TerminalManager::default();
+                let id = terminals
+                    .create(
+                        &TerminalCommand {
+                            command: "sh".to_string(),
+                            args: vec!["-c".to_string(), "printf hello; exit 3".to_string()],
+                            env: Vec::new(),
+                            cwd: None,
+                            output_byte_limit: Some(3),
+                        },
+                        updates,
+                    )
+                    .unwrap();
+
+                let status = terminals.wait_for_exit(&id).await.unwrap();
+                assert_eq!(status.exit_code, Some(3));
+                let (output, truncated, exit) = terminals.output(&id).unwrap();
+                assert_eq!(output, "llo");
+                assert!(truncated);
+                assert!(exit.is_some());
+
+                let mut streamed = String::new();
+                while let Ok(event) = update_rx.try_recv() {
+                    assert_eq!(event.session_id.as_deref(), Some("session"));
+                    if let AgentUpdate::TerminalOutput { text, .. } = event.update {
+                        streamed.push_str(&text);
+                    }
+                }
+                assert_eq!(streamed, "hello");
+
+                terminals.release(&id).unwrap();
+                assert!(terminals.output(&id).is_err());
+            })
+            .await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn kill_stops_what_the_command_started() {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let (update_tx, _update_rx) = tokio::sync::mpsc::unbounded_channel();
+                let updates = UpdateSender::new(update_tx, "agent".to_string(), None, None);
+                let terminals = TerminalManager::default();
+                let id = terminals
+                    .create(
+                        &TerminalCommand {
+                            command: "sh".to_string(),
+                            args: vec!["-c".to_string(), "sleep 30 & wait".to_string()],
+                            env: Vec::new(),
+                            cwd: None,
+                            output_byte_limit: None,
+                        },
+                        updates,
+                    )
+                    .unwrap();
+
+                terminals.kill(&id).unwrap();
+                // The exit is reported once the output closes, which `sleep` holds open
+                let status = tokio::time::timeout(
+                    std::time::Duration::from_secs(5),
+                    terminals.wait_for_exit(&id),
+                )
+                .await
+                .expect("the background sleep kept running")
+                .unwrap();
+                assert_eq!(status.signal.as_deref(), Some("SIGKILL"));
+            })
+            .await;
+    }
+}
@@ -0,0 +1,364 @@
+//! Saving an agent turn to its conversation as it streams in, so the transcript
+//! survives a crash or a hidden window and the CLI can read partial answers; and
+//! reconciling a resumed session's replayed history with what was saved.
+
+use std::time::{Duration, Instant};
+
+use golaunch_core::{ConversationMessage, Database, NewConversationMessage};
+
+use super::types::PlanEntry;
+
+/// How often streamed text is written while the turn runs.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// One streamed message of a turn, written to its row as it grows.
+#[derive(Default)]
+struct StreamedMessage {
+    id: Option<String>,
+    content: String,
+    dirty: bool,
+}
+
+/// The messages of one turn. Every message is stored as incomplete until
+/// [`TurnTranscript::finish`] is called for a turn the agent finished.
+pub struct TurnTranscript {
+    conversation_id: String,
+    /// Messages stored for this turn, the user query first.
+    message_ids: Vec<String>,
+    answer: StreamedMessage,
+    thoughts: StreamedMessage,
+    plan: StreamedMessage,
+    last_flush: Instant,
+}
+
+impl TurnTranscript {
+    /// Start a turn whose user query is already stored as `user_message_id`.
+    pub fn new(conversation_id: String, user_message_id: Option<String>) -> Self {
+        Self {
+            conversation_id,
+            message_ids: user_message_id.into_iter().collect(),
+            answer: StreamedMessage::default(),
+            thoughts: StreamedMessage::default(),
+            plan: StreamedMessage::default(),
+            last_flush: Instant::now(),
+        }
+    }
+
+    pub fn message_chunk(&mut self, text: &str) {
+        self.answer.content.push_str(text);
+        self.answer.dirty = true;
+        self.flush_soon();
+    }
+
+    pub fn thought_chunk(&mut self, text: &str) {
+        self.thoughts.content.push_str(text);
+        self.thoughts.dirty = true;
+        self.flush_soon();
+    }
+
+    /// Agents send the whole plan on every change; the last one is kept.
+    pub fn plan(&mut self, entries: &[PlanEntry]) {
+        self.plan.content = entries
+            .iter()
+            .map(|entry| format!("[{}] {}", entry.status, entry.content))
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.plan.dirty = true;
+        self.flush_soon();
+    }
+
+    /// Write what is left, and mark the turn complete unless it was cancelled or failed.
+    pub fn finish(mut self, complete: bool) {
+        self.flush();
+        if !complete {
+            return;
+        }
+        let result =
+            Database::new().and_then(|db| db.complete_conversation_messages(&self.message_ids));
+        if let Err(e) = result {
+            eprintln!("Failed to save conversation: {e}");
+        }
+    }
+
+    fn flush_soon(&mut self) {
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        self.last_flush = Instant::now();
+        if !(self.answer.dirty || self.thoughts.dirty || self.plan.dirty) {
+            return;
+        }
+        let result = Database::new().and_then(|db| {
+            for (role, message) in [
+                ("thought", &mut self.thoughts),
+                ("plan", &mut self.plan),
+                ("assistant", &mut self.answer),
+            ] {
+                if !message.dirty {
+                    continue;
+                }
+                match &message.id {
+                    Some(id) => db.update_conversation_message(id, &message.content)?,
+                    None => {
+                        let stored = db.add_conversation_message(NewConversationMessage {
+                            conversation_id: self.conversation_id.clone(),
+                            role: role.to_string(),
+                            content: message.content.clone(),
+                            incomplete: true,
+                        })?;
+                        self.message_ids.push(stored.id.clone());
+                        message.id = Some(stored.id);
+                    }
+                }
+                message.dirty = false;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("Failed to save conversation: {e}");
+        }
+    }
+}
+
+impl Drop for TurnTranscript {
+    /// A turn dropped without `finish` (the agent was restarted) keeps what arrived.
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// A message an agent replays when a session is loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayedMessage {
+    /// "user" or "assistant".
+    pub role: String,
+    pub content: String,
+}
+
+/// Collects the history an agent replays during `session/load`, joining the chunks
+/// of each message.
+#[derive(Default)]
+pub struct Replay {
+    messages: Vec<ReplayedMessage>,
+    /// Whether the next chunk of the same role continues the last message.
+    open: bool,
+}
+
+impl Replay {
+    pub fn push(&mut self, role: &str, text: &str) {
+        match self.messages.last_mut() {
+            Some(last) if self.open && last.role == role => last.content.push_str(text),
+            _ => self.messages.push(ReplayedMessage {
+                role: role.to_string(),
+                content: text.to_string(),
+            }),
+        }
+        self.open = true;
+    }
+
+    /// Anything else the agent replays (a tool call, a plan) ends the current message.
+    pub fn break_message(&mut self) {
+        self.open = false;
+    }
+
+    pub fn into_messages(self) -> Vec<ReplayedMessage> {
+        self.messages
+    }
+}
+
+/// How a replayed message changes the saved conversation.
+#[derive(Debug, PartialEq)]
+pub enum ReplayChange {
+    /// A saved answer that was cut off (the app closed mid-turn) gets the full text.
+    Update { id: String, content: String },
+    /// An answer that never made it to the database, placed before the saved message
+    /// `before` (the next query), or at the end when that is `None`.
+    Add {
+        role: String,
+        content: String,
+        before: Option<String>,
+    },
+}
+
+/// A turn of the saved conversation: its answer and the query that follows it.
+#[derive(Default)]
+struct StoredTurn<'a> {
+    answer: Option<&'a ConversationMessage>,
+    next_query: Option<&'a str>,
+}
+
+/// Compare replayed history with the saved messages turn by turn, pairing the n-th
+/// replayed prompt with the n-th saved query. Replayed prompts carry GoLaunch's
+/// context around the query, so only their position is compared; the answers of a
+/// turn are joined the way a streamed answer is saved.
+pub fn replay_changes(
+    stored: &[ConversationMessage],
+    replayed: &[ReplayedMessage],
+) -> Vec<ReplayChange> {
+    let mut stored_turns = vec![StoredTurn::default()];
+    for message in stored {
+        match message.role.as_str() {
+            "user" => {
+                let last = stored_turns.len() - 1;
+                stored_turns[last].next_query = Some(&message.id);
+                stored_turns.push(StoredTurn::default());
+            }
+            "assistant" => {
+                let last = stored_turns.len() - 1;
+                stored_turns[last].answer.get_or_insert(message);
+            }
+            _ => {}
+        }
+    }
+    let mut replayed_turns = vec![String::new()];
+    for message in replayed {
+        match message.role.as_str() {
+            "user" => replayed_turns.push(String::new()),
+            "assistant" => {
+                let last = replayed_turns.len() - 1;
+                replayed_turns[last].push_str(&message.content);
+            }
+            _ => {}
+        }
+    }
+
+    let mut changes = Vec::new();
+    // Turns past the saved queries have nowhere to go
+    for (turn, answer) in stored_turns.iter().zip(replayed_turns) {
+        let text = answer.trim();
+        if text.is_empty() {
+            continue;
+        }
+        match turn.answer {
+            Some(saved) if saved.content.trim() == text => {}
+            Some(saved) => {
+                let saved_text = saved.content.trim();
+                if saved.incomplete && text.starts_with(saved_text) {
+                    changes.push(ReplayChange::Update {
+                        id: saved.id.clone(),
+                        content: answer,
+                    });
+                }
+            }
+            None => changes.push(ReplayChange::Add {
+                role: "assistant".to_string(),
+                content: answer,
+                before: turn.next_query.map(str::to_string),
+            }),
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored(id: &str, role: &str, content: &str, incomplete: bool) -> ConversationMessage {
+        ConversationMessage {
+            id: id.to_string(),
+            conversation_id: "c".to_string(),
+            role: role.to_string(),
+            content: content.to_string(),
+            incomplete,
+            created_at: String::new(),
+        }
+    }
+
+    fn replayed(role: &str, content: &str) -> ReplayedMessage {
+        ReplayedMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn joins_replayed_chunks() {
+        let mut replay = Replay::default();
+        replay.push("user", "Hi ");
+        replay.push("user", "there");
+        replay.push("assistant", "Hello");
+        replay.break_message();
+        replay.push("assistant", "Done");
+        let roles: Vec<(String, String)> = replay
+            .into_messages()
+            .into_iter()
+            .map(|m| (m.role, m.content))
+            .collect();
+        assert_eq!(
+            roles,
+            [
+                ("user".to_string(), "Hi there".to_string()),
+                ("assistant".to_string(), "Hello".to_string()),
+                ("assistant".to_string(), "Done".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_saved_messages_and_completes_cut_off_answers() {
+        let saved = [
+            stored("1", "user", "list my items", false),
+            stored("2", "assistant", "Here are your items.", false),
+            stored("3", "user", "and memories?", true),
+            stored("4", "thought", "Looking up memories", true),
+            stored("5", "assistant", "You have", true),
+        ];
+        let changes = replay_changes(
+            &saved,
+            &[
+                replayed("user", "## User Query\nlist my items"),
+                replayed("assistant", "Here are your items.\n"),
+                replayed("user", "## User Query\nand memories?"),
+                replayed("assistant", "You have 3 memories. "),
+                replayed("assistant", "Anything else?"),
+            ],
+        );
+        assert_eq!(
+            changes,
+            [ReplayChange::Update {
+                id: "5".to_string(),
+                content: "You have 3 memories. Anything else?".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn adds_missing_answers_in_their_turn() {
+        let saved = [
+            stored("1", "user", "rename the file", false),
+            stored("2", "user", "now commit it", false),
+            stored("3", "assistant", "Done.", false),
+            stored("4", "user", "push it", false),
+        ];
+        let changes = replay_changes(
+            &saved,
+            &[
+                replayed("user", "rename the file"),
+                replayed("assistant", "Done."),
+                replayed("user", "now commit it"),
+                replayed("assistant", "Done."),
+                replayed("user", "push it"),
+                replayed("assistant", "Pushed."),
+            ],
+        );
+        assert_eq!(
+            changes,
+            [
+                ReplayChange::Add {
+                    role: "assistant".to_string(),
+                    content: "Done.".to_string(),
+                    before: Some("2".to_string()),
+                },
+                ReplayChange::Add {
+                    role: "assistant".to_string(),
+                    content: "Pushed.".to_string(),
+                    before: None,
+                },
+            ]
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RequiredEnvVar {
@@ -30,9 +31,23 @@ pub enum AgentStatus {
     Disconnected,
     Connecting,
     Connected,
+    /// Shut down after going unused; started again by the next prompt.
+    Idle,
     Error,
 }
 
+/// What `acp_get_status` reports about an agent.
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentStatusInfo {
+    pub agent_id: Option<String>,
+    pub status: AgentStatus,
+    /// Seconds since the agent process was last started; `None` while it isn't running.
+    pub uptime_secs: Option<u64>,
+    /// Times the agent process was started again after it died or hung.
+    pub restart_count: u32,
+    pub last_error: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum AgentUpdate {
@@ -60,7 +75,68 @@ pub enum AgentUpdate {
     },
     StatusChange {
         status: AgentStatus,
+        /// Why the agent failed or stopped, with `Error`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    TerminalCreated {
+        terminal_id: String,
+        command: String,
+    },
+    TerminalOutput {
+        terminal_id: String,
+        text: String,
     },
+    TerminalExited {
+        terminal_id: String,
+        exit_code: Option<u32>,
+        signal: Option<String>,
+    },
+}
+
+/// An update as emitted to the frontend, with the agent and conversation it belongs to.
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentUpdateEvent {
+    pub agent_id: String,
+    /// Session the update came from; `None` for updates about the whole agent.
+    pub session_id: Option<String>,
+    pub conversation_id: Option<String>,
+    #[serde(flatten)]
+    pub update: AgentUpdate,
+}
+
+/// Sends the updates of one session, tagged with where they belong.
+#[derive(Clone)]
+pub struct UpdateSender {
+    tx: mpsc::UnboundedSender<AgentUpdateEvent>,
+    agent_id: String,
+    session_id: Option<String>,
+    conversation_id: Option<String>,
+}
+
+impl UpdateSender {
+    pub fn new(
+        tx: mpsc::UnboundedSender<AgentUpdateEvent>,
+        agent_id: String,
+        session_id: Option<String>,
+        conversation_id: Option<String>,
+    ) -> Self {
+        Self {
+            tx,
+            agent_id,
+            session_id,
+            conversation_id,
+        }
+    }
+
+    pub fn send(&self, update: AgentUpdate) {
+        let _ = self.tx.send(AgentUpdateEvent {
+            agent_id: self.agent_id.clone(),
+            session_id: self.session_id.clone(),
+            conversation_id: self.conversation_id.clone(),
+            update,
+        });
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,7 +149,10 @@ pub struct PlanEntry {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PermissionRequest {
     pub request_id: String,
+    /// Agent that asks; permissions are answered through it.
+    pub agent_id: String,
     pub session_id: String,
+    pub conversation_id: Option<String>,
     pub tool_name: String,
     pub tool_description: Option<String>,
     pub command_preview: Option<String>,
@@ -133,3 +212,12 @@ pub struct SessionConfigOptionInfo {
     pub current_value: String,
     pub select_options: SessionConfigSelectOptionsInfo,
 }
+
+/// The config options of one agent session, as emitted to the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigOptionsEvent {
+    pub agent_id: String,
+    /// `None` for a session no conversation has used yet.
+    pub conversation_id: Option<String>,
+    pub options: Vec<SessionConfigOptionInfo>,
+}
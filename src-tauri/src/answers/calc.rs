@@ -0,0 +1,476 @@
+use super::{format_number, AnswerKind, Now, QuickAnswer};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    /// A number written in hex, binary or octal (`0xff`, `0b1010`, `0o17`).
+    Based(f64),
+    Ident(String),
+    Op(char),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '0' && matches!(chars.get(i + 1), Some('x' | 'X' | 'b' | 'B' | 'o' | 'O')) {
+            let radix = match chars[i + 1].to_ascii_lowercase() {
+                'x' => 16,
+                'b' => 2,
+                _ => 8,
+            };
+            let start = i + 2;
+            let mut end = start;
+            while end < chars.len() && (chars[end].is_digit(radix) || chars[end] == '_') {
+                end += 1;
+            }
+            let digits: String = chars[start..end].iter().filter(|c| **c != '_').collect();
+            let value =
+                u64::from_str_radix(&digits, radix).map_err(|_| "Invalid number".to_string())?;
+            tokens.push(Token::Based(value as f64));
+            i = end;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i] == '_')
+            {
+                i += 1;
+            }
+            // Exponent: 1e3, 2.5E-4
+            if i < chars.len() && matches!(chars[i], 'e' | 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && matches!(chars[j], '+' | '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..i].iter().filter(|c| **c != '_').collect();
+            let value = text
+                .parse()
+                .map_err(|_| format!("Invalid number '{text}'"))?;
+            tokens.push(Token::Num(value));
+        } else if c.is_alphabetic() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(
+                chars[start..i].iter().collect::<String>().to_lowercase(),
+            ));
+        } else {
+            let op = match c {
+                '*' if chars.get(i + 1) == Some(&'*') => {
+                    i += 1;
+                    '^'
+                }
+                '×' | '·' => '*',
+                '÷' => '/',
+                '−' => '-',
+                '+' | '-' | '*' | '/' | '%' | '^' | '(' | ')' | ',' | '!' => c,
+                _ => return Err(format!("Unexpected '{c}'")),
+            };
+            tokens.push(Token::Op(op));
+            i += 1;
+        }
+    }
+    Ok(tokens)
+}
+
+/// A parsed value; `percent` marks values written as `N%` so `200 + 10%` can mean
+/// "200 plus 10% of 200".
+#[derive(Debug, Clone, Copy)]
+struct Value {
+    number: f64,
+    percent: bool,
+}
+
+impl Value {
+    fn plain(number: f64) -> Self {
+        Self {
+            number,
+            percent: false,
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn eat_op(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Token::Op(op)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn starts_operand(token: Option<&Token>) -> bool {
+        match token {
+            Some(Token::Num(_) | Token::Based(_) | Token::Op('(')) => true,
+            Some(Token::Ident(name)) => name != "of",
+            _ => false,
+        }
+    }
+
+    fn expression(&mut self) -> Result<Value, String> {
+        let mut left = self.product()?;
+        loop {
+            let sign = if self.eat_op('+') {
+                1.0
+            } else if self.eat_op('-') {
+                -1.0
+            } else {
+                return Ok(left);
+            };
+            let right = self.product()?;
+            let delta = if right.percent && !left.percent {
+                left.number * right.number
+            } else {
+                right.number
+            };
+            left = Value::plain(left.number + sign * delta);
+        }
+    }
+
+    fn product(&mut self) -> Result<Value, String> {
+        let mut left = self.power()?;
+        loop {
+            let next = self.peek().cloned();
+            left = match next {
+                Some(Token::Op('*')) => {
+                    self.pos += 1;
+                    Value::plain(left.number * self.power()?.number)
+                }
+                Some(Token::Op('/')) => {
+                    self.pos += 1;
+                    let divisor = self.power()?.number;
+                    if divisor == 0.0 {
+                        return Err("Division by zero".to_string());
+                    }
+                    Value::plain(left.number / divisor)
+                }
+                Some(Token::Op('%')) => {
+                    self.pos += 1;
+                    let divisor = self.power()?.number;
+                    if divisor == 0.0 {
+                        return Err("Division by zero".to_string());
+                    }
+                    Value::plain(left.number.rem_euclid(divisor))
+                }
+                // "10% of 250"
+                Some(Token::Ident(ref word)) if word == "of" => {
+                    self.pos += 1;
+                    Value::plain(left.number * self.power()?.number)
+                }
+                // Implicit multiplication: 2pi, 3(4 + 1)
+                _ if Self::starts_operand(next.as_ref()) => {
+                    Value::plain(left.number * self.power()?.number)
+                }
+                _ => return Ok(left),
+            };
+        }
+    }
+
+    fn power(&mut self) -> Result<Value, String> {
+        let base = self.unary()?;
+        if self.eat_op('^') {
+            // Right-associative, and binds tighter than a leading minus: -2^2 = -4
+            let exponent = self.power()?;
+            return Ok(Value::plain(base.number.powf(exponent.number)));
+        }
+        Ok(base)
+    }
+
+    fn unary(&mut self) -> Result<Value, String> {
+        if self.eat_op('-') {
+            let value = self.power()?;
+            return Ok(Value {
+                number: -value.number,
+                percent: value.percent,
+            });
+        }
+        if self.eat_op('+') {
+            return self.power();
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Value, String> {
+        let mut value = self.primary()?;
+        loop {
+            if self.peek() == Some(&Token::Op('!')) {
+                self.pos += 1;
+                value = Value::plain(factorial(value.number)?);
+            } else if self.peek() == Some(&Token::Op('%')) && !Self::starts_operand(self.peek_at(1))
+            {
+                // A '%' followed by an operand is modulo; otherwise it is a percentage.
+                self.pos += 1;
+                value = Value {
+                    number: value.number / 100.0,
+                    percent: true,
+                };
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Value, String> {
+        let token = self.peek().cloned().ok_or("Unexpected end of input")?;
+        self.pos += 1;
+        match token {
+            Token::Num(n) | Token::Based(n) => Ok(Value::plain(n)),
+            Token::Op('(') => {
+                let value = self.expression()?;
+                if !self.eat_op(')') {
+                    return Err("Missing ')'".to_string());
+                }
+                Ok(value)
+            }
+            Token::Ident(name) => {
+                if let Some(constant) = constant(&name) {
+                    return Ok(Value::plain(constant));
+                }
+                if !self.eat_op('(') {
+                    return Err(format!("Unknown name '{name}'"));
+                }
+                let mut args = Vec::new();
+                if !self.eat_op(')') {
+                    loop {
+                        args.push(self.expression()?.number);
+                        if self.eat_op(')') {
+                            break;
+                        }
+                        if !self.eat_op(',') {
+                            return Err("Expected ',' or ')'".to_string());
+                        }
+                    }
+                }
+                call(&name, &args).map(Value::plain)
+            }
+            Token::Op(op) => Err(format!("Unexpected '{op}'")),
+        }
+    }
+}
+
+fn constant(name: &str) -> Option<f64> {
+    match name {
+        "pi" | "π" => Some(std::f64::consts::PI),
+        "e" => Some(std::f64::consts::E),
+        "tau" => Some(std::f64::consts::TAU),
+        _ => None,
+    }
+}
+
+fn call(name: &str, args: &[f64]) -> Result<f64, String> {
+    let one = || match args {
+        [x] => Ok(*x),
+        _ => Err(format!("{name}() takes one argument")),
+    };
+    Ok(match name {
+        "sqrt" => one()?.sqrt(),
+        "cbrt" => one()?.cbrt(),
+        "abs" => one()?.abs(),
+        "sin" => one()?.sin(),
+        "cos" => one()?.cos(),
+        "tan" => one()?.tan(),
+        "asin" => one()?.asin(),
+        "acos" => one()?.acos(),
+        "atan" => one()?.atan(),
+        "ln" => one()?.ln(),
+        "log" | "log10" => one()?.log10(),
+        "log2" => one()?.log2(),
+        "exp" => one()?.exp(),
+        "floor" => one()?.floor(),
+        "ceil" => one()?.ceil(),
+        "round" => one()?.round(),
+        "min" if !args.is_empty() => args.iter().copied().fold(f64::INFINITY, f64::min),
+        "max" if !args.is_empty() => args.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        "pow" => match args {
+            [base, exponent] => base.powf(*exponent),
+            _ => return Err("pow() takes two arguments".to_string()),
+        },
+        _ => return Err(format!("Unknown function '{name}'")),
+    })
+}
+
+fn factorial(n: f64) -> Result<f64, String> {
+    if n < 0.0 || n.fract() != 0.0 || n > 170.0 {
+        return Err("Factorial needs a whole number between 0 and 170".to_string());
+    }
+    Ok((1..=n as u64).fold(1.0, |acc, k| acc * k as f64))
+}
+
+/// Evaluate an arithmetic expression.
+pub fn evaluate(input: &str) -> Result<f64, String> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+    };
+    let value = parser.expression()?;
+    if parser.pos < parser.tokens.len() {
+        return Err("Unexpected input after expression".to_string());
+    }
+    if !value.number.is_finite() {
+        return Err("Result is not a finite number".to_string());
+    }
+    Ok(value.number)
+}
+
+/// Split off a trailing `in hex` / `as bin` style base conversion.
+fn split_base(query: &str) -> (&str, Option<u32>) {
+    let lower = query.to_ascii_lowercase();
+    for sep in [" in ", " to ", " as "] {
+        if let Some(pos) = lower.rfind(sep) {
+            let radix = match lower[pos + sep.len()..].trim() {
+                "hex" | "hexadecimal" => 16,
+                "bin" | "binary" => 2,
+                "oct" | "octal" => 8,
+                "dec" | "decimal" => 10,
+                _ => continue,
+            };
+            return (&query[..pos], Some(radix));
+        }
+    }
+    (query, None)
+}
+
+fn format_radix(value: f64, radix: u32) -> Option<String> {
+    if value.fract() != 0.0 || value.abs() > u64::MAX as f64 {
+        return None;
+    }
+    let magnitude = value.abs() as u64;
+    let digits = match radix {
+        16 => format!("0x{magnitude:x}"),
+        2 => format!("0b{magnitude:b}"),
+        8 => format!("0o{magnitude:o}"),
+        _ => magnitude.to_string(),
+    };
+    Some(if value < 0.0 {
+        format!("-{digits}")
+    } else {
+        digits
+    })
+}
+
+/// Looks like an ISO date (2024-05-01), which the date provider answers instead.
+fn looks_like_date(query: &str) -> bool {
+    let bytes = query.as_bytes();
+    bytes.len() >= 10
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[5..7].iter().all(u8::is_ascii_digit)
+        && bytes[7] == b'-'
+}
+
+pub fn answer(query: &str, _now: &Now) -> Option<QuickAnswer> {
+    if looks_like_date(query) {
+        return None;
+    }
+    let (expression, radix) = split_base(query);
+    let tokens = tokenize(expression).ok()?;
+    // A bare decimal number is not worth an answer; a bare 0xff is (it shows 255).
+    let trivial = matches!(tokens.as_slice(), [Token::Num(_)] | [Token::Ident(_)]);
+    if tokens.is_empty() || (trivial && radix.is_none()) {
+        return None;
+    }
+    let value = evaluate(expression).ok()?;
+
+    let (title, copy) = match radix {
+        Some(radix) => {
+            let text = format_radix(value, radix)?;
+            (text.clone(), text)
+        }
+        None => (
+            format_number(value, 10, true),
+            format_number(value, 10, false),
+        ),
+    };
+    Some(QuickAnswer {
+        kind: AnswerKind::Calculator,
+        title: format!("= {title}"),
+        subtitle: expression.trim().to_string(),
+        value: copy,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(input: &str) -> f64 {
+        evaluate(input).unwrap_or_else(|e| panic!("{input}: {e}"))
+    }
+
+    #[test]
+    fn arithmetic_and_precedence() {
+        assert!((eval("12*1.21") - 14.52).abs() < 1e-9);
+        assert_eq!(eval("2 + 3 * 4"), 14.0);
+        assert_eq!(eval("(2 + 3) * 4"), 20.0);
+        assert_eq!(eval("2^3^2"), 512.0);
+        assert_eq!(eval("2**10"), 1024.0);
+        assert_eq!(eval("-2^2"), -4.0);
+        assert_eq!(eval("7 / 2"), 3.5);
+        assert_eq!(eval("10 % 3"), 1.0);
+        assert_eq!(eval("5!"), 120.0);
+        assert_eq!(eval("2pi"), 2.0 * std::f64::consts::PI);
+        assert_eq!(eval("1e3 + 2.5E-1"), 1000.25);
+    }
+
+    #[test]
+    fn functions_and_bases() {
+        assert_eq!(eval("sqrt(16) + abs(-2)"), 6.0);
+        assert_eq!(eval("max(1, 7, 3)"), 7.0);
+        assert_eq!(eval("log(1000)"), 3.0);
+        assert_eq!(eval("0xff + 0b11"), 258.0);
+        assert_eq!(eval("0o17"), 15.0);
+        assert!(evaluate("foo(2)").is_err());
+        assert!(evaluate("1 / 0").is_err());
+    }
+
+    #[test]
+    fn percentages() {
+        assert_eq!(eval("50%"), 0.5);
+        assert_eq!(eval("200 + 10%"), 220.0);
+        assert_eq!(eval("200 - 25%"), 150.0);
+        assert_eq!(eval("10% of 250"), 25.0);
+        assert_eq!(eval("80 * 15%"), 12.0);
+    }
+
+    #[test]
+    fn answers() {
+        let now = Now::fixed("2024-05-01T12:00:00Z");
+        let answer = answer("1234 * 1000", &now).unwrap();
+        assert_eq!(answer.title, "= 1,234,000");
+        assert_eq!(answer.value, "1234000");
+        assert_eq!(super::answer("255 in hex", &now).unwrap().value, "0xff");
+        assert_eq!(super::answer("0xff", &now).unwrap().value, "255");
+        assert_eq!(super::answer("0b101 in dec", &now).unwrap().value, "5");
+        assert!(super::answer("42", &now).is_none());
+        assert!(super::answer("pi", &now).is_none());
+        assert!(super::answer("firefox", &now).is_none());
+        assert!(super::answer("2024-05-01", &now).is_none());
+        assert!(super::answer("5 km in mi", &now).is_none());
+    }
+}
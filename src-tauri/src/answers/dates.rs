@@ -0,0 +1,453 @@
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+
+use super::{AnswerKind, Now, QuickAnswer};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Date(NaiveDate),
+    Time(NaiveTime),
+    Num(u32),
+    Word(String),
+    Plus,
+    Minus,
+}
+
+fn tokenize(query: &str) -> Option<Vec<Token>> {
+    let chars: Vec<char> = query.to_lowercase().chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() || c == ',' {
+            i += 1;
+        } else if c == '+' {
+            tokens.push(Token::Plus);
+            i += 1;
+        } else if c == '-' || c == '−' {
+            tokens.push(Token::Minus);
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || matches!(chars[i], '-' | ':')) {
+                // A '-' only continues an ISO date (2024-05-01), never "today-3d" style input.
+                if chars[i] == '-' && !(i - start == 4 || i - start == 7) {
+                    break;
+                }
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let token = if text.len() == 10 && text.contains('-') {
+                Token::Date(NaiveDate::parse_from_str(&text, "%Y-%m-%d").ok()?)
+            } else if text.contains(':') {
+                Token::Time(NaiveTime::parse_from_str(&text, "%H:%M").ok()?)
+            } else {
+                Token::Num(text.parse().ok()?)
+            };
+            tokens.push(token);
+        } else if c.is_alphabetic() {
+            let start = i;
+            while i < chars.len() && chars[i].is_alphabetic() {
+                i += 1;
+            }
+            tokens.push(Token::Word(chars[start..i].iter().collect()));
+        } else {
+            return None;
+        }
+    }
+    Some(tokens)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Unit {
+    Minutes,
+    Hours,
+    Days,
+    Weeks,
+    Months,
+    Years,
+}
+
+fn unit(word: &str) -> Option<Unit> {
+    Some(match word {
+        "min" | "mins" | "minute" | "minutes" => Unit::Minutes,
+        "h" | "hr" | "hrs" | "hour" | "hours" => Unit::Hours,
+        "d" | "day" | "days" => Unit::Days,
+        "w" | "wk" | "wks" | "week" | "weeks" => Unit::Weeks,
+        "mo" | "month" | "months" => Unit::Months,
+        "y" | "yr" | "yrs" | "year" | "years" => Unit::Years,
+        _ => return None,
+    })
+}
+
+fn weekday(word: &str) -> Option<Weekday> {
+    Some(match word {
+        "monday" | "mon" => Weekday::Mon,
+        "tuesday" | "tue" => Weekday::Tue,
+        "wednesday" | "wed" => Weekday::Wed,
+        "thursday" | "thu" => Weekday::Thu,
+        "friday" | "fri" => Weekday::Fri,
+        "saturday" | "sat" => Weekday::Sat,
+        "sunday" | "sun" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// A point in time; `has_time` is false for whole days.
+#[derive(Debug, Clone, Copy)]
+struct Moment {
+    at: NaiveDateTime,
+    has_time: bool,
+}
+
+impl Moment {
+    fn day(date: NaiveDate) -> Self {
+        Self {
+            at: date.and_time(NaiveTime::MIN),
+            has_time: false,
+        }
+    }
+
+    fn shift(self, amount: u32, unit: Unit, forward: bool) -> Option<Self> {
+        let at = self.at;
+        let months = |n: u32| {
+            if forward {
+                at.checked_add_months(Months::new(n))
+            } else {
+                at.checked_sub_months(Months::new(n))
+            }
+        };
+        let by = |duration: Duration| {
+            if forward {
+                at.checked_add_signed(duration)
+            } else {
+                at.checked_sub_signed(duration)
+            }
+        };
+        let (at, has_time) = match unit {
+            Unit::Minutes => (by(Duration::minutes(amount.into()))?, true),
+            Unit::Hours => (by(Duration::hours(amount.into()))?, true),
+            Unit::Days => (by(Duration::days(amount.into()))?, self.has_time),
+            Unit::Weeks => (by(Duration::weeks(amount.into()))?, self.has_time),
+            Unit::Months => (months(amount)?, self.has_time),
+            Unit::Years => (months(amount.checked_mul(12)?)?, self.has_time),
+        };
+        Some(Self { at, has_time })
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    now: &'a Now,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Word(w)) if w == word) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn done(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn today(&self) -> NaiveDate {
+        self.now.local.date()
+    }
+
+    /// today, now, tomorrow, yesterday, 2024-05-01 [14:30], next friday, last monday
+    fn moment(&mut self) -> Option<Moment> {
+        let today = self.today();
+        let moment = match self.next()?.clone() {
+            Token::Date(date) => match self.peek() {
+                Some(Token::Time(time)) => {
+                    let time = *time;
+                    self.pos += 1;
+                    Moment {
+                        at: date.and_time(time),
+                        has_time: true,
+                    }
+                }
+                _ => Moment::day(date),
+            },
+            Token::Word(word) => match word.as_str() {
+                "today" => Moment::day(today),
+                "tomorrow" => Moment::day(today.succ_opt()?),
+                "yesterday" => Moment::day(today.pred_opt()?),
+                "now" => Moment {
+                    at: self.now.local,
+                    has_time: true,
+                },
+                "next" | "last" | "this" => {
+                    let Some(Token::Word(day)) = self.next() else {
+                        return None;
+                    };
+                    let target = weekday(day)?.num_days_from_monday() as i64;
+                    let current = today.weekday().num_days_from_monday() as i64;
+                    let offset = match word.as_str() {
+                        "next" => (target - current - 1).rem_euclid(7) + 1,
+                        "last" => -((current - target - 1).rem_euclid(7) + 1),
+                        _ => (target - current).rem_euclid(7),
+                    };
+                    Moment::day(today.checked_add_signed(Duration::days(offset))?)
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+        Some(moment)
+    }
+
+    /// "3d", "3 days", "2 weeks"
+    fn duration(&mut self) -> Option<(u32, Unit)> {
+        let Some(Token::Num(amount)) = self.next().cloned() else {
+            return None;
+        };
+        let Some(Token::Word(word)) = self.next() else {
+            return None;
+        };
+        Some((amount, unit(word)?))
+    }
+}
+
+enum Outcome {
+    Moment(Moment),
+    /// Whole days between two moments.
+    Days(i64, Moment, Moment),
+}
+
+fn evaluate(tokens: &[Token], now: &Now) -> Option<Outcome> {
+    let mut p = Parser {
+        tokens,
+        pos: 0,
+        now,
+    };
+
+    // "days until 2024-12-25", "days since 2024-01-01"
+    if p.eat_word("days") {
+        let until = match p.next()? {
+            Token::Word(w) if matches!(w.as_str(), "until" | "till" | "to") => true,
+            Token::Word(w) if matches!(w.as_str(), "since" | "from") => false,
+            _ => return None,
+        };
+        let target = p.moment()?;
+        let today = Moment::day(p.today());
+        if !p.done() {
+            return None;
+        }
+        return Some(if until {
+            Outcome::Days(days_between(today, target), today, target)
+        } else {
+            Outcome::Days(days_between(target, today), target, today)
+        });
+    }
+
+    // "in 3 days"
+    if p.eat_word("in") {
+        let (amount, unit) = p.duration()?;
+        let base = Moment::day(p.today());
+        return p
+            .done()
+            .then(|| base.shift(amount, unit, true))
+            .flatten()
+            .map(Outcome::Moment);
+    }
+
+    // "3 days ago", "2 weeks from tomorrow"
+    if matches!(p.peek(), Some(Token::Num(_))) {
+        let (amount, unit) = p.duration()?;
+        let (base, forward) = if p.eat_word("ago") {
+            (Moment::day(p.today()), false)
+        } else if p.eat_word("from") || p.eat_word("after") {
+            (p.moment()?, true)
+        } else if p.eat_word("before") {
+            (p.moment()?, false)
+        } else {
+            return None;
+        };
+        return p
+            .done()
+            .then(|| base.shift(amount, unit, forward))
+            .flatten()
+            .map(Outcome::Moment);
+    }
+
+    // "today + 3 weeks - 1 day", "2024-12-25 - today"
+    let mut moment = p.moment()?;
+    while !p.done() {
+        let forward = match p.next()? {
+            Token::Plus => true,
+            Token::Minus => false,
+            _ => return None,
+        };
+        if !forward && !matches!(p.peek(), Some(Token::Num(_))) {
+            let other = p.moment()?;
+            return p
+                .done()
+                .then(|| Outcome::Days(days_between(other, moment), other, moment));
+        }
+        let (amount, unit) = p.duration()?;
+        moment = moment.shift(amount, unit, forward)?;
+    }
+    Some(Outcome::Moment(moment))
+}
+
+fn days_between(from: Moment, to: Moment) -> i64 {
+    (to.at.date() - from.at.date()).num_days()
+}
+
+fn plural(n: i64, word: &str) -> String {
+    if n.abs() == 1 {
+        format!("{n} {word}")
+    } else {
+        format!("{n} {word}s")
+    }
+}
+
+fn relative_day(days: i64) -> String {
+    match days {
+        0 => "Today".to_string(),
+        1 => "Tomorrow".to_string(),
+        -1 => "Yesterday".to_string(),
+        d if d > 0 => format!("In {}", plural(d, "day")),
+        d => format!("{} ago", plural(-d, "day")),
+    }
+}
+
+fn weeks_and_days(days: i64) -> String {
+    let (weeks, rest) = (days.abs() / 7, days.abs() % 7);
+    match (weeks, rest) {
+        (0, _) => plural(rest, "day"),
+        (_, 0) => plural(weeks, "week"),
+        _ => format!("{}, {}", plural(weeks, "week"), plural(rest, "day")),
+    }
+}
+
+/// Answers "today + 3 weeks", "2024-12-25 - today", "days until 2024-12-25",
+/// "3 days ago", "next friday", or a bare date like "2024-05-01".
+pub fn answer(query: &str, now: &Now) -> Option<QuickAnswer> {
+    let tokens = tokenize(query)?;
+    // "now" alone is not worth an answer (and is a likely item search).
+    if tokens.is_empty() || tokens == [Token::Word("now".to_string())] {
+        return None;
+    }
+
+    let (title, subtitle, value) = match evaluate(&tokens, now)? {
+        Outcome::Moment(moment) => {
+            let relative = relative_day((moment.at.date() - now.local.date()).num_days());
+            if moment.has_time {
+                (
+                    moment.at.format("%a %-d %b %Y, %H:%M").to_string(),
+                    relative,
+                    moment.at.format("%Y-%m-%d %H:%M").to_string(),
+                )
+            } else {
+                (
+                    moment.at.format("%A, %-d %B %Y").to_string(),
+                    format!("{relative} · week {}", moment.at.iso_week().week()),
+                    moment.at.format("%Y-%m-%d").to_string(),
+                )
+            }
+        }
+        Outcome::Days(days, from, to) => (
+            plural(days, "day"),
+            format!(
+                "{} from {} to {}",
+                weeks_and_days(days),
+                from.at.format("%-d %b %Y"),
+                to.at.format("%-d %b %Y")
+            ),
+            days.to_string(),
+        ),
+    };
+    Some(QuickAnswer {
+        kind: AnswerKind::Date,
+        title,
+        subtitle,
+        value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Wednesday 1 May 2024
+    fn value(query: &str) -> String {
+        let now = Now::fixed("2024-05-01T09:15:00Z");
+        answer(query, &now)
+            .unwrap_or_else(|| panic!("no answer for {query}"))
+            .value
+    }
+
+    #[test]
+    fn arithmetic_on_dates() {
+        assert_eq!(value("today + 3 days"), "2024-05-04");
+        assert_eq!(value("today+2w-1d"), "2024-05-14");
+        assert_eq!(value("2024-01-31 + 1 month"), "2024-02-29");
+        assert_eq!(value("tomorrow + 1 year"), "2025-05-02");
+        assert_eq!(value("now + 2h"), "2024-05-01 11:15");
+        assert_eq!(value("2024-05-01 18:00 - 30 min"), "2024-05-01 17:30");
+    }
+
+    #[test]
+    fn relative_phrases() {
+        assert_eq!(value("in 10 days"), "2024-05-11");
+        assert_eq!(value("3 days ago"), "2024-04-28");
+        assert_eq!(value("2 weeks from tomorrow"), "2024-05-16");
+        assert_eq!(value("next friday"), "2024-05-03");
+        assert_eq!(value("next wednesday"), "2024-05-08");
+        assert_eq!(value("last wednesday"), "2024-04-24");
+        assert_eq!(value("this friday"), "2024-05-03");
+        assert_eq!(value("yesterday"), "2024-04-30");
+    }
+
+    #[test]
+    fn differences() {
+        assert_eq!(value("2024-12-25 - today"), "238");
+        assert_eq!(value("days until 2024-12-25"), "238");
+        assert_eq!(value("days since 2024-01-01"), "121");
+    }
+
+    #[test]
+    fn describes_results() {
+        let now = Now::fixed("2024-05-01T09:15:00Z");
+        let answer = answer("2024-05-08", &now).unwrap();
+        assert_eq!(answer.title, "Wednesday, 8 May 2024");
+        assert_eq!(answer.subtitle, "In 7 days · week 19");
+        let answer = super::answer("days until 2024-05-18", &now).unwrap();
+        assert_eq!(
+            answer.subtitle,
+            "2 weeks, 3 days from 1 May 2024 to 18 May 2024"
+        );
+    }
+
+    #[test]
+    fn ignores_other_queries() {
+        let now = Now::fixed("2024-05-01T09:15:00Z");
+        for query in [
+            "now",
+            "firefox",
+            "12*3",
+            "5 km in mi",
+            "today is nice",
+            "2024-13-01",
+        ] {
+            assert!(answer(query, &now).is_none(), "{query}");
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Quick answers: instant results computed from the query itself (arithmetic, unit and
+//! time zone conversion, date arithmetic), shown above the launcher's item results.
+//! Everything here is offline.
+
+mod calc;
+mod dates;
+mod timezones;
+mod units;
+
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerKind {
+    Calculator,
+    Conversion,
+    TimeZone,
+    Date,
+}
+
+/// One result row.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuickAnswer {
+    pub kind: AnswerKind,
+    /// The answer as displayed, e.g. "= 14.52" or "3.106856 mi".
+    pub title: String,
+    /// What was understood from the query, e.g. "5 km → mi".
+    pub subtitle: String,
+    /// Plain text copied when the row is chosen.
+    pub value: String,
+}
+
+/// The moment a query is answered, so providers can be tested with a fixed clock.
+pub struct Now {
+    pub utc: DateTime<Utc>,
+    /// Wall-clock time in the user's time zone.
+    pub local: NaiveDateTime,
+}
+
+impl Now {
+    pub fn current() -> Self {
+        Self {
+            utc: Utc::now(),
+            local: Local::now().naive_local(),
+        }
+    }
+
+    /// A fixed clock where local time is UTC.
+    #[cfg(test)]
+    pub fn fixed(rfc3339: &str) -> Self {
+        let utc = DateTime::parse_from_rfc3339(rfc3339)
+            .expect("valid timestamp")
+            .with_timezone(&Utc);
+        Self {
+            utc,
+            local: utc.naive_utc(),
+        }
+    }
+}
+
+type Provider = fn(&str, &Now) -> Option<QuickAnswer>;
+
+/// Providers in display order. Each one ignores queries it does not understand.
+const PROVIDERS: &[Provider] = &[
+    calc::answer,
+    units::answer,
+    timezones::answer,
+    dates::answer,
+];
+
+/// Longer queries are prose (or agent prompts), not something to compute.
+const MAX_QUERY_LEN: usize = 200;
+
+pub fn quick_answers(query: &str) -> Vec<QuickAnswer> {
+    quick_answers_at(query, &Now::current())
+}
+
+pub fn quick_answers_at(query: &str, now: &Now) -> Vec<QuickAnswer> {
+    let query = query.trim();
+    if query.is_empty() || query.len() > MAX_QUERY_LEN {
+        return Vec::new();
+    }
+    PROVIDERS
+        .iter()
+        .filter_map(|provider| provider(query, now))
+        .collect()
+}
+
+/// Format a number with at most `max_decimals` decimals and no trailing zeros,
+/// optionally with thousands separators.
+pub(crate) fn format_number(value: f64, max_decimals: usize, grouped: bool) -> String {
+    let magnitude = value.abs();
+    if magnitude >= 1e15 || (magnitude != 0.0 && magnitude < 1e-6) {
+        return format!("{value:e}");
+    }
+    let mut text = format!("{value:.max_decimals$}");
+    if text.contains('.') {
+        text = text.trim_end_matches('0').trim_end_matches('.').to_string();
+    }
+    if text == "-0" {
+        text = "0".to_string();
+    }
+    if !grouped {
+        return text;
+    }
+
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", text.as_str()),
+    };
+    let (integer, fraction) = match unsigned.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (unsigned, None),
+    };
+    let mut grouped_integer = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped_integer.push(',');
+        }
+        grouped_integer.push(digit);
+    }
+    match fraction {
+        Some(fraction) => format!("{sign}{grouped_integer}.{fraction}"),
+        None => format!("{sign}{grouped_integer}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_formatting() {
+        assert_eq!(format_number(14.520000000000001, 10, true), "14.52");
+        assert_eq!(format_number(1234567.5, 10, true), "1,234,567.5");
+        assert_eq!(format_number(-1234.0, 10, true), "-1,234");
+        assert_eq!(format_number(1234.0, 10, false), "1234");
+        assert_eq!(format_number(-0.0000000001, 6, false), "-1e-10");
+        assert_eq!(format_number(-0.0000001, 3, false), "-1e-7");
+        assert_eq!(format_number(-0.0001, 3, false), "0");
+    }
+
+    #[test]
+    fn pipeline_runs_every_provider() {
+        let now = Now::fixed("2024-05-01T12:00:00Z");
+        assert_eq!(
+            quick_answers_at("12*1.21", &now)[0].title,
+            "= 14.52".to_string()
+        );
+        assert_eq!(
+            quick_answers_at("5 km in mi", &now)[0].kind,
+            AnswerKind::Conversion
+        );
+        assert_eq!(
+            quick_answers_at("12:00 utc in tokyo", &now)[0].kind,
+            AnswerKind::TimeZone
+        );
+        assert_eq!(
+            quick_answers_at("today + 3 days", &now)[0].kind,
+            AnswerKind::Date
+        );
+        assert!(quick_answers_at("firefox", &now).is_empty());
+        assert!(quick_answers_at("", &now).is_empty());
+    }
+}
@@ -0,0 +1,259 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::{Tz, TZ_VARIANTS};
+
+use super::{AnswerKind, Now, QuickAnswer};
+
+/// Common abbreviations and names that are not IANA zone or city names.
+const ALIASES: &[(&str, &str)] = &[
+    ("gmt", "Etc/GMT"),
+    ("z", "UTC"),
+    ("pt", "America/Los_Angeles"),
+    ("pst", "America/Los_Angeles"),
+    ("pdt", "America/Los_Angeles"),
+    ("mt", "America/Denver"),
+    ("mst", "America/Denver"),
+    ("mdt", "America/Denver"),
+    ("ct", "America/Chicago"),
+    ("cst", "America/Chicago"),
+    ("cdt", "America/Chicago"),
+    ("et", "America/New_York"),
+    ("est", "America/New_York"),
+    ("edt", "America/New_York"),
+    ("bst", "Europe/London"),
+    ("cet", "Europe/Berlin"),
+    ("cest", "Europe/Berlin"),
+    ("eet", "Europe/Athens"),
+    ("eest", "Europe/Athens"),
+    ("ist", "Asia/Kolkata"),
+    ("jst", "Asia/Tokyo"),
+    ("kst", "Asia/Seoul"),
+    ("sgt", "Asia/Singapore"),
+    ("hkt", "Asia/Hong_Kong"),
+    ("aest", "Australia/Sydney"),
+    ("aedt", "Australia/Sydney"),
+    ("nzst", "Pacific/Auckland"),
+    ("nzdt", "Pacific/Auckland"),
+    ("san francisco", "America/Los_Angeles"),
+    ("sf", "America/Los_Angeles"),
+    ("seattle", "America/Los_Angeles"),
+    ("boston", "America/New_York"),
+    ("washington", "America/New_York"),
+    ("nyc", "America/New_York"),
+    ("beijing", "Asia/Shanghai"),
+    ("mumbai", "Asia/Kolkata"),
+    ("delhi", "Asia/Kolkata"),
+    ("bangalore", "Asia/Kolkata"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Zone {
+    Local,
+    Tz(Tz),
+}
+
+impl Zone {
+    fn name(&self) -> String {
+        match self {
+            Zone::Local => "local time".to_string(),
+            Zone::Tz(tz) => tz.name().rsplit('/').next().unwrap_or("").replace('_', " "),
+        }
+    }
+
+    fn today(&self, now: &Now) -> NaiveDate {
+        match self {
+            Zone::Local => now.local.date(),
+            Zone::Tz(tz) => now.utc.with_timezone(tz).date_naive(),
+        }
+    }
+
+    /// Wall-clock time in this zone, with the zone's abbreviation (or offset).
+    fn wall_clock(&self, utc: DateTime<Utc>) -> (NaiveDateTime, String) {
+        match self {
+            Zone::Local => {
+                let local = utc.with_timezone(&Local);
+                (local.naive_local(), local.format("UTC%:z").to_string())
+            }
+            Zone::Tz(tz) => {
+                let zoned = utc.with_timezone(tz);
+                (zoned.naive_local(), zoned.format("%Z").to_string())
+            }
+        }
+    }
+
+    /// The instant a wall-clock time in this zone refers to. Times skipped by a DST
+    /// change have none; repeated ones use the first occurrence.
+    fn instant(&self, wall_clock: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            Zone::Local => Local
+                .from_local_datetime(&wall_clock)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+            Zone::Tz(tz) => tz
+                .from_local_datetime(&wall_clock)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+        }
+    }
+}
+
+fn find_zone(name: &str) -> Option<Zone> {
+    let name = name.trim().to_ascii_lowercase();
+    if name.is_empty() {
+        return None;
+    }
+    if name == "local" || name == "here" {
+        return Some(Zone::Local);
+    }
+    if let Some((_, iana)) = ALIASES.iter().find(|(alias, _)| *alias == name) {
+        return iana.parse().ok().map(Zone::Tz);
+    }
+    let spaced = name.replace(' ', "_");
+    TZ_VARIANTS
+        .iter()
+        .find(|tz| tz.name().eq_ignore_ascii_case(&spaced))
+        .or_else(|| {
+            TZ_VARIANTS.iter().find(|tz| {
+                tz.name()
+                    .rsplit('/')
+                    .next()
+                    .is_some_and(|city| city.eq_ignore_ascii_case(&spaced))
+            })
+        })
+        .map(|tz| Zone::Tz(*tz))
+}
+
+/// Parse a time at the start of `words` ("15:30", "3pm", "3:30 pm", "noon"). Returns
+/// the time and how many words it used.
+fn parse_time(words: &[&str]) -> Option<(NaiveTime, usize)> {
+    let first = *words.first()?;
+    match first {
+        "noon" => return Some((NaiveTime::from_hms_opt(12, 0, 0)?, 1)),
+        "midnight" => return Some((NaiveTime::MIN, 1)),
+        _ => {}
+    }
+    let (text, used, meridiem) = match words.get(1) {
+        Some(&m @ ("am" | "pm")) => (first, 2, Some(m)),
+        _ => match first
+            .strip_suffix("am")
+            .or_else(|| first.strip_suffix("pm"))
+        {
+            Some(rest) => (rest, 1, Some(&first[first.len() - 2..])),
+            None => (first, 1, None),
+        },
+    };
+    let (hour, minute) = match text.split_once(':') {
+        Some((h, m)) if m.len() == 2 => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        // A bare number is only a time with am/pm ("3pm"), not on its own ("3").
+        None if meridiem.is_some() => (text.parse::<u32>().ok()?, 0),
+        _ => return None,
+    };
+    let hour = match meridiem {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some("am") => hour % 12,
+        Some(_) => hour % 12 + 12,
+        None => hour,
+    };
+    Some((NaiveTime::from_hms_opt(hour, minute, 0)?, used))
+}
+
+/// Answers "10:00 pst in cet", "3pm london to new york", "time in tokyo".
+pub fn answer(query: &str, now: &Now) -> Option<QuickAnswer> {
+    let lower = query.to_ascii_lowercase();
+    let (pos, sep_len) = [" in ", " to "]
+        .iter()
+        .filter_map(|sep| lower.rfind(sep).map(|pos| (pos, sep.len())))
+        .max_by_key(|(pos, _)| *pos)?;
+    let target = find_zone(&lower[pos + sep_len..])?;
+
+    let words: Vec<&str> = lower[..pos].split_whitespace().collect();
+    let (instant, source) = match words.as_slice() {
+        ["time" | "now"] | ["current", "time"] => (now.utc, None),
+        _ => {
+            let (time, used) = match parse_time(&words) {
+                Some((time, used)) => (Some(time), used),
+                None if words.first() == Some(&"now") => (None, 1),
+                None => (None, 0),
+            };
+            let rest = words[used..].join(" ");
+            let source = if rest.is_empty() {
+                // A time on its own is in the local zone; nothing at all is not a query.
+                if used == 0 {
+                    return None;
+                }
+                Zone::Local
+            } else {
+                find_zone(&rest)?
+            };
+            let instant = match time {
+                Some(time) => source.instant(source.today(now).and_time(time))?,
+                None => now.utc,
+            };
+            (instant, Some(source))
+        }
+    };
+
+    let (target_time, target_abbr) = target.wall_clock(instant);
+    let mut title = format!("{} {target_abbr}", target_time.format("%H:%M"));
+    let subtitle = match source {
+        Some(source) => {
+            let (source_time, source_abbr) = source.wall_clock(instant);
+            if source_time.date() != target_time.date() {
+                title.push_str(&target_time.format(" (%a %-d %b)").to_string());
+            }
+            format!(
+                "{} {source_abbr} ({}) → {}",
+                source_time.format("%H:%M"),
+                source.name(),
+                target.name()
+            )
+        }
+        None => format!("Current time in {}", target.name()),
+    };
+
+    Some(QuickAnswer {
+        kind: AnswerKind::TimeZone,
+        title,
+        subtitle,
+        value: target_time.format("%H:%M").to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(query: &str) -> QuickAnswer {
+        let now = Now::fixed("2024-01-15T12:00:00Z");
+        answer(query, &now).unwrap_or_else(|| panic!("no answer for {query}"))
+    }
+
+    #[test]
+    fn converts_between_named_zones() {
+        assert_eq!(convert("10:00 pst in cet").value, "19:00");
+        assert_eq!(convert("3pm london to new york").value, "10:00");
+        assert_eq!(convert("9:30 am utc in tokyo").value, "18:30");
+        assert_eq!(convert("noon utc in Asia/Kolkata").value, "17:30");
+    }
+
+    #[test]
+    fn shows_the_day_when_it_changes() {
+        let answer = convert("11pm utc in tokyo");
+        assert_eq!(answer.value, "08:00");
+        assert_eq!(answer.title, "08:00 JST (Tue 16 Jan)");
+    }
+
+    #[test]
+    fn current_time_in_a_zone() {
+        assert_eq!(convert("time in tokyo").value, "21:00");
+        assert_eq!(convert("now utc in new york").value, "07:00");
+    }
+
+    #[test]
+    fn ignores_other_queries() {
+        let now = Now::fixed("2024-01-15T12:00:00Z");
+        assert!(answer("5 km in mi", &now).is_none());
+        assert!(answer("log in to github", &now).is_none());
+        assert!(answer("3 in tokyo", &now).is_none());
+        assert!(answer("13pm utc in tokyo", &now).is_none());
+    }
+}
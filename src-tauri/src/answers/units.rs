@@ -0,0 +1,229 @@
+use super::{format_number, AnswerKind, Now, QuickAnswer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Length,
+    Mass,
+    Volume,
+    Area,
+    Speed,
+    Duration,
+    Data,
+    Temperature,
+}
+
+struct Unit {
+    /// Accepted spellings; the first one is used in results.
+    names: &'static [&'static str],
+    dimension: Dimension,
+    /// Value in the dimension's base unit is `value * factor + offset`.
+    factor: f64,
+    offset: f64,
+}
+
+const fn unit(names: &'static [&'static str], dimension: Dimension, factor: f64) -> Unit {
+    Unit {
+        names,
+        dimension,
+        factor,
+        offset: 0.0,
+    }
+}
+
+use Dimension::*;
+
+/// Base units: metre, kilogram, litre, square metre, metre per second, second, byte
+/// and kelvin.
+const UNITS: &[Unit] = &[
+    unit(&["mm", "millimeter", "millimetre"], Length, 0.001),
+    unit(&["cm", "centimeter", "centimetre"], Length, 0.01),
+    unit(&["m", "meter", "metre"], Length, 1.0),
+    unit(&["km", "kilometer", "kilometre"], Length, 1000.0),
+    unit(&["in", "inch", "inches", "\""], Length, 0.0254),
+    unit(&["ft", "foot", "feet", "'"], Length, 0.3048),
+    unit(&["yd", "yard"], Length, 0.9144),
+    unit(&["mi", "mile"], Length, 1609.344),
+    unit(&["nmi", "nautical mile"], Length, 1852.0),
+    unit(&["mg", "milligram"], Mass, 1e-6),
+    unit(&["g", "gram"], Mass, 0.001),
+    unit(&["kg", "kilogram", "kilo"], Mass, 1.0),
+    unit(&["t", "tonne", "metric ton"], Mass, 1000.0),
+    unit(&["oz", "ounce"], Mass, 0.028349523125),
+    unit(&["lb", "lbs", "pound"], Mass, 0.45359237),
+    unit(&["st", "stone"], Mass, 6.35029318),
+    unit(&["ml", "milliliter", "millilitre"], Volume, 0.001),
+    unit(&["cl", "centiliter", "centilitre"], Volume, 0.01),
+    unit(&["dl", "deciliter", "decilitre"], Volume, 0.1),
+    unit(&["l", "liter", "litre"], Volume, 1.0),
+    unit(&["m3", "m³", "cubic meter", "cubic metre"], Volume, 1000.0),
+    unit(&["tsp", "teaspoon"], Volume, 0.00492892159375),
+    unit(&["tbsp", "tablespoon"], Volume, 0.01478676478125),
+    unit(&["fl oz", "floz", "fluid ounce"], Volume, 0.0295735295625),
+    unit(&["cup"], Volume, 0.2365882365),
+    unit(&["pt", "pint"], Volume, 0.473176473),
+    unit(&["qt", "quart"], Volume, 0.946352946),
+    unit(&["gal", "gallon"], Volume, 3.785411784),
+    unit(&["cm2", "cm²", "square centimeter"], Area, 1e-4),
+    unit(
+        &["m2", "m²", "sqm", "square meter", "square metre"],
+        Area,
+        1.0,
+    ),
+    unit(&["ha", "hectare"], Area, 1e4),
+    unit(&["km2", "km²", "square kilometer"], Area, 1e6),
+    unit(
+        &["ft2", "ft²", "sqft", "square foot", "square feet"],
+        Area,
+        0.09290304,
+    ),
+    unit(&["ac", "acre"], Area, 4046.8564224),
+    unit(&["mi2", "mi²", "sqmi", "square mile"], Area, 2589988.110336),
+    unit(&["m/s", "mps", "meters per second"], Speed, 1.0),
+    unit(
+        &["km/h", "kmh", "kph", "kilometers per hour"],
+        Speed,
+        1.0 / 3.6,
+    ),
+    unit(&["mph", "mi/h", "miles per hour"], Speed, 0.44704),
+    unit(&["kn", "kt", "knot"], Speed, 1852.0 / 3600.0),
+    unit(&["ft/s", "fps", "feet per second"], Speed, 0.3048),
+    unit(&["ms", "millisecond"], Duration, 0.001),
+    unit(&["s", "sec", "second"], Duration, 1.0),
+    unit(&["min", "minute"], Duration, 60.0),
+    unit(&["h", "hr", "hour"], Duration, 3600.0),
+    unit(&["d", "day"], Duration, 86400.0),
+    unit(&["wk", "week"], Duration, 604800.0),
+    // Julian year, the usual choice for unit conversion.
+    unit(&["yr", "year"], Duration, 31557600.0),
+    unit(&["bit", "b"], Data, 0.125),
+    unit(&["B", "byte"], Data, 1.0),
+    unit(&["KB", "kilobyte"], Data, 1e3),
+    unit(&["MB", "megabyte"], Data, 1e6),
+    unit(&["GB", "gigabyte"], Data, 1e9),
+    unit(&["TB", "terabyte"], Data, 1e12),
+    unit(&["KiB", "kibibyte"], Data, 1024.0),
+    unit(&["MiB", "mebibyte"], Data, 1048576.0),
+    unit(&["GiB", "gibibyte"], Data, 1073741824.0),
+    unit(&["TiB", "tebibyte"], Data, 1099511627776.0),
+    Unit {
+        names: &["°C", "c", "celsius", "degc"],
+        dimension: Temperature,
+        factor: 1.0,
+        offset: 273.15,
+    },
+    Unit {
+        names: &["°F", "f", "fahrenheit", "degf"],
+        dimension: Temperature,
+        factor: 5.0 / 9.0,
+        offset: 459.67 * 5.0 / 9.0,
+    },
+    Unit {
+        names: &["K", "kelvin"],
+        dimension: Temperature,
+        factor: 1.0,
+        offset: 0.0,
+    },
+];
+
+/// Find a unit by name. An exact match wins so that `b`/`B` (bit/byte) stay distinct;
+/// otherwise names match case-insensitively, with or without a plural "s".
+fn find_unit(name: &str) -> Option<&'static Unit> {
+    let name = name.trim();
+    let matches = |name: &str| {
+        UNITS
+            .iter()
+            .find(|u| u.names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+    };
+    UNITS
+        .iter()
+        .find(|u| u.names.contains(&name))
+        .or_else(|| matches(name))
+        .or_else(|| name.strip_suffix(['s', 'S']).and_then(matches))
+}
+
+/// Split "5km" or "5 km" or "-3.5 °C" into the number and the unit text.
+fn split_quantity(text: &str) -> Option<(f64, &str)> {
+    let text = text.trim();
+    let end = text
+        .char_indices()
+        .find(|(i, c)| !(c.is_ascii_digit() || *c == '.' || (*i == 0 && (*c == '-' || *c == '+'))))
+        .map_or(text.len(), |(i, _)| i);
+    let number = text[..end].parse().ok()?;
+    let unit = text[end..].trim();
+    (!unit.is_empty()).then_some((number, unit))
+}
+
+/// Answers "5 km in mi", "100f to c", "3.5 GB as MiB".
+pub fn answer(query: &str, _now: &Now) -> Option<QuickAnswer> {
+    let lower = query.to_ascii_lowercase();
+    // The last separator splits, so "5 in in cm" reads as "5 inches in cm".
+    let (pos, sep_len) = [" in ", " to ", " as "]
+        .iter()
+        .filter_map(|sep| lower.rfind(sep).map(|pos| (pos, sep.len())))
+        .max_by_key(|(pos, _)| *pos)?;
+    let (amount, from_name) = split_quantity(&query[..pos])?;
+    let from = find_unit(from_name)?;
+    let to = find_unit(&query[pos + sep_len..])?;
+    if from.dimension != to.dimension {
+        return None;
+    }
+
+    let base = amount * from.factor + from.offset;
+    let converted = (base - to.offset) / to.factor;
+    let to_name = to.names[0];
+    let from_name = from.names[0];
+    Some(QuickAnswer {
+        kind: AnswerKind::Conversion,
+        title: format!("{} {to_name}", format_number(converted, 6, true)),
+        subtitle: format!("{} {from_name} → {to_name}", format_number(amount, 6, true)),
+        value: format_number(converted, 6, false),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(query: &str) -> String {
+        let now = Now::fixed("2024-05-01T12:00:00Z");
+        answer(query, &now)
+            .unwrap_or_else(|| panic!("no answer for {query}"))
+            .value
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(convert("5 km in mi"), "3.106856");
+        assert_eq!(convert("5km to miles"), "3.106856");
+        assert_eq!(convert("1 mile in feet"), "5280");
+        assert_eq!(convert("2 lb in kg"), "0.907185");
+        assert_eq!(convert("1 gal in l"), "3.785412");
+        assert_eq!(convert("90 km/h in mph"), "55.923407");
+        assert_eq!(convert("1 ha in m2"), "10000");
+        assert_eq!(convert("1.5 h in min"), "90");
+        assert_eq!(convert("5 in in cm"), "12.7");
+    }
+
+    #[test]
+    fn temperatures() {
+        assert_eq!(convert("100 c in f"), "212");
+        assert_eq!(convert("-40 °F to °C"), "-40");
+        assert_eq!(convert("0 K in celsius"), "-273.15");
+    }
+
+    #[test]
+    fn data_sizes_keep_bits_and_bytes_apart() {
+        assert_eq!(convert("8 b in B"), "1");
+        assert_eq!(convert("1 GiB in MB"), "1073.741824");
+        assert_eq!(convert("1 gb in mb"), "1000");
+    }
+
+    #[test]
+    fn rejects_mismatched_or_unknown_units() {
+        let now = Now::fixed("2024-05-01T12:00:00Z");
+        assert!(answer("5 km in kg", &now).is_none());
+        assert!(answer("5 apples in pears", &now).is_none());
+        assert!(answer("log in to github", &now).is_none());
+        assert!(answer("255 in hex", &now).is_none());
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::mpsc;
+use std::time::Duration;
+
+use golaunch_core::apps::{application_dirs, scan_applications, APPLICATIONS_SOURCE};
+use golaunch_core::Database;
+use notify::{RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter};
+
+/// Package managers touch many files per install; wait for things to settle before rescanning.
+const RESCAN_DEBOUNCE: Duration = Duration::from_secs(2);
+
+fn sync_applications(db: &Database, app: &AppHandle) {
+    let items = scan_applications()
+        .iter()
+        .map(|desktop_app| desktop_app.to_managed_item())
+        .collect();
+    match db.sync_managed_items(APPLICATIONS_SOURCE, items) {
+        Ok(report) if report.added + report.updated + report.removed > 0 => {
+            let _ = app.emit("items-changed", ());
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to sync applications: {e}"),
+    }
+}
+
+/// Sync installed applications into the launcher now, then again whenever one of the
+/// XDG applications directories changes.
+pub fn spawn_indexer(app: AppHandle) {
+    std::thread::spawn(move || {
+        let db = match Database::new() {
+            Ok(db) => db,
+            Err(e) => {
+                eprintln!("Application indexer unavailable: {e}");
+                return;
+            }
+        };
+        sync_applications(&db, &app);
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = match notify::recommended_watcher(tx) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                eprintln!("Failed to watch application directories: {e}");
+                return;
+            }
+        };
+        for dir in application_dirs().into_iter().filter(|d| d.is_dir()) {
+            if let Err(e) = watcher.watch(&dir, RecursiveMode::Recursive) {
+                eprintln!("Failed to watch {}: {e}", dir.display());
+            }
+        }
+
+        // Each received event starts a quiet period; rescan once it passes.
+        while rx.recv().is_ok() {
+            while rx.recv_timeout(RESCAN_DEBOUNCE).is_ok() {}
+            sync_applications(&db, &app);
+        }
+    });
+}
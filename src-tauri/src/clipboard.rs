@@ -0,0 +1,187 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use golaunch_core::{ClipboardImage, Database, NewClipboardEntry};
+use tauri::{AppHandle, Emitter};
+
+use crate::context;
+
+/// arboard has no change notification, so the watcher polls.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Unix time (ms) until which clipboard changes come from GoLaunch itself (selection
+/// capture, paste, restore) and must not be recorded.
+static SUPPRESS_UNTIL_MS: AtomicU64 = AtomicU64::new(0);
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Ignore clipboard changes for the given duration.
+pub fn suppress_recording(duration: Duration) {
+    SUPPRESS_UNTIL_MS.fetch_max(now_ms() + duration.as_millis() as u64, Ordering::SeqCst);
+}
+
+enum Contents {
+    Text(String),
+    Image(ClipboardImage),
+}
+
+impl Contents {
+    fn read(clipboard: &mut arboard::Clipboard, include_images: bool) -> Option<Self> {
+        if let Some(text) = clipboard.get_text().ok().filter(|t| !t.trim().is_empty()) {
+            return Some(Self::Text(text));
+        }
+        if !include_images {
+            return None;
+        }
+        let image = clipboard.get_image().ok()?;
+        Some(Self::Image(ClipboardImage {
+            width: image.width,
+            height: image.height,
+            rgba: image.bytes.into_owned(),
+        }))
+    }
+
+    fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        match self {
+            Self::Text(text) => text.hash(&mut hasher),
+            Self::Image(image) => {
+                (image.width, image.height).hash(&mut hasher);
+                image.rgba.hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+
+    fn into_entry(self, source_app: Option<String>) -> NewClipboardEntry {
+        match self {
+            Self::Text(content) => NewClipboardEntry {
+                content_type: "text".to_string(),
+                content,
+                image: None,
+                source_app,
+            },
+            Self::Image(image) => NewClipboardEntry {
+                content_type: "image".to_string(),
+                content: String::new(),
+                image: Some(image),
+                source_app,
+            },
+        }
+    }
+}
+
+/// Record clipboard changes into the history while it is enabled in settings.
+/// Whatever is on the clipboard when recording starts is not recorded.
+pub fn spawn_watcher(app: AppHandle) {
+    std::thread::spawn(move || {
+        let db = match Database::new() {
+            Ok(db) => db,
+            Err(e) => {
+                eprintln!("Clipboard history unavailable: {e}");
+                return;
+            }
+        };
+        let mut clipboard: Option<arboard::Clipboard> = None;
+        let mut last_seen: Option<u64> = None;
+
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let settings = match db.get_clipboard_settings() {
+                Ok(settings) if settings.enabled => settings,
+                _ => {
+                    last_seen = None;
+                    continue;
+                }
+            };
+
+            if clipboard.is_none() {
+                clipboard = arboard::Clipboard::new().ok();
+            }
+            let Some(cb) = clipboard.as_mut() else {
+                continue;
+            };
+            let Some(contents) = Contents::read(cb, settings.capture_images) else {
+                continue;
+            };
+
+            let fingerprint = contents.fingerprint();
+            if last_seen == Some(fingerprint) {
+                continue;
+            }
+            let first_read = last_seen.is_none();
+            last_seen = Some(fingerprint);
+            if first_read || now_ms() < SUPPRESS_UNTIL_MS.load(Ordering::SeqCst) {
+                continue;
+            }
+
+            let source_app = context::foreground_window().process_name;
+            if settings.is_excluded(source_app.as_deref()) {
+                continue;
+            }
+
+            match db.add_clipboard_entry(contents.into_entry(source_app)) {
+                Ok(Some(entry)) => {
+                    let _ = app.emit("clipboard-history-changed", &entry.id);
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("Failed to record clipboard entry: {e}"),
+            }
+        }
+    });
+}
+
+/// Put a history entry back on the clipboard and paste it into the focused app.
+pub fn paste_entry(id: &str) -> Result<(), String> {
+    let db = Database::new()?;
+    let entry = db.get_clipboard_entry(id)?;
+
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|e| format!("Failed to init clipboard: {e}"))?;
+    // The watcher would otherwise count this copy a second time.
+    suppress_recording(Duration::from_secs(1));
+    if entry.content_type == "image" {
+        let image = db.get_clipboard_image(id)?;
+        clipboard.set_image(arboard::ImageData {
+            width: image.width,
+            height: image.height,
+            bytes: image.rgba.into(),
+        })
+    } else {
+        clipboard.set_text(entry.content)
+    }
+    .map_err(|e| format!("Failed to set clipboard: {e}"))?;
+
+    db.touch_clipboard_entry(id)?;
+    context::send_shortcut('v')
+}
+
+/// Put plain text on the clipboard, e.g. a quick answer or a file path.
+pub fn copy_text(text: String) -> Result<(), String> {
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|e| format!("Failed to init clipboard: {e}"))?;
+    #[cfg(target_os = "linux")]
+    {
+        // X11/Wayland clipboards are served by the owning process; keep serving the
+        // text until another app takes over the clipboard.
+        std::thread::spawn(move || {
+            use arboard::SetExtLinux;
+            let _ = clipboard.set().wait().text(text);
+        });
+        Ok(())
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        clipboard
+            .set_text(text)
+            .map_err(|e| format!("Failed to set clipboard: {e}"))
+    }
+}
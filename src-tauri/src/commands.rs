@@ -1,21 +1,36 @@
 use chrono::Timelike;
+use golaunch_core::schedules::parse_run_time;
+use golaunch_core::workflows::WORKFLOW_ACTION_TYPE;
 use golaunch_core::{
-    CommandHistory, CommandSuggestion, Conversation, ConversationMessage, ConversationWithPreview,
-    Database, Item, Memory, NewCommandHistory, NewConversation, NewConversationMessage, NewItem,
-    NewMemory, NewSlashCommand, SlashCommand,
+    ClipboardEntry, ClipboardSettings, CommandHistory, CommandSuggestion, ContextScript,
+    Conversation, ConversationMessage, ConversationWithPreview, Database, FileEntry, HotkeyBinding,
+    Item, ItemAction, Memory, NewCommandHistory, NewConversation, NewConversationMessage, NewItem,
+    NewMemory, NewSlashCommand, RunningProcess, Schedule, SlashCommand, UpdateItem,
+    COPY_ACTION_TYPE, SNIPPET_ACTION_TYPE,
 };
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, LogicalSize, Manager, PhysicalPosition, Position, Size};
 use tokio::sync::Mutex;
 
-use crate::acp::manager::AcpManager;
+use crate::acp::manager::{conversation_title, AcpManager};
+use crate::acp::pool::{AcpPool, SharedManager};
 use crate::acp::registry::{check_agents_installed, fetch_registry};
-use crate::acp::types::{AgentConfig, AgentStatus, RegistryAgent, SessionConfigOptionInfo};
-use crate::context::LaunchContext;
+use crate::acp::types::{
+    AgentConfig, AgentStatus, AgentStatusInfo, RegistryAgent, SessionConfigOptionInfo,
+};
+use crate::context::{ContextProviderInfo, LaunchContext};
+use crate::hotkeys::{self, HotkeyConflict};
+use crate::processes::{shell_command, ProcessState};
+use crate::search::{search_all, SearchProvider, SearchResult};
 use crate::LaunchContextState;
 
-pub struct AcpState(pub Arc<Mutex<AcpManager>>);
+pub struct AcpState(pub Arc<Mutex<AcpPool>>);
+
+/// Registered search providers, queried together by the `search` command.
+pub struct SearchState(pub Vec<Arc<dyn SearchProvider>>);
+
+const DEFAULT_SEARCH_LIMIT: usize = 200;
 
 // --- Existing item commands ---
 
@@ -29,24 +44,182 @@ pub fn search_items(query: String) -> Result<Vec<Item>, String> {
     }
 }
 
+/// Search every provider (or only those listed in `providers`) and merge the results.
+#[tauri::command]
+pub async fn search(
+    state: tauri::State<'_, SearchState>,
+    query: String,
+    providers: Option<Vec<String>>,
+    limit: Option<usize>,
+) -> Result<Vec<SearchResult>, String> {
+    let selected: Vec<Arc<dyn SearchProvider>> = state
+        .0
+        .iter()
+        .filter(|p| {
+            providers
+                .as_ref()
+                .is_none_or(|ids| ids.iter().any(|id| id == p.id()))
+        })
+        .cloned()
+        .collect();
+    Ok(search_all(&selected, &query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT)).await)
+}
+
+#[tauri::command]
+pub fn copy_quick_answer(app: AppHandle, value: String) -> Result<(), String> {
+    crate::clipboard::copy_text(value)?;
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.hide();
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub fn get_all_items() -> Result<Vec<Item>, String> {
     let db = Database::new()?;
     db.list_items(None, false)
 }
 
+/// Execute an item's primary action, or one of its actions when `action_id` is given.
+/// The launcher handles the built-in edit and history actions itself.
+#[tauri::command]
+pub fn execute_item(app: AppHandle, id: String, action_id: Option<String>) -> Result<(), String> {
+    let Some(action_id) = action_id else {
+        return run_item(&app, &id, "", None);
+    };
+    match golaunch_core::builtin_action_name(&action_id) {
+        Some("copy_value") => {
+            let item = Database::new()?.get_item(&id)?;
+            crate::clipboard::copy_text(item.action_value)
+        }
+        Some("disable") => {
+            Database::new()?.update_item(
+                &id,
+                UpdateItem {
+                    enabled: Some(false),
+                    ..Default::default()
+                },
+            )?;
+            let _ = app.emit("items-changed", ());
+            Ok(())
+        }
+        Some(name) => Err(format!("Built-in action '{name}' runs in the launcher")),
+        None => {
+            let action = Database::new()?.get_item_action(&action_id)?;
+            run_item_action(&app, &id, Some(&action), "", None)
+        }
+    }
+}
+
+/// Every action available on an item: its own actions, then the built-in ones.
+#[tauri::command]
+pub fn get_item_actions(id: String) -> Result<Vec<ItemAction>, String> {
+    let db = Database::new()?;
+    db.list_all_item_actions(&id)
+}
+
 #[tauri::command]
-pub fn execute_item(id: String) -> Result<(), String> {
+pub fn update_item(app: AppHandle, id: String, update: UpdateItem) -> Result<Item, String> {
+    let hotkey_changed = update.hotkey.is_some();
+    let db = Database::new()?;
+    let item = db.update_item(&id, update)?;
+    if hotkey_changed {
+        hotkeys::register_all(&app)?;
+    }
+    Ok(item)
+}
+
+#[tauri::command]
+pub fn get_item_history(id: String, limit: Option<usize>) -> Result<Vec<CommandHistory>, String> {
+    let db = Database::new()?;
+    db.get_item_history(&id, limit.unwrap_or(50))
+}
+
+/// Insert a snippet item (or a snippet action of an item) into the app that was focused
+/// before the launcher opened.
+#[tauri::command]
+pub async fn execute_snippet(
+    app: AppHandle,
+    context_state: tauri::State<'_, LaunchContextState>,
+    id: String,
+    args: Option<String>,
+    action_id: Option<String>,
+) -> Result<(), String> {
+    let launch_context = context_state
+        .0
+        .lock()
+        .map(|c| c.clone())
+        .unwrap_or_default();
+
+    // Hide the launcher first so the source app regains focus
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.hide();
+    }
+
+    // Small delay to let the OS switch focus back to the source app
+    tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+
+    let handle = app.clone();
+    tokio::task::spawn_blocking(move || {
+        let action = match action_id {
+            Some(action_id) => Some(Database::new()?.get_item_action(&action_id)?),
+            None => None,
+        };
+        run_item_action(
+            &handle,
+            &id,
+            action.as_ref(),
+            args.as_deref().unwrap_or(""),
+            Some(&launch_context),
+        )
+    })
+    .await
+    .map_err(|e| format!("Task failed: {e}"))?
+}
+
+/// Execute an item, recording usage. `args` and `launch_context` only apply to snippets.
+pub(crate) fn run_item(
+    app: &AppHandle,
+    id: &str,
+    args: &str,
+    launch_context: Option<&LaunchContext>,
+) -> Result<(), String> {
+    run_item_action(app, id, None, args, launch_context)
+}
+
+/// Execute one of an item's custom actions, or its primary action when `action` is `None`.
+fn run_item_action(
+    app: &AppHandle,
+    id: &str,
+    action: Option<&ItemAction>,
+    args: &str,
+    launch_context: Option<&LaunchContext>,
+) -> Result<(), String> {
     let db = Database::new()?;
-    let item = db.get_item(&id)?;
-    db.increment_frequency(&id)?;
+    let item = db.get_item(id)?;
+    let (action_type, action_value) = match action {
+        Some(action) if action.item_id != item.id => {
+            return Err(format!("Action {} does not belong to item {id}", action.id));
+        }
+        Some(action) => (
+            action.action_type.clone(),
+            golaunch_core::expand_action_value(
+                &action.action_type,
+                &action.value,
+                &item.action_value,
+            ),
+        ),
+        None => (item.action_type.clone(), item.action_value.clone()),
+    };
+    db.increment_frequency(id)?;
 
     // Record command history
     let _ = db.record_command(NewCommandHistory {
-        item_id: Some(id.clone()),
-        command_text: item.action_value.clone(),
-        action_type: item.action_type.clone(),
+        item_id: Some(id.to_string()),
+        command_text: action_value.clone(),
+        action_type: action_type.clone(),
         source: Some("launcher".to_string()),
+        exit_code: None,
     });
 
     // Auto-learn: record category preference
@@ -69,41 +242,32 @@ pub fn execute_item(id: String) -> Result<(), String> {
         confidence: Some(0.3),
     });
 
-    match item.action_type.as_str() {
+    match action_type.as_str() {
         "url" => {
-            open::that(&item.action_value).map_err(|e| format!("Failed to open URL: {e}"))?;
+            open::that(&action_value).map_err(|e| format!("Failed to open URL: {e}"))?;
         }
-        "command" => {
-            #[cfg(target_os = "windows")]
-            {
-                std::process::Command::new("cmd")
-                    .args(["/C", &item.action_value])
-                    .spawn()
-                    .map_err(|e| format!("Failed to execute command: {e}"))?;
-            }
-            #[cfg(not(target_os = "windows"))]
-            {
-                std::process::Command::new("sh")
-                    .args(["-c", &item.action_value])
-                    .spawn()
-                    .map_err(|e| format!("Failed to execute command: {e}"))?;
-            }
+        "command" | "script" => {
+            let title = match action {
+                Some(action) => format!("{} ({})", item.title, action.label),
+                None => item.title.clone(),
+            };
+            let processes = &app.state::<ProcessState>().inner().0;
+            processes.spawn(
+                app,
+                shell_command(&action_value),
+                Some(item.id.clone()),
+                title,
+                action_value,
+            )?;
         }
-        "script" => {
-            #[cfg(target_os = "windows")]
-            {
-                std::process::Command::new("cmd")
-                    .args(["/C", &item.action_value])
-                    .spawn()
-                    .map_err(|e| format!("Failed to execute script: {e}"))?;
-            }
-            #[cfg(not(target_os = "windows"))]
-            {
-                std::process::Command::new("sh")
-                    .args(["-c", &item.action_value])
-                    .spawn()
-                    .map_err(|e| format!("Failed to execute script: {e}"))?;
-            }
+        SNIPPET_ACTION_TYPE => {
+            crate::snippets::insert_snippet(&action_value, args, launch_context)?;
+        }
+        WORKFLOW_ACTION_TYPE => {
+            crate::workflows::spawn_workflow(app.clone(), item.clone(), launch_context.cloned());
+        }
+        COPY_ACTION_TYPE => {
+            crate::clipboard::copy_text(action_value)?;
         }
         other => {
             return Err(format!("Unknown action type: {other}"));
@@ -253,6 +417,7 @@ pub fn record_command(
         command_text,
         action_type,
         source: Some("launcher".to_string()),
+        exit_code: None,
     })
 }
 
@@ -278,6 +443,7 @@ pub fn add_item_from_suggestion(
         action_value,
         category,
         tags: None,
+        hotkey: None,
     })
 }
 
@@ -361,6 +527,7 @@ pub fn add_conversation_message(
         conversation_id,
         role,
         content,
+        incomplete: false,
     })
 }
 
@@ -378,37 +545,120 @@ pub fn delete_conversation(id: String) -> Result<bool, String> {
 
 // --- ACP lifecycle commands ---
 
+/// Connect an agent, next to those already connected, and make it the default for
+/// new conversations. Returns the agent's ID.
 #[tauri::command]
 pub async fn acp_connect(
     app: AppHandle,
     state: tauri::State<'_, AcpState>,
     config: AgentConfig,
+) -> Result<String, String> {
+    let agent_id = config.agent_id.clone();
+    let manager = state.inner().0.lock().await.manager(&agent_id);
+    manager.lock().await.connect(app.clone(), config).await?;
+    state.inner().0.lock().await.set_default_agent(&agent_id);
+    Ok(agent_id)
+}
+
+/// Disconnect one agent, or all of them without `agent_id`.
+#[tauri::command]
+pub async fn acp_disconnect(
+    state: tauri::State<'_, AcpState>,
+    agent_id: Option<String>,
 ) -> Result<(), String> {
-    let mut manager = state.inner().0.lock().await;
-    manager.connect(app.clone(), config).await
+    let managers = {
+        let mut pool = state.inner().0.lock().await;
+        match agent_id {
+            Some(agent_id) => pool.remove(&agent_id).into_iter().collect(),
+            None => pool.drain(),
+        }
+    };
+    for manager in managers {
+        manager.lock().await.disconnect().await?;
+    }
+    Ok(())
 }
 
+/// Status of an agent, or of the default agent without `agent_id`, with its uptime,
+/// restarts and last error.
 #[tauri::command]
-pub async fn acp_disconnect(state: tauri::State<'_, AcpState>) -> Result<(), String> {
-    let mut manager = state.inner().0.lock().await;
-    manager.disconnect().await
+pub async fn acp_get_status(
+    state: tauri::State<'_, AcpState>,
+    agent_id: Option<String>,
+) -> Result<AgentStatusInfo, String> {
+    let manager = state.inner().0.lock().await.resolve(agent_id.as_deref());
+    Ok(match manager {
+        Some(manager) => manager.lock().await.status_info(),
+        None => AgentStatusInfo {
+            agent_id,
+            status: AgentStatus::Disconnected,
+            uptime_secs: None,
+            restart_count: 0,
+            last_error: None,
+        },
+    })
 }
 
+/// Every connected agent with its status, to choose one for a conversation.
 #[tauri::command]
-pub async fn acp_get_status(state: tauri::State<'_, AcpState>) -> Result<AgentStatus, String> {
-    let manager = state.inner().0.lock().await;
-    Ok(manager.status())
+pub async fn acp_list_agents(
+    state: tauri::State<'_, AcpState>,
+) -> Result<Vec<AgentStatusInfo>, String> {
+    let managers = state.inner().0.lock().await.managers();
+    let mut agents = Vec::new();
+    for manager in managers {
+        agents.push(manager.lock().await.status_info());
+    }
+    agents.sort_by(|a, b| a.agent_id.cmp(&b.agent_id));
+    Ok(agents)
+}
+
+/// The agent for a conversation: `agent_id` when given, otherwise the agent that
+/// held the conversation if it is still connected, otherwise the default agent.
+async fn route_agent(
+    pool: &Mutex<AcpPool>,
+    agent_id: Option<&str>,
+    conversation_id: Option<&str>,
+) -> Result<SharedManager, String> {
+    let pool = pool.lock().await;
+    if let Some(agent_id) = agent_id {
+        return pool
+            .get(agent_id)
+            .ok_or_else(|| format!("Agent {agent_id} is not connected"));
+    }
+    if let Some(conversation_id) = conversation_id {
+        let held_by = Database::new()?.conversation_agent_session(conversation_id)?;
+        if let Some(manager) = held_by.and_then(|(agent_id, _)| pool.get(&agent_id)) {
+            return Ok(manager);
+        }
+    }
+    pool.resolve(None)
+        .ok_or_else(|| "Not connected to agent".to_string())
 }
 
 // --- ACP prompting commands ---
 
+/// Start the conversation for a first prompt, titled after it. The launcher knows the
+/// conversation's ID before any of its updates arrive.
+#[tauri::command]
+pub fn acp_new_conversation(query: String) -> Result<String, String> {
+    let db = Database::new()?;
+    Ok(db
+        .create_conversation(NewConversation {
+            title: conversation_title(&query),
+        })?
+        .id)
+}
+
 #[tauri::command]
 pub async fn acp_prompt(
     state: tauri::State<'_, AcpState>,
     context_state: tauri::State<'_, LaunchContextState>,
     query: String,
     context_items: Vec<Item>,
-) -> Result<(), String> {
+    conversation_id: Option<String>,
+    agent_id: Option<String>,
+) -> Result<String, String> {
     let db = Database::new()?;
 
     // Read the current launch context
@@ -454,7 +704,13 @@ pub async fn acp_prompt(
     // Fetch registered slash commands
     let slash_commands = db.list_slash_commands().unwrap_or_default();
 
-    let mut manager = state.inner().0.lock().await;
+    let manager = route_agent(
+        &state.inner().0,
+        agent_id.as_deref(),
+        conversation_id.as_deref(),
+    )
+    .await?;
+    let mut manager = manager.lock().await;
     manager
         .prompt(
             &query,
@@ -465,14 +721,40 @@ pub async fn acp_prompt(
             &recent_conversations,
             &launch_context,
             &slash_commands,
+            conversation_id,
         )
         .await
 }
 
+/// Open a conversation in the agent thread: resumes the agent session it was held in
+/// when the agent is connected, and returns its messages.
+#[tauri::command]
+pub async fn acp_open_conversation(
+    state: tauri::State<'_, AcpState>,
+    id: String,
+) -> Result<Vec<ConversationMessage>, String> {
+    let db = Database::new()?;
+    db.get_conversation(&id)?;
+    if let Ok(manager) = route_agent(&state.inner().0, None, Some(&id)).await {
+        let mut manager = manager.lock().await;
+        if matches!(manager.status(), AgentStatus::Connected | AgentStatus::Idle) {
+            if let Err(e) = manager.open_conversation(&id).await {
+                eprintln!("Failed to resume conversation {id}: {e}");
+            }
+        }
+    }
+    db.get_conversation_messages(&id)
+}
+
+/// Cancel the running turn of a conversation, or of the default agent's unused
+/// session without one.
 #[tauri::command]
-pub async fn acp_cancel(state: tauri::State<'_, AcpState>) -> Result<(), String> {
-    let mut manager = state.inner().0.lock().await;
-    manager.cancel().await
+pub async fn acp_cancel(
+    state: tauri::State<'_, AcpState>,
+    conversation_id: Option<String>,
+) -> Result<(), String> {
+    let manager = route_agent(&state.inner().0, None, conversation_id.as_deref()).await?;
+    AcpManager::cancel(&manager, conversation_id.as_deref()).await
 }
 
 // --- ACP permission commands ---
@@ -480,10 +762,12 @@ pub async fn acp_cancel(state: tauri::State<'_, AcpState>) -> Result<(), String>
 #[tauri::command]
 pub async fn acp_resolve_permission(
     state: tauri::State<'_, AcpState>,
+    agent_id: String,
     request_id: String,
     option_id: String,
 ) -> Result<(), String> {
-    let mut manager = state.inner().0.lock().await;
+    let manager = route_agent(&state.inner().0, Some(&agent_id), None).await?;
+    let mut manager = manager.lock().await;
     manager.resolve_permission(&request_id, &option_id).await
 }
 
@@ -492,19 +776,40 @@ pub async fn acp_resolve_permission(
 #[tauri::command]
 pub async fn acp_get_config_options(
     state: tauri::State<'_, AcpState>,
+    agent_id: Option<String>,
+    conversation_id: Option<String>,
 ) -> Result<Vec<SessionConfigOptionInfo>, String> {
-    let manager = state.inner().0.lock().await;
-    Ok(manager.get_config_options())
+    let Ok(manager) = route_agent(
+        &state.inner().0,
+        agent_id.as_deref(),
+        conversation_id.as_deref(),
+    )
+    .await
+    else {
+        return Ok(Vec::new());
+    };
+    let manager = manager.lock().await;
+    Ok(manager.get_config_options(conversation_id.as_deref()))
 }
 
 #[tauri::command]
 pub async fn acp_set_config_option(
     state: tauri::State<'_, AcpState>,
+    agent_id: Option<String>,
+    conversation_id: Option<String>,
     config_id: String,
     value: String,
 ) -> Result<Vec<SessionConfigOptionInfo>, String> {
-    let mut manager = state.inner().0.lock().await;
-    manager.set_config_option(&config_id, &value).await
+    let manager = route_agent(
+        &state.inner().0,
+        agent_id.as_deref(),
+        conversation_id.as_deref(),
+    )
+    .await?;
+    let mut manager = manager.lock().await;
+    manager
+        .set_config_option(conversation_id.as_deref(), &config_id, &value)
+        .await
 }
 
 // --- ACP registry commands ---
@@ -628,6 +933,30 @@ pub fn get_launch_context(
     Ok(lock.clone())
 }
 
+#[tauri::command]
+pub fn list_context_providers() -> Result<Vec<ContextProviderInfo>, String> {
+    let db = Database::new()?;
+    crate::context::list_context_providers(&db)
+}
+
+#[tauri::command]
+pub fn set_context_provider_enabled(provider_id: String, enabled: bool) -> Result<(), String> {
+    let db = Database::new()?;
+    db.set_context_provider_enabled(&provider_id, enabled)
+}
+
+#[tauri::command]
+pub fn set_context_script(name: String, command: String) -> Result<ContextScript, String> {
+    let db = Database::new()?;
+    db.set_context_script(&name, &command)
+}
+
+#[tauri::command]
+pub fn remove_context_script(name: String) -> Result<bool, String> {
+    let db = Database::new()?;
+    db.remove_context_script(&name)
+}
+
 #[tauri::command]
 pub async fn type_text_to_app(app: AppHandle, text: String) -> Result<(), String> {
     // Hide the launcher first so the source app regains focus
@@ -668,6 +997,7 @@ pub fn record_rewrite(prompt: String) -> Result<(), String> {
         command_text: prompt,
         action_type: "rewrite".to_string(),
         source: Some("selection_rewrite".to_string()),
+        exit_code: None,
     })?;
     Ok(())
 }
@@ -703,12 +1033,17 @@ pub fn add_slash_command(
     name: String,
     description: String,
     script_path: String,
+    hotkey: Option<String>,
 ) -> Result<SlashCommand, String> {
+    if let Some(ref h) = hotkey {
+        hotkeys::parse_shortcut(h)?;
+    }
     let db = Database::new()?;
     db.add_slash_command(NewSlashCommand {
         name,
         description,
         script_path,
+        hotkey,
     })
 }
 
@@ -718,10 +1053,23 @@ pub fn remove_slash_command(name: String) -> Result<bool, String> {
     db.remove_slash_command_by_name(&name)
 }
 
+/// Run a slash command and wait for it, returning its output. It is listed with the
+/// other running processes meanwhile.
 #[tauri::command]
-pub fn execute_slash_command(name: String, args: String) -> Result<String, String> {
+pub async fn execute_slash_command(
+    app: AppHandle,
+    name: String,
+    args: String,
+) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || run_slash_command(&app, &name, &args))
+        .await
+        .map_err(|e| format!("Task failed: {e}"))?
+}
+
+/// Blocking body of `execute_slash_command`, shared with slash-command hotkeys.
+pub fn run_slash_command(app: &AppHandle, name: &str, args: &str) -> Result<String, String> {
     let db = Database::new()?;
-    let cmd = db.get_slash_command_by_name(&name)?;
+    let cmd = db.get_slash_command_by_name(name)?;
     db.increment_slash_command_usage(&cmd.id)?;
 
     let _ = db.record_command(NewCommandHistory {
@@ -729,34 +1077,375 @@ pub fn execute_slash_command(name: String, args: String) -> Result<String, Strin
         command_text: format!("/{} {}", name, args),
         action_type: "slash_command".to_string(),
         source: Some("launcher".to_string()),
+        exit_code: None,
     });
 
     #[cfg(target_os = "windows")]
-    let output = {
-        std::process::Command::new("powershell")
-            .args(["-ExecutionPolicy", "Bypass", "-File", &cmd.script_path])
-            .args(args.split_whitespace().collect::<Vec<&str>>())
-            .output()
-            .map_err(|e| format!("Failed to execute script: {e}"))?
+    let mut command = {
+        let mut command = std::process::Command::new("powershell");
+        command.args(["-ExecutionPolicy", "Bypass", "-File", &cmd.script_path]);
+        command
     };
 
     #[cfg(not(target_os = "windows"))]
-    let output = {
-        std::process::Command::new("sh")
-            .arg(&cmd.script_path)
-            .args(args.split_whitespace().collect::<Vec<&str>>())
-            .output()
-            .map_err(|e| format!("Failed to execute script: {e}"))?
+    let mut command = {
+        let mut command = std::process::Command::new("sh");
+        command.arg(&cmd.script_path);
+        command
+    };
+    command.args(args.split_whitespace());
+
+    let processes = Arc::clone(&app.state::<ProcessState>().inner().0);
+    let pid = processes.spawn(
+        app,
+        command,
+        None,
+        format!("/{name}"),
+        format!("/{name} {args}").trim_end().to_string(),
+    )?;
+    let exit_code = processes.wait(pid)?;
+    let output = processes.tail(pid, usize::MAX)?.join("\n");
+    if exit_code == Some(0) {
+        Ok(output)
+    } else {
+        Err(format!("Script failed:\n{output}"))
+    }
+}
+
+// --- Running process commands ---
+
+#[tauri::command]
+pub fn list_running(processes: tauri::State<'_, ProcessState>) -> Vec<RunningProcess> {
+    processes.0.list()
+}
+
+#[tauri::command]
+pub fn kill_running(processes: tauri::State<'_, ProcessState>, pid: u32) -> Result<(), String> {
+    processes.0.kill(pid)
+}
+
+#[tauri::command]
+pub fn tail_running(
+    processes: tauri::State<'_, ProcessState>,
+    pid: u32,
+    lines: Option<usize>,
+) -> Result<Vec<String>, String> {
+    processes
+        .0
+        .tail(pid, lines.unwrap_or(crate::control::DEFAULT_TAIL_LINES))
+}
+
+fn process_to_item(process: RunningProcess, last_line: Option<String>) -> Item {
+    let started = chrono::DateTime::parse_from_rfc3339(&process.started_at)
+        .map(|t| t.format("%H:%M:%S").to_string())
+        .unwrap_or_default();
+    let status = match (process.running, process.exit_code) {
+        (true, _) => "running".to_string(),
+        (false, Some(code)) => format!("exited {code}"),
+        (false, None) => "killed".to_string(),
     };
+    let mut subtitle = format!("pid {} · {status} · started {started}", process.pid);
+    if let Some(line) = last_line.filter(|l| !l.trim().is_empty()) {
+        subtitle.push_str(" · ");
+        subtitle.push_str(line.trim());
+    }
+    Item {
+        id: format!("process:{}", process.pid),
+        title: process.title,
+        subtitle: Some(subtitle),
+        icon: Some(if process.running { "▶️" } else { "⏹️" }.to_string()),
+        action_type: "process".to_string(),
+        action_value: process.pid.to_string(),
+        category: "Processes".to_string(),
+        tags: process.command,
+        frequency: 0,
+        enabled: process.running,
+        hotkey: None,
+        source: None,
+        created_at: process.started_at.clone(),
+        updated_at: process.started_at,
+    }
+}
+
+/// Processes shaped as launcher items (for the `ps:` prefix), newest first.
+#[tauri::command]
+pub fn search_running_items(processes: tauri::State<'_, ProcessState>, query: String) -> Vec<Item> {
+    let query = query.trim().to_lowercase();
+    processes
+        .0
+        .list()
+        .into_iter()
+        .filter(|p| {
+            query.is_empty()
+                || p.title.to_lowercase().contains(&query)
+                || p.command.to_lowercase().contains(&query)
+        })
+        .map(|p| {
+            let last_line = processes
+                .0
+                .tail(p.pid, 1)
+                .ok()
+                .and_then(|l| l.into_iter().next());
+            process_to_item(p, last_line)
+        })
+        .collect()
+}
+
+/// Copy a process's recent output ("copy_output") or stop it ("kill").
+#[tauri::command]
+pub fn process_item_action(
+    app: AppHandle,
+    processes: tauri::State<'_, ProcessState>,
+    pid: u32,
+    action: String,
+) -> Result<(), String> {
+    match action.as_str() {
+        "kill" => processes.0.kill(pid),
+        "copy_output" => {
+            let output = processes
+                .0
+                .tail(pid, crate::control::DEFAULT_TAIL_LINES)?
+                .join("\n");
+            crate::clipboard::copy_text(output)?;
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.hide();
+            }
+            Ok(())
+        }
+        other => Err(format!("Unknown process action: {other}")),
+    }
+}
+
+// --- Hotkey commands ---
+
+#[tauri::command]
+pub fn get_hotkeys() -> Result<Vec<HotkeyBinding>, String> {
+    let db = Database::new()?;
+    db.list_hotkey_bindings()
+}
+
+/// Change the launcher toggle shortcut and re-register all hotkeys immediately.
+/// Returns the bindings that could not be registered.
+#[tauri::command]
+pub fn set_toggle_hotkey(app: AppHandle, hotkey: String) -> Result<Vec<HotkeyConflict>, String> {
+    hotkeys::parse_shortcut(&hotkey)?;
+    let db = Database::new()?;
+    db.set_toggle_hotkey(&hotkey)?;
+    hotkeys::register_all(&app)
+}
+
+/// Assign (or clear, with `None`) the global hotkey of an item.
+#[tauri::command]
+pub fn set_item_hotkey(
+    app: AppHandle,
+    id: String,
+    hotkey: Option<String>,
+) -> Result<Vec<HotkeyConflict>, String> {
+    if let Some(ref h) = hotkey {
+        hotkeys::parse_shortcut(h)?;
+    }
+    let db = Database::new()?;
+    db.update_item(
+        &id,
+        UpdateItem {
+            hotkey: Some(hotkey.unwrap_or_default()),
+            ..Default::default()
+        },
+    )?;
+    hotkeys::register_all(&app)
+}
+
+#[tauri::command]
+pub fn reload_hotkeys(app: AppHandle) -> Result<Vec<HotkeyConflict>, String> {
+    hotkeys::register_all(&app)
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+// --- Clipboard history commands ---
 
-    if output.status.success() {
-        Ok(stdout)
+/// How many history entries `clip:` search returns.
+const CLIPBOARD_SEARCH_LIMIT: usize = 50;
+
+fn clipboard_entry_to_item(entry: ClipboardEntry) -> Item {
+    let subtitle = match &entry.source_app {
+        Some(app) => format!("{} · {}", app, entry.last_copied_at),
+        None => entry.last_copied_at.clone(),
+    };
+    Item {
+        id: format!("clipboard:{}", entry.id),
+        title: entry.preview,
+        subtitle: Some(subtitle),
+        icon: Some(if entry.pinned { "📌" } else { "📋" }.to_string()),
+        action_type: "clipboard".to_string(),
+        action_value: entry.id,
+        category: "Clipboard".to_string(),
+        tags: entry.content_type,
+        frequency: entry.copy_count,
+        enabled: true,
+        hotkey: None,
+        source: None,
+        created_at: entry.created_at,
+        updated_at: entry.last_copied_at,
+    }
+}
+
+/// Clipboard history entries shaped as launcher items (for the `clip:` prefix).
+#[tauri::command]
+pub fn search_clipboard_items(query: String) -> Result<Vec<Item>, String> {
+    let db = Database::new()?;
+    let entries = if query.trim().is_empty() {
+        db.list_clipboard_entries(CLIPBOARD_SEARCH_LIMIT)?
     } else {
-        Err(format!("Script failed:\n{stderr}\n{stdout}"))
+        db.search_clipboard_entries(query.trim(), CLIPBOARD_SEARCH_LIMIT)?
+    };
+    Ok(entries.into_iter().map(clipboard_entry_to_item).collect())
+}
+
+#[tauri::command]
+pub async fn paste_clipboard_entry(app: AppHandle, id: String) -> Result<(), String> {
+    // Hide the launcher first so the source app regains focus
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.hide();
     }
+
+    // Small delay to let the OS switch focus back to the source app
+    tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+
+    tokio::task::spawn_blocking(move || crate::clipboard::paste_entry(&id))
+        .await
+        .map_err(|e| format!("Task failed: {e}"))?
+}
+
+#[tauri::command]
+pub fn get_clipboard_settings() -> Result<ClipboardSettings, String> {
+    let db = Database::new()?;
+    db.get_clipboard_settings()
+}
+
+#[tauri::command]
+pub fn set_clipboard_settings(settings: ClipboardSettings) -> Result<(), String> {
+    let db = Database::new()?;
+    db.set_clipboard_settings(&settings)
+}
+
+const FILE_SEARCH_LIMIT: usize = 50;
+
+fn file_entry_to_item(entry: FileEntry) -> Item {
+    let parent = std::path::Path::new(&entry.path)
+        .parent()
+        .map(|p| p.to_string_lossy().into_owned());
+    let modified = entry
+        .modified
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_default();
+    Item {
+        id: format!("file:{}", entry.path),
+        title: entry.name,
+        subtitle: parent,
+        icon: Some(if entry.is_dir { "📁" } else { "📄" }.to_string()),
+        action_type: "file".to_string(),
+        action_value: entry.path,
+        category: "Files".to_string(),
+        tags: if entry.is_dir { "directory" } else { "file" }.to_string(),
+        frequency: 0,
+        enabled: true,
+        hotkey: None,
+        source: None,
+        created_at: modified.clone(),
+        updated_at: modified,
+    }
+}
+
+/// Indexed files shaped as launcher items (for the `file:` and `~` prefixes).
+#[tauri::command]
+pub fn search_file_items(query: String) -> Result<Vec<Item>, String> {
+    let db = Database::new()?;
+    let entries = golaunch_core::files::search_file_index(&db, &query, FILE_SEARCH_LIMIT)?;
+    Ok(entries.into_iter().map(file_entry_to_item).collect())
+}
+
+/// Open, reveal or copy the path of a file search result.
+#[tauri::command]
+pub fn file_item_action(app: AppHandle, path: String, action: String) -> Result<(), String> {
+    crate::files::run_action(&path, &action)?;
+    if action != "copy_path" {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.hide();
+        }
+    }
+    Ok(())
+}
+
+// --- Schedules ---
+
+/// Schedules shaped as launcher items (for the `schedule:` prefix), showing their
+/// next and last runs.
+fn schedule_to_item(schedule: Schedule) -> Item {
+    let local = |time: &Option<String>| {
+        time.as_deref()
+            .and_then(parse_run_time)
+            .map(|t| t.format("%a %Y-%m-%d %H:%M").to_string())
+    };
+    let mut subtitle = vec![schedule.spec.clone()];
+    if let Some(next) = local(&schedule.next_run_at) {
+        subtitle.push(format!("next {next}"));
+    }
+    if let Some(last) = local(&schedule.last_run_at) {
+        let status = schedule.last_status.as_deref().unwrap_or("?");
+        subtitle.push(format!("last {last} ({status})"));
+    }
+    Item {
+        id: format!("schedule:{}", schedule.id),
+        title: schedule
+            .target_name
+            .unwrap_or_else(|| format!("(missing {})", schedule.target_type)),
+        subtitle: Some(subtitle.join(" · ")),
+        icon: None,
+        action_type: "schedule".to_string(),
+        action_value: schedule.id,
+        category: "Schedules".to_string(),
+        tags: schedule.spec,
+        frequency: 0,
+        enabled: schedule.enabled,
+        hotkey: None,
+        source: None,
+        created_at: schedule.created_at.clone(),
+        updated_at: schedule.created_at,
+    }
+}
+
+#[tauri::command]
+pub fn list_schedules() -> Result<Vec<Schedule>, String> {
+    Database::new()?.list_schedules()
+}
+
+#[tauri::command]
+pub fn search_schedule_items(query: String) -> Result<Vec<Item>, String> {
+    let query = query.trim().to_lowercase();
+    Ok(Database::new()?
+        .list_schedules()?
+        .into_iter()
+        .filter(|s| {
+            query.is_empty()
+                || s.spec.to_lowercase().contains(&query)
+                || s.target_name
+                    .as_deref()
+                    .is_some_and(|name| name.to_lowercase().contains(&query))
+        })
+        .map(schedule_to_item)
+        .collect())
+}
+
+/// Run a schedule right away; the result is recorded like a scheduled run.
+#[tauri::command]
+pub async fn run_schedule_now(app: AppHandle, id: String) -> Result<i32, String> {
+    tokio::task::spawn_blocking(move || {
+        let db = Database::new()?;
+        let schedule = db.get_schedule(&id)?;
+        crate::scheduler::run_now(&app, &db, &schedule)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {e}"))?
 }
 
 // --- Per-agent env var commands ---
@@ -1,3 +1,8 @@
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use golaunch_core::Database;
 use serde::{Deserialize, Serialize};
 
 /// Context captured at the moment the launcher is invoked.
@@ -12,105 +17,837 @@ pub struct LaunchContext {
     pub source_window_title: Option<String>,
     /// Process name of the source application (e.g. "Code.exe", "chrome.exe").
     pub source_process_name: Option<String>,
+    /// Process id of the source application, when the platform exposes it.
+    #[serde(default)]
+    pub source_process_id: Option<u32>,
+    /// Structured fields added by the enabled context providers (cwd, git, ...).
+    #[serde(default)]
+    pub sections: Vec<ContextSection>,
+}
+
+/// A group of fields contributed by one [`ContextProvider`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextSection {
+    /// Provider id, e.g. "git" or "script:k8s".
+    pub provider: String,
+    /// Heading used when rendering the section into the agent prompt.
+    pub label: String,
+    pub fields: Vec<ContextField>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextField {
+    pub key: String,
+    pub value: String,
+}
+
+impl LaunchContext {
+    /// Look up a field contributed by an earlier provider.
+    pub fn field(&self, provider: &str, key: &str) -> Option<&str> {
+        self.sections
+            .iter()
+            .find(|s| s.provider == provider)?
+            .fields
+            .iter()
+            .find(|f| f.key == key)
+            .map(|f| f.value.as_str())
+    }
 }
 
 /// Capture the full launch context from the OS.
 /// Must be called *before* the launcher window takes focus.
-pub fn capture_launch_context() -> LaunchContext {
-    let source_window_title = get_foreground_window_title();
-    let source_process_name = get_foreground_process_name();
+pub fn capture_launch_context(db: Option<&Database>) -> LaunchContext {
+    let window = foreground_window();
     let clipboard_text = get_clipboard_text();
-    let selected_text = capture_selected_text(&clipboard_text);
+    let selected_text = if selection_capture_enabled(db) {
+        capture_selected_text()
+    } else {
+        None
+    };
 
     LaunchContext {
         clipboard_text,
         selected_text,
-        source_window_title,
-        source_process_name,
+        source_window_title: window.title,
+        source_process_name: window.process_name,
+        source_process_id: window.process_id,
+        sections: Vec::new(),
+    }
+}
+
+// ── Context providers ──
+
+/// Adds structured fields (repo, file, cwd, ...) to a captured launch context.
+///
+/// Providers only look at what was captured from the source window, so they can run
+/// after the launcher is shown. They run in order and may read fields from earlier
+/// providers via [`LaunchContext::field`].
+pub trait ContextProvider: Send + Sync {
+    /// Stable id, used for the `context.provider.<id>` enable setting.
+    fn id(&self) -> String;
+    fn label(&self) -> String;
+    fn description(&self) -> String;
+    fn provide(&self, ctx: &LaunchContext) -> Vec<ContextField>;
+}
+
+/// A provider as shown in settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextProviderInfo {
+    pub id: String,
+    pub label: String,
+    pub description: String,
+    pub enabled: bool,
+    /// Shell command for script providers, `None` for built-ins.
+    pub command: Option<String>,
+}
+
+/// How long a single provider subprocess (git, user script) may run.
+const PROVIDER_TIMEOUT: Duration = Duration::from_millis(1500);
+/// Output beyond this many bytes from a provider subprocess is dropped.
+const PROVIDER_OUTPUT_LIMIT: usize = 4096;
+
+/// Built-in providers followed by the user's shell-script providers.
+pub fn context_providers(db: &Database) -> Vec<Box<dyn ContextProvider>> {
+    let mut providers: Vec<Box<dyn ContextProvider>> = vec![
+        Box::new(CwdProvider),
+        Box::new(EditorFileProvider),
+        Box::new(GitProvider),
+        Box::new(BrowserProvider),
+    ];
+    for script in db.list_context_scripts().unwrap_or_default() {
+        providers.push(Box::new(ScriptProvider {
+            name: script.name,
+            command: script.command,
+        }));
+    }
+    providers
+}
+
+pub fn list_context_providers(db: &Database) -> Result<Vec<ContextProviderInfo>, String> {
+    let scripts = db.list_context_scripts()?;
+    context_providers(db)
+        .into_iter()
+        .map(|provider| {
+            let id = provider.id();
+            let command = scripts
+                .iter()
+                .find(|s| format!("script:{}", s.name) == id)
+                .map(|s| s.command.clone());
+            Ok(ContextProviderInfo {
+                enabled: db.is_context_provider_enabled(&id)?,
+                label: provider.label(),
+                description: provider.description(),
+                command,
+                id,
+            })
+        })
+        .collect()
+}
+
+/// Run every enabled provider against the context, appending their sections.
+pub fn apply_context_providers(db: &Database, ctx: &mut LaunchContext) {
+    ctx.sections.clear();
+    for provider in context_providers(db) {
+        let id = provider.id();
+        // User scripts run arbitrary commands, so only when the setting says so.
+        let enabled = db
+            .is_context_provider_enabled(&id)
+            .unwrap_or(!id.starts_with("script:"));
+        if !enabled {
+            continue;
+        }
+        let fields = provider.provide(ctx);
+        if !fields.is_empty() {
+            ctx.sections.push(ContextSection {
+                provider: id,
+                label: provider.label(),
+                fields,
+            });
+        }
+    }
+}
+
+fn field(key: &str, value: impl Into<String>) -> ContextField {
+    ContextField {
+        key: key.to_string(),
+        value: value.into(),
+    }
+}
+
+/// Lowercased process name without a `.exe` suffix, for matching across platforms.
+fn process_key(ctx: &LaunchContext) -> String {
+    let name = ctx.source_process_name.as_deref().unwrap_or_default();
+    name.trim_end_matches(".exe").to_lowercase()
+}
+
+/// Run a command with a timeout, returning stdout if it exits successfully.
+/// Stdout is drained on a separate thread so a chatty child can't block on a full pipe;
+/// only the first `PROVIDER_OUTPUT_LIMIT` bytes are kept, and a background process that
+/// inherited the pipe can't hold the result past the timeout.
+fn run_provider_command(mut cmd: Command) -> Option<String> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    let mut stdout = child.stdout.take()?;
+    let (sender, output) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = (&mut stdout)
+            .take(PROVIDER_OUTPUT_LIMIT as u64)
+            .read_to_end(&mut buf);
+        let _ = sender.send(buf);
+        let _ = std::io::copy(&mut stdout, &mut std::io::sink());
+    });
+
+    let deadline = Instant::now() + PROVIDER_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    };
+
+    if !status.success() {
+        return None;
+    }
+    let output = output
+        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .ok()?;
+    Some(String::from_utf8_lossy(&output).trim().to_string())
+}
+
+/// Working directory of the source application (Linux only, via `/proc`).
+struct CwdProvider;
+
+impl ContextProvider for CwdProvider {
+    fn id(&self) -> String {
+        "cwd".to_string()
+    }
+
+    fn label(&self) -> String {
+        "Working directory".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Current directory of the source app (or the newest shell inside a terminal)".to_string()
+    }
+
+    fn provide(&self, ctx: &LaunchContext) -> Vec<ContextField> {
+        #[cfg(target_os = "linux")]
+        {
+            let Some((pid, cwd)) = ctx.source_process_id.and_then(linux::working_directory) else {
+                return Vec::new();
+            };
+            let mut fields = vec![field("cwd", cwd)];
+            if Some(pid) != ctx.source_process_id {
+                if let Some(name) = linux::process_name(pid) {
+                    fields.push(field("shell", name));
+                }
+            }
+            fields
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = ctx;
+            Vec::new()
+        }
+    }
+}
+
+/// The file open in a known editor, parsed from its window title.
+struct EditorFileProvider;
+
+impl ContextProvider for EditorFileProvider {
+    fn id(&self) -> String {
+        "editor".to_string()
+    }
+
+    fn label(&self) -> String {
+        "Editor".to_string()
+    }
+
+    fn description(&self) -> String {
+        "File and project open in VS Code, JetBrains IDEs, Sublime Text, Vim, Zed".to_string()
+    }
+
+    fn provide(&self, ctx: &LaunchContext) -> Vec<ContextField> {
+        let Some(title) = ctx.source_window_title.as_deref() else {
+            return Vec::new();
+        };
+        let Some(file) = parse_editor_title(&process_key(ctx), title) else {
+            return Vec::new();
+        };
+
+        let mut fields = vec![field("file", file.name)];
+        if let Some(project) = file.project {
+            fields.push(field("project", project));
+        }
+        if let Some(path) = file.path {
+            fields.push(field("path", path));
+        }
+        fields
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct EditorFile {
+    name: String,
+    project: Option<String>,
+    /// Full path, when the title contains one.
+    path: Option<String>,
+}
+
+const TITLE_SEPARATORS: [&str; 3] = [" - ", " — ", " – "];
+
+fn split_title(title: &str) -> Vec<&str> {
+    let mut parts = vec![title];
+    for sep in TITLE_SEPARATORS {
+        parts = parts.into_iter().flat_map(|p| p.split(sep)).collect();
+    }
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// Drop unsaved-changes markers editors prepend to the file name.
+fn strip_modified_marker(name: &str) -> &str {
+    name.trim_start_matches(['●', '•', '*']).trim()
+}
+
+/// Expand a leading `~` and join a directory with a file name.
+fn join_path(dir: &str, name: &str) -> String {
+    let dir = match (dir.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) => format!("{}{rest}", home.display()),
+        _ => dir.to_string(),
+    };
+    std::path::Path::new(&dir)
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Split "name (dir)" as used by Vim, gedit and Sublime Text titles.
+fn split_name_and_paren(segment: &str) -> (&str, Option<&str>) {
+    match segment.rfind(" (") {
+        Some(idx) if segment.ends_with(')') => {
+            (&segment[..idx], Some(&segment[idx + 2..segment.len() - 1]))
+        }
+        _ => (segment, None),
+    }
+}
+
+fn parse_editor_title(process: &str, title: &str) -> Option<EditorFile> {
+    let parts = split_title(title);
+    let first = *parts.first()?;
+
+    match process {
+        // "● main.rs - my-project - Visual Studio Code"
+        "code" | "code-oss" | "codium" | "vscodium" | "cursor" | "windsurf" | "electron" => {
+            if parts.len() < 2 {
+                return None;
+            }
+            Some(EditorFile {
+                name: strip_modified_marker(first).to_string(),
+                project: (parts.len() >= 3).then(|| parts[1].to_string()),
+                path: None,
+            })
+        }
+        // "my-project – src/main.rs" (JetBrains uses an en dash)
+        p if [
+            "idea",
+            "pycharm",
+            "webstorm",
+            "goland",
+            "clion",
+            "rider",
+            "rustrover",
+            "phpstorm",
+            "rubymine",
+            "datagrip",
+            "studio",
+        ]
+        .iter()
+        .any(|ide| p.starts_with(ide)) =>
+        {
+            let (project, file) = title.split_once(" – ")?;
+            let file = strip_modified_marker(file.trim());
+            let name = file.rsplit(['/', '\\']).next().unwrap_or(file);
+            Some(EditorFile {
+                name: name.to_string(),
+                project: Some(project.trim().to_string()),
+                path: None,
+            })
+        }
+        // "~/code/app/main.rs (app) - Sublime Text"
+        "sublime_text" | "sublime text" | "subl" => {
+            let (path, project) = split_name_and_paren(strip_modified_marker(first));
+            let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+            let is_path = path.contains('/') || path.contains('\\');
+            Some(EditorFile {
+                name: name.to_string(),
+                project: project.map(String::from),
+                path: is_path.then(|| join_path(path, "")),
+            })
+        }
+        // "main.rs — app" or "app — main.rs"
+        "zed" | "zed-editor" => {
+            let name = parts.iter().find(|p| looks_like_file(p))?;
+            Some(EditorFile {
+                name: strip_modified_marker(name).to_string(),
+                project: parts.iter().find(|p| *p != name).map(|p| p.to_string()),
+                path: None,
+            })
+        }
+        // "main.rs (~/code/app) - VIM" / "main.rs + (~/code/app) - NVIM" / "main.rs (~/code/app) - gedit"
+        _ => {
+            let last = parts.last()?.to_lowercase();
+            let is_vim = last.ends_with("vim");
+            if !is_vim
+                && !matches!(
+                    process,
+                    "gvim" | "gedit" | "gnome-text-editor" | "kate" | "mousepad"
+                )
+            {
+                return None;
+            }
+            let (name, dir) = split_name_and_paren(first);
+            let name = name.trim_end_matches(" +").trim_end_matches(" [+]").trim();
+            if name.is_empty() {
+                return None;
+            }
+            Some(EditorFile {
+                name: name.to_string(),
+                project: None,
+                path: dir.map(|d| join_path(d, name)),
+            })
+        }
+    }
+}
+
+fn looks_like_file(segment: &str) -> bool {
+    let name = strip_modified_marker(segment);
+    !name.contains(' ')
+        && name
+            .rsplit_once('.')
+            .is_some_and(|(stem, ext)| !stem.is_empty() && !ext.is_empty())
+}
+
+/// Branch, remote and dirty status of the repository the user is working in.
+struct GitProvider;
+
+impl GitProvider {
+    /// Prefer the editor's file location, then the source app's working directory.
+    fn directory(ctx: &LaunchContext) -> Option<String> {
+        if let Some(path) = ctx.field("editor", "path") {
+            if let Some(parent) = std::path::Path::new(path).parent() {
+                if parent.is_dir() {
+                    return Some(parent.to_string_lossy().into_owned());
+                }
+            }
+        }
+        if let Some(cwd) = ctx.field("cwd", "cwd") {
+            return Some(cwd.to_string());
+        }
+        #[cfg(target_os = "linux")]
+        {
+            ctx.source_process_id
+                .and_then(linux::working_directory)
+                .map(|(_, cwd)| cwd)
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            None
+        }
+    }
+
+    fn git(dir: &str, args: &[&str]) -> Option<String> {
+        let mut cmd = Command::new("git");
+        cmd.args(["--no-optional-locks", "-C", dir]).args(args);
+        run_provider_command(cmd).filter(|out| !out.is_empty())
+    }
+}
+
+impl ContextProvider for GitProvider {
+    fn id(&self) -> String {
+        "git".to_string()
+    }
+
+    fn label(&self) -> String {
+        "Git repository".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Branch, remote and uncommitted changes for the source app's directory".to_string()
+    }
+
+    fn provide(&self, ctx: &LaunchContext) -> Vec<ContextField> {
+        let Some(dir) = Self::directory(ctx) else {
+            return Vec::new();
+        };
+        let Some(root) = Self::git(&dir, &["rev-parse", "--show-toplevel"]) else {
+            return Vec::new();
+        };
+
+        let mut fields = vec![field("repo", root.clone())];
+
+        let status = Self::git(&root, &["status", "--porcelain=v1", "--branch"]);
+        let mut lines = status.as_deref().unwrap_or_default().lines();
+        // "## main...origin/main [ahead 1]" or "## No commits yet on main"
+        if let Some(header) = lines.next().and_then(|l| l.strip_prefix("## ")) {
+            let (branch, tracking) = match header.split_once("...") {
+                Some((branch, rest)) => (branch, Some(rest)),
+                None => (header.trim_start_matches("No commits yet on "), None),
+            };
+            fields.push(field("branch", branch));
+            if let Some(divergence) = tracking
+                .and_then(|t| t.split_once(" ["))
+                .map(|(_, d)| d.trim_end_matches(']'))
+            {
+                fields.push(field("upstream", divergence));
+            }
+        }
+        if status.is_some() {
+            let changed = lines.count();
+            let dirty = if changed == 0 {
+                "clean".to_string()
+            } else {
+                format!("{changed} uncommitted change(s)")
+            };
+            fields.push(field("status", dirty));
+        }
+
+        let remote = Self::git(&root, &["remote", "get-url", "origin"]).or_else(|| {
+            let first = Self::git(&root, &["remote"])?.lines().next()?.to_string();
+            Self::git(&root, &["remote", "get-url", &first])
+        });
+        if let Some(remote) = remote {
+            fields.push(field("remote", remote));
+        }
+        fields
+    }
+}
+
+/// The page open in a browser: title everywhere, URL where the browser exposes it.
+struct BrowserProvider;
+
+const BROWSERS: [(&str, &str); 9] = [
+    ("firefox", "Mozilla Firefox"),
+    ("chrome", "Google Chrome"),
+    ("google chrome", "Google Chrome"),
+    ("chromium", "Chromium"),
+    ("brave", "Brave"),
+    ("msedge", "Microsoft Edge"),
+    ("vivaldi", "Vivaldi"),
+    ("safari", "Safari"),
+    ("arc", "Arc"),
+];
+
+impl ContextProvider for BrowserProvider {
+    fn id(&self) -> String {
+        "browser".to_string()
+    }
+
+    fn label(&self) -> String {
+        "Browser".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Page title (and URL on macOS) of the active browser tab".to_string()
+    }
+
+    fn provide(&self, ctx: &LaunchContext) -> Vec<ContextField> {
+        let process = process_key(ctx);
+        let Some((_, browser)) = BROWSERS
+            .iter()
+            .find(|(name, _)| process == *name || process.starts_with(&format!("{name}-")))
+        else {
+            return Vec::new();
+        };
+
+        let mut fields = vec![field("browser", *browser)];
+        if let Some(title) = ctx.source_window_title.as_deref() {
+            let page = TITLE_SEPARATORS
+                .iter()
+                .find_map(|sep| title.strip_suffix(&format!("{sep}{browser}")))
+                .unwrap_or(title);
+            fields.push(field("page", page));
+        }
+        #[cfg(target_os = "macos")]
+        if let Some(url) = ctx
+            .source_process_name
+            .as_deref()
+            .and_then(macos::browser_url)
+        {
+            fields.push(field("url", url));
+        }
+        fields
+    }
+}
+
+/// A user shell command whose `key=value` output lines become context fields.
+/// Lines without `=` are collected into an `output` field.
+struct ScriptProvider {
+    name: String,
+    command: String,
+}
+
+impl ContextProvider for ScriptProvider {
+    fn id(&self) -> String {
+        format!("script:{}", self.name)
+    }
+
+    fn label(&self) -> String {
+        self.name.clone()
+    }
+
+    fn description(&self) -> String {
+        self.command.clone()
+    }
+
+    fn provide(&self, ctx: &LaunchContext) -> Vec<ContextField> {
+        #[cfg(target_os = "windows")]
+        let mut cmd = {
+            let mut cmd = Command::new("cmd");
+            cmd.args(["/C", &self.command]);
+            cmd
+        };
+        #[cfg(not(target_os = "windows"))]
+        let mut cmd = {
+            let mut cmd = Command::new("sh");
+            cmd.args(["-c", &self.command]);
+            cmd
+        };
+
+        let env = [
+            ("GOLAUNCH_WINDOW_TITLE", ctx.source_window_title.clone()),
+            ("GOLAUNCH_PROCESS_NAME", ctx.source_process_name.clone()),
+            ("GOLAUNCH_PID", ctx.source_process_id.map(|p| p.to_string())),
+            ("GOLAUNCH_CWD", ctx.field("cwd", "cwd").map(String::from)),
+        ];
+        for (key, value) in env {
+            cmd.env(key, value.unwrap_or_default());
+        }
+        if let Some(cwd) = ctx.field("cwd", "cwd") {
+            cmd.current_dir(cwd);
+        }
+
+        let Some(output) = run_provider_command(cmd) else {
+            return Vec::new();
+        };
+
+        let mut fields = Vec::new();
+        let mut free_text = Vec::new();
+        for line in output.lines().filter(|l| !l.trim().is_empty()) {
+            match line.split_once('=') {
+                Some((key, value)) if !key.trim().is_empty() && !key.contains(' ') => {
+                    fields.push(field(key.trim(), value.trim()));
+                }
+                _ => free_text.push(line),
+            }
+        }
+        if !free_text.is_empty() {
+            fields.push(field("output", free_text.join("\n")));
+        }
+        fields
     }
 }
 
 // ── Cross-platform clipboard via arboard ──
 
+/// How long to wait for the source app to answer a simulated copy.
+const COPY_TIMEOUT: Duration = Duration::from_millis(250);
+const COPY_POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// How long the target app gets to read a pasted value before the clipboard is restored.
+const PASTE_RESTORE_DELAY: Duration = Duration::from_millis(300);
+/// Extra time the clipboard history watcher ignores changes after we touch the clipboard.
+const CLIPBOARD_SETTLE_TIME: Duration = Duration::from_millis(1000);
+
 /// Read text from the system clipboard.
 fn get_clipboard_text() -> Option<String> {
     let mut clipboard = arboard::Clipboard::new().ok()?;
     clipboard.get_text().ok().filter(|s| !s.is_empty())
 }
 
-/// Write text to the system clipboard.
-fn set_clipboard_text(text: &str) -> Result<(), String> {
-    let mut clipboard =
-        arboard::Clipboard::new().map_err(|e| format!("Failed to init clipboard: {e}"))?;
-    clipboard
-        .set_text(text.to_string())
-        .map_err(|e| format!("Failed to set clipboard: {e}"))
+/// The clipboard contents before GoLaunch simulates a copy or paste.
+///
+/// arboard can't enumerate arbitrary formats, so this keeps the ones it can round-trip
+/// (file list, image, HTML with its plain-text alternative, text) and restores the richest.
+struct ClipboardSnapshot {
+    text: Option<String>,
+    html: Option<String>,
+    image: Option<arboard::ImageData<'static>>,
+    files: Vec<std::path::PathBuf>,
 }
 
-// ── Cross-platform selected text capture ──
+impl ClipboardSnapshot {
+    fn take(clipboard: &mut arboard::Clipboard) -> Self {
+        Self {
+            text: clipboard.get_text().ok(),
+            html: clipboard.get().html().ok(),
+            image: clipboard.get_image().ok(),
+            files: clipboard.get().file_list().unwrap_or_default(),
+        }
+    }
+
+    fn restore(self, clipboard: &mut arboard::Clipboard) -> Result<(), String> {
+        let result = if !self.files.is_empty() {
+            clipboard.set().file_list(&self.files)
+        } else if let Some(image) = self.image {
+            clipboard.set_image(image)
+        } else if let Some(html) = self.html {
+            clipboard.set_html(html, self.text)
+        } else if let Some(text) = self.text {
+            clipboard.set_text(text)
+        } else {
+            clipboard.clear()
+        };
+        result.map_err(|e| format!("Failed to restore clipboard: {e}"))
+    }
+}
 
-/// Capture the currently selected text by simulating Ctrl+C (or Cmd+C on macOS)
-/// and reading clipboard. Compares against the previous clipboard contents to
-/// detect whether something was copied.
-fn capture_selected_text(previous_clipboard: &Option<String>) -> Option<String> {
+/// Press Cmd+<key> on macOS or Ctrl+<key> on Windows/Linux.
+pub(crate) fn send_shortcut(key: char) -> Result<(), String> {
     use enigo::{Direction, Enigo, Key, Keyboard, Settings};
 
-    let mut enigo = Enigo::new(&Settings::default()).ok()?;
+    let mut enigo =
+        Enigo::new(&Settings::default()).map_err(|e| format!("Failed to init enigo: {e}"))?;
 
-    // Use Cmd on macOS, Ctrl on Windows/Linux
     let modifier = if cfg!(target_os = "macos") {
         Key::Meta
     } else {
         Key::Control
     };
 
-    enigo.key(modifier, Direction::Press).ok()?;
-    enigo.key(Key::Unicode('c'), Direction::Click).ok()?;
-    enigo.key(modifier, Direction::Release).ok()?;
+    enigo
+        .key(modifier, Direction::Press)
+        .map_err(|e| format!("Failed to press modifier: {e}"))?;
+    enigo
+        .key(Key::Unicode(key), Direction::Click)
+        .map_err(|e| format!("Failed to press {}: {e}", key.to_ascii_uppercase()))?;
+    enigo
+        .key(modifier, Direction::Release)
+        .map_err(|e| format!("Failed to release modifier: {e}"))?;
 
-    // Give the target app time to update the clipboard
-    std::thread::sleep(std::time::Duration::from_millis(80));
+    Ok(())
+}
 
-    let new_clipboard = get_clipboard_text();
+/// Press the Left arrow `count` times, e.g. to place the caret inside inserted text.
+pub(crate) fn move_caret_left(count: usize) -> Result<(), String> {
+    use enigo::{Direction, Enigo, Key, Keyboard, Settings};
 
-    match (&new_clipboard, previous_clipboard) {
-        (Some(new), Some(old)) if new != old => Some(new.clone()),
-        (Some(new), None) => Some(new.clone()),
-        _ => None,
+    let mut enigo =
+        Enigo::new(&Settings::default()).map_err(|e| format!("Failed to init enigo: {e}"))?;
+    for _ in 0..count {
+        enigo
+            .key(Key::LeftArrow, Direction::Click)
+            .map_err(|e| format!("Failed to press Left: {e}"))?;
     }
+    Ok(())
 }
 
-// ── Foreground window info (platform-specific) ──
+// ── Cross-platform selected text capture ──
 
-/// Get the title of the foreground window.
-fn get_foreground_window_title() -> Option<String> {
-    #[cfg(target_os = "windows")]
+/// Settings key that turns selection capture off when set to "false".
+pub const SELECTION_CAPTURE_SETTING: &str = "context.capture_selection";
+
+fn selection_capture_enabled(db: Option<&Database>) -> bool {
+    db.and_then(|db| db.get_setting(SELECTION_CAPTURE_SETTING).ok())
+        .map(|v| v.as_deref() != Some("false"))
+        .unwrap_or(true)
+}
+
+/// Capture the currently selected text.
+///
+/// On Linux the PRIMARY selection already holds it, so no keystroke is needed. Elsewhere
+/// (or when PRIMARY is unavailable) this simulates Ctrl+C / Cmd+C and polls the clipboard,
+/// then puts the user's previous clipboard contents back.
+pub(crate) fn capture_selected_text() -> Option<String> {
+    #[cfg(target_os = "linux")]
     {
-        win32::get_foreground_window_title()
+        use arboard::{GetExtLinux, LinuxClipboardKind};
+
+        let mut clipboard = arboard::Clipboard::new().ok()?;
+        match clipboard
+            .get()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text()
+        {
+            Ok(text) => return Some(text).filter(|t| !t.is_empty()),
+            // Nothing is selected; don't send a copy keystroke (it would be SIGINT in a terminal).
+            Err(arboard::Error::ContentNotAvailable) => return None,
+            Err(_) => {}
+        }
     }
-    #[cfg(target_os = "macos")]
-    {
-        macos::get_foreground_window_title()
+
+    let mut clipboard = arboard::Clipboard::new().ok()?;
+    crate::clipboard::suppress_recording(COPY_TIMEOUT + CLIPBOARD_SETTLE_TIME);
+    let snapshot = ClipboardSnapshot::take(&mut clipboard);
+    // Clear first so any text that shows up must come from the copy, even when the
+    // selection equals what was already on the clipboard.
+    let _ = clipboard.clear();
+
+    let copied = send_shortcut('c')
+        .ok()
+        .and_then(|_| wait_for_clipboard_text(&mut clipboard));
+
+    if let Err(e) = snapshot.restore(&mut clipboard) {
+        eprintln!("{e}");
     }
-    #[cfg(target_os = "linux")]
-    {
-        None
+    copied
+}
+
+/// Poll the clipboard until text appears or [`COPY_TIMEOUT`] passes.
+fn wait_for_clipboard_text(clipboard: &mut arboard::Clipboard) -> Option<String> {
+    let deadline = Instant::now() + COPY_TIMEOUT;
+    loop {
+        if let Some(text) = clipboard.get_text().ok().filter(|t| !t.is_empty()) {
+            return Some(text);
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        std::thread::sleep(COPY_POLL_INTERVAL);
     }
 }
 
-/// Get the process name of the foreground window.
-fn get_foreground_process_name() -> Option<String> {
+// ── Foreground window info (platform-specific) ──
+
+/// The window that had focus when the launcher was invoked.
+#[derive(Debug, Default)]
+pub(crate) struct ForegroundWindow {
+    pub title: Option<String>,
+    /// Process name, e.g. "Code.exe" or "firefox".
+    pub process_name: Option<String>,
+    pub process_id: Option<u32>,
+}
+
+/// Look up the foreground window once, so its title and process always describe the
+/// same window even if focus changes meanwhile.
+pub(crate) fn foreground_window() -> ForegroundWindow {
     #[cfg(target_os = "windows")]
     {
-        win32::get_foreground_process_name()
+        win32::foreground_window()
     }
     #[cfg(target_os = "macos")]
     {
-        macos::get_foreground_process_name()
+        macos::foreground_window()
     }
     #[cfg(target_os = "linux")]
     {
-        None
+        linux::foreground_window()
     }
 }
 
@@ -131,30 +868,28 @@ pub fn type_text(text: &str) -> Result<(), String> {
 }
 
 /// Simulate replacing the current selection: put text on clipboard, then paste.
-/// Uses Cmd+V on macOS, Ctrl+V on Windows/Linux.
+/// Uses Cmd+V on macOS, Ctrl+V on Windows/Linux. The previous clipboard contents
+/// are restored once the target app has had time to read the pasted text.
 pub fn replace_selection(text: &str) -> Result<(), String> {
-    set_clipboard_text(text)?;
-
-    use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|e| format!("Failed to init clipboard: {e}"))?;
+    crate::clipboard::suppress_recording(PASTE_RESTORE_DELAY + CLIPBOARD_SETTLE_TIME);
+    let snapshot = ClipboardSnapshot::take(&mut clipboard);
+    clipboard
+        .set_text(text.to_string())
+        .map_err(|e| format!("Failed to set clipboard: {e}"))?;
 
-    let mut enigo =
-        Enigo::new(&Settings::default()).map_err(|e| format!("Failed to init enigo: {e}"))?;
+    send_shortcut('v')?;
 
-    let modifier = if cfg!(target_os = "macos") {
-        Key::Meta
-    } else {
-        Key::Control
-    };
-
-    enigo
-        .key(modifier, Direction::Press)
-        .map_err(|e| format!("Failed to press modifier: {e}"))?;
-    enigo
-        .key(Key::Unicode('v'), Direction::Click)
-        .map_err(|e| format!("Failed to press V: {e}"))?;
-    enigo
-        .key(modifier, Direction::Release)
-        .map_err(|e| format!("Failed to release modifier: {e}"))?;
+    std::thread::spawn(move || {
+        std::thread::sleep(PASTE_RESTORE_DELAY);
+        let restored = arboard::Clipboard::new()
+            .map_err(|e| format!("Failed to init clipboard: {e}"))
+            .and_then(|mut clipboard| snapshot.restore(&mut clipboard));
+        if let Err(e) = restored {
+            eprintln!("{e}");
+        }
+    });
 
     Ok(())
 }
@@ -163,73 +898,65 @@ pub fn replace_selection(text: &str) -> Result<(), String> {
 
 #[cfg(target_os = "windows")]
 mod win32 {
+    use super::ForegroundWindow;
     use windows::Win32::Foundation::HWND;
     use windows::Win32::UI::WindowsAndMessaging::{
         GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId,
     };
 
-    pub fn get_foreground_window_title() -> Option<String> {
+    pub fn foreground_window() -> ForegroundWindow {
         unsafe {
             let hwnd = GetForegroundWindow();
             if hwnd == HWND::default() {
-                return None;
+                return ForegroundWindow::default();
             }
 
             let mut title = vec![0u16; 512];
             let len = GetWindowTextW(hwnd, &mut title);
-
-            if len > 0 {
-                let text = String::from_utf16_lossy(&title[..len as usize]);
-                Some(text)
-            } else {
-                None
-            }
-        }
-    }
-
-    pub fn get_foreground_process_name() -> Option<String> {
-        unsafe {
-            let hwnd = GetForegroundWindow();
-            if hwnd == HWND::default() {
-                return None;
-            }
+            let title = (len > 0).then(|| String::from_utf16_lossy(&title[..len as usize]));
 
             let mut process_id: u32 = 0;
             GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+            let process_id = (process_id != 0).then_some(process_id);
 
-            if process_id == 0 {
-                return None;
+            ForegroundWindow {
+                title,
+                process_name: process_id.and_then(|pid| process_name(pid)),
+                process_id,
             }
+        }
+    }
 
-            use windows::Win32::System::Threading::{
-                OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_FORMAT,
-                PROCESS_QUERY_LIMITED_INFORMATION,
-            };
+    /// File name of the process' executable.
+    unsafe fn process_name(process_id: u32) -> Option<String> {
+        use windows::Win32::System::Threading::{
+            OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_FORMAT,
+            PROCESS_QUERY_LIMITED_INFORMATION,
+        };
 
-            let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id).ok()?;
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id).ok()?;
 
-            let mut name_buf = vec![0u16; 1024];
-            let mut size = name_buf.len() as u32;
-            let ok = QueryFullProcessImageNameW(
-                process,
-                PROCESS_NAME_FORMAT(0),
-                windows::core::PWSTR(name_buf.as_mut_ptr()),
-                &mut size,
-            );
+        let mut name_buf = vec![0u16; 1024];
+        let mut size = name_buf.len() as u32;
+        let ok = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_FORMAT(0),
+            windows::core::PWSTR(name_buf.as_mut_ptr()),
+            &mut size,
+        );
 
-            let _ = windows::Win32::Foundation::CloseHandle(process);
+        let _ = windows::Win32::Foundation::CloseHandle(process);
 
-            if ok.is_ok() && size > 0 {
-                let full_path = String::from_utf16_lossy(&name_buf[..size as usize]);
-                let name = full_path
-                    .rsplit('\\')
-                    .next()
-                    .unwrap_or(&full_path)
-                    .to_string();
-                Some(name)
-            } else {
-                None
-            }
+        if ok.is_ok() && size > 0 {
+            let full_path = String::from_utf16_lossy(&name_buf[..size as usize]);
+            let name = full_path
+                .rsplit('\\')
+                .next()
+                .unwrap_or(&full_path)
+                .to_string();
+            Some(name)
+        } else {
+            None
         }
     }
 }
@@ -238,22 +965,54 @@ mod win32 {
 
 #[cfg(target_os = "macos")]
 mod macos {
+    use super::ForegroundWindow;
     use std::process::Command;
 
-    pub fn get_foreground_window_title() -> Option<String> {
+    pub fn foreground_window() -> ForegroundWindow {
         let output = Command::new("osascript")
             .args([
                 "-e",
                 r#"tell application "System Events"
                     set frontApp to first application process whose frontmost is true
+                    set appName to name of frontApp
                     try
                         set winTitle to name of front window of frontApp
-                        return winTitle
                     on error
-                        return name of frontApp
+                        set winTitle to appName
                     end try
+                    return appName & linefeed & (unix id of frontApp) & linefeed & winTitle
                 end tell"#,
             ])
+            .output();
+        let Ok(output) = output else {
+            return ForegroundWindow::default();
+        };
+
+        // The title comes last, as it may itself contain line breaks
+        let text = String::from_utf8_lossy(&output.stdout);
+        let mut lines = text.trim().splitn(3, '\n');
+        let non_empty = |line: Option<&str>| line.map(str::trim).filter(|l| !l.is_empty());
+        ForegroundWindow {
+            process_name: non_empty(lines.next()).map(String::from),
+            process_id: non_empty(lines.next()).and_then(|pid| pid.parse().ok()),
+            title: non_empty(lines.next()).map(String::from),
+        }
+    }
+
+    /// URL of the active tab, for browsers with an AppleScript dictionary.
+    pub fn browser_url(app_name: &str) -> Option<String> {
+        let script = match app_name {
+            "Safari" => r#"tell application "Safari" to return URL of front document"#.to_string(),
+            "Google Chrome" | "Chromium" | "Brave Browser" | "Microsoft Edge" | "Vivaldi"
+            | "Arc" => {
+                format!(
+                    r#"tell application "{app_name}" to return URL of active tab of front window"#
+                )
+            }
+            _ => return None,
+        };
+        let output = Command::new("osascript")
+            .args(["-e", &script])
             .output()
             .ok()?;
 
@@ -264,24 +1023,550 @@ mod macos {
             Some(text)
         }
     }
+}
 
-    pub fn get_foreground_process_name() -> Option<String> {
-        let output = Command::new("osascript")
-            .args([
-                "-e",
-                r#"tell application "System Events"
-                    set frontApp to first application process whose frontmost is true
-                    return name of frontApp
-                end tell"#,
-            ])
-            .output()
-            .ok()?;
+// ── Linux-specific implementations ──
 
-        let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if text.is_empty() {
+/// Linux has no single API for the focused window, so each session type is probed
+/// in turn: sway/i3 IPC, the Hyprland socket, GNOME's "Window Calls" extension over
+/// D-Bus, and finally X11 (`_NET_ACTIVE_WINDOW`), which also covers XWayland apps.
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    /// Upper bound for a single compositor IPC round-trip.
+    const IPC_TIMEOUT: Duration = Duration::from_millis(300);
+
+    /// The focused window as the compositor or X server reports it.
+    #[derive(Debug, Default)]
+    struct ActiveWindow {
+        title: Option<String>,
+        pid: Option<u32>,
+        /// Wayland app id or X11 WM_CLASS, used when the process can't be resolved.
+        app_id: Option<String>,
+    }
+
+    pub fn foreground_window() -> super::ForegroundWindow {
+        let Some(window) = active_window() else {
+            return super::ForegroundWindow::default();
+        };
+        super::ForegroundWindow {
+            title: window.title.filter(|t| !t.is_empty()),
+            process_name: window
+                .pid
+                .and_then(process_name)
+                .or(window.app_id)
+                .filter(|n| !n.is_empty()),
+            process_id: window.pid,
+        }
+    }
+
+    fn active_window() -> Option<ActiveWindow> {
+        let env_set = |name: &str| std::env::var_os(name).is_some_and(|v| !v.is_empty());
+
+        if env_set("SWAYSOCK") || env_set("I3SOCK") {
+            if let Some(window) = sway::active_window() {
+                return Some(window);
+            }
+        }
+        if env_set("HYPRLAND_INSTANCE_SIGNATURE") {
+            if let Some(window) = hyprland::active_window() {
+                return Some(window);
+            }
+        }
+        if env_set("WAYLAND_DISPLAY") && is_gnome() {
+            if let Some(window) = gnome::active_window() {
+                return Some(window);
+            }
+        }
+        if env_set("DISPLAY") {
+            return x11::active_window();
+        }
+        None
+    }
+
+    fn is_gnome() -> bool {
+        std::env::var("XDG_CURRENT_DESKTOP")
+            .map(|d| d.split(':').any(|part| part.eq_ignore_ascii_case("gnome")))
+            .unwrap_or(false)
+    }
+
+    /// Read the short process name from `/proc/<pid>/comm`.
+    pub fn process_name(pid: u32) -> Option<String> {
+        let comm = std::fs::read_to_string(format!("/proc/{pid}/comm")).ok()?;
+        let name = comm.trim().to_string();
+        if name.is_empty() {
             None
         } else {
-            Some(text)
+            Some(name)
         }
     }
+
+    const SHELLS: [&str; 11] = [
+        "bash", "zsh", "fish", "sh", "dash", "ksh", "tcsh", "nu", "xonsh", "elvish", "pwsh",
+    ];
+
+    /// Working directory for a source process. Terminals keep their own cwd at `$HOME`,
+    /// so when the process has shell descendants the most recently started shell wins.
+    /// Returns the pid whose cwd was used alongside the path.
+    pub fn working_directory(pid: u32) -> Option<(u32, String)> {
+        let target = newest_descendant_shell(pid).unwrap_or(pid);
+        let cwd = std::fs::read_link(format!("/proc/{target}/cwd")).ok()?;
+        Some((target, cwd.to_string_lossy().into_owned()))
+    }
+
+    fn newest_descendant_shell(root: u32) -> Option<u32> {
+        // ppid -> [(pid, start time)], built from one pass over /proc/*/stat.
+        let mut children: std::collections::HashMap<u32, Vec<(u32, u64)>> =
+            std::collections::HashMap::new();
+        for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+            let Some(pid) = entry
+                .file_name()
+                .to_str()
+                .and_then(|n| n.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            let Ok(stat) = std::fs::read_to_string(format!("/proc/{pid}/stat")) else {
+                continue;
+            };
+            // The comm field may contain spaces and parens, so parse after the last ')'.
+            let Some((_, rest)) = stat.rsplit_once(')') else {
+                continue;
+            };
+            let fields: Vec<&str> = rest.split_whitespace().collect();
+            let ppid = fields.get(1).and_then(|f| f.parse::<u32>().ok());
+            let start = fields.get(19).and_then(|f| f.parse::<u64>().ok());
+            if let (Some(ppid), Some(start)) = (ppid, start) {
+                children.entry(ppid).or_default().push((pid, start));
+            }
+        }
+
+        let mut newest: Option<(u32, u64)> = None;
+        let mut stack = vec![root];
+        while let Some(pid) = stack.pop() {
+            for &(child, start) in children.get(&pid).map(Vec::as_slice).unwrap_or_default() {
+                let is_shell = process_name(child).is_some_and(|n| SHELLS.contains(&n.as_str()));
+                if is_shell && newest.is_none_or(|(_, s)| start > s) {
+                    newest = Some((child, start));
+                }
+                stack.push(child);
+            }
+        }
+        newest.map(|(pid, _)| pid)
+    }
+
+    fn json_window(
+        title: &serde_json::Value,
+        pid: &serde_json::Value,
+        app_id: &serde_json::Value,
+    ) -> ActiveWindow {
+        ActiveWindow {
+            title: title.as_str().map(String::from),
+            pid: pid
+                .as_i64()
+                .filter(|p| *p > 0)
+                .and_then(|p| u32::try_from(p).ok()),
+            app_id: app_id.as_str().map(String::from),
+        }
+    }
+
+    /// sway (and i3) IPC: GET_TREE and find the focused container.
+    mod sway {
+        use super::*;
+
+        const MAGIC: &[u8] = b"i3-ipc";
+        const GET_TREE: u32 = 4;
+
+        pub fn active_window() -> Option<ActiveWindow> {
+            let path = std::env::var_os("SWAYSOCK").or_else(|| std::env::var_os("I3SOCK"))?;
+            let mut stream = UnixStream::connect(path).ok()?;
+            stream.set_read_timeout(Some(IPC_TIMEOUT)).ok()?;
+            stream.set_write_timeout(Some(IPC_TIMEOUT)).ok()?;
+
+            let mut request = Vec::with_capacity(MAGIC.len() + 8);
+            request.extend_from_slice(MAGIC);
+            request.extend_from_slice(&0u32.to_ne_bytes());
+            request.extend_from_slice(&GET_TREE.to_ne_bytes());
+            stream.write_all(&request).ok()?;
+
+            let mut header = [0u8; 14];
+            stream.read_exact(&mut header).ok()?;
+            if &header[..MAGIC.len()] != MAGIC {
+                return None;
+            }
+            let len = u32::from_ne_bytes(header[6..10].try_into().ok()?) as usize;
+            let mut payload = vec![0u8; len];
+            stream.read_exact(&mut payload).ok()?;
+
+            let tree: serde_json::Value = serde_json::from_slice(&payload).ok()?;
+            let node = find_focused(&tree)?;
+            let app_id = if node["app_id"].is_string() {
+                &node["app_id"]
+            } else {
+                &node["window_properties"]["class"]
+            };
+            Some(json_window(&node["name"], &node["pid"], app_id))
+        }
+
+        /// Depth-first search for the focused window (an empty workspace doesn't count).
+        pub(super) fn find_focused(node: &serde_json::Value) -> Option<&serde_json::Value> {
+            let is_window = matches!(node["type"].as_str(), Some("con" | "floating_con"));
+            if node["focused"].as_bool() == Some(true) && is_window {
+                return Some(node);
+            }
+            ["nodes", "floating_nodes"]
+                .iter()
+                .filter_map(|key| node[*key].as_array())
+                .flatten()
+                .find_map(find_focused)
+        }
+    }
+
+    /// Hyprland: `j/activewindow` on the instance's request socket.
+    mod hyprland {
+        use super::*;
+        use std::path::PathBuf;
+
+        pub fn active_window() -> Option<ActiveWindow> {
+            let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
+
+            // Hyprland >= 0.40 lives under XDG_RUNTIME_DIR, older releases under /tmp.
+            let mut candidates = Vec::new();
+            if let Some(runtime) = std::env::var_os("XDG_RUNTIME_DIR") {
+                candidates.push(PathBuf::from(runtime).join("hypr"));
+            }
+            candidates.push(PathBuf::from("/tmp/hypr"));
+
+            let mut stream = candidates
+                .into_iter()
+                .map(|dir| dir.join(&signature).join(".socket.sock"))
+                .find_map(|path| UnixStream::connect(path).ok())?;
+            stream.set_read_timeout(Some(IPC_TIMEOUT)).ok()?;
+            stream.set_write_timeout(Some(IPC_TIMEOUT)).ok()?;
+
+            stream.write_all(b"j/activewindow").ok()?;
+            let mut response = Vec::new();
+            stream.read_to_end(&mut response).ok()?;
+
+            // With no focused window Hyprland answers with an empty object.
+            let window: serde_json::Value = serde_json::from_slice(&response).ok()?;
+            if window.as_object().is_none_or(|w| w.is_empty()) {
+                return None;
+            }
+            Some(json_window(
+                &window["title"],
+                &window["pid"],
+                &window["class"],
+            ))
+        }
+    }
+
+    /// GNOME (Wayland): mutter exposes no public focused-window API, so this relies on
+    /// the "Window Calls" shell extension, which provides `org.gnome.Shell.Extensions.Windows`.
+    mod gnome {
+        use super::*;
+        use std::process::Command;
+
+        const DEST: &str = "org.gnome.Shell";
+        const OBJECT_PATH: &str = "/org/gnome/Shell/Extensions/Windows";
+        const INTERFACE: &str = "org.gnome.Shell.Extensions.Windows";
+
+        pub fn active_window() -> Option<ActiveWindow> {
+            let list = call("List", &[])?;
+            let windows: serde_json::Value = serde_json::from_str(&list).ok()?;
+            let focused = windows
+                .as_array()?
+                .iter()
+                .find(|w| w["focus"].as_bool() == Some(true))?;
+
+            let mut window = json_window(&focused["title"], &focused["pid"], &focused["wm_class"]);
+            if window.title.is_none() {
+                // Older extension versions only expose the title through GetTitle.
+                if let Some(id) = focused["id"].as_u64() {
+                    window.title = call("GetTitle", &[id.to_string()]);
+                }
+            }
+            Some(window)
+        }
+
+        /// Call a method through `gdbus` and return its single string result.
+        fn call(method: &str, args: &[String]) -> Option<String> {
+            let output = Command::new("gdbus")
+                .args(["call", "--session", "--timeout", "1"])
+                .args(["--dest", DEST, "--object-path", OBJECT_PATH])
+                .args(["--method", &format!("{INTERFACE}.{method}")])
+                .args(args)
+                .output()
+                .ok()?;
+            if !output.status.success() {
+                return None;
+            }
+            parse_gvariant_string(String::from_utf8_lossy(&output.stdout).trim())
+        }
+
+        /// Unwrap gdbus' `('value',)` tuple output into the plain string.
+        pub(super) fn parse_gvariant_string(text: &str) -> Option<String> {
+            let inner = text.strip_prefix('(')?.strip_suffix(",)")?.trim();
+            let quote = inner.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+            let body = inner.strip_prefix(quote)?.strip_suffix(quote)?;
+
+            let mut result = String::with_capacity(body.len());
+            let mut chars = body.chars();
+            while let Some(c) = chars.next() {
+                if c != '\\' {
+                    result.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some(escaped @ ('\\' | '\'' | '"')) => result.push(escaped),
+                    Some(other) => {
+                        result.push('\\');
+                        result.push(other);
+                    }
+                    None => result.push('\\'),
+                }
+            }
+            Some(result)
+        }
+    }
+
+    /// X11 via EWMH: `_NET_ACTIVE_WINDOW` on the root window, then the window's
+    /// `_NET_WM_NAME`/`WM_NAME`, `_NET_WM_PID` and `WM_CLASS`.
+    mod x11 {
+        use super::*;
+        use x11rb::connection::Connection;
+        use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt, Window};
+        use x11rb::rust_connection::RustConnection;
+
+        pub fn active_window() -> Option<ActiveWindow> {
+            let (conn, screen_num) = x11rb::connect(None).ok()?;
+            let root = conn.setup().roots.get(screen_num)?.root;
+
+            let net_active_window = intern(&conn, b"_NET_ACTIVE_WINDOW")?;
+            let window = property(&conn, root, net_active_window, AtomEnum::WINDOW.into())?
+                .value32()?
+                .next()
+                .filter(|w| *w != 0)?;
+
+            let utf8_string = intern(&conn, b"UTF8_STRING")?;
+            let net_wm_name = intern(&conn, b"_NET_WM_NAME")?;
+            let title = string_property(&conn, window, net_wm_name, utf8_string).or_else(|| {
+                string_property(
+                    &conn,
+                    window,
+                    AtomEnum::WM_NAME.into(),
+                    AtomEnum::ANY.into(),
+                )
+            });
+
+            let net_wm_pid = intern(&conn, b"_NET_WM_PID")?;
+            let pid = property(&conn, window, net_wm_pid, AtomEnum::CARDINAL.into())
+                .and_then(|reply| reply.value32()?.next())
+                .filter(|p| *p != 0);
+
+            // WM_CLASS holds "instance\0class\0"; the class is the friendlier name.
+            let app_id = property(
+                &conn,
+                window,
+                AtomEnum::WM_CLASS.into(),
+                AtomEnum::STRING.into(),
+            )
+            .and_then(|reply| {
+                reply
+                    .value
+                    .split(|b| *b == 0)
+                    .filter(|part| !part.is_empty())
+                    .nth(1)
+                    .map(|class| String::from_utf8_lossy(class).into_owned())
+            });
+
+            Some(ActiveWindow { title, pid, app_id })
+        }
+
+        fn intern(conn: &RustConnection, name: &[u8]) -> Option<Atom> {
+            Some(conn.intern_atom(false, name).ok()?.reply().ok()?.atom)
+        }
+
+        fn property(
+            conn: &RustConnection,
+            window: Window,
+            property: Atom,
+            type_: Atom,
+        ) -> Option<x11rb::protocol::xproto::GetPropertyReply> {
+            conn.get_property(false, window, property, type_, 0, 1024)
+                .ok()?
+                .reply()
+                .ok()
+                .filter(|reply| reply.type_ != u32::from(AtomEnum::NONE))
+        }
+
+        fn string_property(
+            conn: &RustConnection,
+            window: Window,
+            name: Atom,
+            type_: Atom,
+        ) -> Option<String> {
+            let reply = property(conn, window, name, type_)?;
+            let text = String::from_utf8_lossy(&reply.value).into_owned();
+            if text.is_empty() {
+                None
+            } else {
+                Some(text)
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn finds_the_focused_sway_window() {
+            let tree = serde_json::json!({
+                "type": "root",
+                "nodes": [{
+                    "type": "workspace",
+                    "focused": false,
+                    "nodes": [
+                        { "type": "con", "name": "other", "focused": false, "pid": 10 },
+                    ],
+                    "floating_nodes": [
+                        { "type": "floating_con", "name": "notes", "focused": true, "pid": 11 },
+                    ],
+                }],
+            });
+            let node = sway::find_focused(&tree).unwrap();
+            assert_eq!(node["name"], "notes");
+
+            // A focused empty workspace is not a window
+            let empty = serde_json::json!({
+                "type": "root",
+                "nodes": [{ "type": "workspace", "focused": true, "nodes": [] }],
+            });
+            assert!(sway::find_focused(&empty).is_none());
+        }
+
+        #[test]
+        fn reads_window_fields_from_json() {
+            let window = json_window(
+                &serde_json::json!("main.rs - Code"),
+                &serde_json::json!(0),
+                &serde_json::json!("code"),
+            );
+            assert_eq!(window.title.as_deref(), Some("main.rs - Code"));
+            assert_eq!(window.pid, None);
+            assert_eq!(window.app_id.as_deref(), Some("code"));
+        }
+
+        #[test]
+        fn unwraps_gdbus_strings() {
+            assert_eq!(
+                gnome::parse_gvariant_string(r#"('it\'s "here"',)"#).as_deref(),
+                Some(r#"it's "here""#)
+            );
+            assert_eq!(
+                gnome::parse_gvariant_string(r#"("[{\"focus\": true}]",)"#).as_deref(),
+                Some(r#"[{"focus": true}]"#)
+            );
+            assert_eq!(gnome::parse_gvariant_string("()"), None);
+        }
+
+        /// Replaces the root window's `_NET_ACTIVE_WINDOW`, so it only runs against a
+        /// throwaway X server, e.g.
+        /// `GOLAUNCH_XVFB_TESTS=1 xvfb-run cargo test reads_the_active_x11_window`.
+        #[test]
+        fn reads_the_active_x11_window() {
+            use x11rb::connection::Connection;
+            use x11rb::protocol::xproto::{
+                AtomEnum, ConnectionExt, CreateWindowAux, PropMode, WindowClass,
+            };
+            use x11rb::wrapper::ConnectionExt as _;
+
+            if std::env::var_os("GOLAUNCH_XVFB_TESTS").is_none() {
+                eprintln!("GOLAUNCH_XVFB_TESTS not set; skipping");
+                return;
+            }
+            let Ok((conn, screen_num)) = x11rb::connect(None) else {
+                eprintln!("No X display; skipping");
+                return;
+            };
+            let root = conn.setup().roots[screen_num].root;
+            let atom = |name: &[u8]| conn.intern_atom(false, name).unwrap().reply().unwrap().atom;
+
+            let window = conn.generate_id().unwrap();
+            conn.create_window(
+                x11rb::COPY_DEPTH_FROM_PARENT,
+                window,
+                root,
+                0,
+                0,
+                10,
+                10,
+                0,
+                WindowClass::INPUT_OUTPUT,
+                0,
+                &CreateWindowAux::new(),
+            )
+            .unwrap();
+            conn.change_property8(
+                PropMode::REPLACE,
+                window,
+                atom(b"_NET_WM_NAME"),
+                atom(b"UTF8_STRING"),
+                "notes.md - Editor".as_bytes(),
+            )
+            .unwrap();
+            conn.change_property32(
+                PropMode::REPLACE,
+                window,
+                atom(b"_NET_WM_PID"),
+                AtomEnum::CARDINAL,
+                &[std::process::id()],
+            )
+            .unwrap();
+            conn.change_property8(
+                PropMode::REPLACE,
+                window,
+                AtomEnum::WM_CLASS,
+                AtomEnum::STRING,
+                b"editor\0Editor\0",
+            )
+            .unwrap();
+            conn.change_property32(
+                PropMode::REPLACE,
+                root,
+                atom(b"_NET_ACTIVE_WINDOW"),
+                AtomEnum::WINDOW,
+                &[window],
+            )
+            .unwrap();
+            conn.sync().unwrap();
+
+            let active = x11::active_window().unwrap();
+            assert_eq!(active.title.as_deref(), Some("notes.md - Editor"));
+            assert_eq!(active.pid, Some(std::process::id()));
+            assert_eq!(active.app_id.as_deref(), Some("Editor"));
+
+            conn.destroy_window(window).unwrap();
+            conn.sync().unwrap();
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provider_commands_keep_the_first_bytes_and_do_not_wait_for_background_processes() {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "head -c 10000 /dev/zero | tr '\\0' x; sleep 30 &"]);
+        let started = Instant::now();
+        let output = run_provider_command(cmd).unwrap();
+        assert_eq!(output.len(), PROVIDER_OUTPUT_LIMIT);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
 }
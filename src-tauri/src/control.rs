@@ -0,0 +1,265 @@
+//! Control socket server: lets the CLI, scripts and window-manager bindings drive the
+//! running app with line-delimited JSON-RPC (see `golaunch_core::control`).
+
+use std::sync::Arc;
+
+use golaunch_core::control::{
+    RpcError, RpcRequest, RpcResponse, APP_ERROR, INVALID_PARAMS, METHOD_NOT_FOUND, PARSE_ERROR,
+    UNAUTHORIZED,
+};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::commands;
+use crate::hotkeys;
+use crate::processes::{ProcessRegistry, ProcessState};
+
+/// Output lines returned by `processes.tail` when the caller does not say.
+pub const DEFAULT_TAIL_LINES: usize = 100;
+
+/// Emitted with a query string for the launcher to show (`show {query}`).
+pub const CONTROL_QUERY_EVENT: &str = "control-query";
+
+/// Start listening on the control socket. Does nothing if another GoLaunch instance
+/// already answers on it.
+pub fn spawn_server(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let result = match golaunch_core::control::load_or_create_token() {
+            Ok(token) => serve(app, Arc::new(token)).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            eprintln!("Control socket unavailable: {e}");
+        }
+    });
+}
+
+#[cfg(unix)]
+async fn serve(app: AppHandle, token: Arc<String>) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::net::{UnixListener, UnixStream};
+
+    let path = golaunch_core::control::socket_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    if UnixStream::connect(&path).await.is_ok() {
+        return Err(format!("{} is in use by another instance", path.display()));
+    }
+    // Left behind by an instance that did not shut down cleanly
+    let _ = std::fs::remove_file(&path);
+    let listener =
+        UnixListener::bind(&path).map_err(|e| format!("Failed to bind {}: {e}", path.display()))?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to secure {}: {e}", path.display()))?;
+
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .map_err(|e| format!("Failed to accept connection: {e}"))?;
+        let app = app.clone();
+        let token = Arc::clone(&token);
+        tauri::async_runtime::spawn(async move { handle_connection(&app, &token, stream).await });
+    }
+}
+
+#[cfg(windows)]
+async fn serve(app: AppHandle, token: Arc<String>) -> Result<(), String> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let path = golaunch_core::control::socket_path()?;
+    // Fails if another instance already owns the pipe
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(&path)
+        .map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+
+    loop {
+        server
+            .connect()
+            .await
+            .map_err(|e| format!("Failed to accept connection: {e}"))?;
+        let connected = server;
+        // Create the next instance before serving this one, so clients never miss it
+        server = ServerOptions::new()
+            .create(&path)
+            .map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+        let app = app.clone();
+        let token = Arc::clone(&token);
+        tauri::async_runtime::spawn(
+            async move { handle_connection(&app, &token, connected).await },
+        );
+    }
+}
+
+/// Read one request line from a connection and write back one response line.
+async fn handle_connection<S: AsyncRead + AsyncWrite>(app: &AppHandle, token: &str, stream: S) {
+    let (read, mut write) = tokio::io::split(stream);
+    let mut line = String::new();
+    if BufReader::new(read).read_line(&mut line).await.is_err() {
+        return;
+    }
+    let mut response = handle_line(app, token, &line).await;
+    response.push('\n');
+    let _ = write.write_all(response.as_bytes()).await;
+    let _ = write.flush().await;
+}
+
+/// Answer one request line with one response line.
+async fn handle_line(app: &AppHandle, token: &str, line: &str) -> String {
+    let response = match serde_json::from_str::<RpcRequest>(line) {
+        Ok(request) if request.token.as_deref() != Some(token) => RpcResponse::new(
+            request.id,
+            Err(RpcError::new(
+                UNAUTHORIZED,
+                "Invalid or missing control token",
+            )),
+        ),
+        Ok(request) => {
+            let result = dispatch(app, &request.method, request.params).await;
+            RpcResponse::new(request.id, result)
+        }
+        Err(e) => RpcResponse::new(
+            Value::Null,
+            Err(RpcError::new(PARSE_ERROR, format!("Invalid request: {e}"))),
+        ),
+    };
+    serde_json::to_string(&response).unwrap_or_default()
+}
+
+fn failed(message: String) -> RpcError {
+    RpcError::new(APP_ERROR, message)
+}
+
+/// A text parameter; numbers and booleans are accepted as their text, since the CLI
+/// reads `key=value` values as JSON when they parse.
+fn str_param(params: &Value, key: &str) -> Option<String> {
+    match params.get(key)? {
+        Value::String(text) => Some(text.clone()),
+        value @ (Value::Number(_) | Value::Bool(_)) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn required_str(params: &Value, key: &str) -> Result<String, RpcError> {
+    str_param(params, key)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Missing parameter: {key}")))
+}
+
+async fn dispatch(app: &AppHandle, method: &str, params: Value) -> Result<Value, RpcError> {
+    let processes = || -> Arc<ProcessRegistry> { Arc::clone(&app.state::<ProcessState>().0) };
+    let pid = || -> Result<u32, RpcError> {
+        params
+            .get("pid")
+            .and_then(Value::as_u64)
+            .and_then(|pid| u32::try_from(pid).ok())
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing parameter: pid"))
+    };
+    match method {
+        "show" => {
+            show(app).await?;
+            if let Some(query) = str_param(&params, "query") {
+                let _ = app.emit(CONTROL_QUERY_EVENT, query);
+            }
+            Ok(Value::Null)
+        }
+        "hide" => {
+            hotkeys::hide_launcher(app);
+            Ok(Value::Null)
+        }
+        "toggle" => {
+            let app = app.clone();
+            // Capturing the launch context may shell out
+            tokio::task::spawn_blocking(move || hotkeys::toggle_launcher(&app))
+                .await
+                .map_err(|e| failed(format!("Task failed: {e}")))?;
+            Ok(Value::Null)
+        }
+        "refresh" => {
+            let _ = app.emit("items-changed", ());
+            Ok(Value::Null)
+        }
+        "search" => {
+            let query = required_str(&params, "query")?;
+            // A list, or a comma-separated string from the command line
+            let providers = match params.get("providers") {
+                None | Some(Value::Null) => None,
+                Some(Value::String(list)) => {
+                    Some(list.split(',').map(|p| p.trim().to_string()).collect())
+                }
+                Some(list) => Some(serde_json::from_value::<Vec<String>>(list.clone()).map_err(
+                    |e| RpcError::new(INVALID_PARAMS, format!("Invalid providers: {e}")),
+                )?),
+            };
+            let limit = params
+                .get("limit")
+                .and_then(Value::as_u64)
+                .map(|n| n as usize);
+            let results = commands::search(app.state(), query, providers, limit)
+                .await
+                .map_err(failed)?;
+            Ok(json!(results))
+        }
+        "execute_item" => {
+            let id = required_str(&params, "id")?;
+            let action_id = str_param(&params, "action_id");
+            let app = app.clone();
+            tokio::task::spawn_blocking(move || commands::execute_item(app, id, action_id))
+                .await
+                .map_err(|e| failed(format!("Task failed: {e}")))?
+                .map_err(failed)?;
+            Ok(Value::Null)
+        }
+        "acp.prompt" => {
+            let text = required_str(&params, "text")?;
+            let conversation_id = commands::acp_prompt(
+                app.state(),
+                app.state(),
+                text,
+                Vec::new(),
+                str_param(&params, "conversation_id"),
+                str_param(&params, "agent_id"),
+            )
+            .await
+            .map_err(failed)?;
+            Ok(json!({ "conversation_id": conversation_id }))
+        }
+        "acp.status" => {
+            let status = commands::acp_get_status(app.state(), str_param(&params, "agent_id"))
+                .await
+                .map_err(failed)?;
+            Ok(json!(status))
+        }
+        "context.get" => Ok(json!(
+            commands::get_launch_context(app.state()).map_err(failed)?
+        )),
+        "processes.list" => Ok(json!(processes().list())),
+        "processes.kill" => {
+            processes().kill(pid()?).map_err(failed)?;
+            Ok(Value::Null)
+        }
+        "processes.tail" => {
+            let lines = params
+                .get("lines")
+                .and_then(Value::as_u64)
+                .map_or(DEFAULT_TAIL_LINES, |n| n as usize);
+            Ok(json!(processes().tail(pid()?, lines).map_err(failed)?))
+        }
+        other => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method: {other}"),
+        )),
+    }
+}
+
+/// Show the launcher off the async runtime, since capturing the launch context may
+/// shell out.
+async fn show(app: &AppHandle) -> Result<(), RpcError> {
+    let app = app.clone();
+    tokio::task::spawn_blocking(move || hotkeys::show_launcher(&app))
+        .await
+        .map_err(|e| failed(format!("Task failed: {e}")))
+}
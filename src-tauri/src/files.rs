@@ -0,0 +1,119 @@
+use std::path::Path;
+
+use golaunch_core::files::{rebuild_file_index, watch_file_index};
+use golaunch_core::Database;
+
+/// Rebuild the index on startup when it is older than this; the watcher covers the rest.
+const REBUILD_AFTER_HOURS: i64 = 24;
+
+fn index_is_stale(db: &Database) -> Result<bool, String> {
+    if db.count_file_entries()? == 0 {
+        return Ok(true);
+    }
+    let Some(built_at) = db.file_index_built_at()? else {
+        return Ok(true);
+    };
+    Ok(chrono::Utc::now().timestamp() - built_at > REBUILD_AFTER_HOURS * 3600)
+}
+
+/// Build the file index in the background when it is missing or stale, then keep it
+/// up to date with filesystem changes. Does nothing unless the index is turned on.
+pub fn spawn_indexer() {
+    std::thread::spawn(move || {
+        let db = match Database::new() {
+            Ok(db) => db,
+            Err(e) => {
+                eprintln!("File indexer unavailable: {e}");
+                return;
+            }
+        };
+        let settings = match db.get_file_index_settings() {
+            Ok(settings) => settings,
+            Err(e) => {
+                eprintln!("File indexer unavailable: {e}");
+                return;
+            }
+        };
+        if !settings.enabled {
+            // An index left from before it was turned off isn't kept searchable
+            if db.count_file_entries().is_ok_and(|count| count > 0) {
+                if let Err(e) = db.replace_file_index(&[]) {
+                    eprintln!("Failed to clear file index: {e}");
+                }
+            }
+            return;
+        }
+
+        match index_is_stale(&db) {
+            Ok(true) => {
+                if let Err(e) = rebuild_file_index(&db, &settings) {
+                    eprintln!("Failed to build file index: {e}");
+                }
+            }
+            Ok(false) => {}
+            Err(e) => eprintln!("Failed to read file index state: {e}"),
+        }
+
+        if let Err(e) = watch_file_index(&db, &settings) {
+            eprintln!("Failed to watch indexed files: {e}");
+        }
+    });
+}
+
+/// Show a file or directory selected in the system file manager.
+fn reveal(path: &Path) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg("-R")
+            .arg(path)
+            .spawn()
+            .map_err(|e| format!("Failed to reveal file: {e}"))?;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(path);
+        std::process::Command::new("explorer")
+            .arg(select)
+            .spawn()
+            .map_err(|e| format!("Failed to reveal file: {e}"))?;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        // File managers implementing org.freedesktop.FileManager1 can select the item;
+        // otherwise open the containing directory.
+        let uri = url::Url::from_file_path(path)
+            .map_err(|_| format!("Not an absolute path: {}", path.display()))?;
+        let selected = std::process::Command::new("gdbus")
+            .args([
+                "call",
+                "--session",
+                "--dest",
+                "org.freedesktop.FileManager1",
+                "--object-path",
+                "/org/freedesktop/FileManager1",
+                "--method",
+                "org.freedesktop.FileManager1.ShowItems",
+                &format!("['{}']", uri.as_str().replace('\'', "%27")),
+                "",
+            ])
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if !selected {
+            let parent = path.parent().unwrap_or(path);
+            open::that(parent).map_err(|e| format!("Failed to open folder: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Run one of the file result actions: "open", "reveal" or "copy_path".
+pub fn run_action(path: &str, action: &str) -> Result<(), String> {
+    match action {
+        "open" => open::that(path).map_err(|e| format!("Failed to open file: {e}")),
+        "reveal" => reveal(Path::new(path)),
+        "copy_path" => crate::clipboard::copy_text(path.to_string()),
+        other => Err(format!("Unknown file action: {other}")),
+    }
+}
@@ -0,0 +1,235 @@
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use golaunch_core::{Database, HotkeyBinding};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::context;
+use crate::LaunchContextState;
+
+/// How often the background watcher checks the database for hotkey changes
+/// made outside the app (e.g. via `golaunch-cli add --hotkey`).
+const WATCH_INTERVAL_SECS: u64 = 3;
+
+/// Counts launcher opens, so context providers still running for an earlier launch
+/// don't overwrite the context of a newer one.
+static LAUNCH_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// A hotkey that could not be registered, reported to the frontend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeyConflict {
+    pub hotkey: String,
+    pub label: String,
+    pub reason: String,
+}
+
+/// Check that a hotkey string can be registered as a global shortcut.
+pub fn parse_shortcut(hotkey: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(hotkey).map_err(|e| format!("Invalid hotkey '{hotkey}': {e}"))
+}
+
+/// Unregister every global shortcut and register the current set from the database.
+/// Bindings that fail (invalid, duplicated, or taken by another application) are
+/// returned and emitted as a `hotkey-conflicts` event.
+pub fn register_all(app: &AppHandle) -> Result<Vec<HotkeyConflict>, String> {
+    let db = Database::new()?;
+    let bindings = db.list_hotkey_bindings()?;
+
+    let global_shortcut = app.global_shortcut();
+    global_shortcut
+        .unregister_all()
+        .map_err(|e| format!("Failed to unregister hotkeys: {e}"))?;
+
+    let mut registered: Vec<(Shortcut, String)> = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for binding in bindings {
+        let shortcut = match parse_shortcut(&binding.hotkey) {
+            Ok(s) => s,
+            Err(reason) => {
+                conflicts.push(HotkeyConflict {
+                    hotkey: binding.hotkey,
+                    label: binding.label,
+                    reason,
+                });
+                continue;
+            }
+        };
+
+        if let Some((_, other)) = registered.iter().find(|(s, _)| s.id() == shortcut.id()) {
+            conflicts.push(HotkeyConflict {
+                reason: format!("Same key combination as '{other}'"),
+                hotkey: binding.hotkey,
+                label: binding.label,
+            });
+            continue;
+        }
+
+        let label = binding.label.clone();
+        let hotkey = binding.hotkey.clone();
+        let target = binding;
+        let result = global_shortcut.on_shortcut(shortcut, move |app, _shortcut, event| {
+            if event.state != ShortcutState::Pressed {
+                return;
+            }
+            handle_binding(app, &target);
+        });
+
+        match result {
+            Ok(()) => registered.push((shortcut, label)),
+            Err(e) => conflicts.push(HotkeyConflict {
+                hotkey,
+                label,
+                reason: format!("Could not register (already in use by another application?): {e}"),
+            }),
+        }
+    }
+
+    if !conflicts.is_empty() {
+        let _ = app.emit("hotkey-conflicts", &conflicts);
+    }
+
+    Ok(conflicts)
+}
+
+/// Watch the database for hotkey changes and re-register when they differ.
+pub fn spawn_watcher(app: AppHandle) {
+    std::thread::spawn(move || {
+        // One connection for the life of the watcher: opening one runs the schema setup
+        let db = match Database::new() {
+            Ok(db) => db,
+            Err(e) => {
+                eprintln!("Hotkey watcher unavailable: {e}");
+                return;
+            }
+        };
+        let mut last = fingerprint(&db).unwrap_or_default();
+        loop {
+            std::thread::sleep(std::time::Duration::from_secs(WATCH_INTERVAL_SECS));
+            let Ok(current) = fingerprint(&db) else {
+                continue;
+            };
+            if current != last {
+                if let Err(e) = register_all(&app) {
+                    eprintln!("Failed to reload hotkeys: {e}");
+                }
+                last = current;
+            }
+        }
+    });
+}
+
+fn fingerprint(db: &Database) -> Result<Vec<(String, Option<String>)>, String> {
+    Ok(db
+        .list_hotkey_bindings()?
+        .into_iter()
+        .map(|b| (b.hotkey, b.target_id))
+        .collect())
+}
+
+/// Dispatch a pressed hotkey. Runs inside the shortcut plugin's event handler, so
+/// anything that may block (or touch shortcut registration) happens on another thread.
+fn handle_binding(app: &AppHandle, binding: &HotkeyBinding) {
+    match (binding.target_type.as_str(), binding.target_id.clone()) {
+        ("toggle", _) => toggle_launcher(app),
+        ("item", Some(id)) => {
+            let app = app.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = crate::commands::run_item(&app, &id, "", None) {
+                    eprintln!("Hotkey item failed: {e}");
+                }
+            });
+        }
+        ("slash_command", Some(id)) => {
+            let app = app.clone();
+            std::thread::spawn(move || {
+                let result = Database::new()
+                    .and_then(|db| db.get_slash_command(&id))
+                    .and_then(|cmd| crate::commands::run_slash_command(&app, &cmd.name, ""));
+                if let Err(e) = result {
+                    eprintln!("Hotkey slash command failed: {e}");
+                }
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Show the launcher (capturing the launch context first) or hide it if visible.
+pub fn toggle_launcher(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    if window.is_visible().unwrap_or(false) {
+        hide_launcher(app);
+    } else {
+        show_launcher(app);
+    }
+}
+
+/// Hide the launcher and reset its query.
+pub fn hide_launcher(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let _ = app.emit("launcher-reset", ());
+    let _ = window.hide();
+}
+
+/// Capture the launch context and show the launcher. Does nothing if it is already
+/// visible, so the context of the earlier launch is kept.
+pub fn show_launcher(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if window.is_visible().unwrap_or(false) {
+        let _ = window.set_focus();
+        return;
+    }
+
+    // One connection serves both the capture settings and the providers below.
+    let db = Database::new().ok();
+
+    // Capture context BEFORE showing the launcher (while source app has focus)
+    let generation = LAUNCH_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let ctx = context::capture_launch_context(db.as_ref());
+    if let Some(state) = app.try_state::<LaunchContextState>() {
+        if let Ok(mut lock) = state.0.lock() {
+            *lock = ctx.clone();
+        }
+    }
+    // Emit the context to the frontend
+    let _ = app.emit("launch-context", &ctx);
+
+    let _ = window.show();
+    let _ = window.set_focus();
+    let _ = window.center();
+
+    // Providers (cwd, git, scripts) may shell out, so enrich after showing the window.
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let Some(db) = db else {
+            return;
+        };
+        let mut ctx = ctx;
+        context::apply_context_providers(&db, &mut ctx);
+        if ctx.sections.is_empty() {
+            return;
+        }
+        let Some(state) = app.try_state::<LaunchContextState>() else {
+            return;
+        };
+        // Checked under the lock: a newer launch bumps the generation before storing its context
+        let Ok(mut lock) = state.0.lock() else {
+            return;
+        };
+        if LAUNCH_GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        *lock = ctx.clone();
+        let _ = app.emit("launch-context", &ctx);
+    });
+}
@@ -1,15 +1,27 @@
 mod acp;
+mod answers;
+#[cfg(target_os = "linux")]
+mod apps;
+mod clipboard;
 mod commands;
 mod context;
+mod control;
+mod files;
+mod hotkeys;
+mod processes;
+mod scheduler;
+mod search;
+mod snippets;
+mod workflows;
 
 use commands::*;
 use context::LaunchContext;
+use processes::{ProcessRegistry, ProcessState};
 use std::sync::{Arc, Mutex as StdMutex};
 use tauri::{Emitter, Manager, WindowEvent};
-use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
 use tokio::sync::Mutex;
 
-use acp::manager::AcpManager;
+use acp::pool::AcpPool;
 
 /// Shared state holding the most recent launch context.
 pub struct LaunchContextState(pub StdMutex<LaunchContext>);
@@ -20,9 +32,15 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .invoke_handler(tauri::generate_handler![
+            search,
             search_items,
+            copy_quick_answer,
             get_all_items,
             execute_item,
+            get_item_actions,
+            update_item,
+            get_item_history,
+            execute_snippet,
             get_categories,
             hide_window,
             set_window_compact,
@@ -33,8 +51,11 @@ pub fn run() {
             acp_connect,
             acp_disconnect,
             acp_get_status,
+            acp_list_agents,
+            acp_new_conversation,
             acp_prompt,
             acp_cancel,
+            acp_open_conversation,
             acp_resolve_permission,
             acp_get_config_options,
             acp_set_config_option,
@@ -58,6 +79,10 @@ pub fn run() {
             get_memory_by_key,
             get_relevant_memories,
             get_launch_context,
+            list_context_providers,
+            set_context_provider_enabled,
+            set_context_script,
+            remove_context_script,
             type_text_to_app,
             replace_selection_text,
             record_rewrite,
@@ -68,28 +93,46 @@ pub fn run() {
             add_slash_command,
             remove_slash_command,
             execute_slash_command,
+            get_hotkeys,
+            set_toggle_hotkey,
+            set_item_hotkey,
+            reload_hotkeys,
+            search_clipboard_items,
+            paste_clipboard_entry,
+            get_clipboard_settings,
+            set_clipboard_settings,
+            search_file_items,
+            file_item_action,
+            list_schedules,
+            search_schedule_items,
+            run_schedule_now,
+            list_running,
+            kill_running,
+            tail_running,
+            search_running_items,
+            process_item_action,
         ])
         .setup(|app| {
             let handle = app.handle().clone();
 
-            // Initialize ACP manager state
-            app.manage(AcpState(Arc::new(Mutex::new(AcpManager::new()))));
+            // Initialize the ACP agent pool, shutting down agents left unused
+            let acp_pool = Arc::new(Mutex::new(AcpPool::new()));
+            tauri::async_runtime::spawn(acp::pool::watch_idle_agents(acp_pool.clone()));
+            app.manage(AcpState(acp_pool));
+
+            // Child processes started by items and slash commands
+            app.manage(ProcessState(Arc::new(ProcessRegistry::default())));
+
+            // Built-in search providers for the aggregated `search` command
+            app.manage(SearchState(search::builtin_providers()));
 
             // Initialize launch context state
             app.manage(LaunchContextState(StdMutex::new(LaunchContext::default())));
 
-            // Register global shortcut: Option+Space on macOS, Ctrl+Space on Windows/Linux
-            let shortcut = if cfg!(target_os = "macos") {
-                Shortcut::new(Some(Modifiers::ALT), Code::Space)
-            } else {
-                Shortcut::new(Some(Modifiers::CONTROL), Code::Space)
-            };
             app.handle()
                 .plugin(tauri_plugin_global_shortcut::Builder::new().build())
                 .unwrap_or(());
 
-            let handle_clone = handle.clone();
-
             if let Some(window) = app.get_webview_window("main") {
                 let handle_on_close = handle.clone();
                 window.on_window_event(move |event| {
@@ -103,33 +146,26 @@ pub fn run() {
                 });
             }
 
-            app.global_shortcut()
-                .on_shortcut(shortcut, move |_app, _shortcut, event| {
-                    if event.state != ShortcutState::Pressed {
-                        return;
-                    }
+            // Register the launcher toggle plus per-item/slash-command hotkeys from settings,
+            // then keep them in sync with changes made through the CLI.
+            hotkeys::register_all(&handle)?;
+            hotkeys::spawn_watcher(handle.clone());
 
-                    if let Some(window) = handle_clone.get_webview_window("main") {
-                        if window.is_visible().unwrap_or(false) {
-                            let _ = handle_clone.emit("launcher-reset", ());
-                            let _ = window.hide();
-                        } else {
-                            // Capture context BEFORE showing the launcher (while source app has focus)
-                            let ctx = context::capture_launch_context();
-                            if let Some(state) = handle_clone.try_state::<LaunchContextState>() {
-                                if let Ok(mut lock) = state.0.lock() {
-                                    *lock = ctx.clone();
-                                }
-                            }
-                            // Emit the context to the frontend
-                            let _ = handle_clone.emit("launch-context", &ctx);
-
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                            let _ = window.center();
-                        }
-                    }
-                })?;
+            // Record clipboard history (a no-op until enabled in settings).
+            clipboard::spawn_watcher(handle.clone());
+
+            // Keep installed applications (.desktop entries) in sync with the launcher.
+            #[cfg(target_os = "linux")]
+            apps::spawn_indexer(handle.clone());
+
+            // Index files under the configured roots for the `file:` and `~` searches.
+            files::spawn_indexer();
+
+            // Run items and slash commands on their schedules and triggers.
+            scheduler::spawn_scheduler(handle.clone());
+
+            // Answer `golaunch-cli ps`/`kill` and other requests from the CLI.
+            control::spawn_server(handle.clone());
 
             Ok(())
         })
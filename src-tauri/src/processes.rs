@@ -0,0 +1,249 @@
+//! Registry of child processes started by the app, so long-running commands can be
+//! listed, tailed and stopped from the launcher and through the control socket.
+
+use std::collections::VecDeque;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Condvar, Mutex};
+
+use golaunch_core::RunningProcess;
+use tauri::{AppHandle, Emitter};
+
+/// Emitted (without payload) when a process starts or exits.
+pub const PROCESSES_CHANGED_EVENT: &str = "processes-changed";
+
+/// Output lines kept per process; older lines are dropped.
+const OUTPUT_LINES: usize = 1000;
+/// Longer lines are cut so one runaway line can't fill memory.
+const MAX_LINE_CHARS: usize = 4096;
+/// Exited processes stay listed (with their output) until this many newer ones exit.
+const KEEP_FINISHED: usize = 20;
+
+struct Entry {
+    info: RunningProcess,
+    output: VecDeque<String>,
+}
+
+#[derive(Default)]
+pub struct ProcessRegistry {
+    entries: Mutex<Vec<Entry>>,
+    /// Signalled whenever a process exits.
+    exited: Condvar,
+}
+
+/// Processes started by the app, shared with the control socket.
+pub struct ProcessState(pub Arc<ProcessRegistry>);
+
+/// A shell running `command_line` (`sh -c` or `cmd /C`).
+pub fn shell_command(command_line: &str) -> Command {
+    #[cfg(target_os = "windows")]
+    {
+        let mut command = Command::new("cmd");
+        command.args(["/C", command_line]);
+        command
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut command = Command::new("sh");
+        command.args(["-c", command_line]);
+        command
+    }
+}
+
+impl ProcessRegistry {
+    /// Start `command` with its output captured and return its pid. The process is
+    /// listed until well after it exits.
+    pub fn spawn(
+        self: &Arc<Self>,
+        app: &AppHandle,
+        mut command: Command,
+        item_id: Option<String>,
+        title: String,
+        command_text: String,
+    ) -> Result<u32, String> {
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // Own process group, so stopping `sh -c "npm run dev"` also stops npm's children
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("Failed to execute {title}: {e}"))?;
+        let pid = child.id();
+
+        self.entries.lock().unwrap().push(Entry {
+            info: RunningProcess {
+                pid,
+                item_id,
+                title,
+                command: command_text,
+                started_at: chrono::Local::now().to_rfc3339(),
+                running: true,
+                exit_code: None,
+            },
+            output: VecDeque::new(),
+        });
+        let _ = app.emit(PROCESSES_CHANGED_EVENT, ());
+
+        let readers: Vec<_> = [
+            child
+                .stdout
+                .take()
+                .map(|s| Box::new(s) as Box<dyn Read + Send>),
+            child
+                .stderr
+                .take()
+                .map(|s| Box::new(s) as Box<dyn Read + Send>),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|stream| {
+            let registry = Arc::clone(self);
+            std::thread::spawn(move || registry.capture(pid, stream))
+        })
+        .collect();
+
+        let registry = Arc::clone(self);
+        let app = app.clone();
+        std::thread::spawn(move || {
+            let exit_code = child.wait().ok().and_then(|status| status.code());
+            // Output is complete once both pipes are closed
+            for reader in readers {
+                let _ = reader.join();
+            }
+            registry.finish(pid, exit_code);
+            let _ = app.emit(PROCESSES_CHANGED_EVENT, ());
+        });
+        Ok(pid)
+    }
+
+    /// Record a stream's lines as they arrive. Reads go through a fixed buffer and
+    /// bytes past `MAX_LINE_CHARS` are dropped, so a process printing one endless
+    /// line can't grow memory.
+    fn capture(&self, pid: u32, mut stream: Box<dyn Read + Send>) {
+        // Room for MAX_LINE_CHARS characters of up to four UTF-8 bytes each
+        let max_line_bytes = MAX_LINE_CHARS * 4;
+        let mut chunk = [0u8; 8192];
+        let mut line = Vec::new();
+        loop {
+            let read = match stream.read(&mut chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => read,
+            };
+            let mut rest = &chunk[..read];
+            while let Some(end) = rest.iter().position(|&b| b == b'\n') {
+                let room = max_line_bytes.saturating_sub(line.len());
+                line.extend_from_slice(&rest[..end.min(room)]);
+                self.push_line(pid, &line);
+                line.clear();
+                rest = &rest[end + 1..];
+            }
+            let room = max_line_bytes.saturating_sub(line.len());
+            line.extend_from_slice(&rest[..rest.len().min(room)]);
+        }
+        if !line.is_empty() {
+            self.push_line(pid, &line);
+        }
+    }
+
+    fn push_line(&self, pid: u32, bytes: &[u8]) {
+        let line: String = String::from_utf8_lossy(bytes)
+            .trim_end_matches('\r')
+            .chars()
+            .take(MAX_LINE_CHARS)
+            .collect();
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries
+            .iter_mut()
+            .rev()
+            .find(|e| e.info.pid == pid && e.info.running)
+        {
+            if entry.output.len() == OUTPUT_LINES {
+                entry.output.pop_front();
+            }
+            entry.output.push_back(line);
+        }
+    }
+
+    fn finish(&self, pid: u32, exit_code: Option<i32>) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries
+            .iter_mut()
+            .rev()
+            .find(|e| e.info.pid == pid && e.info.running)
+        {
+            entry.info.running = false;
+            entry.info.exit_code = exit_code;
+        }
+        let finished = entries.iter().filter(|e| !e.info.running).count();
+        let mut excess = finished.saturating_sub(KEEP_FINISHED);
+        entries.retain(|e| {
+            let drop = excess > 0 && !e.info.running;
+            if drop {
+                excess -= 1;
+            }
+            !drop
+        });
+        self.exited.notify_all();
+    }
+
+    /// Listed processes, newest first.
+    pub fn list(&self) -> Vec<RunningProcess> {
+        let entries = self.entries.lock().unwrap();
+        entries.iter().rev().map(|e| e.info.clone()).collect()
+    }
+
+    /// The newest entry for `pid` (pids can be reused after a process exits).
+    fn latest(entries: &[Entry], pid: u32) -> Result<&Entry, String> {
+        entries
+            .iter()
+            .rev()
+            .find(|e| e.info.pid == pid)
+            .ok_or_else(|| format!("No process with pid {pid} was started by GoLaunch"))
+    }
+
+    /// The last `lines` lines of a process's output (stdout and stderr interleaved).
+    pub fn tail(&self, pid: u32, lines: usize) -> Result<Vec<String>, String> {
+        let entries = self.entries.lock().unwrap();
+        let entry = Self::latest(&entries, pid)?;
+        let skip = entry.output.len().saturating_sub(lines);
+        Ok(entry.output.iter().skip(skip).cloned().collect())
+    }
+
+    /// Block until a process exits and return its exit code.
+    pub fn wait(&self, pid: u32) -> Result<Option<i32>, String> {
+        let mut entries = self.entries.lock().unwrap();
+        loop {
+            let entry = Self::latest(&entries, pid)?;
+            if !entry.info.running {
+                return Ok(entry.info.exit_code);
+            }
+            entries = self.exited.wait(entries).unwrap();
+        }
+    }
+
+    /// Stop a running process and everything it started.
+    pub fn kill(&self, pid: u32) -> Result<(), String> {
+        {
+            let entries = self.entries.lock().unwrap();
+            if !Self::latest(&entries, pid)?.info.running {
+                return Err(format!("Process {pid} has already exited"));
+            }
+        }
+        #[cfg(target_os = "windows")]
+        let status = Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .status();
+        #[cfg(not(target_os = "windows"))]
+        let status = Command::new("kill")
+            .args(["-TERM", "--", &format!("-{pid}")])
+            .status();
+        match status {
+            Ok(status) if status.success() => Ok(()),
+            Ok(status) => Err(format!("Failed to stop process {pid} ({status})")),
+            Err(e) => Err(format!("Failed to stop process {pid}: {e}")),
+        }
+    }
+}
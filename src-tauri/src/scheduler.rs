@@ -0,0 +1,161 @@
+//! Runs scheduled items and slash commands: on their timetable, when the app starts
+//! and when watched tables change. Schedules are re-read every tick, so ones added
+//! through the CLI are picked up without a restart. Each run gets its own thread, so a
+//! slow item or slash command doesn't hold up the other schedules.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use chrono::Local;
+use golaunch_core::schedules::{
+    next_run_at, parse_run_time, run_schedule, MissedRunPolicy, ScheduleTrigger,
+};
+use golaunch_core::{Database, Schedule};
+use tauri::{AppHandle, Emitter};
+
+use crate::workflows::{AppStepRunner, WORKFLOW_PROGRESS_EVENT};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A due time further in the past than this was missed (app closed, machine asleep)
+/// and goes through the schedule's missed-run policy instead of running right away.
+const MISSED_AFTER_SECS: i64 = 120;
+
+/// Run a schedule now, reporting workflow progress to the launcher. Returns the exit code.
+pub fn run_now(app: &AppHandle, db: &Database, schedule: &Schedule) -> Result<i32, String> {
+    let mut runner = AppStepRunner::new(None);
+    run_schedule(db, schedule, &mut runner, &mut |progress| {
+        let _ = app.emit(WORKFLOW_PROGRESS_EVENT, progress);
+    })
+}
+
+pub fn spawn_scheduler(app: AppHandle) {
+    std::thread::spawn(move || {
+        let db = match Database::new() {
+            Ok(db) => db,
+            Err(e) => {
+                eprintln!("Scheduler unavailable: {e}");
+                return;
+            }
+        };
+        let mut versions = db.table_versions().unwrap_or_default();
+        let mut starting = true;
+        // Schedules with a run in progress, which are not started again until it ends.
+        let mut running = HashSet::new();
+        let (done_tx, done_rx) = mpsc::channel::<FinishedRun>();
+
+        loop {
+            // Table versions the runs that just ended left behind
+            let mut written: HashMap<String, i64> = HashMap::new();
+            while let Ok(run) = done_rx.try_recv() {
+                running.remove(&run.schedule_id);
+                for (table, version) in run.written {
+                    let latest = written.entry(table).or_insert(version);
+                    *latest = (*latest).max(version);
+                }
+            }
+
+            let current = db.table_versions().unwrap_or_default();
+            // Changes made by the runs that just ended should not trigger another round,
+            // but an edit made after them still does.
+            let changed: Vec<&String> = current
+                .iter()
+                .filter(|(table, version)| {
+                    versions.get(*table) != Some(version) && written.get(*table) != Some(version)
+                })
+                .map(|(table, _)| table)
+                .collect();
+
+            let schedules = db.list_schedules().unwrap_or_default();
+            for schedule in schedules.iter().filter(|s| s.enabled) {
+                if running.contains(&schedule.id) {
+                    continue;
+                }
+                if is_due(&db, schedule, starting, &changed) {
+                    running.insert(schedule.id.clone());
+                    spawn_run(app.clone(), schedule.clone(), done_tx.clone());
+                }
+            }
+
+            versions = current;
+            starting = false;
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+/// A run that ended, with the version of each table that changed while it ran.
+struct FinishedRun {
+    schedule_id: String,
+    written: HashMap<String, i64>,
+}
+
+/// Run a schedule on its own thread and report it on `done` when it ends.
+fn spawn_run(app: AppHandle, schedule: Schedule, done: mpsc::Sender<FinishedRun>) {
+    std::thread::spawn(move || {
+        let mut written = HashMap::new();
+        let result = Database::new().and_then(|db| {
+            let before = db.table_versions().unwrap_or_default();
+            let result = run_now(&app, &db, &schedule);
+            written = db
+                .table_versions()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(table, version)| before.get(table) != Some(version))
+                .collect();
+            result
+        });
+        if let Err(e) = result {
+            eprintln!(
+                "Scheduled run of {} failed: {e}",
+                schedule
+                    .target_name
+                    .as_deref()
+                    .unwrap_or(&schedule.target_id)
+            );
+        }
+        let _ = done.send(FinishedRun {
+            schedule_id: schedule.id,
+            written,
+        });
+    });
+}
+
+/// Whether a schedule should run on this tick. Timetable schedules whose runs were
+/// missed either run once or move on to their next time, per their policy.
+fn is_due(db: &Database, schedule: &Schedule, starting: bool, changed: &[&String]) -> bool {
+    let trigger = match ScheduleTrigger::parse(&schedule.spec) {
+        Ok(trigger) => trigger,
+        Err(e) => {
+            eprintln!("Skipping schedule {}: {e}", schedule.id);
+            return false;
+        }
+    };
+    match &trigger {
+        ScheduleTrigger::AppStart => return starting,
+        ScheduleTrigger::TableChange(table) => return changed.contains(&table),
+        ScheduleTrigger::Cron(_) => {}
+    }
+
+    let now = Local::now();
+    let Some(due) = schedule.next_run_at.as_deref().and_then(parse_run_time) else {
+        let next = next_run_at(&trigger, now);
+        let _ = db.set_schedule_next_run(&schedule.id, next.as_deref());
+        return false;
+    };
+    if due > now {
+        return false;
+    }
+    if (now - due).num_seconds() <= MISSED_AFTER_SECS {
+        return true;
+    }
+    match MissedRunPolicy::parse(&schedule.missed_policy).unwrap_or(MissedRunPolicy::Skip) {
+        MissedRunPolicy::RunOnce => true,
+        MissedRunPolicy::Skip => {
+            let next = next_run_at(&trigger, now);
+            let _ = db.set_schedule_next_run(&schedule.id, next.as_deref());
+            false
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Aggregated launcher search: every source of results implements `SearchProvider`,
+//! and `search_all` queries them concurrently and merges the results by score.
+
+mod providers;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+
+pub use providers::builtin_providers;
+
+/// Something the user can do with a result, e.g. run it or copy its value.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchAction {
+    pub id: String,
+    pub title: String,
+}
+
+impl SearchAction {
+    pub fn new(id: &str, title: impl Into<String>) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.into(),
+        }
+    }
+}
+
+/// One row from any provider.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    /// What the result is ("item", "slash_command", "conversation", "memory", "answer").
+    pub kind: String,
+    /// Unique within `kind`.
+    pub id: String,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub icon: Option<String>,
+    /// Higher is better. Text matches score up to 1.0; providers may go above that to
+    /// pin results (quick answers) or below it to rank them after launcher items.
+    pub score: f64,
+    /// Available actions, the default one first.
+    pub actions: Vec<SearchAction>,
+    /// The underlying record (an `Item`, `SlashCommand`, ...) for kind-specific handling.
+    pub data: serde_json::Value,
+}
+
+#[async_trait::async_trait]
+pub trait SearchProvider: Send + Sync {
+    /// Stable id used to select providers, e.g. "items".
+    fn id(&self) -> &'static str;
+
+    /// How long the aggregated search waits for this provider before leaving it out.
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(250)
+    }
+
+    async fn search(&self, query: String, limit: usize) -> Result<Vec<SearchResult>, String>;
+}
+
+/// Query `providers` concurrently and merge their results, best first. A provider
+/// that fails or exceeds its timeout contributes nothing instead of failing the search.
+pub async fn search_all(
+    providers: &[Arc<dyn SearchProvider>],
+    query: &str,
+    limit: usize,
+) -> Vec<SearchResult> {
+    let searches = providers.iter().map(|provider| {
+        let query = query.to_string();
+        async move {
+            match tokio::time::timeout(provider.timeout(), provider.search(query, limit)).await {
+                Ok(Ok(results)) => results,
+                Ok(Err(e)) => {
+                    eprintln!("Search provider '{}' failed: {e}", provider.id());
+                    Vec::new()
+                }
+                Err(_) => {
+                    eprintln!("Search provider '{}' timed out", provider.id());
+                    Vec::new()
+                }
+            }
+        }
+    });
+
+    let mut results: Vec<SearchResult> = futures::future::join_all(searches)
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+    // Stable, so equal scores keep provider order.
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results.truncate(limit);
+    results
+}
+
+/// How well `text` matches `query`: 1.0 for an exact match, then prefix, word prefix
+/// and substring matches. Results that only matched on other fields get 0.5.
+pub fn text_score(query: &str, text: &str) -> f64 {
+    let query = query.trim().to_lowercase();
+    let text = text.to_lowercase();
+    if query.is_empty() {
+        0.5
+    } else if text == query {
+        1.0
+    } else if text.starts_with(&query) {
+        0.9
+    } else if text
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(&query))
+    {
+        0.8
+    } else if text.contains(&query) {
+        0.7
+    } else {
+        0.5
+    }
+}
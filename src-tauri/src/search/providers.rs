@@ -0,0 +1,256 @@
+use std::sync::{Arc, Mutex};
+
+use golaunch_core::{Database, Item};
+
+use super::{text_score, SearchAction, SearchProvider, SearchResult};
+use crate::answers::quick_answers;
+
+/// Conversations and memories are only searched once the query is this long; a single
+/// letter matches nearly every message.
+const MIN_TEXT_QUERY_LEN: usize = 2;
+
+/// Subtitles are single lines in the launcher.
+const SUBTITLE_CHARS: usize = 80;
+
+pub fn builtin_providers() -> Vec<Arc<dyn SearchProvider>> {
+    vec![
+        Arc::new(AnswersProvider),
+        Arc::new(ItemsProvider::default()),
+        Arc::new(SlashCommandsProvider::default()),
+        Arc::new(ConversationsProvider::default()),
+        Arc::new(MemoriesProvider::default()),
+    ]
+}
+
+/// A provider's database connection, opened by its first search and kept for the
+/// next keystrokes.
+#[derive(Default)]
+struct ProviderDb(Arc<Mutex<Option<Database>>>);
+
+impl ProviderDb {
+    /// Run a database query off the async runtime.
+    async fn with<T: Send + 'static>(
+        &self,
+        query: impl FnOnce(&Database) -> Result<T, String> + Send + 'static,
+    ) -> Result<T, String> {
+        let connection = Arc::clone(&self.0);
+        tokio::task::spawn_blocking(move || {
+            let mut connection = connection
+                .lock()
+                .map_err(|_| "Search database lock poisoned".to_string())?;
+            let db = match &mut *connection {
+                Some(db) => db,
+                None => connection.insert(Database::new()?),
+            };
+            query(db)
+        })
+        .await
+        .map_err(|e| format!("Search task failed: {e}"))?
+    }
+}
+
+fn one_line(text: &str) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > SUBTITLE_CHARS {
+        let truncated: String = line.chars().take(SUBTITLE_CHARS).collect();
+        format!("{truncated}…")
+    } else {
+        line
+    }
+}
+
+fn to_data<T: serde::Serialize>(record: &T) -> serde_json::Value {
+    serde_json::to_value(record).unwrap_or_default()
+}
+
+/// Small bonus for often-used results, so they win ties without beating better matches.
+fn usage_boost(count: i64) -> f64 {
+    count.clamp(0, 50) as f64 / 500.0
+}
+
+struct AnswersProvider;
+
+#[async_trait::async_trait]
+impl SearchProvider for AnswersProvider {
+    fn id(&self) -> &'static str {
+        "answers"
+    }
+
+    async fn search(&self, query: String, _limit: usize) -> Result<Vec<SearchResult>, String> {
+        Ok(quick_answers(&query)
+            .into_iter()
+            .enumerate()
+            .map(|(index, answer)| SearchResult {
+                kind: "answer".to_string(),
+                id: index.to_string(),
+                title: answer.title.clone(),
+                subtitle: Some(answer.subtitle.clone()),
+                icon: None,
+                // An answer means the query was a calculation; show it first.
+                score: 2.0,
+                actions: vec![SearchAction::new("copy", "Copy")],
+                data: to_data(&answer),
+            })
+            .collect())
+    }
+}
+
+#[derive(Default)]
+struct ItemsProvider {
+    db: ProviderDb,
+}
+
+fn item_actions(item: &Item) -> Vec<SearchAction> {
+    let title = match item.action_type.as_str() {
+        "url" => "Open",
+        "snippet" => "Insert",
+        _ => "Run",
+    };
+    vec![SearchAction::new("execute", title)]
+}
+
+#[async_trait::async_trait]
+impl SearchProvider for ItemsProvider {
+    fn id(&self) -> &'static str {
+        "items"
+    }
+
+    async fn search(&self, query: String, limit: usize) -> Result<Vec<SearchResult>, String> {
+        let q = query.clone();
+        let items = self
+            .db
+            .with(move |db| {
+                if q.trim().is_empty() {
+                    db.list_items(None, false)
+                } else {
+                    db.search_items(q.trim())
+                }
+            })
+            .await?;
+        Ok(items
+            .into_iter()
+            .take(limit)
+            .map(|item| SearchResult {
+                kind: "item".to_string(),
+                id: item.id.clone(),
+                title: item.title.clone(),
+                subtitle: item.subtitle.clone(),
+                icon: item.icon.clone(),
+                score: text_score(&query, &item.title) + usage_boost(item.frequency),
+                actions: item_actions(&item),
+                data: to_data(&item),
+            })
+            .collect())
+    }
+}
+
+#[derive(Default)]
+struct SlashCommandsProvider {
+    db: ProviderDb,
+}
+
+#[async_trait::async_trait]
+impl SearchProvider for SlashCommandsProvider {
+    fn id(&self) -> &'static str {
+        "slash_commands"
+    }
+
+    async fn search(&self, query: String, limit: usize) -> Result<Vec<SearchResult>, String> {
+        let name_query = query.trim().trim_start_matches('/').to_string();
+        if name_query.is_empty() {
+            return Ok(Vec::new());
+        }
+        let q = name_query.clone();
+        let commands = self.db.with(move |db| db.search_slash_commands(&q)).await?;
+        Ok(commands
+            .into_iter()
+            .take(limit)
+            .map(|command| SearchResult {
+                kind: "slash_command".to_string(),
+                id: command.id.clone(),
+                title: format!("/{}", command.name),
+                subtitle: Some(one_line(&command.description)),
+                icon: None,
+                score: 0.95 * text_score(&name_query, &command.name)
+                    + usage_boost(command.usage_count),
+                actions: vec![
+                    SearchAction::new("execute", "Run"),
+                    SearchAction::new("insert", "Add arguments"),
+                ],
+                data: to_data(&command),
+            })
+            .collect())
+    }
+}
+
+#[derive(Default)]
+struct ConversationsProvider {
+    db: ProviderDb,
+}
+
+#[async_trait::async_trait]
+impl SearchProvider for ConversationsProvider {
+    fn id(&self) -> &'static str {
+        "conversations"
+    }
+
+    async fn search(&self, query: String, limit: usize) -> Result<Vec<SearchResult>, String> {
+        if query.trim().chars().count() < MIN_TEXT_QUERY_LEN {
+            return Ok(Vec::new());
+        }
+        let q = query.trim().to_string();
+        let conversations = self.db.with(move |db| db.search_conversations(&q)).await?;
+        Ok(conversations
+            .into_iter()
+            .take(limit)
+            .map(|conversation| SearchResult {
+                kind: "conversation".to_string(),
+                id: conversation.id.clone(),
+                title: conversation.title.clone(),
+                subtitle: Some(match &conversation.last_message_preview {
+                    Some(preview) => one_line(preview),
+                    None => format!("{} messages", conversation.message_count),
+                }),
+                icon: Some("💬".to_string()),
+                // Past conversations rank after launcher items with a similar match.
+                score: 0.6 * text_score(&query, &conversation.title),
+                actions: vec![SearchAction::new("open", "Open conversation")],
+                data: to_data(&conversation),
+            })
+            .collect())
+    }
+}
+
+#[derive(Default)]
+struct MemoriesProvider {
+    db: ProviderDb,
+}
+
+#[async_trait::async_trait]
+impl SearchProvider for MemoriesProvider {
+    fn id(&self) -> &'static str {
+        "memories"
+    }
+
+    async fn search(&self, query: String, limit: usize) -> Result<Vec<SearchResult>, String> {
+        if query.trim().chars().count() < MIN_TEXT_QUERY_LEN {
+            return Ok(Vec::new());
+        }
+        let q = query.trim().to_string();
+        let memories = self.db.with(move |db| db.search_memories(&q)).await?;
+        Ok(memories
+            .into_iter()
+            .take(limit)
+            .map(|memory| SearchResult {
+                kind: "memory".to_string(),
+                id: memory.id.clone(),
+                title: memory.key.clone(),
+                subtitle: Some(one_line(&memory.value)),
+                icon: Some("🧠".to_string()),
+                score: 0.5 * text_score(&query, &memory.key),
+                actions: vec![SearchAction::new("copy", "Copy value")],
+                data: to_data(&memory),
+            })
+            .collect())
+    }
+}
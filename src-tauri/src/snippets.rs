@@ -0,0 +1,55 @@
+use golaunch_core::snippets::snippet_uses;
+use golaunch_core::{expand_snippet, SnippetInputs};
+
+use crate::context::{self, LaunchContext};
+
+/// Expand a snippet and insert it into the focused app, leaving the caret at `{cursor}`.
+///
+/// `launch_context` is the context captured when the launcher opened; without it (e.g.
+/// when the snippet runs from a global hotkey) the selection and clipboard are read now.
+pub fn insert_snippet(
+    template: &str,
+    args: &str,
+    launch_context: Option<&LaunchContext>,
+) -> Result<(), String> {
+    let selection = match launch_context {
+        Some(ctx) => ctx.selected_text.clone(),
+        None if snippet_uses(template, "selection") => context::capture_selected_text(),
+        None => None,
+    };
+    let clipboard = match launch_context.and_then(|ctx| ctx.clipboard_text.clone()) {
+        Some(text) => Some(text),
+        None if snippet_uses(template, "clipboard") => arboard::Clipboard::new()
+            .ok()
+            .and_then(|mut clipboard| clipboard.get_text().ok()),
+        None => None,
+    };
+
+    let expansion = expand_snippet(
+        template,
+        &SnippetInputs {
+            clipboard,
+            selection,
+            args: args.to_string(),
+        },
+    )?;
+    if expansion.text.is_empty() {
+        return Ok(());
+    }
+
+    // Pasting is instant and keeps formatting-sensitive text (newlines, indentation)
+    // intact; typing is the fallback when the clipboard is unavailable.
+    if let Err(e) = context::replace_selection(&expansion.text) {
+        eprintln!("Snippet paste failed, typing instead: {e}");
+        context::type_text(&expansion.text)?;
+    }
+
+    match expansion.cursor_from_end {
+        Some(count) if count > 0 => {
+            // Let the target app process the paste before moving the caret.
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            context::move_caret_left(count)
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,68 @@
+//! Runs workflow items in the background and reports each step to the launcher.
+
+use golaunch_core::workflows::{run_shell_captured, run_workflow, StepOutcome, StepRunner};
+use golaunch_core::{Database, Item, COPY_ACTION_TYPE, SNIPPET_ACTION_TYPE};
+use tauri::{AppHandle, Emitter};
+
+use crate::context::LaunchContext;
+
+/// Emitted with a `WorkflowProgress` whenever a run or one of its steps changes state.
+pub const WORKFLOW_PROGRESS_EVENT: &str = "workflow-progress";
+
+/// Runs steps the way the launcher runs items, except that commands are waited on so
+/// their exit code and output can feed the next step.
+pub(crate) struct AppStepRunner {
+    launch_context: Option<LaunchContext>,
+}
+
+impl AppStepRunner {
+    pub(crate) fn new(launch_context: Option<LaunchContext>) -> Self {
+        Self { launch_context }
+    }
+}
+
+impl StepRunner for AppStepRunner {
+    fn run_step(
+        &mut self,
+        action_type: &str,
+        value: &str,
+        previous: &StepOutcome,
+    ) -> Result<StepOutcome, String> {
+        match action_type {
+            "url" => {
+                open::that(value).map_err(|e| format!("Failed to open URL: {e}"))?;
+                Ok(StepOutcome::success())
+            }
+            "command" | "script" => run_shell_captured(value, previous),
+            SNIPPET_ACTION_TYPE => {
+                crate::snippets::insert_snippet(value, "", self.launch_context.as_ref())?;
+                Ok(StepOutcome::success())
+            }
+            COPY_ACTION_TYPE => {
+                crate::clipboard::copy_text(value.to_string())?;
+                Ok(StepOutcome {
+                    exit_code: 0,
+                    output: value.to_string(),
+                })
+            }
+            other => Err(format!("Unknown action type: {other}")),
+        }
+    }
+}
+
+/// Run a workflow item on its own thread, since steps wait on commands and delays.
+pub fn spawn_workflow(app: AppHandle, item: Item, launch_context: Option<LaunchContext>) {
+    std::thread::spawn(move || {
+        let mut runner = AppStepRunner::new(launch_context);
+        let result = Database::new().and_then(|db| {
+            run_workflow(&db, &item, &mut runner, &mut |progress| {
+                let _ = app.emit(WORKFLOW_PROGRESS_EVENT, progress);
+            })
+        });
+        match result {
+            Ok(true) => {}
+            Ok(false) => eprintln!("Workflow '{}' had failing steps", item.title),
+            Err(e) => eprintln!("Workflow '{}' failed: {e}", item.title),
+        }
+    });
+}
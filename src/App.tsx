@@ -1,7 +1,7 @@
 import { useEffect, useRef, useState, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import { useLauncher } from "./hooks/useLauncher";
+import { PROCESS_PREFIX, useLauncher } from "./hooks/useLauncher";
 import { useAcpAgent } from "./hooks/useAcpAgent";
 import { useLaunchContext } from "./hooks/useLaunchContext";
 import SearchBar from "./components/SearchBar";
@@ -10,11 +10,15 @@ import ItemList from "./components/ItemList";
 import StatusBar from "./components/StatusBar";
 import { AgentResponse } from "./components/AgentResponse";
 import { AgentSettings } from "./components/AgentSettings";
+import { ItemEditor } from "./components/ItemEditor";
 import CommandSuggestionPanel from "./components/CommandSuggestionPanel";
 import SlashCommandList from "./components/SlashCommandList";
 import ConversationHistory from "./components/ConversationHistory";
 import { RewriteQuickActions } from "./components/RewriteQuickActions";
-import type { AgentConfig, LaunchItem } from "./types";
+import type { AgentConfig, LaunchItem, WorkflowProgress } from "./types";
+
+/** How long a finished workflow stays in the status bar. */
+const WORKFLOW_DONE_DISPLAY_MS = 5000;
 
 function App() {
   const [settingsOpen, setSettingsOpen] = useState(false);
@@ -24,6 +28,8 @@ function App() {
   const [historySelectedIndex, setHistorySelectedIndex] = useState(0);
   const [rewriteSelectedIndex, setRewriteSelectedIndex] = useState(0);
   const [newlyCreatedItems, setNewlyCreatedItems] = useState<LaunchItem[]>([]);
+  const [workflowProgress, setWorkflowProgress] =
+    useState<WorkflowProgress | null>(null);
   const containerRef = useRef<HTMLDivElement>(null);
   const itemIdsBeforeTurnRef = useRef<Set<string>>(new Set());
 
@@ -82,6 +88,58 @@ function App() {
     };
   }, [launcher, agent]);
 
+  // Background indexers (e.g. installed applications) changed the item list
+  useEffect(() => {
+    const unlisten = listen("items-changed", () => {
+      launcher.refresh();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [launcher]);
+
+  // Processes started or exited; keep the "ps:" list current
+  useEffect(() => {
+    const unlisten = listen("processes-changed", () => {
+      if (launcher.query.startsWith(PROCESS_PREFIX)) {
+        launcher.refresh();
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [launcher]);
+
+  // `golaunch-cli app show query=...`
+  useEffect(() => {
+    const unlisten = listen<string>("control-query", (event) => {
+      launcher.setQuery(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [launcher]);
+
+  // Workflow items run in the background; show the latest step in the status bar
+  useEffect(() => {
+    let clearTimer: ReturnType<typeof setTimeout> | undefined;
+    const unlisten = listen<WorkflowProgress>("workflow-progress", (event) => {
+      const progress = event.payload;
+      if (clearTimer) clearTimeout(clearTimer);
+      setWorkflowProgress(progress);
+      if (progress.step === 0 && progress.status !== "running") {
+        clearTimer = setTimeout(
+          () => setWorkflowProgress(null),
+          WORKFLOW_DONE_DISPLAY_MS,
+        );
+      }
+    });
+    return () => {
+      if (clearTimer) clearTimeout(clearTimer);
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   const handleConnect = useCallback(
     async (config: AgentConfig) => {
       setAutoFallback(config.auto_fallback);
@@ -342,6 +400,9 @@ function App() {
               onResolvePermission={agent.resolvePermission}
               onNewConversation={handleNewConversation}
               onShowHistory={handleShowHistory}
+              agents={agent.agents}
+              selectedAgentId={agent.selectedAgentId}
+              onSelectAgent={agent.selectAgent}
               hasSelection={launchCtx.hasSelection}
               newlyCreatedItems={newlyCreatedItems}
               onExecuteItem={handleExecuteItem}
@@ -436,6 +497,7 @@ function App() {
             ) : (
               <ItemList
                 items={launcher.items}
+                selectedActions={launcher.itemActions}
                 selectedIndex={launcher.selectedIndex}
                 onSelect={launcher.setSelectedIndex}
                 onExecute={launcher.executeSelected}
@@ -448,14 +510,28 @@ function App() {
               agentMode={launcher.agentMode}
               agentTurnActive={agent.turnActive}
               hasSuggestions={launcher.suggestions.length > 0}
+              historyTitle={launcher.historyItem?.title}
+              workflowProgress={workflowProgress}
             />
           )}
         </>
       )}
 
+      {launcher.editingItem && (
+        <ItemEditor
+          item={launcher.editingItem}
+          onClose={launcher.closeEditor}
+          onSaved={() => {
+            launcher.closeEditor();
+            launcher.refresh();
+          }}
+        />
+      )}
+
       {settingsOpen && (
         <AgentSettings
           status={agent.status}
+          statusError={agent.statusError}
           configOptions={agent.configOptions}
           onConnect={handleConnect}
           onDisconnect={agent.disconnect}
@@ -1,7 +1,12 @@
-import { useEffect, useRef, useState } from "react";
+import { Fragment, useEffect, useRef, useState } from "react";
 import ReactMarkdown from "react-markdown";
 import remarkGfm from "remark-gfm";
-import type { AgentThreadMessage, LaunchItem, PermissionRequest } from "../types";
+import type {
+  AgentStatusInfo,
+  AgentThreadMessage,
+  LaunchItem,
+  PermissionRequest,
+} from "../types";
 import { PermissionDialog } from "./PermissionDialog";
 
 function ReplaceSelectionAction({
@@ -129,6 +134,10 @@ interface AgentResponseProps {
   onResolvePermission: (requestId: string, optionId: string) => void;
   onNewConversation?: () => void;
   onShowHistory?: () => void;
+  // Connected agents; with more than one, the conversation's agent can be chosen
+  agents?: AgentStatusInfo[];
+  selectedAgentId?: string | null;
+  onSelectAgent?: (agentId: string | null) => void;
   hasSelection?: boolean;
   newlyCreatedItems?: LaunchItem[];
   onExecuteItem?: (itemId: string) => void;
@@ -144,6 +153,9 @@ export function AgentResponse({
   onResolvePermission,
   onNewConversation,
   onShowHistory,
+  agents,
+  selectedAgentId,
+  onSelectAgent,
   hasSelection,
   newlyCreatedItems,
   onExecuteItem,
@@ -161,6 +173,26 @@ export function AgentResponse({
     <div className="flex flex-col flex-1 min-h-0">
       {(onNewConversation || onShowHistory) && (
         <div className="flex items-center justify-end px-3 py-1 border-b border-launcher-border/20 flex-shrink-0">
+          {onSelectAgent && agents && agents.length > 1 && (
+            <select
+              value={selectedAgentId ?? ""}
+              onChange={(e) => onSelectAgent(e.target.value || null)}
+              className="text-xs px-1 py-0.5 mr-auto rounded bg-transparent text-launcher-muted hover:text-launcher-text hover:bg-launcher-hover transition-colors"
+              title="Agent for this conversation"
+            >
+              <option value="">Default agent</option>
+              {agents.flatMap((a) =>
+                a.agent_id
+                  ? [
+                      <option key={a.agent_id} value={a.agent_id}>
+                        {a.agent_id}
+                        {a.status === "error" ? " (error)" : ""}
+                      </option>,
+                    ]
+                  : [],
+              )}
+            </select>
+          )}
           {onShowHistory && (
             <button
               onClick={onShowHistory}
@@ -224,22 +256,27 @@ export function AgentResponse({
                 ? "DONE"
                 : (entry.toolStatus ?? "running").toUpperCase();
             return (
-              <div key={entry.id} className="tool-call-entry">
-                <span className="tool-call-title">
-                  {entry.toolTitle ?? "Tool"}
-                </span>
-                {entry.commandPreview && (
-                  <span
-                    className="tool-call-command"
-                    title={entry.commandPreview}
-                  >
-                    {entry.commandPreview}
+              <Fragment key={entry.id}>
+                <div className="tool-call-entry">
+                  <span className="tool-call-title">
+                    {entry.toolTitle ?? "Tool"}
                   </span>
+                  {entry.commandPreview && (
+                    <span
+                      className="tool-call-command"
+                      title={entry.commandPreview}
+                    >
+                      {entry.commandPreview}
+                    </span>
+                  )}
+                  <span className={`tool-call-status ${statusClass}`}>
+                    {statusLabel}
+                  </span>
+                </div>
+                {entry.content.length > 0 && (
+                  <pre className="tool-call-output">{entry.content}</pre>
                 )}
-                <span className={`tool-call-status ${statusClass}`}>
-                  {statusLabel}
-                </span>
-              </div>
+              </Fragment>
             );
           }
 
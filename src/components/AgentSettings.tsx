@@ -3,12 +3,15 @@ import { invoke } from "@tauri-apps/api/core";
 import type {
   AgentConfig,
   AgentStatus,
+  ClipboardSettings,
+  ContextProviderInfo,
   RegistryAgent,
   SessionConfigOptionInfo,
 } from "../types";
 
 interface AgentSettingsProps {
   status: AgentStatus;
+  statusError?: string | null;
   configOptions: SessionConfigOptionInfo[];
   onConnect: (config: AgentConfig) => void;
   onDisconnect: () => void;
@@ -18,6 +21,7 @@ interface AgentSettingsProps {
 
 export function AgentSettings({
   status,
+  statusError,
   configOptions,
   onConnect,
   onDisconnect,
@@ -33,8 +37,17 @@ export function AgentSettings({
   const [agentEnvValues, setAgentEnvValues] = useState<
     Record<string, Record<string, string>>
   >({});
+  const [contextProviders, setContextProviders] = useState<
+    ContextProviderInfo[]
+  >([]);
+  const [captureSelection, setCaptureSelection] = useState(true);
+  const [idleTimeout, setIdleTimeout] = useState("30");
+  const [clipboardSettings, setClipboardSettings] =
+    useState<ClipboardSettings | null>(null);
   const [loading, setLoading] = useState(true);
   const [installing, setInstalling] = useState<string | null>(null);
+  // An idle agent was shut down but starts again on the next prompt
+  const agentRunning = status === "connected" || status === "idle";
 
   // Load saved config, fetch registry, check installs
   useEffect(() => {
@@ -44,6 +57,26 @@ export function AgentSettings({
         setSelectedAgentId(config.agent_id);
         setAutoFallback(config.auto_fallback);
 
+        invoke<string | null>("get_setting", {
+          key: "context.capture_selection",
+        })
+          .then((v) => setCaptureSelection(v !== "false"))
+          .catch(() => {});
+
+        invoke<string | null>("get_setting", {
+          key: "acp.idle_timeout_minutes",
+        })
+          .then((v) => setIdleTimeout(v ?? "30"))
+          .catch(() => {});
+
+        invoke<ClipboardSettings>("get_clipboard_settings")
+          .then(setClipboardSettings)
+          .catch(() => setClipboardSettings(null));
+
+        invoke<ContextProviderInfo[]>("list_context_providers")
+          .then(setContextProviders)
+          .catch(() => setContextProviders([]));
+
         const registryAgents =
           await invoke<RegistryAgent[]>("acp_fetch_registry");
         setAgents(registryAgents);
@@ -179,6 +212,53 @@ export function AgentSettings({
     onConnect(config);
   }
 
+  async function toggleContextProvider(providerId: string, enabled: boolean) {
+    try {
+      await invoke("set_context_provider_enabled", { providerId, enabled });
+      setContextProviders((prev) =>
+        prev.map((p) => (p.id === providerId ? { ...p, enabled } : p)),
+      );
+    } catch (e) {
+      console.error("Failed to update context provider:", e);
+    }
+  }
+
+  async function toggleCaptureSelection(enabled: boolean) {
+    setCaptureSelection(enabled);
+    try {
+      await invoke("set_setting", {
+        key: "context.capture_selection",
+        value: enabled ? "true" : "false",
+      });
+    } catch (e) {
+      console.error("Failed to save selection capture setting:", e);
+    }
+  }
+
+  async function saveIdleTimeout() {
+    const minutes = Math.max(0, Math.floor(Number(idleTimeout) || 0));
+    setIdleTimeout(String(minutes));
+    try {
+      await invoke("set_setting", {
+        key: "acp.idle_timeout_minutes",
+        value: String(minutes),
+      });
+    } catch (e) {
+      console.error("Failed to save agent idle timeout:", e);
+    }
+  }
+
+  async function toggleClipboardHistory(enabled: boolean) {
+    if (!clipboardSettings) return;
+    const next = { ...clipboardSettings, enabled };
+    setClipboardSettings(next);
+    try {
+      await invoke("set_clipboard_settings", { settings: next });
+    } catch (e) {
+      console.error("Failed to save clipboard settings:", e);
+    }
+  }
+
   const selectedAgent = agents.find((a) => a.id === selectedAgentId);
   const canConnect =
     selectedAgent && installStatus[selectedAgentId] !== false;
@@ -321,7 +401,7 @@ export function AgentSettings({
           )}
 
           {/* Session Config Options - shown when connected */}
-          {status === "connected" && configOptions.length > 0 && (
+          {agentRunning && configOptions.length > 0 && (
             <div className="config-options-section">
               <div className="config-options-header">Session Config</div>
               {configOptions.map((opt) => {
@@ -364,6 +444,50 @@ export function AgentSettings({
             </div>
           )}
 
+          <div className="config-options-section">
+            <div className="config-options-header">Launch Context</div>
+            <label
+              className="settings-label"
+              title="Read the selection in the source app when the launcher opens"
+            >
+              <input
+                type="checkbox"
+                checked={captureSelection}
+                onChange={(e) => toggleCaptureSelection(e.target.checked)}
+              />
+              Capture selected text
+            </label>
+            {clipboardSettings && (
+              <label
+                className="settings-label"
+                title="Keep a searchable history of copied text (search with clip:)"
+              >
+                <input
+                  type="checkbox"
+                  checked={clipboardSettings.enabled}
+                  onChange={(e) => toggleClipboardHistory(e.target.checked)}
+                />
+                Record clipboard history
+              </label>
+            )}
+            {contextProviders.map((provider) => (
+              <label
+                key={provider.id}
+                className="settings-label"
+                title={provider.description}
+              >
+                <input
+                  type="checkbox"
+                  checked={provider.enabled}
+                  onChange={(e) =>
+                    toggleContextProvider(provider.id, e.target.checked)
+                  }
+                />
+                {provider.label}
+              </label>
+            ))}
+          </div>
+
           <div className="settings-footer">
             <label className="settings-label">
               <input
@@ -373,9 +497,24 @@ export function AgentSettings({
               />
               Auto-fallback to agent on zero results
             </label>
+            <label
+              className="settings-label"
+              title="Agents left unused are shut down and start again on the next prompt (0 = never)"
+            >
+              Stop idle agents after
+              <input
+                type="number"
+                min={0}
+                className="settings-input settings-input-minutes"
+                value={idleTimeout}
+                onChange={(e) => setIdleTimeout(e.target.value)}
+                onBlur={saveIdleTimeout}
+              />
+              minutes
+            </label>
 
             <div className="settings-actions">
-              {status === "connected" ? (
+              {agentRunning ? (
                 <button
                   className="settings-btn settings-btn-danger"
                   onClick={onDisconnect}
@@ -391,7 +530,10 @@ export function AgentSettings({
                   {status === "connecting" ? "Connecting..." : "Connect"}
                 </button>
               )}
-              <span className={`settings-status settings-status-${status}`}>
+              <span
+                className={`settings-status settings-status-${status}`}
+                title={statusError ?? undefined}
+              >
                 {status}
               </span>
             </div>
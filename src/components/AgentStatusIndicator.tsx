@@ -8,6 +8,7 @@ interface AgentStatusIndicatorProps {
 const STATUS_COLORS: Record<AgentStatus, string> = {
   connected: "#4ade80",
   connecting: "#facc15",
+  idle: "#60a5fa",
   disconnected: "#9ca3af",
   error: "#f87171",
 };
@@ -15,7 +16,7 @@ const STATUS_COLORS: Record<AgentStatus, string> = {
 export function AgentStatusIndicator({ status, onClick }: AgentStatusIndicatorProps) {
   if (status === "disconnected") return null;
 
-  const clickable = onClick && status === "connected";
+  const clickable = onClick && (status === "connected" || status === "idle");
 
   return (
     <button
@@ -24,7 +25,7 @@ export function AgentStatusIndicator({ status, onClick }: AgentStatusIndicatorPr
           ? "hover:bg-launcher-hover cursor-pointer"
           : "cursor-default"
       }`}
-      title={status === "connected" ? "Enter agent mode" : `Agent: ${status}`}
+      title={clickable ? "Enter agent mode" : `Agent: ${status}`}
       onClick={clickable ? onClick : undefined}
       tabIndex={clickable ? 0 : -1}
       type="button"
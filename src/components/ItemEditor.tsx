@@ -0,0 +1,104 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import type { LaunchItem } from "../types";
+
+interface ItemEditorProps {
+  item: LaunchItem;
+  onClose: () => void;
+  onSaved: () => void;
+}
+
+const FIELDS = [
+  { key: "title", label: "Title" },
+  { key: "subtitle", label: "Subtitle" },
+  { key: "icon", label: "Icon" },
+  { key: "action_value", label: "Value" },
+  { key: "category", label: "Category" },
+  { key: "tags", label: "Tags" },
+] as const;
+
+type FieldKey = (typeof FIELDS)[number]["key"];
+
+export function ItemEditor({ item, onClose, onSaved }: ItemEditorProps) {
+  const [values, setValues] = useState<Record<FieldKey, string>>(() => ({
+    title: item.title,
+    subtitle: item.subtitle ?? "",
+    icon: item.icon ?? "",
+    action_value: item.action_value,
+    category: item.category,
+    tags: item.tags,
+  }));
+  const [saving, setSaving] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const save = async () => {
+    setSaving(true);
+    setError(null);
+    try {
+      await invoke("update_item", { id: item.id, update: values });
+      onSaved();
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  // Keep Enter/Escape away from the launcher's own key handling
+  const handleKeyDown = (e: React.KeyboardEvent) => {
+    e.stopPropagation();
+    if (e.key === "Escape") {
+      e.preventDefault();
+      onClose();
+    } else if (e.key === "Enter" && !saving) {
+      e.preventDefault();
+      save();
+    }
+  };
+
+  return (
+    <div className="agent-settings-overlay" onKeyDown={handleKeyDown}>
+      <div className="agent-settings-panel">
+        <div className="settings-header">
+          <h3>Edit {item.title}</h3>
+          <button className="settings-close-btn" onClick={onClose}>
+            &#x2715;
+          </button>
+        </div>
+
+        <div className="settings-body">
+          <div className="flex flex-col gap-2">
+            {FIELDS.map(({ key, label }, index) => (
+              <div key={key} className="agent-env-row">
+                <label className="agent-env-label w-20">{label}</label>
+                <input
+                  className="agent-env-input"
+                  value={values[key]}
+                  autoFocus={index === 0}
+                  onChange={(e) =>
+                    setValues((prev) => ({ ...prev, [key]: e.target.value }))
+                  }
+                />
+              </div>
+            ))}
+          </div>
+
+          <div className="settings-footer">
+            <div className="settings-actions">
+              <button
+                className="settings-btn settings-btn-primary"
+                onClick={save}
+                disabled={saving || !values.title.trim()}
+              >
+                {saving ? "Saving..." : "Save"}
+              </button>
+              {error && (
+                <span className="text-[11px] text-red-400/80">{error}</span>
+              )}
+            </div>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -1,15 +1,23 @@
 import { useRef, useEffect } from "react";
-import { LaunchItem } from "../types";
+import { ItemAction, LaunchItem } from "../types";
 import ItemRow from "./ItemRow";
 
 interface ItemListProps {
   items: LaunchItem[];
+  /** Actions of the selected item, shown as shortcut hints on its row */
+  selectedActions?: ItemAction[];
   selectedIndex: number;
   onSelect: (index: number) => void;
   onExecute: () => void;
 }
 
-function ItemList({ items, selectedIndex, onSelect, onExecute }: ItemListProps) {
+function ItemList({
+  items,
+  selectedActions,
+  selectedIndex,
+  onSelect,
+  onExecute,
+}: ItemListProps) {
   const listRef = useRef<HTMLDivElement>(null);
 
   useEffect(() => {
@@ -50,6 +58,7 @@ function ItemList({ items, selectedIndex, onSelect, onExecute }: ItemListProps)
           key={item.id}
           item={item}
           isSelected={index === selectedIndex}
+          actions={index === selectedIndex ? selectedActions : undefined}
           onHover={() => onSelect(index)}
           onClick={onExecute}
         />
@@ -1,8 +1,9 @@
-import { LaunchItem } from "../types";
+import { ItemAction, LaunchItem } from "../types";
 
 interface ItemRowProps {
   item: LaunchItem;
   isSelected: boolean;
+  actions?: ItemAction[];
   onHover: () => void;
   onClick: () => void;
 }
@@ -11,10 +12,35 @@ const ACTION_TYPE_ICONS: Record<string, string> = {
   url: "🌐",
   command: "⚡",
   script: "📜",
+  clipboard: "📋",
+  snippet: "✂️",
+  file: "📄",
+  answer: "🧮",
+  workflow: "🔗",
+  schedule: "⏰",
 };
 
-function ItemRow({ item, isSelected, onHover, onClick }: ItemRowProps) {
-  const icon = item.icon || ACTION_TYPE_ICONS[item.action_type] || "📦";
+const SOURCE_ICONS: Record<string, string> = {
+  applications: "🖥️",
+};
+
+// Icon theme names and file paths (e.g. from .desktop entries) can't be shown as text
+function isTextIcon(icon: string): boolean {
+  return !/^[\w./-]{2,}$/.test(icon);
+}
+
+/** Custom actions with a shortcut get a hint on the selected row. */
+const MAX_ACTION_HINTS = 2;
+
+function ItemRow({ item, isSelected, actions, onHover, onClick }: ItemRowProps) {
+  const icon =
+    (item.icon && isTextIcon(item.icon) ? item.icon : null) ||
+    (item.source ? SOURCE_ICONS[item.source] : null) ||
+    ACTION_TYPE_ICONS[item.action_type] ||
+    "📦";
+  const actionHints = (actions ?? [])
+    .filter((a) => !a.builtin && a.shortcut && a.item_id === item.id)
+    .slice(0, MAX_ACTION_HINTS);
 
   return (
     <div
@@ -51,9 +77,26 @@ function ItemRow({ item, isSelected, onHover, onClick }: ItemRowProps) {
       </div>
 
       <div className="flex items-center gap-2 ml-2 flex-shrink-0">
+        {item.hotkey && (
+          <kbd className="text-[10px] px-1.5 py-0.5 rounded bg-launcher-surface/60 text-launcher-muted/70 border border-launcher-border/20">
+            {item.hotkey}
+          </kbd>
+        )}
         <span className="text-[10px] text-launcher-muted/40">
           {item.action_type}
         </span>
+        {isSelected &&
+          actionHints.map((action) => (
+            <span
+              key={action.id}
+              className="text-[10px] text-launcher-muted/60 whitespace-nowrap"
+            >
+              <kbd className="px-1.5 py-0.5 rounded bg-launcher-surface/60 border border-launcher-border/20">
+                {action.shortcut}
+              </kbd>{" "}
+              {action.label}
+            </span>
+          ))}
         {isSelected && (
           <div className="flex items-center gap-1">
             <kbd className="text-[10px] px-1.5 py-0.5 rounded bg-launcher-surface text-launcher-muted border border-launcher-border/30">
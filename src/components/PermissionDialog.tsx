@@ -7,13 +7,23 @@ interface PermissionDialogProps {
 }
 
 export function PermissionDialog({ request, onResolve }: PermissionDialogProps) {
+  const alwaysOption = request.options.find(
+    (option) => option.kind === "AllowAlways",
+  );
+  const neverOption = request.options.find(
+    (option) => option.kind === "RejectAlways",
+  );
+
   const yesOption =
+    request.options.find((option) => option.kind === "AllowOnce") ??
     request.options.find((option) => {
       const value = `${option.kind} ${option.name}`.toLowerCase();
       return value.includes("allow") || value.includes("approve");
-    }) ?? request.options[0];
+    }) ??
+    request.options[0];
 
   const noOption =
+    request.options.find((option) => option.kind === "RejectOnce") ??
     request.options.find((option) => {
       const value = `${option.kind} ${option.name}`.toLowerCase();
       return (
@@ -59,6 +69,15 @@ export function PermissionDialog({ request, onResolve }: PermissionDialogProps)
         >
           Yes
         </button>
+        {alwaysOption && alwaysOption !== yesOption && (
+          <button
+            className="permission-btn permission-btn-approve"
+            onClick={() => onResolve(request.request_id, alwaysOption.option_id)}
+            title="Allow this now and from now on"
+          >
+            Always
+          </button>
+        )}
         <button
           className="permission-btn permission-btn-deny"
           onClick={() => noOption && onResolve(request.request_id, noOption.option_id)}
@@ -66,6 +85,15 @@ export function PermissionDialog({ request, onResolve }: PermissionDialogProps)
         >
           No
         </button>
+        {neverOption && neverOption !== noOption && (
+          <button
+            className="permission-btn permission-btn-deny"
+            onClick={() => onResolve(request.request_id, neverOption.option_id)}
+            title="Reject this now and from now on"
+          >
+            Never
+          </button>
+        )}
       </div>
     </div>
   );
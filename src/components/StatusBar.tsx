@@ -1,8 +1,29 @@
+import type { WorkflowProgress } from "../types";
+
+/** One-line summary of a workflow run, e.g. "Deploy 2/3: ./build.sh". */
+function describeWorkflow(progress: WorkflowProgress): string {
+  if (progress.step === 0) {
+    return progress.status === "running"
+      ? `${progress.workflow} started`
+      : `${progress.workflow} ${progress.status}`;
+  }
+  const suffix =
+    progress.status === "failed"
+      ? ` failed${progress.exit_code !== null ? ` (exit ${progress.exit_code})` : ""}`
+      : progress.status === "skipped"
+        ? " skipped"
+        : "";
+  return `${progress.workflow} ${progress.step}/${progress.total}: ${progress.label}${suffix}`;
+}
+
 interface StatusBarProps {
   itemCount: number;
   agentMode: boolean;
   agentTurnActive: boolean;
   hasSuggestions?: boolean;
+  /** Title of the item whose history is shown instead of search results */
+  historyTitle?: string;
+  workflowProgress?: WorkflowProgress | null;
 }
 
 function StatusBar({
@@ -10,15 +31,21 @@ function StatusBar({
   agentMode,
   agentTurnActive,
   hasSuggestions,
+  historyTitle,
+  workflowProgress,
 }: StatusBarProps) {
   return (
     <div className="flex items-center justify-between px-4 py-2 border-t border-launcher-border/30 text-[11px] text-launcher-muted/50">
       <span>
-        {hasSuggestions
-          ? "Suggestions"
-          : agentMode
-            ? "Agent mode"
-            : `${itemCount} ${itemCount === 1 ? "item" : "items"}`}
+        {workflowProgress
+          ? describeWorkflow(workflowProgress)
+          : hasSuggestions
+            ? "Suggestions"
+            : historyTitle
+              ? `History of ${historyTitle}`
+              : agentMode
+                ? "Agent mode"
+                : `${itemCount} ${itemCount === 1 ? "item" : "items"}`}
       </span>
       <div className="flex items-center gap-3">
         {hasSuggestions ? (
@@ -3,20 +3,27 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import type {
   AgentStatus,
-  AgentUpdate,
+  AgentStatusInfo,
+  AgentUpdateEvent,
   AgentConfig,
+  ConfigOptionsEvent,
   PermissionRequest,
   AgentThreadMessage,
+  ConversationMessage,
   ConversationWithPreview,
   SessionConfigOptionInfo,
 } from "../types";
 
+const TERMINAL_OUTPUT_CHARS = 20000;
+
 function makeMessageId(prefix: "user" | "assistant") {
   return `${prefix}-${Date.now()}-${Math.random().toString(36).slice(2, 10)}`;
 }
 
 export function useAcpAgent() {
   const [status, setStatus] = useState<AgentStatus>("disconnected");
+  // Why the agent failed, while the status is "error"
+  const [statusError, setStatusError] = useState<string | null>(null);
   const [messages, setMessages] = useState("");
   const [thread, setThread] = useState<AgentThreadMessage[]>([]);
   const [thoughts, setThoughts] = useState("");
@@ -26,6 +33,15 @@ export function useAcpAgent() {
     useState<PermissionRequest | null>(null);
   const activeAssistantIdRef = useRef<string | null>(null);
   const startupConnectAttempted = useRef(false);
+  // Agent the launcher connected; other connected agents don't change the status
+  const agentIdRef = useRef<string | null>(null);
+  const permissionRequestRef = useRef<PermissionRequest | null>(null);
+  // Connected agents, and the one chosen for the current conversation (null: the
+  // agent that held it, or the default agent)
+  const [agents, setAgents] = useState<AgentStatusInfo[]>([]);
+  const [selectedAgentId, setSelectedAgentId] = useState<string | null>(null);
+  const selectedAgentIdRef = useRef(selectedAgentId);
+  selectedAgentIdRef.current = selectedAgentId;
 
   // Conversation persistence state
   const [activeConversationId, setActiveConversationId] = useState<
@@ -41,15 +57,41 @@ export function useAcpAgent() {
   >([]);
 
   // Refs for accessing current state in event listeners (avoids stale closures)
-  const threadRef = useRef(thread);
-  threadRef.current = thread;
   const activeConversationIdRef = useRef(activeConversationId);
   activeConversationIdRef.current = activeConversationId;
 
+  const loadAgents = useCallback(async () => {
+    try {
+      const list = await invoke<AgentStatusInfo[]>("acp_list_agents");
+      setAgents(list);
+      if (
+        selectedAgentIdRef.current &&
+        !list.some((a) => a.agent_id === selectedAgentIdRef.current)
+      ) {
+        setSelectedAgentId(null);
+      }
+    } catch (e) {
+      console.error("Failed to list agents:", e);
+    }
+  }, []);
+
   useEffect(() => {
-    const unlistenUpdate = listen<AgentUpdate>("acp-update", (event) => {
+    const unlistenUpdate = listen<AgentUpdateEvent>("acp-update", (event) => {
       const update = event.payload;
 
+      if (update.type === "status_change") {
+        if (!agentIdRef.current || update.agent_id === agentIdRef.current) {
+          setStatus(update.status);
+          setStatusError(
+            update.status === "error" ? (update.reason ?? null) : null,
+          );
+        }
+        loadAgents();
+        return;
+      }
+      // Other conversations keep running in the background
+      if (update.conversation_id !== activeConversationIdRef.current) return;
+
       switch (update.type) {
         case "message_chunk": {
           setMessages((prev) => prev + update.text);
@@ -111,30 +153,53 @@ export function useAcpAgent() {
         case "turn_complete": {
           setTurnActive(false);
           setIsThinking(false);
-
-          // Persist the assistant's final message to the database
-          const convId = activeConversationIdRef.current;
-          const currentThread = threadRef.current;
-          const assistantId = activeAssistantIdRef.current;
-          if (convId && assistantId) {
-            const assistantMsg = currentThread.find(
-              (m) => m.id === assistantId,
-            );
-            if (assistantMsg && assistantMsg.content.length > 0) {
-              invoke("add_conversation_message", {
-                conversationId: convId,
-                role: "assistant",
-                content: assistantMsg.content,
-              }).catch(() => {});
-            }
-          }
-
           activeAssistantIdRef.current = null;
           break;
         }
-        case "status_change":
-          setStatus(update.status);
+        case "terminal_created":
+          setThread((prev) => [
+            ...prev,
+            {
+              id: `terminal-${update.terminal_id}`,
+              role: "tool",
+              content: "",
+              toolTitle: "Terminal",
+              toolStatus: "running",
+              commandPreview: update.command,
+            },
+          ]);
+          break;
+        case "terminal_output": {
+          const terminalEntryId = `terminal-${update.terminal_id}`;
+          setThread((prev) =>
+            prev.map((entry) =>
+              entry.id === terminalEntryId
+                ? {
+                    ...entry,
+                    // Only the tail is shown; the agent gets the full output
+                    content: (entry.content + update.text).slice(
+                      -TERMINAL_OUTPUT_CHARS,
+                    ),
+                  }
+                : entry,
+            ),
+          );
           break;
+        }
+        case "terminal_exited": {
+          const terminalEntryId = `terminal-${update.terminal_id}`;
+          setThread((prev) =>
+            prev.map((entry) =>
+              entry.id === terminalEntryId
+                ? {
+                    ...entry,
+                    toolStatus: update.exit_code === 0 ? "completed" : "error",
+                  }
+                : entry,
+            ),
+          );
+          break;
+        }
       }
     });
 
@@ -142,6 +207,7 @@ export function useAcpAgent() {
       "acp-permission-request",
       (event) => {
         const req = event.payload;
+        permissionRequestRef.current = req;
         setPermissionRequest(req);
 
         // Update the matching tool call entry with the command preview and pending status
@@ -160,10 +226,16 @@ export function useAcpAgent() {
       },
     );
 
-    const unlistenConfigOptions = listen<SessionConfigOptionInfo[]>(
+    const unlistenConfigOptions = listen<ConfigOptionsEvent>(
       "acp-config-options",
       (event) => {
-        setConfigOptions(event.payload);
+        const { agent_id, conversation_id, options } = event.payload;
+        const shown =
+          conversation_id === null
+            ? activeConversationIdRef.current === null &&
+              (!agentIdRef.current || agent_id === agentIdRef.current)
+            : conversation_id === activeConversationIdRef.current;
+        if (shown) setConfigOptions(options);
       },
     );
 
@@ -172,22 +244,26 @@ export function useAcpAgent() {
       unlistenPermission.then((f) => f());
       unlistenConfigOptions.then((f) => f());
     };
-  }, []);
+  }, [loadAgents]);
 
   const connect = useCallback(async (config: AgentConfig) => {
     try {
       setStatus("connecting");
-      await invoke("acp_connect", { config });
+      const agentId = await invoke<string>("acp_connect", { config });
+      agentIdRef.current = agentId;
+      loadAgents();
       // Fetch initial config options after connect
       const opts = await invoke<SessionConfigOptionInfo[]>(
         "acp_get_config_options",
+        { agentId, conversationId: activeConversationIdRef.current },
       );
       setConfigOptions(opts);
     } catch (e) {
       console.error("Failed to connect agent:", e);
       setStatus("error");
+      setStatusError(String(e));
     }
-  }, []);
+  }, [loadAgents]);
 
   useEffect(() => {
     if (startupConnectAttempted.current) return;
@@ -195,11 +271,19 @@ export function useAcpAgent() {
 
     async function connectOnStartup() {
       try {
-        const currentStatus = await invoke<AgentStatus>("acp_get_status");
+        const info = await invoke<AgentStatusInfo>("acp_get_status");
+        const currentStatus = info.status;
+        agentIdRef.current = info.agent_id;
+        loadAgents();
         setStatus(currentStatus);
-
-        if (currentStatus === "connected" || currentStatus === "connecting") {
-          if (currentStatus === "connected") {
+        setStatusError(currentStatus === "error" ? info.last_error : null);
+
+        if (
+          currentStatus === "connected" ||
+          currentStatus === "idle" ||
+          currentStatus === "connecting"
+        ) {
+          if (currentStatus !== "connecting") {
             const opts = await invoke<SessionConfigOptionInfo[]>(
               "acp_get_config_options",
             );
@@ -245,11 +329,12 @@ export function useAcpAgent() {
     }
 
     connectOnStartup();
-  }, [connect]);
+  }, [connect, loadAgents]);
 
   const disconnect = useCallback(async () => {
     try {
       await invoke("acp_disconnect");
+      agentIdRef.current = null;
       setStatus("disconnected");
       setThread([]);
       setMessages("");
@@ -258,6 +343,8 @@ export function useAcpAgent() {
       setIsThinking(false);
       setActiveConversationId(null);
       setConfigOptions([]);
+      setAgents([]);
+      setSelectedAgentId(null);
       activeAssistantIdRef.current = null;
     } catch (e) {
       console.error("Failed to disconnect agent:", e);
@@ -270,33 +357,6 @@ export function useAcpAgent() {
 
     const assistantId = makeMessageId("assistant");
 
-    // Create conversation if none active
-    let convId = activeConversationIdRef.current;
-    if (!convId) {
-      try {
-        const title =
-          normalizedQuery.length > 50
-            ? normalizedQuery.slice(0, 50) + "..."
-            : normalizedQuery;
-        const conv = await invoke<{ id: string }>("create_conversation", {
-          title,
-        });
-        convId = conv.id;
-        setActiveConversationId(convId);
-      } catch (e) {
-        console.error("Failed to create conversation:", e);
-      }
-    }
-
-    // Persist user message
-    if (convId) {
-      invoke("add_conversation_message", {
-        conversationId: convId,
-        role: "user",
-        content: normalizedQuery,
-      }).catch(() => {});
-    }
-
     setThread((prev) => [
       ...prev,
       { id: makeMessageId("user"), role: "user", content: normalizedQuery },
@@ -310,10 +370,22 @@ export function useAcpAgent() {
     setIsThinking(true);
 
     try {
+      // Start the conversation first, so its updates are told apart from those
+      // of conversations running in the background
+      if (activeConversationIdRef.current === null) {
+        const newId = await invoke<string>("acp_new_conversation", {
+          query: normalizedQuery,
+        });
+        activeConversationIdRef.current = newId;
+        setActiveConversationId(newId);
+      }
       const items = await invoke("get_all_items");
-      await invoke("acp_prompt", {
+      // The backend saves the turn
+      await invoke<string>("acp_prompt", {
         query: normalizedQuery,
         contextItems: items,
+        conversationId: activeConversationIdRef.current,
+        agentId: selectedAgentIdRef.current,
       });
     } catch (e) {
       console.error("Failed to prompt agent:", e);
@@ -336,8 +408,11 @@ export function useAcpAgent() {
 
   const cancel = useCallback(async () => {
     try {
-      await invoke("acp_cancel");
-      setTurnActive(false);
+      // The turn stays active until the agent confirms with a "cancelled"
+      // turn_complete; output after this point is dropped.
+      await invoke("acp_cancel", {
+        conversationId: activeConversationIdRef.current,
+      });
       setIsThinking(false);
       activeAssistantIdRef.current = null;
     } catch (e) {
@@ -351,14 +426,25 @@ export function useAcpAgent() {
     setThoughts("");
     setTurnActive(false);
     setIsThinking(false);
+    activeConversationIdRef.current = null;
     setActiveConversationId(null);
+    setSelectedAgentId(null);
     activeAssistantIdRef.current = null;
   }, []);
 
   const resolvePermission = useCallback(
     async (requestId: string, optionId: string) => {
       try {
-        await invoke("acp_resolve_permission", { requestId, optionId });
+        const request = permissionRequestRef.current;
+        const agentId =
+          request?.request_id === requestId
+            ? request.agent_id
+            : agentIdRef.current;
+        await invoke("acp_resolve_permission", {
+          agentId,
+          requestId,
+          optionId,
+        });
         setPermissionRequest(null);
 
         // Mark the tool call entry as approved (or denied based on optionId)
@@ -393,18 +479,30 @@ export function useAcpAgent() {
 
   const loadConversation = useCallback(async (conversationId: string) => {
     try {
-      const msgs = await invoke<
-        { id: string; role: string; content: string }[]
-      >("get_conversation_messages", { conversationId });
+      // Also resumes the agent's session for this conversation
+      const msgs = await invoke<ConversationMessage[]>(
+        "acp_open_conversation",
+        { id: conversationId },
+      );
 
-      const rebuilt: AgentThreadMessage[] = msgs.map((m) => ({
-        id: m.id,
-        role: m.role as "user" | "assistant",
-        content: m.content,
-      }));
+      // Thoughts and plans are kept for the CLI but not shown in the thread
+      const rebuilt: AgentThreadMessage[] = msgs
+        .filter((m) => m.role === "user" || m.role === "assistant")
+        .map((m) => ({
+          id: m.id,
+          role: m.role as "user" | "assistant",
+          content: m.content,
+        }));
 
       setThread(rebuilt);
+      activeConversationIdRef.current = conversationId;
       setActiveConversationId(conversationId);
+      setSelectedAgentId(null);
+      setConfigOptions(
+        await invoke<SessionConfigOptionInfo[]>("acp_get_config_options", {
+          conversationId,
+        }),
+      );
       setMessages("");
       setThoughts("");
       setTurnActive(false);
@@ -421,8 +519,13 @@ export function useAcpAgent() {
     setThoughts("");
     setTurnActive(false);
     setIsThinking(false);
+    activeConversationIdRef.current = null;
     setActiveConversationId(null);
+    setSelectedAgentId(null);
     activeAssistantIdRef.current = null;
+    invoke<SessionConfigOptionInfo[]>("acp_get_config_options")
+      .then(setConfigOptions)
+      .catch(() => {});
   }, []);
 
   const deleteConversation = useCallback(
@@ -454,6 +557,23 @@ export function useAcpAgent() {
     }
   }, []);
 
+  // Prompts of the current conversation go to `agentId` from now on; null goes back
+  // to the agent that held it (or the default agent)
+  const selectAgent = useCallback(async (agentId: string | null) => {
+    selectedAgentIdRef.current = agentId;
+    setSelectedAgentId(agentId);
+    try {
+      setConfigOptions(
+        await invoke<SessionConfigOptionInfo[]>("acp_get_config_options", {
+          agentId,
+          conversationId: activeConversationIdRef.current,
+        }),
+      );
+    } catch (e) {
+      console.error("Failed to load config options:", e);
+    }
+  }, []);
+
   // --- Config option management ---
 
   const setConfigOption = useCallback(
@@ -461,7 +581,12 @@ export function useAcpAgent() {
       try {
         const updated = await invoke<SessionConfigOptionInfo[]>(
           "acp_set_config_option",
-          { configId, value },
+          {
+            agentId: selectedAgentIdRef.current,
+            conversationId: activeConversationIdRef.current,
+            configId,
+            value,
+          },
         );
         setConfigOptions(updated);
       } catch (e) {
@@ -473,6 +598,7 @@ export function useAcpAgent() {
 
   return {
     status,
+    statusError,
     messages,
     thread,
     thoughts,
@@ -482,6 +608,8 @@ export function useAcpAgent() {
     activeConversationId,
     conversations,
     configOptions,
+    agents,
+    selectedAgentId,
     connect,
     disconnect,
     prompt,
@@ -494,5 +622,6 @@ export function useAcpAgent() {
     deleteConversation,
     searchConversations,
     setConfigOption,
+    selectAgent,
   };
 }
@@ -8,6 +8,8 @@ const EMPTY_CONTEXT: LaunchContext = {
   selected_text: null,
   source_window_title: null,
   source_process_name: null,
+  source_process_id: null,
+  sections: [],
 };
 
 export function useLaunchContext() {
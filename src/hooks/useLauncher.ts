@@ -1,6 +1,148 @@
 import { useState, useEffect, useCallback, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { LaunchItem, AgentStatus, CommandSuggestion, SlashCommand } from "../types";
+import {
+  LaunchItem,
+  AgentStatus,
+  CommandHistory,
+  CommandSuggestion,
+  ItemAction,
+  QuickAnswer,
+  SearchResult,
+  SlashCommand,
+} from "../types";
+
+/** Query prefix that switches the launcher to clipboard history search. */
+const CLIPBOARD_PREFIX = "clip:";
+
+/** Query prefix that switches the launcher to file search. */
+const FILE_PREFIX = "file:";
+
+/** Query prefix that lists schedules with their last and next runs. */
+const SCHEDULE_PREFIX = "schedule:";
+
+/** Query prefix that lists processes started from the launcher. */
+export const PROCESS_PREFIX = "ps:";
+
+/** Queries searched somewhere other than the launcher items. */
+function isPrefixedQuery(query: string): boolean {
+  return (
+    query.startsWith(CLIPBOARD_PREFIX) ||
+    query.startsWith(FILE_PREFIX) ||
+    query.startsWith(SCHEDULE_PREFIX) ||
+    query.startsWith(PROCESS_PREFIX) ||
+    query.startsWith("~")
+  );
+}
+
+/** Rows that are not launcher items and so have no item actions. */
+const NON_ITEM_TYPES = new Set([
+  "clipboard",
+  "file",
+  "answer",
+  "history_entry",
+  "schedule",
+  "process",
+]);
+
+/** The pressed key combination in the backend's shortcut form ("Ctrl+Shift+C"). */
+function eventShortcut(e: React.KeyboardEvent): string {
+  let key = e.key === " " ? "Space" : e.key;
+  if (/^Key[A-Z]$/.test(e.code)) key = e.code.slice(3);
+  else if (/^Digit\d$/.test(e.code)) key = e.code.slice(5);
+  else if (e.code === "NumpadEnter") key = "Enter";
+  const parts: string[] = [];
+  if (e.ctrlKey) parts.push("Ctrl");
+  if (e.altKey) parts.push("Alt");
+  if (e.shiftKey) parts.push("Shift");
+  if (e.metaKey) parts.push("Super");
+  parts.push(key.charAt(0).toUpperCase() + key.slice(1));
+  return parts.join("+");
+}
+
+/** Past executions of an item as launcher rows; choosing one runs the item again. */
+function historyToItem(entry: CommandHistory, item: LaunchItem): LaunchItem {
+  return {
+    id: `history:${entry.id}`,
+    title: entry.command_text,
+    subtitle:
+      `${entry.executed_at} · ${entry.source}` +
+      (entry.exit_code !== null ? ` · exit ${entry.exit_code}` : ""),
+    icon: "🕘",
+    action_type: "history_entry",
+    action_value: item.id,
+    category: "History",
+    tags: entry.action_type,
+    frequency: 0,
+    enabled: true,
+    hotkey: null,
+    source: null,
+    created_at: entry.executed_at,
+    updated_at: entry.executed_at,
+  };
+}
+
+const ANSWER_ICONS: Record<QuickAnswer["kind"], string> = {
+  calculator: "🧮",
+  conversion: "📐",
+  time_zone: "🕒",
+  date: "📅",
+};
+
+/** Quick answers shaped as launcher rows; choosing one copies its value. */
+function answerToItem(answer: QuickAnswer, index: number): LaunchItem {
+  return {
+    id: `answer:${index}`,
+    title: answer.title,
+    subtitle: answer.subtitle,
+    icon: ANSWER_ICONS[answer.kind],
+    action_type: "answer",
+    action_value: answer.value,
+    category: "Answers",
+    tags: answer.kind,
+    frequency: 0,
+    enabled: true,
+    hotkey: null,
+    source: null,
+    created_at: "",
+    updated_at: "",
+  };
+}
+
+/** Launcher rows for an unprefixed query: quick answers, then matching items. */
+async function searchLauncher(query: string): Promise<LaunchItem[]> {
+  const results = await invoke<SearchResult[]>("search", {
+    query,
+    providers: ["answers", "items"],
+  });
+  return results.map((result, index) =>
+    result.kind === "answer"
+      ? answerToItem(result.data as QuickAnswer, index)
+      : (result.data as LaunchItem),
+  );
+}
+
+/** Search a prefixed query ("clip:", "file:", "schedule:", "ps:", "~") with its own backend command. */
+function searchPrefixed(query: string): Promise<LaunchItem[]> {
+  if (query.startsWith(CLIPBOARD_PREFIX)) {
+    return invoke<LaunchItem[]>("search_clipboard_items", {
+      query: query.slice(CLIPBOARD_PREFIX.length),
+    });
+  }
+  if (query.startsWith(SCHEDULE_PREFIX)) {
+    return invoke<LaunchItem[]>("search_schedule_items", {
+      query: query.slice(SCHEDULE_PREFIX.length),
+    });
+  }
+  if (query.startsWith(PROCESS_PREFIX)) {
+    return invoke<LaunchItem[]>("search_running_items", {
+      query: query.slice(PROCESS_PREFIX.length),
+    });
+  }
+  // "file:" searches names; "~/proj" browses the file index by path
+  return invoke<LaunchItem[]>("search_file_items", {
+    query: query.startsWith(FILE_PREFIX) ? query.slice(FILE_PREFIX.length) : query,
+  });
+}
 
 interface UseLauncherOptions {
   agentStatus: AgentStatus;
@@ -23,6 +165,13 @@ export function useLauncher(options: UseLauncherOptions) {
   const [savingCommand, setSavingCommand] = useState(false);
   const [slashCommands, setSlashCommands] = useState<SlashCommand[]>([]);
   const [selectedSlashIndex, setSelectedSlashIndex] = useState(0);
+  // Text after the first word when it is used as snippet arguments ("sig Bob")
+  const [snippetArgs, setSnippetArgs] = useState<string | null>(null);
+  // Actions of the selected item, custom ones first
+  const [itemActions, setItemActions] = useState<ItemAction[]>([]);
+  const [editingItem, setEditingItem] = useState<LaunchItem | null>(null);
+  // Item whose execution history replaces the results ("Show in history")
+  const [historyItem, setHistoryItem] = useState<LaunchItem | null>(null);
   const debounceRef = useRef<ReturnType<typeof setTimeout>>();
   const queryBeforeSuggestionSelectRef = useRef<string | null>(null);
 
@@ -35,9 +184,29 @@ export function useLauncher(options: UseLauncherOptions) {
   const fetchItems = useCallback(async (searchQuery: string) => {
     setLoading(true);
     try {
-      const results = await invoke<LaunchItem[]>("search_items", {
-        query: searchQuery,
-      });
+      // "clip:", "file:", "schedule:", "ps:" and "~" search clipboard history,
+      // files, schedules or processes instead of items
+      let results = isPrefixedQuery(searchQuery)
+        ? await searchPrefixed(searchQuery)
+        : await searchLauncher(searchQuery);
+
+      // No full match: treat "name rest..." as a snippet called with arguments
+      let args: string | null = null;
+      const space = searchQuery.indexOf(" ");
+      if (
+        results.length === 0 &&
+        space > 0 &&
+        !isPrefixedQuery(searchQuery)
+      ) {
+        const byName = await invoke<LaunchItem[]>("search_items", {
+          query: searchQuery.slice(0, space),
+        });
+        results = byName.filter((item) => item.action_type === "snippet");
+        args = results.length > 0 ? searchQuery.slice(space + 1) : null;
+      }
+
+      setSnippetArgs(args);
+      setHistoryItem(null);
       setItems(results);
       setSelectedIndex(0);
     } catch (err) {
@@ -139,11 +308,36 @@ export function useLauncher(options: UseLauncherOptions) {
     ? items.filter((item) => item.category === activeCategory)
     : items;
 
+  const selectedItem = filteredItems[selectedIndex];
+  const selectedItemId =
+    selectedItem && !NON_ITEM_TYPES.has(selectedItem.action_type)
+      ? selectedItem.id
+      : null;
+
+  useEffect(() => {
+    if (!selectedItemId) {
+      setItemActions([]);
+      return;
+    }
+    let cancelled = false;
+    invoke<ItemAction[]>("get_item_actions", { id: selectedItemId })
+      .then((actions) => {
+        if (!cancelled) setItemActions(actions);
+      })
+      .catch(() => {
+        if (!cancelled) setItemActions([]);
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [selectedItemId]);
+
   // Agent mode conditions met (used internally — UI doesn't switch until Enter)
   const agentModeReady =
     filteredItems.length === 0 &&
+    !isPrefixedQuery(query) &&
     query.length > 2 &&
-    options.agentStatus === "connected" &&
+    (options.agentStatus === "connected" || options.agentStatus === "idle") &&
     options.agentAutoFallback;
 
   // Agent mode only activates after user confirms with Enter
@@ -246,12 +440,137 @@ export function useLauncher(options: UseLauncherOptions) {
     const item = filteredItems[selectedIndex];
     if (!item) return;
     try {
-      await invoke("execute_item", { id: item.id });
+      if (item.action_type === "clipboard") {
+        // Hides the launcher itself, then pastes into the previous app
+        await invoke("paste_clipboard_entry", { id: item.action_value });
+        return;
+      }
+      if (item.action_type === "snippet") {
+        // Hides the launcher itself, then pastes into the previous app
+        await invoke("execute_snippet", { id: item.id, args: snippetArgs });
+        return;
+      }
+      if (item.action_type === "answer") {
+        await invoke("copy_quick_answer", { value: item.action_value });
+        return;
+      }
+      if (item.action_type === "process") {
+        // Hides the launcher itself
+        await invoke("process_item_action", {
+          pid: Number(item.action_value),
+          action: "copy_output",
+        });
+        return;
+      }
+      if (item.action_type === "schedule") {
+        // Stay open so the refreshed row shows the run's result
+        await invoke("run_schedule_now", { id: item.action_value });
+        fetchItems(query);
+        return;
+      }
+      if (item.action_type === "file") {
+        await invoke("file_item_action", {
+          path: item.action_value,
+          action: "open",
+        });
+        return;
+      }
+      // A history row runs the item it belongs to
+      const id = item.action_type === "history_entry" ? item.action_value : item.id;
+      await invoke("execute_item", { id });
       await invoke("hide_window");
     } catch (err) {
       console.error("Failed to execute item:", err);
     }
-  }, [filteredItems, selectedIndex]);
+  }, [filteredItems, selectedIndex, snippetArgs, fetchItems, query]);
+
+  const showItemHistory = useCallback(async (item: LaunchItem) => {
+    try {
+      const entries = await invoke<CommandHistory[]>("get_item_history", {
+        id: item.id,
+      });
+      setHistoryItem(item);
+      setActiveCategory(null);
+      setItems(entries.map((entry) => historyToItem(entry, item)));
+      setSelectedIndex(0);
+    } catch (err) {
+      console.error("Failed to load item history:", err);
+    }
+  }, []);
+
+  const closeItemHistory = useCallback(() => {
+    setHistoryItem(null);
+    fetchItems(query);
+  }, [fetchItems, query]);
+
+  const closeEditor = useCallback(() => {
+    setEditingItem(null);
+    setFocusInputSignal((prev) => prev + 1);
+  }, []);
+
+  // Run one of an item's actions; the built-in edit and history actions open launcher views
+  const runItemAction = useCallback(
+    async (item: LaunchItem, action: ItemAction) => {
+      try {
+        if (action.id === "builtin:edit") {
+          setEditingItem(item);
+          return;
+        }
+        if (action.id === "builtin:history") {
+          await showItemHistory(item);
+          return;
+        }
+        if (action.id === "builtin:disable") {
+          // The backend emits items-changed, which refreshes the list
+          await invoke("execute_item", { id: item.id, actionId: action.id });
+          return;
+        }
+        if (action.action_type === "snippet") {
+          // Hides the launcher itself, then pastes into the previous app
+          await invoke("execute_snippet", {
+            id: item.id,
+            args: snippetArgs,
+            actionId: action.id,
+          });
+          return;
+        }
+        await invoke("execute_item", { id: item.id, actionId: action.id });
+        await invoke("hide_window");
+      } catch (err) {
+        console.error("Failed to run item action:", err);
+      }
+    },
+    [showItemHistory, snippetArgs],
+  );
+
+  // Secondary actions on a file result: "reveal" in the file manager or "copy_path"
+  const fileAction = useCallback(
+    async (action: "reveal" | "copy_path") => {
+      const item = filteredItems[selectedIndex];
+      if (!item || item.action_type !== "file") return;
+      try {
+        await invoke("file_item_action", { path: item.action_value, action });
+      } catch (err) {
+        console.error("Failed to run file action:", err);
+      }
+    },
+    [filteredItems, selectedIndex],
+  );
+
+  // Stop the selected process and show its new state
+  const killSelectedProcess = useCallback(async () => {
+    const item = filteredItems[selectedIndex];
+    if (!item || item.action_type !== "process") return;
+    try {
+      await invoke("process_item_action", {
+        pid: Number(item.action_value),
+        action: "kill",
+      });
+      fetchItems(query);
+    } catch (err) {
+      console.error("Failed to stop process:", err);
+    }
+  }, [filteredItems, selectedIndex, fetchItems, query]);
 
   const handleKeyDown = useCallback(
     (e: React.KeyboardEvent) => {
@@ -313,6 +632,48 @@ export function useLauncher(options: UseLauncherOptions) {
         return;
       }
 
+      // File results: Ctrl+Enter reveals in the file manager, Ctrl+Shift+C copies the path
+      if (
+        filteredItems[selectedIndex]?.action_type === "file" &&
+        (e.ctrlKey || e.metaKey) &&
+        (e.key === "Enter" || (e.shiftKey && e.key.toLowerCase() === "c"))
+      ) {
+        e.preventDefault();
+        fileAction(e.key === "Enter" ? "reveal" : "copy_path");
+        return;
+      }
+
+      // Process results: Ctrl+K stops the process
+      if (
+        filteredItems[selectedIndex]?.action_type === "process" &&
+        (e.ctrlKey || e.metaKey) &&
+        e.key.toLowerCase() === "k"
+      ) {
+        e.preventDefault();
+        killSelectedProcess();
+        return;
+      }
+
+      // Item action shortcuts. Shift alone is left to typing.
+      const selected = filteredItems[selectedIndex];
+      if (
+        selected &&
+        itemActions.length > 0 &&
+        (e.ctrlKey || e.altKey || e.metaKey || e.key.length > 1)
+      ) {
+        const pressed = eventShortcut(e).toLowerCase();
+        const action = itemActions.find(
+          (a) =>
+            a.item_id === selected.id &&
+            a.shortcut?.toLowerCase() === pressed,
+        );
+        if (action) {
+          e.preventDefault();
+          runItemAction(selected, action);
+          return;
+        }
+      }
+
       if (suggestions.length > 0 && (e.key === "ArrowDown" || e.key === "ArrowUp")) {
         e.preventDefault();
 
@@ -371,6 +732,10 @@ export function useLauncher(options: UseLauncherOptions) {
             restoreQueryFromSuggestionPreview();
             break;
           }
+          if (historyItem) {
+            closeItemHistory();
+            break;
+          }
           if (query) {
             setQuery("");
           } else {
@@ -401,7 +766,14 @@ export function useLauncher(options: UseLauncherOptions) {
     },
     [
       filteredItems,
+      selectedIndex,
       executeSelected,
+      fileAction,
+      killSelectedProcess,
+      itemActions,
+      runItemAction,
+      historyItem,
+      closeItemHistory,
       query,
       categories,
       activeCategory,
@@ -434,6 +806,7 @@ export function useLauncher(options: UseLauncherOptions) {
     setSlashCommands([]);
     setSelectedSlashIndex(0);
     setAgentModeConfirmed(false);
+    setEditingItem(null);
     fetchItems("");
     fetchCategories();
   }, [fetchItems, fetchCategories, setQuery]);
@@ -465,5 +838,10 @@ export function useLauncher(options: UseLauncherOptions) {
     selectedSlashIndex,
     setSelectedSlashIndex,
     executeSlashCommand,
+    itemActions,
+    runItemAction,
+    editingItem,
+    closeEditor,
+    historyItem,
   };
 }
@@ -9,13 +9,97 @@ export interface LaunchItem {
   tags: string;
   frequency: number;
   enabled: boolean;
+  hotkey: string | null;
+  /** Indexer that manages this item (e.g. "applications"), null for user items */
+  source: string | null;
   created_at: string;
   updated_at: string;
 }
 
+/** Extra action on an item; built-in ones (copy, edit, disable, history) exist for every item */
+export interface ItemAction {
+  id: string;
+  item_id: string;
+  label: string;
+  action_type: string;
+  value: string;
+  /** Shortcut while the item is selected, e.g. "Ctrl+Enter" */
+  shortcut: string | null;
+  position: number;
+  builtin: boolean;
+  created_at: string;
+}
+
+export interface CommandHistory {
+  id: string;
+  item_id: string | null;
+  command_text: string;
+  action_type: string;
+  executed_at: string;
+  source: string;
+  /** Set for runs that were waited on, such as workflow steps */
+  exit_code: number | null;
+}
+
+/** Progress of a running workflow item; `step` 0 is the workflow as a whole */
+export interface WorkflowProgress {
+  run_id: string;
+  item_id: string;
+  workflow: string;
+  step: number;
+  total: number;
+  label: string;
+  status: "running" | "succeeded" | "failed" | "skipped";
+  exit_code: number | null;
+  error: string | null;
+}
+
+/** Instant result computed from the query (calculator, conversions, dates) */
+export interface QuickAnswer {
+  kind: "calculator" | "conversion" | "time_zone" | "date";
+  title: string;
+  subtitle: string;
+  /** Plain text copied when the answer is chosen */
+  value: string;
+}
+
+export interface SearchAction {
+  id: string;
+  title: string;
+}
+
+/** A row from the aggregated `search` command */
+export interface SearchResult {
+  kind: "item" | "slash_command" | "conversation" | "memory" | "answer";
+  id: string;
+  title: string;
+  subtitle: string | null;
+  icon: string | null;
+  score: number;
+  /** Available actions, the default one first */
+  actions: SearchAction[];
+  /** The underlying record (LaunchItem, SlashCommand, QuickAnswer, ...) */
+  data: unknown;
+}
+
 // ACP Agent types
 
-export type AgentStatus = "disconnected" | "connecting" | "connected" | "error";
+export type AgentStatus =
+  | "disconnected"
+  | "connecting"
+  | "connected"
+  // Shut down after going unused; started again by the next prompt
+  | "idle"
+  | "error";
+
+export interface AgentStatusInfo {
+  agent_id: string | null;
+  status: AgentStatus;
+  // Seconds since the agent process was last started; null while it isn't running
+  uptime_secs: number | null;
+  restart_count: number;
+  last_error: string | null;
+}
 
 export type AgentUpdate =
   | { type: "message_chunk"; text: string }
@@ -29,7 +113,22 @@ export type AgentUpdate =
     }
   | { type: "plan"; entries: PlanEntry[] }
   | { type: "turn_complete"; stop_reason: string }
-  | { type: "status_change"; status: AgentStatus };
+  | { type: "status_change"; status: AgentStatus; reason?: string }
+  | { type: "terminal_created"; terminal_id: string; command: string }
+  | { type: "terminal_output"; terminal_id: string; text: string }
+  | {
+      type: "terminal_exited";
+      terminal_id: string;
+      exit_code: number | null;
+      signal: string | null;
+    };
+
+/** An `acp-update` event: the update with the agent and conversation it belongs to. */
+export type AgentUpdateEvent = AgentUpdate & {
+  agent_id: string;
+  session_id: string | null;
+  conversation_id: string | null;
+};
 
 export interface AgentThreadMessage {
   id: string;
@@ -49,7 +148,9 @@ export interface PlanEntry {
 
 export interface PermissionRequest {
   request_id: string;
+  agent_id: string;
   session_id: string;
+  conversation_id: string | null;
   tool_name: string;
   tool_description: string | null;
   command_preview: string | null;
@@ -97,6 +198,48 @@ export interface LaunchContext {
   selected_text: string | null;
   source_window_title: string | null;
   source_process_name: string | null;
+  source_process_id: number | null;
+  sections: ContextSection[];
+}
+
+export interface ContextField {
+  key: string;
+  value: string;
+}
+
+export interface ContextSection {
+  provider: string;
+  label: string;
+  fields: ContextField[];
+}
+
+export interface ContextProviderInfo {
+  id: string;
+  label: string;
+  description: string;
+  enabled: boolean;
+  command: string | null;
+}
+
+export interface ClipboardEntry {
+  id: string;
+  content_type: "text" | "image";
+  content: string;
+  preview: string;
+  size_bytes: number;
+  source_app: string | null;
+  pinned: boolean;
+  copy_count: number;
+  created_at: string;
+  last_copied_at: string;
+}
+
+export interface ClipboardSettings {
+  enabled: boolean;
+  capture_images: boolean;
+  max_entries: number;
+  max_item_bytes: number;
+  excluded_apps: string[];
 }
 
 export interface CommandSuggestion {
@@ -126,10 +269,26 @@ export interface SlashCommand {
   description: string;
   script_path: string;
   usage_count: number;
+  hotkey: string | null;
   created_at: string;
   updated_at: string;
 }
 
+// --- Hotkey types ---
+
+export interface HotkeyBinding {
+  hotkey: string;
+  target_type: "toggle" | "item" | "slash_command";
+  target_id: string | null;
+  label: string;
+}
+
+export interface HotkeyConflict {
+  hotkey: string;
+  label: string;
+  reason: string;
+}
+
 // --- Conversation types ---
 
 export interface Conversation {
@@ -153,6 +312,7 @@ export interface ConversationMessage {
   conversation_id: string;
   role: string;
   content: string;
+  incomplete: boolean;
   created_at: string;
 }
 
@@ -182,3 +342,11 @@ export interface SessionConfigOptionInfo {
   current_value: string;
   select_options: SessionConfigSelectOptions;
 }
+
+/** An `acp-config-options` event: the options of one agent session. */
+export interface ConfigOptionsEvent {
+  agent_id: string;
+  /** Null for a session no conversation has used yet. */
+  conversation_id: string | null;
+  options: SessionConfigOptionInfo[];
+}